@@ -0,0 +1,170 @@
+//! Server administration. Reading commands look at the database directly,
+//! without migrating it; commands that change games go through the server's
+//! admin endpoints, so players following those games hear about it.
+
+use crate::{Result, ServerArgs};
+use clap::Subcommand;
+use hitman::config::Config;
+use hitman::engine;
+use hitman::models::{GameStatus, Player};
+use hitman::repository::{self, GameRepository};
+
+/// Purging anything younger would reach into games that are still being
+/// played.
+const MIN_PURGE_HOURS: u64 = 24;
+
+#[derive(Subcommand)]
+pub enum AdminCommand {
+    /// List every game with its status and number of players.
+    Games,
+    /// Show a game's target ring in hunting order and anything wrong with it.
+    Ring { game_code: String },
+    /// End a game straight away, without a winner.
+    Finish {
+        #[command(flatten)]
+        server: ServerArgs,
+        game_code: String,
+    },
+    /// Delete old games together with their players.
+    Purge {
+        #[command(flatten)]
+        server: ServerArgs,
+        /// Delete games created more than this many hours ago, at least 24.
+        /// Defaults to the configured `retention.game_ttl_hours`.
+        #[arg(long, value_parser = clap::value_parser!(u64).range(MIN_PURGE_HOURS..))]
+        older_than_hours: Option<u64>,
+    },
+}
+
+pub async fn run(command: AdminCommand) -> Result<()> {
+    match command {
+        AdminCommand::Games => {
+            let db = repository::open(&Config::load()?.database).await?;
+            let result = games(&*db).await;
+            db.close().await;
+            result
+        }
+        AdminCommand::Ring { game_code } => {
+            let db = repository::open(&Config::load()?.database).await?;
+            let result = ring(&*db, &game_code).await;
+            db.close().await;
+            result
+        }
+        AdminCommand::Finish { server, game_code } => {
+            let config = Config::load_without_database()?;
+            finish(&admin(&config, &server)?, &game_code).await
+        }
+        AdminCommand::Purge {
+            server,
+            older_than_hours,
+        } => {
+            let config = Config::load_without_database()?;
+            let older_than_hours = match older_than_hours {
+                Some(hours) => hours,
+                None => match config.retention.game_ttl_hours {
+                    0 => return Err("pass --older-than-hours or set retention.game_ttl_hours".into()),
+                    ttl if ttl < MIN_PURGE_HOURS => {
+                        return Err(format!(
+                            "retention.game_ttl_hours is {ttl}; pass --older-than-hours {MIN_PURGE_HOURS} or more"
+                        )
+                        .into())
+                    }
+                    ttl => ttl,
+                },
+            };
+            purge(&admin(&config, &server)?, older_than_hours).await
+        }
+    }
+}
+
+/// The server at `server` as its administrator, with the configured
+/// `ADMIN_TOKEN`.
+fn admin(config: &Config, server: &ServerArgs) -> Result<hitman_client::Admin> {
+    let token = config
+        .admin
+        .token
+        .as_deref()
+        .filter(|t| !t.is_empty())
+        .ok_or("set ADMIN_TOKEN to the server's admin token")?;
+    Ok(hitman_client::Client::new(&server.server).admin(token))
+}
+
+async fn games(db: &dyn GameRepository) -> Result<()> {
+    let games = db.get_all_games().await?;
+    if games.is_empty() {
+        println!("No games");
+    }
+    for game in games {
+        println!(
+            "{:<12} {:<12} {} players",
+            game.code, game.status, game.player_count
+        );
+    }
+    Ok(())
+}
+
+async fn ring(db: &dyn GameRepository, game_code: &str) -> Result<()> {
+    let (game, players) = db
+        .get_game_state(game_code)
+        .await?
+        .ok_or_else(|| format!("no game {game_code}"))?;
+    let alive = players.iter().filter(|p| p.is_alive).count();
+    println!(
+        "game {}: {}, {alive} of {} alive",
+        game.code,
+        game.status,
+        players.len()
+    );
+    if game.status != GameStatus::InProgress {
+        return Ok(());
+    }
+
+    // Chains follow each other where the ring is broken, joined by `|`.
+    let by_id = |id: i32| players.iter().find(|p| p.id == id);
+    let order: Vec<&Player> = engine::ring_order(&players)
+        .into_iter()
+        .filter_map(by_id)
+        .collect();
+    let mut chain = String::new();
+    for (idx, player) in order.iter().enumerate() {
+        if idx > 0 {
+            let linked = order[idx - 1].target_id == Some(player.id);
+            chain.push_str(if linked { " -> " } else { " | " });
+        }
+        chain.push_str(&format!("{} (#{})", player.name, player.id));
+    }
+    if let Some(last) = order.last() {
+        match last.target_id.and_then(by_id) {
+            Some(target) => chain.push_str(&format!(" -> {} (#{})", target.name, target.id)),
+            None => chain.push_str(" -> nobody"),
+        }
+    }
+    println!("  {chain}");
+
+    let violations = db.check_ring(game_code).await?;
+    if violations.is_empty() {
+        println!("ring is healthy");
+    }
+    for violation in violations {
+        println!("violation: {violation:?}");
+    }
+    Ok(())
+}
+
+async fn finish(admin: &hitman_client::Admin, game_code: &str) -> Result<()> {
+    if !admin.finish_game(game_code).await?.finished {
+        println!("{game_code} had already finished");
+        return Ok(());
+    }
+    println!("Finished {game_code}");
+    Ok(())
+}
+
+async fn purge(admin: &hitman_client::Admin, older_than_hours: u64) -> Result<()> {
+    let codes = admin.purge_games(older_than_hours).await?.game_codes;
+    println!("Purged {} games", codes.len());
+    for code in codes {
+        println!("  {code}");
+    }
+    Ok(())
+}
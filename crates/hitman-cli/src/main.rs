@@ -0,0 +1,140 @@
+//! `hitman-cli`: play a game from a terminal, or administer a server.
+//!
+//! Game commands go through the HTTP API like any other client. `create`
+//! and `join` print the `HITMAN_GAME` and `HITMAN_TOKEN` to export so the
+//! following commands act as that player.
+
+use clap::{Args, Parser, Subcommand};
+use std::process::ExitCode;
+
+mod admin;
+mod play;
+
+type Result<T, E = Box<dyn std::error::Error>> = std::result::Result<T, E>;
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a game and become its host.
+    Create {
+        #[command(flatten)]
+        server: ServerArgs,
+        /// Your name in the game.
+        name: String,
+    },
+    /// Join a game that is still in its lobby, or a running one that takes
+    /// late joiners; waits for the host when they have to let you in.
+    Join {
+        #[command(flatten)]
+        server: ServerArgs,
+        game_code: String,
+        /// Your name in the game.
+        name: String,
+    },
+    /// Start the game; only the host may.
+    Start(PlayerArgs),
+    /// Tell the lobby you are ready to start.
+    Ready {
+        #[command(flatten)]
+        player: PlayerArgs,
+        /// Take it back: you are not ready after all.
+        #[arg(long)]
+        not: bool,
+    },
+    /// Show the game as you see it.
+    Show(PlayerArgs),
+    /// Eliminate your target with their secret code or kill token.
+    Kill {
+        #[command(flatten)]
+        player: PlayerArgs,
+        secret_code: String,
+    },
+    /// Leave the game.
+    Leave(PlayerArgs),
+    /// Print the game every time it changes, until it is over.
+    Watch {
+        #[command(flatten)]
+        player: PlayerArgs,
+        /// Milliseconds between polls.
+        #[arg(long, default_value_t = 2000)]
+        interval_ms: u64,
+    },
+    /// Inspect and repair the server's games. Reads the same
+    /// `HITMAN_CONFIG`, `DATABASE_URL` and `ADMIN_TOKEN` as the server.
+    #[command(subcommand)]
+    Admin(admin::AdminCommand),
+}
+
+#[derive(Args)]
+struct ServerArgs {
+    /// Base URL of the server.
+    #[arg(long, env = "HITMAN_SERVER", default_value = "http://localhost:3000")]
+    server: String,
+}
+
+#[derive(Args)]
+struct PlayerArgs {
+    #[command(flatten)]
+    server: ServerArgs,
+    /// Code of the game you are in.
+    #[arg(long, env = "HITMAN_GAME")]
+    game: String,
+    /// Your auth token, printed by `create` and `join`.
+    #[arg(long, env = "HITMAN_TOKEN", hide_env_values = true)]
+    token: String,
+}
+
+impl PlayerArgs {
+    fn player(&self) -> hitman_client::Player {
+        hitman_client::Client::new(&self.server.server).player(&self.game, &self.token)
+    }
+}
+
+async fn run(command: Command) -> Result<()> {
+    match command {
+        Command::Create { server, name } => {
+            let client = hitman_client::Client::new(server.server);
+            play::create(&client, &name).await
+        }
+        Command::Join {
+            server,
+            game_code,
+            name,
+        } => {
+            let client = hitman_client::Client::new(server.server);
+            play::join(&client, &game_code, &name).await
+        }
+        Command::Start(player) => play::start(&player.player()).await,
+        Command::Ready { player, not } => play::ready(&player.player(), !not).await,
+        Command::Show(player) => play::show(&player.player()).await,
+        Command::Kill {
+            player,
+            secret_code,
+        } => play::kill(&player.player(), &secret_code).await,
+        Command::Leave(player) => play::leave(player.player()).await,
+        Command::Watch {
+            player,
+            interval_ms,
+        } => play::watch(&player.player(), interval_ms).await,
+        Command::Admin(command) => admin::run(command).await,
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    dotenvy::dotenv().ok();
+    let cli = Cli::parse();
+    match run(cli.command).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
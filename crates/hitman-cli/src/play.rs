@@ -0,0 +1,134 @@
+use crate::Result;
+use hitman_client::types::{GamePayload, GameStatus, PlayerSessionPayload, RequestStatus};
+use hitman_client::{Client, Error, Player, DEFAULT_POLL_INTERVAL};
+use std::time::Duration;
+
+fn print_session(session: &PlayerSessionPayload) {
+    let player = &session.player;
+    println!("game    {}", session.game.code);
+    println!("player  {} (#{})", player.name, player.id);
+    if let Some(secret) = &player.secret_code {
+        println!("secret  {secret}");
+    }
+    println!("token   {}", session.auth_token);
+    println!();
+    println!(
+        "export HITMAN_GAME={} HITMAN_TOKEN={}",
+        session.game.code, session.auth_token
+    );
+}
+
+fn print_game(game: &GamePayload) {
+    println!(
+        "game {}: {} (version {})",
+        game.code, game.status, game.version
+    );
+    for player in &game.players {
+        let mut notes = Vec::new();
+        if game.host_id == Some(player.id) {
+            notes.push("host".to_string());
+        }
+        if !player.is_alive {
+            notes.push("eliminated".to_string());
+        }
+        if game.status == GameStatus::Lobby && player.is_ready {
+            notes.push("ready".to_string());
+        }
+        if let Some(secret) = &player.secret_code {
+            notes.push(format!("you, secret {secret}"));
+            if let Some(target) = player.target_name.as_deref().filter(|t| !t.is_empty()) {
+                notes.push(format!("hunting {target}"));
+            }
+        }
+        match notes.is_empty() {
+            true => println!("  {}", player.name),
+            false => println!("  {} ({})", player.name, notes.join(", ")),
+        }
+    }
+}
+
+pub async fn create(client: &Client, name: &str) -> Result<()> {
+    let (_, session) = client.create_game(name).await?;
+    print_session(&session);
+    Ok(())
+}
+
+pub async fn join(client: &Client, game_code: &str, name: &str) -> Result<()> {
+    let request = match client.join_game(game_code, name).await {
+        Ok((_, session)) => {
+            print_session(&session);
+            return Ok(());
+        }
+        Err(Error::Pending(request)) => request,
+        Err(err) => return Err(err.into()),
+    };
+    println!("The game is already running; waiting for the host to let you in...");
+    loop {
+        tokio::time::sleep(DEFAULT_POLL_INTERVAL).await;
+        let request = client.join_request(game_code, &request.claim_token).await?;
+        match (request.status, request.auth_token) {
+            (RequestStatus::Pending, _) => continue,
+            (RequestStatus::Approved, Some(token)) => {
+                print_game(&client.player(game_code, &token).game().await?);
+                println!();
+                println!("export HITMAN_GAME={game_code} HITMAN_TOKEN={token}");
+                return Ok(());
+            }
+            _ => return Err("the host turned you away".into()),
+        }
+    }
+}
+
+pub async fn start(player: &Player) -> Result<()> {
+    print_game(&player.start().await?);
+    Ok(())
+}
+
+pub async fn ready(player: &Player, ready: bool) -> Result<()> {
+    player.ready(ready).await?;
+    print_game(&player.game().await?);
+    Ok(())
+}
+
+pub async fn show(player: &Player) -> Result<()> {
+    print_game(&player.game().await?);
+    Ok(())
+}
+
+pub async fn kill(player: &Player, secret_code: &str) -> Result<()> {
+    let outcome = player.kill(secret_code).await?;
+    println!("{} is out", outcome.eliminated_player_name);
+    match (outcome.game_over, outcome.new_target_name) {
+        (true, _) => println!("Game over, {} wins", outcome.killer_name),
+        (false, Some(target)) => println!("Your new target is {target}"),
+        (false, None) => {}
+    }
+    Ok(())
+}
+
+pub async fn leave(player: Player) -> Result<()> {
+    let game_code = player.game_code().to_string();
+    player.leave().await?;
+    println!("Left {game_code}");
+    Ok(())
+}
+
+pub async fn watch(player: &Player, interval_ms: u64) -> Result<()> {
+    let game = player.game().await?;
+    print_game(&game);
+    if game.status == GameStatus::Finished {
+        return Ok(());
+    }
+    let mut changes = player
+        .subscribe(game.version)
+        .every(Duration::from_millis(interval_ms));
+    loop {
+        changes.changed().await?;
+        let game = player.game().await?;
+        println!();
+        print_game(&game);
+        if game.status == GameStatus::Finished {
+            return Ok(());
+        }
+    }
+}
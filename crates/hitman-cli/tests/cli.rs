@@ -0,0 +1,179 @@
+//! Runs the built `hitman-cli` binary against a server and a database.
+
+use dashmap::DashMap;
+use hitman::{
+    config::Config,
+    create_router,
+    engine::MIN_PLAYERS,
+    kill_token::KillTokenSigner,
+    metrics::Metrics,
+    repository::{GameRepository, MemoryRepository, SqliteRepository},
+    state::AppState,
+};
+use std::sync::Arc;
+use tera::Tera;
+use tokio::process::Command;
+
+async fn cli(args: &[&str], env: &[(&str, &str)]) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_hitman-cli"))
+        .args(args)
+        .env("HITMAN_CONFIG", "")
+        .envs(env.iter().copied())
+        .output()
+        .await
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    (output.status.success(), stdout + &stderr)
+}
+
+/// The value printed after `label` in `create` and `join` output.
+fn field<'a>(output: &'a str, label: &str) -> &'a str {
+    output
+        .lines()
+        .find_map(|line| line.strip_prefix(label))
+        .map(str::trim)
+        .unwrap_or_else(|| panic!("no {label} in {output}"))
+}
+
+async fn serve(db: Arc<dyn GameRepository>, config: Config) -> String {
+    let templates = concat!(env!("CARGO_MANIFEST_DIR"), "/../../templates/**/*");
+    let mut tera = Tera::new(templates).expect("templates must parse");
+    hitman::i18n::register_tera_functions(&mut tera);
+    let state = AppState {
+        db,
+        tera,
+        versions: Arc::new(DashMap::new()),
+        kill_tokens: KillTokenSigner::new(vec![b"test-key".to_vec()], 60),
+        config: Arc::new(config),
+        metrics: Metrics::new(),
+    };
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, create_router(state)).await });
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn plays_through_the_api() {
+    let server = serve(Arc::new(MemoryRepository::new()), Config::default()).await;
+    let (ok, out) = cli(&["create", "alice"], &[("HITMAN_SERVER", &server)]).await;
+    assert!(ok, "{out}");
+    let code = field(&out, "game").to_string();
+    let alice = field(&out, "token").to_string();
+    for name in ["bob", "carol"] {
+        let (ok, out) = cli(&["join", &code, name], &[("HITMAN_SERVER", &server)]).await;
+        assert!(ok, "{out}");
+    }
+
+    let as_alice = [
+        ("HITMAN_SERVER", server.as_str()),
+        ("HITMAN_GAME", code.as_str()),
+        ("HITMAN_TOKEN", alice.as_str()),
+    ];
+    let (ok, out) = cli(&["ready"], &as_alice).await;
+    assert!(ok, "{out}");
+    assert!(out.contains("alice (host, ready, you"), "{out}");
+    let (ok, out) = cli(&["start"], &as_alice).await;
+    assert!(ok, "{out}");
+    assert!(out.contains("IN_PROGRESS"), "{out}");
+    let (ok, out) = cli(&["show"], &as_alice).await;
+    assert!(ok, "{out}");
+    assert!(out.contains("alice (host, you, secret"), "{out}");
+    assert!(out.contains("hunting "), "{out}");
+
+    let (ok, out) = cli(&["kill", "NOT-A-SECRET"], &as_alice).await;
+    assert!(!ok);
+    assert!(out.contains("UNKNOWN_SECRET"), "{out}");
+}
+
+#[tokio::test]
+async fn administers_the_database() {
+    let path = std::env::temp_dir().join(format!("hitman-cli-{}.db", std::process::id()));
+    let url = format!("sqlite:{}", path.display());
+    let db = SqliteRepository::connect(&url, 1).await.unwrap();
+    let (_, host_id, _, _) = db
+        .create_game("alice".into(), "RING1".into())
+        .await
+        .unwrap();
+    for name in ["bob", "carol"] {
+        db.join_game("RING1".into(), name.into(), usize::MAX, false)
+            .await
+            .unwrap();
+    }
+    db.start_game("RING1", host_id, MIN_PLAYERS).await.unwrap();
+    db.create_game("dave".into(), "LOBBY".into()).await.unwrap();
+    db.close().await;
+    let mut config = Config::default();
+    config.admin.token = Some("admin-secret".into());
+    let server = serve(
+        Arc::new(SqliteRepository::connect(&url, 1).await.unwrap()),
+        config,
+    )
+    .await;
+    let env = [
+        ("DATABASE_URL", url.as_str()),
+        ("HITMAN_SERVER", server.as_str()),
+        ("ADMIN_TOKEN", "admin-secret"),
+    ];
+
+    let (ok, out) = cli(&["admin", "games"], &env).await;
+    assert!(ok, "{out}");
+    assert!(
+        out.contains("RING1") && out.contains("IN_PROGRESS"),
+        "{out}"
+    );
+    assert!(out.contains("LOBBY") && out.contains("1 players"), "{out}");
+
+    let (ok, out) = cli(&["admin", "ring", "RING1"], &env).await;
+    assert!(ok, "{out}");
+    assert!(out.contains("3 of 3 alive"), "{out}");
+    assert_eq!(out.matches(" -> ").count(), 3, "{out}");
+    assert!(out.contains("ring is healthy"), "{out}");
+
+    let (ok, out) = cli(&["admin", "games"], &[("DATABASE_URL", "memory")]).await;
+    assert!(!ok, "{out}");
+
+    // Commands that go through the server don't need the database.
+    let http_env = [
+        ("DATABASE_URL", ""),
+        ("HITMAN_SERVER", server.as_str()),
+        ("ADMIN_TOKEN", "admin-secret"),
+    ];
+    let (ok, out) = cli(
+        &["admin", "finish", "RING1"],
+        &[http_env[0], http_env[1], ("ADMIN_TOKEN", "wrong")],
+    )
+    .await;
+    assert!(!ok);
+    assert!(out.contains("NOT_ADMIN"), "{out}");
+    let (ok, out) = cli(&["admin", "finish", "RING1"], &http_env).await;
+    assert!(ok, "{out}");
+    let (_, out) = cli(&["admin", "finish", "RING1"], &http_env).await;
+    assert!(out.contains("already finished"), "{out}");
+    let (_, out) = cli(&["admin", "games"], &env).await;
+    assert!(out.contains("FINISHED"), "{out}");
+
+    let (ok, out) = cli(&["admin", "purge"], &http_env).await;
+    assert!(!ok, "{out}");
+    let (ok, out) = cli(&["admin", "purge", "--older-than-hours", "0"], &http_env).await;
+    assert!(!ok, "{out}");
+    let (ok, out) = cli(
+        &["admin", "purge"],
+        &[
+            http_env[0],
+            http_env[1],
+            http_env[2],
+            ("RETENTION_GAME_TTL_HOURS", "1"),
+        ],
+    )
+    .await;
+    assert!(!ok, "{out}");
+    let (ok, out) = cli(&["admin", "purge", "--older-than-hours", "24"], &http_env).await;
+    assert!(ok, "{out}");
+    assert!(out.contains("Purged 0 games"), "{out}");
+
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{suffix}", path.display()));
+    }
+}
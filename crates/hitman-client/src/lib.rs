@@ -0,0 +1,496 @@
+//! Async client for the Hitman HTTP API (`/api/v1`).
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), hitman_client::Error> {
+//! let client = hitman_client::Client::new("http://localhost:3000");
+//! let (host, _) = client.create_game("alice").await?;
+//! let (bob, _) = client.join_game(host.game_code(), "bob").await?;
+//! client.join_game(host.game_code(), "carol").await?;
+//!
+//! let game = host.start().await?;
+//! let mut changes = bob.subscribe(game.version);
+//! let version = changes.changed().await?;
+//! println!("game changed, now at version {version}");
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The request and response types come from `hitman-types`, re-exported
+//! here as [`types`].
+
+use hitman_types::{
+    ActivityPayload, ChangesPayload, ChatChannel, ChatMessagePayload, CreateGamePayload,
+    DecideJoinPayload, DecideRecoveryPayload, DisputePayload, DisputeVotePayload, ErrorPayload,
+    FinishedGamePayload, GamePayload, GameSettings, GhostPayload, HintPayload, JoinGamePayload,
+    JoinRequestPayload, KillPayload, KillResponsePayload, KillTokenPayload, OpenDisputePayload,
+    PendingJoinPayload, PendingRecoveryPayload, PlayerSessionPayload, PostMessagePayload,
+    PurgeGamesPayload, PurgedGamesPayload, ReadyPayload, RecoverPayload, RecoveryPayload,
+    SecretRotatedPayload, SendHintPayload, SpectatorLinkPayload, SpectatorPayload,
+};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+use thiserror::Error;
+
+pub use hitman_types as types;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The server answered with one of its error bodies.
+    #[error("{status}: {} ({})", .payload.error, .payload.code)]
+    Api {
+        status: StatusCode,
+        payload: ErrorPayload,
+    },
+    /// The server, or something in front of it, failed without an error body.
+    #[error("unexpected response: {0}")]
+    Status(StatusCode),
+    #[error("request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    /// The game already runs and its host lets newcomers in by hand. Poll
+    /// [`Client::join_request`] with the claim token for the outcome.
+    #[error("waiting for the host to let you in")]
+    Pending(JoinRequestPayload),
+}
+
+impl Error {
+    /// The server's machine readable error code, e.g. `WRONG_TARGET`.
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            Error::Api { payload, .. } => Some(&payload.code),
+            _ => None,
+        }
+    }
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// How often a [`Subscription`] polls, the same as the web frontend.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Entry point: creates and joins games. Cheap to clone.
+#[derive(Debug, Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl Client {
+    /// A client for the server at `base_url`, e.g. `https://hitman.example`.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_http_client(reqwest::Client::new(), base_url)
+    }
+
+    /// Like [`Client::new`], for callers that configure timeouts, proxies
+    /// or TLS themselves.
+    pub fn with_http_client(http: reqwest::Client, base_url: impl Into<String>) -> Self {
+        let base_url = base_url.into().trim_end_matches('/').to_string();
+        Client { http, base_url }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/api/v1{}", self.base_url, path)
+    }
+
+    /// Create a game with `player_name` as its host.
+    pub async fn create_game(&self, player_name: &str) -> Result<(Player, PlayerSessionPayload)> {
+        let request = self.http.post(self.url("/games")).json(&CreateGamePayload {
+            player_name: player_name.to_string(),
+        });
+        self.session(request).await
+    }
+
+    /// Join the lobby of `game_code` as `player_name`, or the running game
+    /// when it takes late joiners. Fails with [`Error::Pending`] when its
+    /// host has to let you in first.
+    pub async fn join_game(
+        &self,
+        game_code: &str,
+        player_name: &str,
+    ) -> Result<(Player, PlayerSessionPayload)> {
+        let response = checked(
+            self.http
+                .post(self.url(&format!("/games/{game_code}/players")))
+                .json(&JoinGamePayload {
+                    player_name: player_name.to_string(),
+                }),
+        )
+        .await?;
+        if response.status() == StatusCode::ACCEPTED {
+            return Err(Error::Pending(response.json().await?));
+        }
+        let session: PlayerSessionPayload = response.json().await?;
+        let player = self.player(&session.game.code, &session.auth_token);
+        Ok((player, session))
+    }
+
+    /// Where a request to join a running game stands; carries the auth
+    /// token for [`Client::player`] once approved.
+    pub async fn join_request(
+        &self,
+        game_code: &str,
+        claim_token: &str,
+    ) -> Result<JoinRequestPayload> {
+        let request = self
+            .http
+            .get(self.url(&format!("/games/{game_code}/join-claims/{claim_token}")));
+        json(request).await
+    }
+
+    /// Act as a player whose auth token was kept from an earlier session.
+    pub fn player(&self, game_code: &str, auth_token: &str) -> Player {
+        Player {
+            client: self.clone(),
+            game_code: game_code.to_string(),
+            auth_token: auth_token.to_string(),
+        }
+    }
+
+    /// Act as the server's administrator, with its `ADMIN_TOKEN`.
+    pub fn admin(&self, admin_token: &str) -> Admin {
+        Admin {
+            client: self.clone(),
+            admin_token: admin_token.to_string(),
+        }
+    }
+
+    /// The public scoreboard of `game_code`, with the spectator token its
+    /// host shared.
+    pub async fn spectate(
+        &self,
+        game_code: &str,
+        spectator_token: &str,
+    ) -> Result<SpectatorPayload> {
+        let request = self
+            .http
+            .get(self.url(&format!("/games/{game_code}/spectate")))
+            .bearer_auth(spectator_token);
+        json(request).await
+    }
+
+    /// Ask the host of `game_code` to let `player_name` back in after losing
+    /// their auth token. Poll [`Client::recovery`] with the claim token.
+    pub async fn recover(&self, game_code: &str, player_name: &str) -> Result<RecoveryPayload> {
+        let request = self
+            .http
+            .post(self.url(&format!("/games/{game_code}/recoveries")))
+            .json(&RecoverPayload {
+                player_name: player_name.to_string(),
+            });
+        json(request).await
+    }
+
+    /// Where a recovery request stands; carries the new auth token once
+    /// approved.
+    pub async fn recovery(&self, game_code: &str, claim_token: &str) -> Result<RecoveryPayload> {
+        let request = self
+            .http
+            .get(self.url(&format!("/games/{game_code}/recovery-claims/{claim_token}")));
+        json(request).await
+    }
+
+    async fn session(&self, request: RequestBuilder) -> Result<(Player, PlayerSessionPayload)> {
+        let session: PlayerSessionPayload = json(request).await?;
+        let player = self.player(&session.game.code, &session.auth_token);
+        Ok((player, session))
+    }
+}
+
+/// A player in one game; every call is authenticated with their token.
+#[derive(Debug, Clone)]
+pub struct Player {
+    client: Client,
+    game_code: String,
+    auth_token: String,
+}
+
+impl Player {
+    pub fn game_code(&self) -> &str {
+        &self.game_code
+    }
+
+    /// Keep this to come back as the same player later.
+    pub fn auth_token(&self) -> &str {
+        &self.auth_token
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> RequestBuilder {
+        let url = self
+            .client
+            .url(&format!("/games/{}{}", self.game_code, path));
+        self.client
+            .http
+            .request(method, url)
+            .bearer_auth(&self.auth_token)
+    }
+
+    /// The game as this player sees it.
+    pub async fn game(&self) -> Result<GamePayload> {
+        json(self.request(reqwest::Method::GET, "")).await
+    }
+
+    /// Start the game; only the host may.
+    pub async fn start(&self) -> Result<GamePayload> {
+        json(self.request(reqwest::Method::POST, "/start")).await
+    }
+
+    /// Tell the lobby whether this player is ready; with auto-start on, the
+    /// last one to get ready starts the game.
+    pub async fn ready(&self, ready: bool) -> Result<()> {
+        let request = self
+            .request(reqwest::Method::PUT, "/players/me/ready")
+            .json(&ReadyPayload { ready });
+        checked(request).await?;
+        Ok(())
+    }
+
+    pub async fn settings(&self) -> Result<GameSettings> {
+        json(self.request(reqwest::Method::GET, "/settings")).await
+    }
+
+    /// Replace the game's settings; only the host may, before the start.
+    pub async fn update_settings(&self, settings: &GameSettings) -> Result<GameSettings> {
+        json(
+            self.request(reqwest::Method::PUT, "/settings")
+                .json(settings),
+        )
+        .await
+    }
+
+    /// The newest page of the game's activity feed, or the page older than
+    /// `before`, the previous page's `next_cursor`.
+    pub async fn activity(&self, before: Option<i64>) -> Result<ActivityPayload> {
+        let mut request = self.request(reqwest::Method::GET, "/activity");
+        if let Some(before) = before {
+            request = request.query(&[("before", before)]);
+        }
+        json(request).await
+    }
+
+    /// Eliminate this player's target with their secret code or kill token.
+    pub async fn kill(&self, secret_code: &str) -> Result<KillResponsePayload> {
+        let request = self
+            .request(reqwest::Method::POST, "/kills")
+            .json(&KillPayload {
+                secret_code: secret_code.to_string(),
+            });
+        json(request).await
+    }
+
+    pub async fn kill_token(&self) -> Result<KillTokenPayload> {
+        json(self.request(reqwest::Method::GET, "/kill-token")).await
+    }
+
+    pub async fn rotate_secret(&self) -> Result<SecretRotatedPayload> {
+        json(self.request(reqwest::Method::POST, "/secret/rotate")).await
+    }
+
+    /// The game's spectator link; only the host may ask.
+    pub async fn spectator_link(&self) -> Result<SpectatorLinkPayload> {
+        json(self.request(reqwest::Method::GET, "/spectator-link")).await
+    }
+
+    /// What this player can do as a ghost; only ghosts may ask.
+    pub async fn ghost(&self) -> Result<GhostPayload> {
+        json(self.request(reqwest::Method::GET, "/ghost")).await
+    }
+
+    /// Ask the other ghosts to overturn the kill `event_id` that eliminated
+    /// this player.
+    pub async fn dispute(&self, event_id: i64) -> Result<DisputePayload> {
+        let request = self
+            .request(reqwest::Method::POST, "/disputes")
+            .json(&OpenDisputePayload { event_id });
+        json(request).await
+    }
+
+    /// Vote to overturn a disputed kill, or to let it stand.
+    pub async fn vote(&self, dispute_id: i64, overturn: bool) -> Result<DisputePayload> {
+        let request = self
+            .request(
+                reqwest::Method::POST,
+                &format!("/disputes/{dispute_id}/votes"),
+            )
+            .json(&DisputeVotePayload { overturn });
+        json(request).await
+    }
+
+    /// Send this ghost's one anonymous hint to the living player `player_id`.
+    pub async fn send_hint(&self, player_id: i32, message: &str) -> Result<()> {
+        let request = self
+            .request(reqwest::Method::POST, "/hints")
+            .json(&SendHintPayload {
+                player_id,
+                message: message.to_string(),
+            });
+        checked(request).await?;
+        Ok(())
+    }
+
+    /// The hints ghosts sent this player.
+    pub async fn hints(&self) -> Result<Vec<HintPayload>> {
+        json(self.request(reqwest::Method::GET, "/hints")).await
+    }
+
+    /// The chat messages this player may read, oldest first; only those
+    /// newer than the message `after` when given.
+    pub async fn messages(&self, after: Option<i64>) -> Result<Vec<ChatMessagePayload>> {
+        let mut request = self.request(reqwest::Method::GET, "/messages");
+        if let Some(after) = after {
+            request = request.query(&[("after", after)]);
+        }
+        json(request).await
+    }
+
+    /// Post `body` to `channel`; announcements are for the host only.
+    pub async fn post_message(
+        &self,
+        channel: ChatChannel,
+        body: &str,
+    ) -> Result<ChatMessagePayload> {
+        let request = self
+            .request(reqwest::Method::POST, "/messages")
+            .json(&PostMessagePayload {
+                channel,
+                body: body.to_string(),
+            });
+        json(request).await
+    }
+
+    /// Players asking to be let back in; only the host may ask.
+    pub async fn recoveries(&self) -> Result<Vec<PendingRecoveryPayload>> {
+        json(self.request(reqwest::Method::GET, "/recoveries")).await
+    }
+
+    /// Approve or deny the recovery request `request_id`; only the host may.
+    pub async fn decide_recovery(&self, request_id: i64, approve: bool) -> Result<()> {
+        let request = self
+            .request(reqwest::Method::POST, &format!("/recoveries/{request_id}"))
+            .json(&DecideRecoveryPayload { approve });
+        checked(request).await?;
+        Ok(())
+    }
+
+    /// Newcomers asking to join the running game; only the host may ask.
+    pub async fn join_requests(&self) -> Result<Vec<PendingJoinPayload>> {
+        json(self.request(reqwest::Method::GET, "/join-requests")).await
+    }
+
+    /// Let the newcomer behind `request_id` into the game, or turn them
+    /// away; only the host may.
+    pub async fn decide_join_request(&self, request_id: i64, approve: bool) -> Result<()> {
+        let request = self
+            .request(
+                reqwest::Method::POST,
+                &format!("/join-requests/{request_id}"),
+            )
+            .json(&DecideJoinPayload { approve });
+        checked(request).await?;
+        Ok(())
+    }
+
+    /// Leave the game. The token is useless afterwards.
+    pub async fn leave(self) -> Result<()> {
+        checked(self.request(reqwest::Method::DELETE, "/players/me")).await?;
+        Ok(())
+    }
+
+    /// Whether the game changed since `version`.
+    pub async fn changes(&self, version: i64) -> Result<ChangesPayload> {
+        let request = self
+            .request(reqwest::Method::GET, "/changes")
+            .query(&[("version", version)]);
+        json(request).await
+    }
+
+    /// Follow the game's changes from `version` on.
+    pub fn subscribe(&self, version: i64) -> Subscription {
+        Subscription {
+            player: self.clone(),
+            version,
+            interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+}
+
+/// The server's administrator; every call is authenticated with the admin
+/// token.
+#[derive(Debug, Clone)]
+pub struct Admin {
+    client: Client,
+    admin_token: String,
+}
+
+impl Admin {
+    fn request(&self, method: reqwest::Method, path: &str) -> RequestBuilder {
+        let url = self.client.url(&format!("/admin{path}"));
+        self.client
+            .http
+            .request(method, url)
+            .bearer_auth(&self.admin_token)
+    }
+
+    /// End a game straight away, without a winner.
+    pub async fn finish_game(&self, game_code: &str) -> Result<FinishedGamePayload> {
+        json(self.request(reqwest::Method::POST, &format!("/games/{game_code}/finish"))).await
+    }
+
+    /// Delete games created more than `older_than_hours` ago, together with
+    /// their players.
+    pub async fn purge_games(&self, older_than_hours: u64) -> Result<PurgedGamesPayload> {
+        let request = self
+            .request(reqwest::Method::POST, "/purge")
+            .json(&PurgeGamesPayload { older_than_hours });
+        json(request).await
+    }
+}
+
+/// Polls a game for changes; see [`Player::subscribe`].
+#[derive(Debug)]
+pub struct Subscription {
+    player: Player,
+    version: i64,
+    interval: Duration,
+}
+
+impl Subscription {
+    /// Poll every `interval` instead of [`DEFAULT_POLL_INTERVAL`].
+    pub fn every(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// The last version seen.
+    pub fn version(&self) -> i64 {
+        self.version
+    }
+
+    /// Wait until the game changes and return its new version. Fetch the
+    /// game with [`Player::game`] to see what changed.
+    pub async fn changed(&mut self) -> Result<i64> {
+        loop {
+            let changes = self.player.changes(self.version).await?;
+            if changes.changed {
+                self.version = changes.current_version;
+                return Ok(self.version);
+            }
+            tokio::time::sleep(self.interval).await;
+        }
+    }
+}
+
+async fn checked(request: RequestBuilder) -> Result<Response> {
+    let response = request.send().await?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    match response.json::<ErrorPayload>().await {
+        Ok(payload) => Err(Error::Api { status, payload }),
+        Err(_) => Err(Error::Status(status)),
+    }
+}
+
+async fn json<T: DeserializeOwned>(request: RequestBuilder) -> Result<T> {
+    Ok(checked(request).await?.json().await?)
+}
@@ -0,0 +1,179 @@
+//! The client against a real server on a local port.
+
+use dashmap::DashMap;
+use hitman::{
+    config::{AdminConfig, Config},
+    create_router,
+    kill_token::KillTokenSigner,
+    metrics::Metrics,
+    repository::MemoryRepository,
+    state::AppState,
+};
+use hitman_client::{
+    types::{GameSettings, GameStatus, RequestStatus},
+    Client, Error,
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tera::Tera;
+
+async fn serve() -> Client {
+    let templates = concat!(env!("CARGO_MANIFEST_DIR"), "/../../templates/**/*");
+    let mut tera = Tera::new(templates).expect("templates must parse");
+    hitman::i18n::register_tera_functions(&mut tera);
+    let state = AppState {
+        db: Arc::new(MemoryRepository::new()),
+        tera,
+        versions: Arc::new(DashMap::new()),
+        kill_tokens: KillTokenSigner::new(vec![b"test-key".to_vec()], 60),
+        config: Arc::new(Config {
+            admin: AdminConfig {
+                token: Some("admin-secret".into()),
+            },
+            ..Config::default()
+        }),
+        metrics: Metrics::new(),
+    };
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, create_router(state)).await });
+    Client::new(format!("http://{addr}/"))
+}
+
+#[tokio::test]
+async fn plays_a_game_to_the_end() {
+    let client = serve().await;
+    let (alice, created) = client.create_game("alice").await.unwrap();
+    assert_eq!(created.game.status, GameStatus::Lobby);
+    let (bob, _) = client.join_game(alice.game_code(), "bob").await.unwrap();
+    let (carol, _) = client.join_game(alice.game_code(), "carol").await.unwrap();
+
+    let err = bob.start().await.unwrap_err();
+    assert_eq!(err.code(), Some("NOT_HOST"), "{err}");
+    let game = alice.start().await.unwrap();
+    assert_eq!(game.status, GameStatus::InProgress);
+
+    let mut changes = carol
+        .subscribe(game.version)
+        .every(Duration::from_millis(10));
+    let mut players = vec![alice, bob, carol];
+    let mut version = game.version;
+    loop {
+        // The first player still alive kills their target.
+        let mut hunter = None;
+        for player in &players {
+            let me = player.game().await.unwrap().me().unwrap().is_alive;
+            if me {
+                hunter = Some(player.clone());
+                break;
+            }
+        }
+        let hunter = hunter.unwrap();
+        let target = hunter
+            .game()
+            .await
+            .unwrap()
+            .me()
+            .unwrap()
+            .target_name
+            .clone();
+        let target = target.unwrap();
+        let mut secret = None;
+        for player in &players {
+            let game = player.game().await.unwrap();
+            let me = game.me().unwrap();
+            if me.name == target {
+                secret = me.secret_code.clone();
+            }
+        }
+        let outcome = hunter.kill(&secret.unwrap()).await.unwrap();
+        assert_eq!(outcome.eliminated_player_name, target);
+        version = changes.changed().await.unwrap().max(version);
+        if outcome.game_over {
+            break;
+        }
+    }
+    assert_eq!(changes.version(), version);
+    let game = players[0].game().await.unwrap();
+    assert_eq!(game.status, GameStatus::Finished);
+
+    players.pop().unwrap().leave().await.unwrap();
+}
+
+#[tokio::test]
+async fn errors_carry_the_server_code() {
+    let client = serve().await;
+    let err = client.join_game("NOPE", "bob").await.unwrap_err();
+    assert_eq!(err.code(), Some("GAME_NOT_FOUND"), "{err}");
+    match err {
+        Error::Api { status, payload } => {
+            assert_eq!(status.as_u16(), 404);
+            assert!(payload.request_id.is_some());
+        }
+        other => panic!("unexpected error {other}"),
+    }
+}
+
+#[tokio::test]
+async fn late_joiners_wait_for_the_host() {
+    let client = serve().await;
+    let (alice, _) = client.create_game("alice").await.unwrap();
+    let settings = GameSettings {
+        late_join: true,
+        late_join_approval: true,
+        ..Default::default()
+    };
+    alice.update_settings(&settings).await.unwrap();
+    client.join_game(alice.game_code(), "bob").await.unwrap();
+    alice.start().await.unwrap();
+
+    let request = match client.join_game(alice.game_code(), "carol").await {
+        Err(Error::Pending(request)) => request,
+        other => panic!("expected a pending join, got {other:?}"),
+    };
+    let pending = alice.join_requests().await.unwrap();
+    assert_eq!(pending[0].player_name, "carol");
+    alice
+        .decide_join_request(pending[0].id, true)
+        .await
+        .unwrap();
+
+    let request = client
+        .join_request(alice.game_code(), &request.claim_token)
+        .await
+        .unwrap();
+    assert_eq!(request.status, RequestStatus::Approved);
+    let carol = client.player(alice.game_code(), &request.auth_token.unwrap());
+    let game = carol.game().await.unwrap();
+    assert_eq!(game.status, GameStatus::InProgress);
+    assert_eq!(game.players.len(), 3);
+}
+
+#[tokio::test]
+async fn admins_finish_and_purge_games() {
+    let client = serve().await;
+    let (alice, _) = client.create_game("alice").await.unwrap();
+    for name in ["bob", "carol"] {
+        client.join_game(alice.game_code(), name).await.unwrap();
+    }
+    let game = alice.start().await.unwrap();
+
+    let err = client
+        .admin("wrong")
+        .finish_game(alice.game_code())
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Some("NOT_ADMIN"), "{err}");
+
+    let admin = client.admin("admin-secret");
+    let mut changes = alice
+        .subscribe(game.version)
+        .every(Duration::from_millis(10));
+    assert!(admin.finish_game(alice.game_code()).await.unwrap().finished);
+    changes.changed().await.unwrap();
+    assert_eq!(alice.game().await.unwrap().status, GameStatus::Finished);
+    assert!(!admin.finish_game(alice.game_code()).await.unwrap().finished);
+
+    let purged = admin.purge_games(0).await.unwrap();
+    assert_eq!(purged.game_codes, vec![alice.game_code().to_string()]);
+}
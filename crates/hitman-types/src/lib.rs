@@ -0,0 +1,599 @@
+//! The wire format of the Hitman HTTP API.
+//!
+//! The server serialises exactly these types, so clients that depend on this
+//! crate cannot drift from it.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[cfg_attr(
+    feature = "sqlx",
+    derive(sqlx::Type),
+    sqlx(type_name = "game_status", rename_all = "lowercase")
+)]
+pub enum GameStatus {
+    Lobby,
+    #[cfg_attr(feature = "sqlx", sqlx(rename = "in_progress"))]
+    InProgress,
+    Finished,
+}
+
+impl Display for GameStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameStatus::Lobby => write!(f, "LOBBY"),
+            GameStatus::InProgress => write!(f, "IN_PROGRESS"),
+            GameStatus::Finished => write!(f, "FINISHED"),
+        }
+    }
+}
+
+/// Something that happened in a game, as recorded in its event log.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum GameEventKind {
+    Joined,
+    Started,
+    Kill,
+    /// Left the lobby.
+    Left,
+    /// Left a running game.
+    Forfeited,
+    /// `actor_name` took over as host.
+    HostChanged,
+    /// `actor_name` is back in the game after the ghosts overturned their
+    /// kill.
+    Revived,
+    Finished,
+}
+
+impl GameEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GameEventKind::Joined => "joined",
+            GameEventKind::Started => "started",
+            GameEventKind::Kill => "kill",
+            GameEventKind::Left => "left",
+            GameEventKind::Forfeited => "forfeited",
+            GameEventKind::HostChanged => "host_changed",
+            GameEventKind::Revived => "revived",
+            GameEventKind::Finished => "finished",
+        }
+    }
+}
+
+impl FromStr for GameEventKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "joined" => Ok(GameEventKind::Joined),
+            "started" => Ok(GameEventKind::Started),
+            "kill" => Ok(GameEventKind::Kill),
+            "left" => Ok(GameEventKind::Left),
+            "forfeited" => Ok(GameEventKind::Forfeited),
+            "host_changed" => Ok(GameEventKind::HostChanged),
+            "revived" => Ok(GameEventKind::Revived),
+            "finished" => Ok(GameEventKind::Finished),
+            _ => Err(format!("unknown game event {s:?}")),
+        }
+    }
+}
+
+/// Rules the host picks for one game, before it starts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[serde(default)]
+pub struct GameSettings {
+    /// Leave the killer out of kills in the activity feed; only the killer
+    /// still sees their own name.
+    pub anonymise_killer: bool,
+    /// Eliminated players stay on as ghosts: they see the whole kill feed
+    /// and vote on disputed kills.
+    pub ghosts: bool,
+    /// Ghosts may each send one anonymous hint to a living player.
+    pub ghost_hints: bool,
+    /// Eliminated players get a chat of their own while the game runs.
+    pub dead_chat: bool,
+    /// Spectators see the host's announcements and the lobby chat.
+    pub spectator_chat: bool,
+    /// Start the game as soon as every player in the lobby is ready and
+    /// there are at least `min_players` of them.
+    pub auto_start: bool,
+    /// Fewest players the game starts with; never fewer than two.
+    pub min_players: Option<u32>,
+    /// Most players the lobby takes, within the server's own limit.
+    pub max_players: Option<u32>,
+    /// Newcomers may still join once the game runs; each is spliced into
+    /// the ring at a random point.
+    pub late_join: bool,
+    /// Late joiners wait for the host to let them in.
+    pub late_join_approval: bool,
+}
+
+/// What the requesting player can still do in a game.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum PlayerRole {
+    Alive,
+    /// Eliminated from a running game with ghosts on.
+    Ghost,
+    Eliminated,
+}
+
+/// Where a disputed kill stands.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum DisputeStatus {
+    Open,
+    /// The ghosts agreed the kill stands.
+    Upheld,
+    /// The ghosts voted the kill down and the victim is back in the game.
+    Overturned,
+}
+
+impl DisputeStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DisputeStatus::Open => "open",
+            DisputeStatus::Upheld => "upheld",
+            DisputeStatus::Overturned => "overturned",
+        }
+    }
+}
+
+impl FromStr for DisputeStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(DisputeStatus::Open),
+            "upheld" => Ok(DisputeStatus::Upheld),
+            "overturned" => Ok(DisputeStatus::Overturned),
+            _ => Err(format!("unknown dispute status {s:?}")),
+        }
+    }
+}
+
+/// Where a chat message was posted; see [`ChatMessagePayload`].
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ChatChannel {
+    /// From the host, to everyone.
+    Announcements,
+    /// Everyone, until the game starts.
+    Lobby,
+    /// Eliminated players only, when the host allows it.
+    Dead,
+}
+
+impl ChatChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChatChannel::Announcements => "announcements",
+            ChatChannel::Lobby => "lobby",
+            ChatChannel::Dead => "dead",
+        }
+    }
+}
+
+impl FromStr for ChatChannel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "announcements" => Ok(ChatChannel::Announcements),
+            "lobby" => Ok(ChatChannel::Lobby),
+            "dead" => Ok(ChatChannel::Dead),
+            _ => Err(format!("unknown chat channel {s:?}")),
+        }
+    }
+}
+
+/// Where a request for the host to let a player in stands: back in after
+/// losing their link, or into a game that is already running.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum RequestStatus {
+    /// Waiting for the host.
+    Pending,
+    /// The host let the player in with a fresh auth token.
+    Approved,
+    Denied,
+}
+
+impl RequestStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RequestStatus::Pending => "pending",
+            RequestStatus::Approved => "approved",
+            RequestStatus::Denied => "denied",
+        }
+    }
+}
+
+impl FromStr for RequestStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(RequestStatus::Pending),
+            "approved" => Ok(RequestStatus::Approved),
+            "denied" => Ok(RequestStatus::Denied),
+            _ => Err(format!("unknown request status {s:?}")),
+        }
+    }
+}
+
+// --- Client-to-Server Payloads ---
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct CreateGamePayload {
+    /// Name of the host, 1 to 32 characters.
+    pub player_name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct JoinGamePayload {
+    /// Name to join under, unique within the game and 1 to 32 characters.
+    pub player_name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct KillPayload {
+    /// The victim's secret code, or a kill token scanned from their QR code.
+    pub secret_code: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct OpenDisputePayload {
+    /// The kill in the activity feed that eliminated you.
+    pub event_id: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct DisputeVotePayload {
+    /// `true` to undo the kill, `false` to let it stand.
+    pub overturn: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct SendHintPayload {
+    /// The living player to whisper to.
+    pub player_id: i32,
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct PostMessagePayload {
+    pub channel: ChatChannel,
+    pub body: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct RecoverPayload {
+    /// The name you joined the game under.
+    pub player_name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct DecideRecoveryPayload {
+    /// `true` to let the player back in, `false` to turn them away.
+    pub approve: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct DecideJoinPayload {
+    /// `true` to let the newcomer into the game, `false` to turn them away.
+    pub approve: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct ReadyPayload {
+    pub ready: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct PurgeGamesPayload {
+    /// Delete games created more than this many hours ago.
+    pub older_than_hours: u64,
+}
+
+// --- Server-to-Client Payloads ---
+
+/// A game as seen by one of its players.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct GamePayload {
+    pub code: String,
+    pub status: GameStatus,
+    pub host_id: Option<i32>,
+    pub players: Vec<PlayerPayload>,
+    /// What the requesting player can still do.
+    pub role: PlayerRole,
+    /// Bumped on every change; poll `/changes` with it.
+    pub version: i64,
+}
+
+impl GamePayload {
+    /// The requesting player, the only one whose secret code is included.
+    pub fn me(&self) -> Option<&PlayerPayload> {
+        self.players.iter().find(|p| p.secret_code.is_some())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct PlayerPayload {
+    pub id: i32,
+    pub name: String,
+    pub is_alive: bool,
+    /// Marked ready in the lobby.
+    pub is_ready: bool,
+    /// Only ever present on the requesting player.
+    pub target_name: Option<String>,
+    /// Only ever present on the requesting player.
+    pub secret_code: Option<String>,
+}
+
+/// Returned when creating or joining a game: everything a client needs to
+/// act as the new player.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct PlayerSessionPayload {
+    pub player: PlayerPayload,
+    /// Bearer token for every later request as this player.
+    pub auth_token: String,
+    pub game: GamePayload,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct ChangesPayload {
+    pub changed: bool,
+    pub current_version: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct KillResponsePayload {
+    pub eliminated_player_name: String,
+    pub killer_name: String,
+    /// `None` when the kill ended the game.
+    pub new_target_name: Option<String>,
+    pub game_over: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct SecretRotatedPayload {
+    pub secret_code: String,
+    pub rotations_left: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct KillTokenPayload {
+    pub token: String,
+    /// Unix seconds.
+    pub expires_at: i64,
+    pub refresh_in_secs: i64,
+}
+
+/// One entry of a game's event log. `actor_name` did it, to `subject_name`:
+/// the killer and the victim of a kill, the winner of a finished game. The
+/// killer is `None` in games that anonymise killers.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct GameEventPayload {
+    pub id: i64,
+    pub kind: GameEventKind,
+    pub actor_name: Option<String>,
+    pub subject_name: Option<String>,
+    /// Unix seconds.
+    pub created_at: i64,
+}
+
+/// A page of a game's activity feed, newest first.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct ActivityPayload {
+    pub events: Vec<GameEventPayload>,
+    /// Pass as `before` to get the next, older page; `None` on the last one.
+    pub next_cursor: Option<i64>,
+}
+
+/// A kill the victim disputed, as the ghosts vote on it.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct DisputePayload {
+    pub id: i64,
+    /// The disputed kill in the activity feed.
+    pub event_id: i64,
+    pub victim_name: Option<String>,
+    pub killer_name: Option<String>,
+    pub status: DisputeStatus,
+    pub overturn_votes: u32,
+    pub uphold_votes: u32,
+    /// Votes either way that settle the dispute: a majority of the ghosts
+    /// other than the victim and the killer.
+    pub votes_needed: u32,
+    /// Whether the requesting ghost may vote: the dispute is open and they
+    /// were neither the victim nor the killer.
+    pub can_vote: bool,
+    /// How the requesting ghost voted; `None` before they did.
+    pub my_vote: Option<bool>,
+}
+
+/// Everything a ghost can do in their game.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct GhostPayload {
+    /// Oldest first.
+    pub disputes: Vec<DisputePayload>,
+    /// The kill that eliminated the requesting ghost, while they can still
+    /// dispute it.
+    pub disputable_event_id: Option<i64>,
+    /// Whether the game allows hints and this ghost has not sent theirs.
+    pub can_hint: bool,
+}
+
+/// An anonymous hint from a ghost.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct HintPayload {
+    pub id: i64,
+    pub message: String,
+    /// Unix seconds.
+    pub created_at: i64,
+}
+
+/// A message in one of a game's chat channels.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct ChatMessagePayload {
+    pub id: i64,
+    pub channel: ChatChannel,
+    /// `None` once the sender left the game.
+    pub sender_id: Option<i32>,
+    pub sender_name: String,
+    pub body: String,
+    /// Unix seconds.
+    pub created_at: i64,
+}
+
+/// A request to get back into a game, as the player who made it sees it.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct RecoveryPayload {
+    /// Poll the request with this; only its holder learns the outcome.
+    pub claim_token: String,
+    pub status: RequestStatus,
+    /// The player's new auth token, once the host approved. The old one no
+    /// longer works.
+    pub auth_token: Option<String>,
+}
+
+/// A request to get back into a game, as whoever may answer it sees it.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct PendingRecoveryPayload {
+    pub id: i64,
+    pub player_id: i32,
+    pub player_name: String,
+    /// Unix seconds.
+    pub created_at: i64,
+}
+
+/// A request to join a running game, as the newcomer who made it sees it.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct JoinRequestPayload {
+    /// Poll the request with this; only its holder learns the outcome.
+    pub claim_token: String,
+    pub status: RequestStatus,
+    /// The newcomer's auth token, once the host let them in.
+    pub auth_token: Option<String>,
+}
+
+/// A request to join a running game, as the host sees it.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct PendingJoinPayload {
+    pub id: i64,
+    pub player_name: String,
+    /// Unix seconds.
+    pub created_at: i64,
+}
+
+/// A player as spectators see them: never a target or a secret.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct SpectatedPlayerPayload {
+    pub id: i32,
+    pub name: String,
+    pub is_alive: bool,
+}
+
+/// The public scoreboard of a game.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct SpectatorPayload {
+    pub code: String,
+    pub status: GameStatus,
+    pub players: Vec<SpectatedPlayerPayload>,
+    /// Oldest first.
+    pub events: Vec<GameEventPayload>,
+    /// Announcements and lobby chat, oldest first; empty unless the host
+    /// lets spectators read along.
+    pub messages: Vec<ChatMessagePayload>,
+    /// Unix seconds; `None` until the game starts.
+    pub started_at: Option<i64>,
+    /// Unix seconds; `None` until the game is over.
+    pub finished_at: Option<i64>,
+    pub version: i64,
+}
+
+/// Read-only access to a game's scoreboard, for the host to share.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct SpectatorLinkPayload {
+    pub spectator_token: String,
+    /// Path of the spectator page, relative to the server.
+    pub path: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct FinishedGamePayload {
+    pub game_code: String,
+    /// `false` when the game had already finished.
+    pub finished: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct PurgedGamesPayload {
+    /// Codes of the deleted games.
+    pub game_codes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct HealthPayload {
+    pub status: String,
+}
+
+/// Body of every error response.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct ErrorPayload {
+    /// Human readable, in the request's locale.
+    pub error: String,
+    /// Stable machine readable code, e.g. `WRONG_TARGET`.
+    #[cfg_attr(feature = "utoipa", schema(example = "WRONG_TARGET"))]
+    pub code: String,
+    /// Quote this when reporting a problem.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
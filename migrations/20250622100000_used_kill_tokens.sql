@@ -0,0 +1,7 @@
+-- Nonces of signed kill tokens that have already been used for a kill, so a
+-- captured QR code cannot be replayed. Rows can be dropped once expired.
+CREATE TABLE used_kill_tokens (
+    nonce TEXT PRIMARY KEY,
+    game_id INTEGER NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    expires_at TIMESTAMPTZ NOT NULL
+);
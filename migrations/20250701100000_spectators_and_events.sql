@@ -0,0 +1,22 @@
+-- A separate read-only token per game for spectators, so the spectator link
+-- never grants anything a player's link would. The default only fills in
+-- existing games; new ones get a random UUID from the application.
+ALTER TABLE games
+    ADD COLUMN spectator_token TEXT NOT NULL UNIQUE
+        DEFAULT md5(random()::text || clock_timestamp()::text);
+ALTER TABLE games ALTER COLUMN spectator_token DROP DEFAULT;
+
+-- What happened in a game, in order: joins, the start, kills, leaves and the
+-- end. Names are copied so the feed survives players leaving the lobby.
+CREATE TABLE game_events (
+    id BIGSERIAL PRIMARY KEY,
+    game_id INTEGER NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    actor_id INTEGER,
+    actor_name TEXT,
+    subject_id INTEGER,
+    subject_name TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX game_events_game_id_idx ON game_events (game_id, id);
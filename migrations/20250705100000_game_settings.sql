@@ -0,0 +1,2 @@
+-- Per-game rules the host picks in the lobby, see `GameSettings`.
+ALTER TABLE games ADD COLUMN settings JSONB NOT NULL DEFAULT '{}';
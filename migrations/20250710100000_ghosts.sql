@@ -0,0 +1,32 @@
+-- Kills a victim asked the ghosts of their game to overturn. One dispute per
+-- kill; `killer_id` is copied from the event so the vote can leave them out.
+CREATE TABLE kill_disputes (
+    id BIGSERIAL PRIMARY KEY,
+    game_id INTEGER NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    event_id BIGINT NOT NULL UNIQUE REFERENCES game_events(id) ON DELETE CASCADE,
+    victim_id INTEGER NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    killer_id INTEGER,
+    status TEXT NOT NULL DEFAULT 'open',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX kill_disputes_game_id_idx ON kill_disputes (game_id, id);
+
+CREATE TABLE dispute_votes (
+    dispute_id BIGINT NOT NULL REFERENCES kill_disputes(id) ON DELETE CASCADE,
+    player_id INTEGER NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    overturn BOOLEAN NOT NULL,
+    PRIMARY KEY (dispute_id, player_id)
+);
+
+-- Every ghost gets to send a single anonymous hint.
+CREATE TABLE ghost_hints (
+    id BIGSERIAL PRIMARY KEY,
+    game_id INTEGER NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    sender_id INTEGER NOT NULL UNIQUE REFERENCES players(id) ON DELETE CASCADE,
+    recipient_id INTEGER NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    message TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX ghost_hints_game_id_idx ON ghost_hints (game_id, id);
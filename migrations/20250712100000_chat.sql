@@ -0,0 +1,13 @@
+-- A game's chat: host announcements, lobby chat and the dead players' chat.
+-- The sender's name is copied so messages outlive players who leave.
+CREATE TABLE chat_messages (
+    id BIGSERIAL PRIMARY KEY,
+    game_id INTEGER NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    channel TEXT NOT NULL,
+    sender_id INTEGER REFERENCES players(id) ON DELETE SET NULL,
+    sender_name TEXT NOT NULL,
+    body TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX chat_messages_game_id_idx ON chat_messages (game_id, id);
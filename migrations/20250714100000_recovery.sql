@@ -0,0 +1,14 @@
+-- Players who lost their link ask the host to let them back in. Whoever
+-- asked holds `claim_token` and collects the fresh `auth_token` with it once
+-- the host approves.
+CREATE TABLE recovery_requests (
+    id BIGSERIAL PRIMARY KEY,
+    game_id INTEGER NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    player_id INTEGER NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    claim_token TEXT NOT NULL UNIQUE,
+    status TEXT NOT NULL DEFAULT 'pending',
+    auth_token TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX recovery_requests_game_id_idx ON recovery_requests (game_id, id);
@@ -0,0 +1,14 @@
+-- Newcomers asking the host to let them into a game that already runs. As
+-- with recovery requests, whoever asked holds `claim_token` and collects
+-- their `auth_token` with it once the host approves.
+CREATE TABLE join_requests (
+    id BIGSERIAL PRIMARY KEY,
+    game_id INTEGER NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    player_name TEXT NOT NULL,
+    claim_token TEXT NOT NULL UNIQUE,
+    status TEXT NOT NULL DEFAULT 'pending',
+    auth_token TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX join_requests_game_id_idx ON join_requests (game_id, id);
@@ -0,0 +1,4 @@
+-- Track how often a player has regenerated their secret code and when they
+-- last did so (unix seconds), so rotations can be rate limited per game.
+ALTER TABLE players ADD COLUMN secret_rotations INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE players ADD COLUMN secret_rotated_at INTEGER;
@@ -0,0 +1,8 @@
+-- Nonces of signed kill tokens that have already been used for a kill, so a
+-- captured QR code cannot be replayed. Rows can be dropped once expired
+-- (`expires_at` is in unix seconds).
+CREATE TABLE used_kill_tokens (
+    nonce TEXT PRIMARY KEY,
+    game_id INTEGER NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    expires_at INTEGER NOT NULL
+);
@@ -0,0 +1,19 @@
+-- A separate read-only token per game for spectators. Existing games get a
+-- random one here; new ones get a random UUID from the application.
+ALTER TABLE games ADD COLUMN spectator_token TEXT;
+UPDATE games SET spectator_token = lower(hex(randomblob(16)));
+CREATE UNIQUE INDEX games_spectator_token_idx ON games (spectator_token);
+
+-- What happened in a game, in order (`created_at` is in unix seconds).
+CREATE TABLE game_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    game_id INTEGER NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    actor_id INTEGER,
+    actor_name TEXT,
+    subject_id INTEGER,
+    subject_name TEXT,
+    created_at INTEGER NOT NULL
+);
+
+CREATE INDEX game_events_game_id_idx ON game_events (game_id, id);
@@ -0,0 +1,2 @@
+-- Per-game rules the host picks in the lobby, see `GameSettings` (JSON).
+ALTER TABLE games ADD COLUMN settings TEXT NOT NULL DEFAULT '{}';
@@ -0,0 +1,12 @@
+-- A game's chat (`created_at` is in unix seconds).
+CREATE TABLE chat_messages (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    game_id INTEGER NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    channel TEXT NOT NULL,
+    sender_id INTEGER REFERENCES players(id) ON DELETE SET NULL,
+    sender_name TEXT NOT NULL,
+    body TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+
+CREATE INDEX chat_messages_game_id_idx ON chat_messages (game_id, id);
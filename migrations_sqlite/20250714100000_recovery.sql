@@ -0,0 +1,13 @@
+-- Players who lost their link ask the host to let them back in
+-- (`created_at` is in unix seconds).
+CREATE TABLE recovery_requests (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    game_id INTEGER NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    player_id INTEGER NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+    claim_token TEXT NOT NULL UNIQUE,
+    status TEXT NOT NULL DEFAULT 'pending',
+    auth_token TEXT,
+    created_at INTEGER NOT NULL
+);
+
+CREATE INDEX recovery_requests_game_id_idx ON recovery_requests (game_id, id);
@@ -0,0 +1,2 @@
+-- Players mark themselves ready in the lobby, see `GameSettings::auto_start`.
+ALTER TABLE players ADD COLUMN is_ready BOOLEAN NOT NULL DEFAULT FALSE;
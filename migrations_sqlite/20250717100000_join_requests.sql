@@ -0,0 +1,13 @@
+-- Newcomers asking the host to let them into a game that already runs
+-- (`created_at` is in unix seconds).
+CREATE TABLE join_requests (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    game_id INTEGER NOT NULL REFERENCES games(id) ON DELETE CASCADE,
+    player_name TEXT NOT NULL,
+    claim_token TEXT NOT NULL UNIQUE,
+    status TEXT NOT NULL DEFAULT 'pending',
+    auth_token TEXT,
+    created_at INTEGER NOT NULL
+);
+
+CREATE INDEX join_requests_game_id_idx ON join_requests (game_id, id);
@@ -0,0 +1,4 @@
+-- A player has at most one recovery request waiting for an answer; a second
+-- one has to wait until the host answers the first.
+CREATE UNIQUE INDEX recovery_requests_pending_idx
+    ON recovery_requests (player_id) WHERE status = 'pending';
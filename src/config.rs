@@ -0,0 +1,518 @@
+//! Settings, loaded once at startup from an optional TOML file and the
+//! environment, then validated before anything else starts.
+//!
+//! The file is read from `HITMAN_CONFIG` when set. Environment variables win
+//! over the file, so a container can override a single value without
+//! shipping a whole file.
+
+use crate::engine;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("cannot read config file {}: {source}", path.display())]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid config file {}: {source}", path.display())]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("invalid value {value:?} for {var}: {reason}")]
+    Env {
+        var: &'static str,
+        value: String,
+        reason: String,
+    },
+    #[error("invalid setting {key}: {reason}")]
+    Invalid { key: &'static str, reason: String },
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub game: GameConfig,
+    pub kill_tokens: KillTokenConfig,
+    pub rate_limits: RateLimitConfig,
+    pub retention: RetentionConfig,
+    pub features: FeatureConfig,
+    pub admin: AdminConfig,
+    pub log: LogConfig,
+    pub telemetry: TelemetryConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// `BIND_ADDRESS`
+    pub bind: SocketAddr,
+    /// `CORS_ORIGINS`, comma separated. Empty allows same-origin requests
+    /// only.
+    pub cors_origins: Vec<String>,
+    /// `BEHIND_TLS`: the app is served over HTTPS (usually by a reverse
+    /// proxy), so browsers may be told to never use plain HTTP.
+    pub behind_tls: bool,
+    /// `SHUTDOWN_TIMEOUT_SECS`: how long to let in-flight requests finish
+    /// after SIGTERM/SIGINT before exiting anyway.
+    pub shutdown_timeout_secs: u64,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind: SocketAddr::from(([0, 0, 0, 0], 3000)),
+            cors_origins: Vec::new(),
+            behind_tls: false,
+            shutdown_timeout_secs: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    /// `DATABASE_URL`: a Postgres URL, a `sqlite:` URL or `memory`.
+    pub url: String,
+    /// `DATABASE_MAX_CONNECTIONS`
+    pub max_connections: u32,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            max_connections: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    /// `GAME_CODE_LENGTH`
+    pub code_length: usize,
+    /// `GAME_MAX_PLAYERS`: the most players any lobby takes; hosts may
+    /// pick a lower limit for their own game.
+    pub max_players: u32,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            code_length: 4,
+            max_players: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KillTokenConfig {
+    /// `KILL_TOKEN_TTL_SECS`
+    pub ttl_secs: i64,
+    /// `KILL_TOKEN_KEYS`, comma separated, newest first. Without keys a
+    /// random one is used, so tokens do not survive a restart.
+    pub keys: Vec<String>,
+}
+
+impl Default for KillTokenConfig {
+    fn default() -> Self {
+        Self {
+            ttl_secs: 60,
+            keys: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// `SECRET_ROTATION_COOLDOWN_SECS`
+    pub secret_rotation_cooldown_secs: i64,
+    /// `SECRET_ROTATION_LIMIT`: rotations allowed per player per game.
+    pub secret_rotation_limit: i32,
+    /// `CHAT_MESSAGE_LIMIT`: chat messages a player may send per window.
+    pub chat_message_limit: u32,
+    /// `CHAT_WINDOW_SECS`
+    pub chat_window_secs: i64,
+    /// `RECOVERY_REQUEST_LIMIT`: recovery requests a game takes per window.
+    pub recovery_request_limit: u32,
+    /// `RECOVERY_WINDOW_SECS`
+    pub recovery_window_secs: i64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            secret_rotation_cooldown_secs: 300,
+            secret_rotation_limit: 3,
+            chat_message_limit: 5,
+            chat_window_secs: 30,
+            recovery_request_limit: 5,
+            recovery_window_secs: 600,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetentionConfig {
+    /// `RETENTION_GAME_TTL_HOURS`: games created longer ago than this are
+    /// deleted, whatever their state. `0` keeps games forever.
+    pub game_ttl_hours: u64,
+    /// `RETENTION_PURGE_INTERVAL_SECS`
+    pub purge_interval_secs: u64,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            game_ttl_hours: 0,
+            purge_interval_secs: 3600,
+        }
+    }
+}
+
+impl RetentionConfig {
+    pub fn game_ttl(&self) -> Option<Duration> {
+        (self.game_ttl_hours > 0).then(|| Duration::from_secs(self.game_ttl_hours * 3600))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeatureConfig {
+    /// `FEATURE_KILL_TOKENS`: show signed, expiring kill tokens in the QR
+    /// code. When off the QR code holds the bare secret.
+    pub kill_tokens: bool,
+    /// `FEATURE_SECRET_ROTATION`: let players replace their secret code.
+    pub secret_rotation: bool,
+    /// `FEATURE_SPECTATORS`: let hosts share a read-only scoreboard link.
+    pub spectators: bool,
+}
+
+impl Default for FeatureConfig {
+    fn default() -> Self {
+        Self {
+            kill_tokens: true,
+            secret_rotation: true,
+            spectators: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AdminConfig {
+    /// `ADMIN_TOKEN`: bearer token for the admin endpoints, which are
+    /// disabled without one.
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    /// `LOG_FORMAT`: `text` for people, `json` for log collectors.
+    pub format: LogFormat,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TelemetryConfig {
+    /// `OTEL_EXPORTER_OTLP_ENDPOINT`: base URL of an OTLP/HTTP collector,
+    /// e.g. `http://otel-collector:4318`. Without one no traces are exported.
+    pub otlp_endpoint: Option<String>,
+    /// `OTEL_SERVICE_NAME`
+    pub service_name: String,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            otlp_endpoint: None,
+            service_name: "hitman".into(),
+        }
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err("expected text or json".into()),
+        }
+    }
+}
+
+impl Config {
+    /// Load from `HITMAN_CONFIG` (if set) and the process environment.
+    pub fn load() -> Result<Self, ConfigError> {
+        let file = dotenvy::var("HITMAN_CONFIG").ok().filter(|p| !p.is_empty());
+        Self::from_sources(file.as_deref().map(Path::new), |var| dotenvy::var(var).ok())
+    }
+
+    /// Like [`Config::load`], but `database.url` may be left unset: for tools
+    /// that talk to a running server rather than to its database.
+    pub fn load_without_database() -> Result<Self, ConfigError> {
+        let file = dotenvy::var("HITMAN_CONFIG").ok().filter(|p| !p.is_empty());
+        let config = Self::read(file.as_deref().map(Path::new), &|var| {
+            dotenvy::var(var).ok()
+        })?;
+        config.validate_settings()?;
+        Ok(config)
+    }
+
+    /// Layer `env` over the TOML file at `file` (if any) over the defaults,
+    /// and validate the result.
+    pub fn from_sources(
+        file: Option<&Path>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, ConfigError> {
+        let config = Self::read(file, &env)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn read(
+        file: Option<&Path>,
+        env: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Self, ConfigError> {
+        let mut config = match file {
+            Some(path) => {
+                let text = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+                    path: path.to_path_buf(),
+                    source,
+                })?;
+                toml::from_str(&text).map_err(|source| ConfigError::Parse {
+                    path: path.to_path_buf(),
+                    source,
+                })?
+            }
+            None => Config::default(),
+        };
+        config.apply_env(env)?;
+        Ok(config)
+    }
+
+    fn apply_env(&mut self, env: &dyn Fn(&str) -> Option<String>) -> Result<(), ConfigError> {
+        let env = Env(env);
+        env.parse("BIND_ADDRESS", &mut self.server.bind)?;
+        env.list("CORS_ORIGINS", &mut self.server.cors_origins);
+        env.flag("BEHIND_TLS", &mut self.server.behind_tls)?;
+        env.parse(
+            "SHUTDOWN_TIMEOUT_SECS",
+            &mut self.server.shutdown_timeout_secs,
+        )?;
+        env.string("DATABASE_URL", &mut self.database.url);
+        env.parse(
+            "DATABASE_MAX_CONNECTIONS",
+            &mut self.database.max_connections,
+        )?;
+        env.parse("GAME_CODE_LENGTH", &mut self.game.code_length)?;
+        env.parse("GAME_MAX_PLAYERS", &mut self.game.max_players)?;
+        env.parse("KILL_TOKEN_TTL_SECS", &mut self.kill_tokens.ttl_secs)?;
+        env.list("KILL_TOKEN_KEYS", &mut self.kill_tokens.keys);
+        env.parse(
+            "SECRET_ROTATION_COOLDOWN_SECS",
+            &mut self.rate_limits.secret_rotation_cooldown_secs,
+        )?;
+        env.parse(
+            "SECRET_ROTATION_LIMIT",
+            &mut self.rate_limits.secret_rotation_limit,
+        )?;
+        env.parse(
+            "CHAT_MESSAGE_LIMIT",
+            &mut self.rate_limits.chat_message_limit,
+        )?;
+        env.parse("CHAT_WINDOW_SECS", &mut self.rate_limits.chat_window_secs)?;
+        env.parse(
+            "RECOVERY_REQUEST_LIMIT",
+            &mut self.rate_limits.recovery_request_limit,
+        )?;
+        env.parse(
+            "RECOVERY_WINDOW_SECS",
+            &mut self.rate_limits.recovery_window_secs,
+        )?;
+        env.parse(
+            "RETENTION_GAME_TTL_HOURS",
+            &mut self.retention.game_ttl_hours,
+        )?;
+        env.parse(
+            "RETENTION_PURGE_INTERVAL_SECS",
+            &mut self.retention.purge_interval_secs,
+        )?;
+        env.flag("FEATURE_KILL_TOKENS", &mut self.features.kill_tokens)?;
+        env.flag(
+            "FEATURE_SECRET_ROTATION",
+            &mut self.features.secret_rotation,
+        )?;
+        env.flag("FEATURE_SPECTATORS", &mut self.features.spectators)?;
+        if let Some(token) = env.get("ADMIN_TOKEN") {
+            self.admin.token = Some(token);
+        }
+        env.parse("LOG_FORMAT", &mut self.log.format)?;
+        if let Some(endpoint) = env.get("OTEL_EXPORTER_OTLP_ENDPOINT") {
+            self.telemetry.otlp_endpoint = Some(endpoint);
+        }
+        env.string("OTEL_SERVICE_NAME", &mut self.telemetry.service_name);
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.database.url.is_empty() {
+            return Err(ConfigError::Invalid {
+                key: "database.url",
+                reason: "set DATABASE_URL (use `memory` to run without a database)".to_string(),
+            });
+        }
+        self.validate_settings()
+    }
+
+    /// Everything [`Config::validate`] checks apart from the database URL.
+    fn validate_settings(&self) -> Result<(), ConfigError> {
+        let invalid = |key, reason: &str| {
+            Err(ConfigError::Invalid {
+                key,
+                reason: reason.to_string(),
+            })
+        };
+        if self.database.max_connections == 0 {
+            return invalid("database.max_connections", "must be at least 1");
+        }
+        if !(4..=12).contains(&self.game.code_length) {
+            return invalid("game.code_length", "must be between 4 and 12");
+        }
+        if (self.game.max_players as usize) < engine::MIN_PLAYERS {
+            return invalid("game.max_players", "must be at least 2");
+        }
+        if self.kill_tokens.ttl_secs <= 0 {
+            return invalid("kill_tokens.ttl_secs", "must be positive");
+        }
+        if self.rate_limits.secret_rotation_cooldown_secs < 0 {
+            return invalid(
+                "rate_limits.secret_rotation_cooldown_secs",
+                "must not be negative",
+            );
+        }
+        if self.rate_limits.secret_rotation_limit < 0 {
+            return invalid("rate_limits.secret_rotation_limit", "must not be negative");
+        }
+        if self.rate_limits.chat_message_limit == 0 {
+            return invalid("rate_limits.chat_message_limit", "must be at least 1");
+        }
+        if self.rate_limits.chat_window_secs < 0 {
+            return invalid("rate_limits.chat_window_secs", "must not be negative");
+        }
+        if self.rate_limits.recovery_request_limit == 0 {
+            return invalid("rate_limits.recovery_request_limit", "must be at least 1");
+        }
+        if self.rate_limits.recovery_window_secs < 0 {
+            return invalid("rate_limits.recovery_window_secs", "must not be negative");
+        }
+        if self.retention.purge_interval_secs == 0 {
+            return invalid("retention.purge_interval_secs", "must be positive");
+        }
+        if let Some(endpoint) = &self.telemetry.otlp_endpoint {
+            if !(endpoint.starts_with("http://") || endpoint.starts_with("https://")) {
+                return Err(ConfigError::Invalid {
+                    key: "telemetry.otlp_endpoint",
+                    reason: format!("{endpoint:?} is not an http(s) URL"),
+                });
+            }
+        }
+        for origin in &self.server.cors_origins {
+            let well_formed = origin
+                .strip_prefix("https://")
+                .or_else(|| origin.strip_prefix("http://"))
+                .is_some_and(|host| !host.is_empty() && !host.contains('/'));
+            if !well_formed {
+                return Err(ConfigError::Invalid {
+                    key: "server.cors_origins",
+                    reason: format!(
+                        "{origin:?} is not an origin like https://example.com (no path)"
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Environment lookups that only touch a setting when its variable is set.
+struct Env<'a>(&'a dyn Fn(&str) -> Option<String>);
+
+impl Env<'_> {
+    fn get(&self, var: &str) -> Option<String> {
+        (self.0)(var).filter(|v| !v.trim().is_empty())
+    }
+
+    fn string(&self, var: &str, target: &mut String) {
+        if let Some(value) = self.get(var) {
+            *target = value;
+        }
+    }
+
+    fn list(&self, var: &str, target: &mut Vec<String>) {
+        if let Some(value) = self.get(var) {
+            *target = value
+                .split(',')
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+    }
+
+    fn parse<T>(&self, var: &'static str, target: &mut T) -> Result<(), ConfigError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        if let Some(value) = self.get(var) {
+            *target = value.trim().parse().map_err(|e: T::Err| ConfigError::Env {
+                var,
+                reason: e.to_string(),
+                value,
+            })?;
+        }
+        Ok(())
+    }
+
+    fn flag(&self, var: &'static str, target: &mut bool) -> Result<(), ConfigError> {
+        if let Some(value) = self.get(var) {
+            *target = match value.trim().to_ascii_lowercase().as_str() {
+                "1" | "true" | "yes" | "on" => true,
+                "0" | "false" | "no" | "off" => false,
+                _ => {
+                    return Err(ConfigError::Env {
+                        var,
+                        value,
+                        reason: "expected true or false".into(),
+                    })
+                }
+            };
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+use crate::db::Db;
+use crate::errors::AppError;
+use crate::models::{ChatChannel, ChatMessage, Player};
+use crate::repository::parse_chat_channel;
+use tracing::instrument;
+
+impl Db {
+    #[instrument(skip_all, fields(game_id = sender.game_id, sender_id = sender.id))]
+    pub async fn post_message(
+        &self,
+        channel: ChatChannel,
+        sender: &Player,
+        body: &str,
+    ) -> Result<ChatMessage, AppError> {
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO chat_messages (game_id, channel, sender_id, sender_name, body)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, EXTRACT(EPOCH FROM created_at)::BIGINT AS "created_at!"
+            "#,
+            sender.game_id,
+            channel.as_str(),
+            sender.id,
+            sender.name,
+            body
+        )
+        .fetch_one(&self.0)
+        .await?;
+        Ok(ChatMessage {
+            id: row.id,
+            game_id: sender.game_id,
+            channel,
+            sender_id: Some(sender.id),
+            sender_name: sender.name.clone(),
+            body: body.to_string(),
+            created_at: row.created_at,
+        })
+    }
+
+    #[instrument(skip_all, fields(game_id))]
+    pub async fn get_messages(&self, game_id: i32) -> Result<Vec<ChatMessage>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                id,
+                channel,
+                sender_id,
+                sender_name,
+                body,
+                EXTRACT(EPOCH FROM created_at)::BIGINT AS "created_at!"
+            FROM chat_messages
+            WHERE game_id = $1
+            ORDER BY id
+            "#,
+            game_id
+        )
+        .fetch_all(&self.0)
+        .await?;
+        rows.into_iter()
+            .map(|row| {
+                Ok(ChatMessage {
+                    id: row.id,
+                    game_id,
+                    channel: parse_chat_channel(&row.channel)?,
+                    sender_id: row.sender_id,
+                    sender_name: row.sender_name,
+                    body: row.body,
+                    created_at: row.created_at,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,119 @@
+use super::super::Db;
+use crate::errors::AppError;
+use crate::models::{GameEvent, NewGameEvent};
+use crate::repository::parse_event_kind;
+use tracing::instrument;
+
+impl Db {
+    /// Append to a game's event log as part of the change it describes.
+    #[instrument(skip_all, fields(game_id, kind = event.kind.as_str()))]
+    pub(crate) async fn record_event_in_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        game_id: i32,
+        event: &NewGameEvent,
+    ) -> Result<(), AppError> {
+        sqlx::query!(
+            "INSERT INTO game_events (game_id, kind, actor_id, actor_name, subject_id, subject_name)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+            game_id,
+            event.kind.as_str(),
+            event.actor_id,
+            event.actor_name,
+            event.subject_id,
+            event.subject_name
+        )
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(game_id))]
+    pub async fn get_events(&self, game_id: i32) -> Result<Vec<GameEvent>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                id,
+                kind,
+                actor_id,
+                actor_name,
+                subject_id,
+                subject_name,
+                EXTRACT(EPOCH FROM created_at)::BIGINT AS "created_at!"
+            FROM game_events
+            WHERE game_id = $1
+            ORDER BY id
+            "#,
+            game_id
+        )
+        .fetch_all(&self.0)
+        .await?;
+        rows.into_iter()
+            .map(|row| {
+                Ok(GameEvent {
+                    id: row.id,
+                    game_id,
+                    kind: parse_event_kind(&row.kind)?,
+                    actor_id: row.actor_id,
+                    actor_name: row.actor_name,
+                    subject_id: row.subject_id,
+                    subject_name: row.subject_name,
+                    created_at: row.created_at,
+                })
+            })
+            .collect()
+    }
+
+    #[instrument(skip_all, fields(game_id))]
+    pub async fn get_events_page(
+        &self,
+        game_id: i32,
+        before: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<GameEvent>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                id,
+                kind,
+                actor_id,
+                actor_name,
+                subject_id,
+                subject_name,
+                EXTRACT(EPOCH FROM created_at)::BIGINT AS "created_at!"
+            FROM game_events
+            WHERE game_id = $1 AND ($2::BIGINT IS NULL OR id < $2)
+            ORDER BY id DESC
+            LIMIT $3
+            "#,
+            game_id,
+            before,
+            limit
+        )
+        .fetch_all(&self.0)
+        .await?;
+        rows.into_iter()
+            .map(|row| {
+                Ok(GameEvent {
+                    id: row.id,
+                    game_id,
+                    kind: parse_event_kind(&row.kind)?,
+                    actor_id: row.actor_id,
+                    actor_name: row.actor_name,
+                    subject_id: row.subject_id,
+                    subject_name: row.subject_name,
+                    created_at: row.created_at,
+                })
+            })
+            .collect()
+    }
+
+    #[instrument(skip_all, fields(game_id))]
+    pub async fn get_spectator_token(&self, game_id: i32) -> Result<Option<String>, AppError> {
+        Ok(
+            sqlx::query_scalar!("SELECT spectator_token FROM games WHERE id = $1", game_id)
+                .fetch_optional(&self.0)
+                .await?,
+        )
+    }
+}
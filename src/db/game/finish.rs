@@ -0,0 +1,56 @@
+use crate::db::Db;
+use crate::errors::AppError;
+use crate::models::{GameEventKind, GameStatus, NewGameEvent};
+use tracing::{info, instrument};
+
+impl Db {
+    /// End a game without a winner, whatever state it is in. Returns `false`
+    /// when it had already finished.
+    #[instrument(skip_all, fields(game_code = %game_code))]
+    pub async fn force_finish_game(&self, game_code: &str) -> Result<bool, AppError> {
+        let mut tx = self.0.begin().await?;
+        let game = self.get_game_by_code_in_tx(&mut tx, game_code).await?;
+        if game.status == GameStatus::Finished {
+            return Ok(false);
+        }
+        self.finish_game_in_tx(&mut tx, game.id, None).await?;
+        tx.commit().await?;
+
+        info!(game_code, "Game finished by an administrator");
+        Ok(true)
+    }
+
+    /// Close a game with `winner_id` as its winner, clear every target and
+    /// log the end of the game.
+    #[instrument(skip_all, fields(game_id = game_id, winner_id))]
+    pub(crate) async fn finish_game_in_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        game_id: i32,
+        winner_id: Option<i32>,
+    ) -> Result<(), AppError> {
+        sqlx::query!(
+            "UPDATE games SET status = 'finished', winner_id = $1 WHERE id = $2",
+            winner_id,
+            game_id
+        )
+        .execute(&mut **tx)
+        .await?;
+        sqlx::query!(
+            "UPDATE players SET target_id = NULL WHERE game_id = $1",
+            game_id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        let mut finished = NewGameEvent::new(GameEventKind::Finished);
+        if let Some(winner_id) = winner_id {
+            let name = sqlx::query!("SELECT name FROM players WHERE id = $1", winner_id)
+                .fetch_one(&mut **tx)
+                .await?
+                .name;
+            finished = finished.by(Some(winner_id), name);
+        }
+        self.record_event_in_tx(tx, game_id, &finished).await
+    }
+}
@@ -0,0 +1,248 @@
+use crate::db::Db;
+use crate::engine;
+use crate::errors::{AppError, ErrorCode};
+use crate::models::{
+    DisputeStatus, Game, GameEventKind, GameStatus, GhostHint, KillDispute, NewGameEvent, Player,
+};
+use crate::repository::parse_dispute_status;
+use tracing::{info, instrument};
+
+impl Db {
+    #[instrument(skip_all, fields(game_id, event_id))]
+    pub async fn open_dispute(
+        &self,
+        game_id: i32,
+        event_id: i64,
+        victim_id: i32,
+        killer_id: Option<i32>,
+    ) -> Result<i64, AppError> {
+        Ok(sqlx::query_scalar!(
+            "INSERT INTO kill_disputes (game_id, event_id, victim_id, killer_id)
+             VALUES ($1, $2, $3, $4) RETURNING id",
+            game_id,
+            event_id,
+            victim_id,
+            killer_id
+        )
+        .fetch_one(&self.0)
+        .await?)
+    }
+
+    #[instrument(skip_all, fields(game_id))]
+    pub async fn get_disputes(&self, game_id: i32) -> Result<Vec<KillDispute>, AppError> {
+        let rows = sqlx::query!(
+            "SELECT id, event_id, victim_id, killer_id, status
+             FROM kill_disputes WHERE game_id = $1 ORDER BY id",
+            game_id
+        )
+        .fetch_all(&self.0)
+        .await?;
+        let votes = sqlx::query!(
+            "SELECT v.dispute_id, v.player_id, v.overturn
+             FROM dispute_votes v JOIN kill_disputes d ON d.id = v.dispute_id
+             WHERE d.game_id = $1",
+            game_id
+        )
+        .fetch_all(&self.0)
+        .await?;
+        rows.into_iter()
+            .map(|row| {
+                Ok(KillDispute {
+                    id: row.id,
+                    game_id,
+                    event_id: row.event_id,
+                    victim_id: row.victim_id,
+                    killer_id: row.killer_id,
+                    status: parse_dispute_status(&row.status)?,
+                    votes: votes
+                        .iter()
+                        .filter(|v| v.dispute_id == row.id)
+                        .map(|v| (v.player_id, v.overturn))
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+
+    /// Cast a ghost's vote and settle the dispute once the votes decide it,
+    /// reviving the victim when it was overturned. The dispute's row stays
+    /// locked from reading the tally to closing it, so concurrent votes are
+    /// counted one at a time.
+    #[instrument(skip_all, fields(game_code = %game_code, dispute_id, voter_id))]
+    pub async fn vote_on_dispute(
+        &self,
+        game_code: &str,
+        dispute_id: i64,
+        voter_id: i32,
+        overturn: bool,
+    ) -> Result<KillDispute, AppError> {
+        self.with_retry("vote_on_dispute", || {
+            self.try_vote_on_dispute(game_code, dispute_id, voter_id, overturn)
+        })
+        .await
+    }
+
+    async fn try_vote_on_dispute(
+        &self,
+        game_code: &str,
+        dispute_id: i64,
+        voter_id: i32,
+        overturn: bool,
+    ) -> Result<KillDispute, AppError> {
+        let mut tx = self.0.begin().await?;
+        let game = self.get_game_by_code_in_tx(&mut tx, game_code).await?;
+        let row = sqlx::query!(
+            "SELECT event_id, victim_id, killer_id, status
+             FROM kill_disputes WHERE id = $1 AND game_id = $2
+             FOR UPDATE",
+            dispute_id,
+            game.id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::NotFound(
+            ErrorCode::DisputeNotFound,
+            "error.DISPUTE_NOT_FOUND",
+        ))?;
+        let votes = sqlx::query!(
+            "SELECT player_id, overturn FROM dispute_votes WHERE dispute_id = $1",
+            dispute_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        let mut dispute = KillDispute {
+            id: dispute_id,
+            game_id: game.id,
+            event_id: row.event_id,
+            victim_id: row.victim_id,
+            killer_id: row.killer_id,
+            status: parse_dispute_status(&row.status)?,
+            votes: votes.iter().map(|v| (v.player_id, v.overturn)).collect(),
+        };
+        let players = self.get_players_by_game_id(&mut *tx, game.id).await?;
+        let outcome = engine::cast_vote(&mut dispute, &players, voter_id, overturn)?;
+
+        sqlx::query!(
+            "INSERT INTO dispute_votes (dispute_id, player_id, overturn) VALUES ($1, $2, $3)
+             ON CONFLICT (dispute_id, player_id) DO UPDATE SET overturn = EXCLUDED.overturn",
+            dispute_id,
+            voter_id,
+            overturn
+        )
+        .execute(&mut *tx)
+        .await?;
+        if let Some(outcome) = outcome {
+            sqlx::query!(
+                "UPDATE kill_disputes SET status = $1 WHERE id = $2",
+                outcome.as_str(),
+                dispute_id
+            )
+            .execute(&mut *tx)
+            .await?;
+            dispute.status = outcome;
+            if outcome == DisputeStatus::Overturned && game.status == GameStatus::InProgress {
+                self.revive_in_tx(&mut tx, &game, &players, &dispute)
+                    .await?;
+            }
+            info!(game_code, dispute_id, ?outcome, "Dispute settled");
+        }
+        self.assert_ring_in_tx(&mut tx, game.id).await?;
+        tx.commit().await?;
+        Ok(dispute)
+    }
+
+    /// Put the victim of an overturned kill back into the ring behind their
+    /// killer, see [`engine::plan_splice`].
+    async fn revive_in_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        game: &Game,
+        players: &[Player],
+        dispute: &KillDispute,
+    ) -> Result<(), AppError> {
+        let Some(victim) = players
+            .iter()
+            .find(|p| p.id == dispute.victim_id && !p.is_alive)
+        else {
+            return Ok(());
+        };
+        let Some(changes) = engine::plan_splice(players, victim.id, dispute.killer_id) else {
+            return Ok(());
+        };
+        sqlx::query!(
+            "UPDATE players SET is_alive = TRUE WHERE id = $1",
+            victim.id
+        )
+        .execute(&mut **tx)
+        .await?;
+        for (pid, target_id) in changes {
+            sqlx::query!(
+                "UPDATE players SET target_id = $1 WHERE id = $2",
+                target_id,
+                pid
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+        let revived = NewGameEvent::new(GameEventKind::Revived).by(Some(victim.id), &victim.name);
+        self.record_event_in_tx(tx, game.id, &revived).await?;
+        info!(
+            game_code = %game.code,
+            dispute_id = dispute.id,
+            "Kill overturned, victim is back"
+        );
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(game_id, sender_id))]
+    pub async fn send_hint(
+        &self,
+        game_id: i32,
+        sender_id: i32,
+        recipient_id: i32,
+        message: &str,
+    ) -> Result<(), AppError> {
+        sqlx::query!(
+            "INSERT INTO ghost_hints (game_id, sender_id, recipient_id, message)
+             VALUES ($1, $2, $3, $4)",
+            game_id,
+            sender_id,
+            recipient_id,
+            message
+        )
+        .execute(&self.0)
+        .await?;
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(game_id))]
+    pub async fn get_hints(&self, game_id: i32) -> Result<Vec<GhostHint>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                id,
+                sender_id,
+                recipient_id,
+                message,
+                EXTRACT(EPOCH FROM created_at)::BIGINT AS "created_at!"
+            FROM ghost_hints
+            WHERE game_id = $1
+            ORDER BY id
+            "#,
+            game_id
+        )
+        .fetch_all(&self.0)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| GhostHint {
+                id: row.id,
+                game_id,
+                sender_id: row.sender_id,
+                recipient_id: row.recipient_id,
+                message: row.message,
+                created_at: row.created_at,
+            })
+            .collect())
+    }
+}
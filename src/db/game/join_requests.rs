@@ -0,0 +1,155 @@
+use crate::db::Db;
+use crate::errors::AppError;
+use crate::models::{JoinRequest, RequestStatus};
+use crate::repository::parse_request_status;
+use tracing::{info, instrument};
+use uuid::Uuid;
+
+impl Db {
+    #[instrument(skip_all, fields(game_id))]
+    pub async fn request_join(
+        &self,
+        game_id: i32,
+        player_name: &str,
+    ) -> Result<JoinRequest, AppError> {
+        let player_name = player_name.trim().to_string();
+        let claim_token = Uuid::new_v4().simple().to_string();
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO join_requests (game_id, player_name, claim_token)
+            VALUES ($1, $2, $3)
+            RETURNING id, EXTRACT(EPOCH FROM created_at)::BIGINT AS "created_at!"
+            "#,
+            game_id,
+            player_name,
+            claim_token
+        )
+        .fetch_one(&self.0)
+        .await?;
+        Ok(JoinRequest {
+            id: row.id,
+            game_id,
+            player_name,
+            claim_token,
+            status: RequestStatus::Pending,
+            auth_token: None,
+            created_at: row.created_at,
+        })
+    }
+
+    #[instrument(skip_all, fields(game_id))]
+    pub async fn get_join_requests(&self, game_id: i32) -> Result<Vec<JoinRequest>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                id,
+                player_name,
+                claim_token,
+                status,
+                auth_token,
+                EXTRACT(EPOCH FROM created_at)::BIGINT AS "created_at!"
+            FROM join_requests
+            WHERE game_id = $1
+            ORDER BY id
+            "#,
+            game_id
+        )
+        .fetch_all(&self.0)
+        .await?;
+        rows.into_iter()
+            .map(|row| {
+                Ok(JoinRequest {
+                    id: row.id,
+                    game_id,
+                    player_name: row.player_name,
+                    claim_token: row.claim_token,
+                    status: parse_request_status(&row.status)?,
+                    auth_token: row.auth_token,
+                    created_at: row.created_at,
+                })
+            })
+            .collect()
+    }
+
+    #[instrument(skip_all)]
+    pub async fn get_join_request(
+        &self,
+        claim_token: &str,
+    ) -> Result<Option<JoinRequest>, AppError> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                id,
+                game_id,
+                player_name,
+                status,
+                auth_token,
+                EXTRACT(EPOCH FROM created_at)::BIGINT AS "created_at!"
+            FROM join_requests
+            WHERE claim_token = $1
+            "#,
+            claim_token
+        )
+        .fetch_optional(&self.0)
+        .await?;
+        row.map(|row| {
+            Ok(JoinRequest {
+                id: row.id,
+                game_id: row.game_id,
+                player_name: row.player_name,
+                claim_token: claim_token.to_string(),
+                status: parse_request_status(&row.status)?,
+                auth_token: row.auth_token,
+                created_at: row.created_at,
+            })
+        })
+        .transpose()
+    }
+
+    /// Answer a pending request; approving joins the newcomer and splices
+    /// them into the ring before the request is closed.
+    #[instrument(skip_all, fields(game_code, request_id))]
+    pub async fn decide_join_request(
+        &self,
+        game_code: &str,
+        request_id: i64,
+        approve: bool,
+        max_players: usize,
+    ) -> Result<bool, AppError> {
+        let mut tx = self.0.begin().await?;
+        let game = self.get_game_by_code_in_tx(&mut tx, game_code).await?;
+        let Some(player_name) = sqlx::query_scalar!(
+            "SELECT player_name FROM join_requests
+             WHERE id = $1 AND game_id = $2 AND status = 'pending'
+             FOR UPDATE",
+            request_id,
+            game.id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(false);
+        };
+        let (status, auth_token) = match approve {
+            true => {
+                let (player_id, _, auth_token) = self
+                    .join_in_tx(&mut tx, &game, &player_name, max_players, true)
+                    .await?;
+                info!(game_code, player_id, "Host let a late joiner in");
+                (RequestStatus::Approved, Some(auth_token))
+            }
+            false => (RequestStatus::Denied, None),
+        };
+        sqlx::query!(
+            "UPDATE join_requests SET status = $1, auth_token = $2 WHERE id = $3",
+            status.as_str(),
+            auth_token,
+            request_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        self.assert_ring_in_tx(&mut tx, game.id).await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+}
@@ -1,75 +1,70 @@
 use crate::db::Db;
+use crate::engine;
 use crate::errors::AppError;
-use crate::models::{Game, GameStatus, Player};
+use crate::kill_token::KillProof;
+use crate::models::{GameEventKind, NewGameEvent, Player};
 use sqlx;
-use tracing::debug;
+use tracing::{debug, instrument};
 
 impl Db {
+    #[instrument(skip_all, fields(game_code = %game_code))]
     pub async fn process_kill(
         &self,
         game_code: &str,
         killer_token: &str,
-        target_secret: &str,
+        proof: &KillProof,
     ) -> Result<(i32, String, String, Option<String>), AppError> {
-        let mut tx = self
-            .0
-            .begin()
-            .await
-            .map_err(|_| AppError::InternalServerError)?;
+        self.with_retry("process_kill", || {
+            self.try_process_kill(game_code, killer_token, proof)
+        })
+        .await
+    }
+
+    async fn try_process_kill(
+        &self,
+        game_code: &str,
+        killer_token: &str,
+        proof: &KillProof,
+    ) -> Result<(i32, String, String, Option<String>), AppError> {
+        let mut tx = self.0.begin().await?;
         debug!("Transaction started for process_kill");
 
         let game = self.get_game_by_code_in_tx(&mut tx, game_code).await?;
         let killer = self
             .get_player_by_auth_token_in_tx(&mut tx, killer_token, game.id)
             .await?;
-        let target = self
-            .get_player_by_secret_in_tx(&mut tx, target_secret, game.id)
-            .await?;
+        let target = match proof {
+            KillProof::Secret(secret) => {
+                self.get_player_by_secret_in_tx(&mut tx, secret, game.id)
+                    .await?
+            }
+            KillProof::Token(claims) => {
+                self.get_player_by_kill_token_in_tx(&mut tx, claims, &game)
+                    .await?
+            }
+        };
 
-        Self::validate_kill(&killer, &target, &game)?;
+        engine::validate_kill(&killer, &target, &game)?;
 
+        if let KillProof::Token(claims) = proof {
+            self.consume_kill_token_in_tx(&mut tx, claims, game.id)
+                .await?;
+        }
+
+        let kill = NewGameEvent::new(GameEventKind::Kill)
+            .by(Some(killer.id), &killer.name)
+            .to(Some(target.id), &target.name);
+        self.record_event_in_tx(&mut tx, game.id, &kill).await?;
         let new_target_name = self
             .update_game_state_after_kill(&mut tx, &killer, &target)
             .await?;
-        tx.commit()
-            .await
-            .map_err(|_| AppError::InternalServerError)?;
+        self.assert_ring_in_tx(&mut tx, game.id).await?;
+        tx.commit().await?;
 
         Ok((killer.id, killer.name, target.name, new_target_name))
     }
 
-    fn validate_kill(killer: &Player, target: &Player, game: &Game) -> Result<(), AppError> {
-        if !killer.is_alive {
-            return Err(AppError::Forbidden(
-                "You have already been eliminated and cannot eliminate anyone.".into(),
-            ));
-        }
-        if !target.is_alive {
-            return Err(AppError::Forbidden(
-                "That player has already been eliminated by someone else.".into(),
-            ));
-        }
-        if killer.game_id != target.game_id {
-            return Err(AppError::Forbidden(
-                "You are not in the same game as that player.".into(),
-            ));
-        }
-        if killer.id == target.id {
-            return Err(AppError::Forbidden("You cannot eliminate yourself.".into()));
-        }
-        if game.status != GameStatus::InProgress {
-            return Err(AppError::UnprocessableEntity(
-                "The game hasn't started yet or has already finished.".into(),
-            ));
-        }
-        if killer.target_id != Some(target.id) {
-            return Err(AppError::Forbidden(
-                "That code does not match your current target. Double-check the secret code given to your target.".into(),
-            ));
-        }
-        Ok(())
-    }
-
+    #[instrument(skip_all, fields(killer_id = killer.id, target_id = target.id))]
     async fn update_game_state_after_kill(
         &self,
         tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
@@ -91,19 +86,8 @@ impl Db {
         .await?
         .unwrap_or(0);
         if alive_count <= 1 {
-            sqlx::query!(
-                "UPDATE games SET status = 'finished', winner_id = $1 WHERE id = $2",
-                killer.id,
-                killer.game_id
-            )
-            .execute(&mut **tx)
-            .await?;
-            sqlx::query!(
-                "UPDATE players SET target_id = NULL WHERE id = $1",
-                killer.id
-            )
-            .execute(&mut **tx)
-            .await?;
+            self.finish_game_in_tx(tx, killer.game_id, Some(killer.id))
+                .await?;
             Ok(None)
         } else {
             let new_target_id = target.target_id;
@@ -122,4 +106,41 @@ impl Db {
             Ok(Some(new_target_name))
         }
     }
+
+    /// Take a living player out of the target ring without a kill: their
+    /// hunter inherits their target, and the game ends once a single player
+    /// is left standing.
+    #[instrument(skip_all, fields(player_id = player.id))]
+    pub(crate) async fn remove_from_ring_in_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        player: &Player,
+    ) -> Result<(), AppError> {
+        sqlx::query!(
+            "UPDATE players SET is_alive = FALSE, target_id = NULL WHERE id = $1",
+            player.id
+        )
+        .execute(&mut **tx)
+        .await?;
+        sqlx::query!(
+            "UPDATE players SET target_id = $1 WHERE game_id = $2 AND target_id = $3 AND is_alive = TRUE",
+            player.target_id,
+            player.game_id,
+            player.id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        let survivors: Vec<i32> = sqlx::query_scalar!(
+            "SELECT id FROM players WHERE game_id = $1 AND is_alive = TRUE",
+            player.game_id
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+        if survivors.len() <= 1 {
+            self.finish_game_in_tx(tx, player.game_id, survivors.first().copied())
+                .await?;
+        }
+        Ok(())
+    }
 }
@@ -1,18 +1,19 @@
 use super::super::Db;
-use crate::errors::AppError;
-use crate::models::{GameStatus, Player};
+use crate::engine;
+use crate::errors::{AppError, ErrorCode};
+use crate::models::{Game, GameEventKind, GameStatus, NewGameEvent, Player};
 use crate::utils::generate_code;
-use rand::seq::SliceRandom;
-use tracing::{debug, info};
+use tracing::{debug, info, instrument};
 use uuid::Uuid;
 
 impl Db {
     /// Create a new game and the first (host) player
+    #[instrument(skip_all, fields(game_code = %game_code))]
     pub async fn create_game(
         &self,
         mut player_name: String,
         game_code: String,
-    ) -> Result<(i32, i32, String, String), sqlx::Error> {
+    ) -> Result<(i32, i32, String, String), AppError> {
         player_name = player_name.trim().to_string();
         info!(
             "Creating game with code {} for player {}",
@@ -24,9 +25,11 @@ impl Db {
         let player_secret = generate_code(7);
         let auth_token = Uuid::new_v4().to_string();
 
+        let spectator_token = Uuid::new_v4().simple().to_string();
         let game_id = sqlx::query!(
-            "INSERT INTO games (code) VALUES ($1) RETURNING id",
-            game_code
+            "INSERT INTO games (code, spectator_token) VALUES ($1, $2) RETURNING id",
+            game_code,
+            spectator_token
         )
         .fetch_one(&mut *tx)
         .await?
@@ -50,46 +53,80 @@ impl Db {
         .execute(&mut *tx)
         .await?;
 
+        let joined = NewGameEvent::new(GameEventKind::Joined).by(Some(player_id), &player_name);
+        self.record_event_in_tx(&mut tx, game_id, &joined).await?;
+
+        self.assert_ring_in_tx(&mut tx, game_id).await?;
         tx.commit().await?;
 
         Ok((game_id, player_id, player_secret, auth_token))
     }
 
-    /// Existing or new player joins a lobby
+    /// Existing or new player joins a lobby, or a running game with
+    /// `late_join`
+    #[instrument(skip_all, fields(game_code = %game_code))]
     pub async fn join_game(
         &self,
         game_code: String,
         player_name: String,
+        max_players: usize,
+        late_join: bool,
     ) -> Result<(i32, i32, String, String), AppError> {
         let player_name = player_name.trim().to_string();
         info!("Player {} joining game {}", player_name, game_code);
-        let mut tx = self
-            .0
-            .begin()
-            .await
-            .map_err(|_| AppError::InternalServerError)?;
-
-        let game = self.get_game_by_code_in_tx(&mut tx, &game_code).await?;
-        if game.status != GameStatus::Lobby {
-            return Err(AppError::UnprocessableEntity(
-                "This game has already started or finished, so new players can no longer join.".to_string(),
-            ));
-        }
+        self.with_retry("join_game", || {
+            self.try_join_game(&game_code, &player_name, max_players, late_join)
+        })
+        .await
+    }
 
-        // Check if a player with the requested name already exists in this game
-        if let Some(p) = self.get_player_by_name(game.id, &player_name).await? {
-            if p.is_alive {
-                // A living player with this name is already in the lobby – reject the join attempt.
-                return Err(AppError::UnprocessableEntity(
-                    "That name is already being used by another player in this lobby. Please choose a different name.".to_string(),
-                ));
-            } else {
-                // The player existed previously but has been eliminated – they cannot re-join.
-                return Err(AppError::Forbidden(
-                    "You were eliminated earlier in this game and cannot rejoin.".to_string(),
-                ));
-            }
-        }
+    async fn try_join_game(
+        &self,
+        game_code: &str,
+        player_name: &str,
+        max_players: usize,
+        late_join: bool,
+    ) -> Result<(i32, i32, String, String), AppError> {
+        let mut tx = self.0.begin().await?;
+
+        // Locks the game row, so concurrent joins count one at a time.
+        let game = self.get_game_by_code_in_tx(&mut tx, game_code).await?;
+        let (player_id, player_secret, auth_token) = self
+            .join_in_tx(&mut tx, &game, player_name, max_players, late_join)
+            .await?;
+
+        self.assert_ring_in_tx(&mut tx, game.id).await?;
+        tx.commit().await?;
+
+        Ok((game.id, player_id, player_secret, auth_token))
+    }
+
+    /// Add `player_name` to `game`, whose row the caller has locked, and
+    /// splice them into the ring if it is already running.
+    pub(crate) async fn join_in_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        game: &Game,
+        player_name: &str,
+        max_players: usize,
+        late_join: bool,
+    ) -> Result<(i32, String, String), AppError> {
+        let same_name = self
+            .get_player_by_name(&mut **tx, game.id, player_name)
+            .await?;
+        let player_count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM players WHERE game_id = $1"#,
+            game.id
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+        engine::check_join(
+            game,
+            same_name.as_ref(),
+            player_count as usize,
+            max_players,
+            late_join,
+        )?;
 
         // New player
         let player_secret = generate_code(7);
@@ -102,12 +139,12 @@ impl Db {
             player_secret,
             auth_token
         )
-        .fetch_one(&mut *tx)
+        .fetch_one(&mut **tx)
         .await
         .map_err(|e| {
             if let Some(db_err) = e.as_database_error() {
                 if db_err.is_unique_violation() {
-                    return AppError::UnprocessableEntity("That name is already being used by another player in this lobby. Please choose a different name.".to_string());
+                    return AppError::Conflict(ErrorCode::NameTaken, "error.NAME_TAKEN");
                 }
             }
             tracing::warn!(
@@ -116,47 +153,67 @@ impl Db {
                 "Failed to insert player: {}",
                 e
             );
-            AppError::InternalServerError
+            AppError::from(e)
         })?;
 
-        tx.commit()
-            .await
-            .map_err(|_| AppError::InternalServerError)?;
+        if game.status == GameStatus::InProgress {
+            let players = self.get_players_by_game_id(&mut **tx, game.id).await?;
+            let changes =
+                engine::plan_late_join(&players, player_id).ok_or(AppError::InternalServerError)?;
+            for (pid, target_id) in changes {
+                sqlx::query!(
+                    "UPDATE players SET target_id = $1 WHERE id = $2",
+                    target_id,
+                    pid
+                )
+                .execute(&mut **tx)
+                .await?;
+            }
+            info!(
+                game_id = game.id,
+                player_id, "Late joiner spliced into the ring"
+            );
+        }
+        let joined = NewGameEvent::new(GameEventKind::Joined).by(Some(player_id), player_name);
+        self.record_event_in_tx(tx, game.id, &joined).await?;
 
-        Ok((game.id, player_id, player_secret, auth_token))
+        Ok((player_id, player_secret, auth_token))
     }
 
     /// Host starts the game – assigns targets and flips status
+    #[instrument(skip_all, fields(game_code = %game_code, player_id = player_id))]
     pub async fn start_game(
         &self,
         game_code: &str,
         player_id: i32,
+        min_players: usize,
     ) -> Result<Vec<Player>, AppError> {
         info!("Starting game {} by player {}", game_code, player_id);
-        let mut tx = self
-            .0
-            .begin()
-            .await
-            .map_err(|_| AppError::InternalServerError)?;
-        let game = self.get_game_by_code_in_tx(&mut tx, game_code).await?;
-        if game.host_id != Some(player_id) {
-            return Err(AppError::Forbidden(
-                "Only the host (the person who created the game) can start it.".to_string(),
-            ));
-        }
+        self.with_retry("start_game", || {
+            self.try_start_game(game_code, player_id, min_players)
+        })
+        .await
+    }
 
+    async fn try_start_game(
+        &self,
+        game_code: &str,
+        player_id: i32,
+        min_players: usize,
+    ) -> Result<Vec<Player>, AppError> {
+        let mut tx = self.0.begin().await?;
+        let game = self.get_game_by_code_in_tx(&mut tx, game_code).await?;
         let players = self.get_players_by_game_id(&mut *tx, game.id).await?;
-        if players.len() < 2 {
-            return Err(AppError::UnprocessableEntity(
-                "You need at least 2 players in the lobby to start the game. Invite someone else to join first!".to_string(),
-            ));
-        }
-
-        let mut ids: Vec<i32> = players.iter().map(|p| p.id).collect();
-        ids.shuffle(&mut rand::rng());
-
-        for (idx, &pid) in ids.iter().enumerate() {
-            let target_id = ids[(idx + 1) % ids.len()];
+        engine::check_start(&game, player_id, players.len(), min_players)?;
+        let starter = players
+            .iter()
+            .find(|p| p.id == player_id)
+            .ok_or(AppError::InternalServerError)?;
+        let started = NewGameEvent::new(GameEventKind::Started).by(Some(starter.id), &starter.name);
+        self.record_event_in_tx(&mut tx, game.id, &started).await?;
+
+        let ids = players.iter().map(|p| p.id).collect();
+        for (pid, target_id) in engine::assign_targets(ids) {
             sqlx::query!(
                 "UPDATE players SET target_id = $1 WHERE id = $2",
                 target_id,
@@ -173,10 +230,21 @@ impl Db {
         .execute(&mut *tx)
         .await?;
 
-        tx.commit()
-            .await
-            .map_err(|_| AppError::InternalServerError)?;
+        self.assert_ring_in_tx(&mut tx, game.id).await?;
+        tx.commit().await?;
 
         Ok(self.get_players_by_game_id(&self.0, game.id).await?)
     }
+
+    #[instrument(skip_all, fields(player_id, ready))]
+    pub async fn set_ready(&self, player_id: i32, ready: bool) -> Result<(), AppError> {
+        sqlx::query!(
+            "UPDATE players SET is_ready = $1 WHERE id = $2",
+            ready,
+            player_id
+        )
+        .execute(&self.0)
+        .await?;
+        Ok(())
+    }
 }
@@ -1,3 +1,12 @@
+pub mod chat;
+pub mod events;
+pub mod finish;
+pub mod ghosts;
+pub mod join_requests;
 pub mod kill;
 pub mod lobby;
+pub mod purge;
 pub mod query;
+pub mod recovery;
+pub mod ring;
+pub mod settings;
@@ -0,0 +1,28 @@
+use crate::db::Db;
+use std::time::Duration;
+use tracing::{info, instrument};
+
+impl Db {
+    /// Delete games created more than `older_than` ago; players and used
+    /// kill tokens go with them through `ON DELETE CASCADE`.
+    #[instrument(skip_all)]
+    pub async fn purge_games(&self, older_than: Duration) -> Result<Vec<String>, sqlx::Error> {
+        let mut tx = self.0.begin().await?;
+        sqlx::query!("DELETE FROM used_kill_tokens WHERE expires_at < NOW()")
+            .execute(&mut *tx)
+            .await?;
+        // `created_at` has no time zone, so compare against local time.
+        let codes = sqlx::query_scalar!(
+            "DELETE FROM games WHERE created_at < LOCALTIMESTAMP - make_interval(secs => $1) RETURNING code",
+            older_than.as_secs_f64()
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        if !codes.is_empty() {
+            info!(count = codes.len(), "Purged old games");
+        }
+        Ok(codes)
+    }
+}
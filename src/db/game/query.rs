@@ -1,10 +1,12 @@
 use super::super::Db;
-use crate::errors::AppError;
+use crate::errors::{AppError, ErrorCode};
 use crate::models::{Game, GameInfo, Player};
+use tracing::instrument;
 
 impl Db {
     // ------- public queries --------
 
+    #[instrument(skip_all)]
     pub async fn get_all_games(&self) -> Result<Vec<GameInfo>, sqlx::Error> {
         let games = sqlx::query_as!(
             GameInfo,
@@ -25,6 +27,7 @@ impl Db {
         Ok(games)
     }
 
+    #[instrument(skip_all, fields(game_code = %code))]
     pub async fn get_game_by_code(&self, code: &str) -> Result<Option<Game>, AppError> {
         let game = sqlx::query_as!(
             Game,
@@ -45,6 +48,7 @@ impl Db {
         Ok(game)
     }
 
+    #[instrument(skip_all, fields(game_code = %game_code))]
     pub async fn get_game_state(
         &self,
         game_code: &str,
@@ -58,6 +62,7 @@ impl Db {
         }
     }
 
+    #[instrument(skip_all, fields(game_id = game_id))]
     pub async fn get_game_by_id(&self, game_id: i32) -> Result<Option<Game>, sqlx::Error> {
         sqlx::query_as!(
             Game,
@@ -77,6 +82,11 @@ impl Db {
     }
 
     // ------- helpers within transaction --------
+
+    /// Fetch a game and lock its row for the rest of the transaction. Every
+    /// mutating transaction goes through here first, so changes to one game
+    /// are serialised and always take their locks in the same order.
+    #[instrument(skip_all, fields(game_code = %game_code))]
     pub(crate) async fn get_game_by_code_in_tx<'a>(
         &self,
         tx: &mut sqlx::Transaction<'a, sqlx::Postgres>,
@@ -92,12 +102,15 @@ impl Db {
                 code          AS "code: _"
             FROM games
             WHERE code = $1
+            FOR UPDATE
             "#,
             game_code
         )
         .fetch_optional(&mut **tx)
-        .await
-        .map_err(|_| AppError::InternalServerError)?
-        .ok_or_else(|| AppError::NotFound("Game not found".to_string()))
+        .await?
+        .ok_or(AppError::NotFound(
+            ErrorCode::GameNotFound,
+            "error.GAME_NOT_FOUND",
+        ))
     }
 }
@@ -0,0 +1,165 @@
+use crate::db::Db;
+use crate::engine;
+use crate::errors::AppError;
+use crate::models::{RecoveryRequest, RequestStatus};
+use crate::repository::parse_request_status;
+use tracing::{info, instrument};
+use uuid::Uuid;
+
+impl Db {
+    #[instrument(skip_all, fields(game_id, player_id))]
+    pub async fn request_recovery(
+        &self,
+        game_id: i32,
+        player_id: i32,
+    ) -> Result<RecoveryRequest, AppError> {
+        let claim_token = Uuid::new_v4().simple().to_string();
+        let mut tx = self.0.begin().await?;
+        sqlx::query!(
+            "DELETE FROM recovery_requests WHERE player_id = $1 AND status <> 'pending'",
+            player_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO recovery_requests (game_id, player_id, claim_token)
+            VALUES ($1, $2, $3)
+            RETURNING id, EXTRACT(EPOCH FROM created_at)::BIGINT AS "created_at!"
+            "#,
+            game_id,
+            player_id,
+            claim_token
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| match e.as_database_error() {
+            Some(db_err) if db_err.is_unique_violation() => engine::recovery_pending(),
+            _ => AppError::from(e),
+        })?;
+        tx.commit().await?;
+        Ok(RecoveryRequest {
+            id: row.id,
+            game_id,
+            player_id,
+            claim_token,
+            status: RequestStatus::Pending,
+            auth_token: None,
+            created_at: row.created_at,
+        })
+    }
+
+    #[instrument(skip_all, fields(game_id))]
+    pub async fn get_recovery_requests(
+        &self,
+        game_id: i32,
+    ) -> Result<Vec<RecoveryRequest>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                id,
+                player_id,
+                claim_token,
+                status,
+                auth_token,
+                EXTRACT(EPOCH FROM created_at)::BIGINT AS "created_at!"
+            FROM recovery_requests
+            WHERE game_id = $1
+            ORDER BY id
+            "#,
+            game_id
+        )
+        .fetch_all(&self.0)
+        .await?;
+        rows.into_iter()
+            .map(|row| {
+                Ok(RecoveryRequest {
+                    id: row.id,
+                    game_id,
+                    player_id: row.player_id,
+                    claim_token: row.claim_token,
+                    status: parse_request_status(&row.status)?,
+                    auth_token: row.auth_token,
+                    created_at: row.created_at,
+                })
+            })
+            .collect()
+    }
+
+    #[instrument(skip_all)]
+    pub async fn get_recovery_request(
+        &self,
+        claim_token: &str,
+    ) -> Result<Option<RecoveryRequest>, AppError> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                id,
+                game_id,
+                player_id,
+                status,
+                auth_token,
+                EXTRACT(EPOCH FROM created_at)::BIGINT AS "created_at!"
+            FROM recovery_requests
+            WHERE claim_token = $1
+            "#,
+            claim_token
+        )
+        .fetch_optional(&self.0)
+        .await?;
+        row.map(|row| {
+            Ok(RecoveryRequest {
+                id: row.id,
+                game_id: row.game_id,
+                player_id: row.player_id,
+                claim_token: claim_token.to_string(),
+                status: parse_request_status(&row.status)?,
+                auth_token: row.auth_token,
+                created_at: row.created_at,
+            })
+        })
+        .transpose()
+    }
+
+    /// Answer a pending request, handing the player a fresh auth token when
+    /// it is approved.
+    #[instrument(skip_all, fields(game_id, request_id))]
+    pub async fn decide_recovery(
+        &self,
+        game_id: i32,
+        request_id: i64,
+        approve: bool,
+    ) -> Result<bool, AppError> {
+        let (status, auth_token) = match approve {
+            true => (RequestStatus::Approved, Some(Uuid::new_v4().to_string())),
+            false => (RequestStatus::Denied, None),
+        };
+        let mut tx = self.0.begin().await?;
+        let Some(player_id) = sqlx::query_scalar!(
+            "UPDATE recovery_requests SET status = $1, auth_token = $2
+             WHERE id = $3 AND game_id = $4 AND status = 'pending'
+             RETURNING player_id",
+            status.as_str(),
+            auth_token,
+            request_id,
+            game_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(false);
+        };
+        if let Some(auth_token) = &auth_token {
+            sqlx::query!(
+                "UPDATE players SET auth_token = $1 WHERE id = $2",
+                auth_token,
+                player_id
+            )
+            .execute(&mut *tx)
+            .await?;
+            info!(game_id, player_id, "Player recovered with a new auth token");
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+}
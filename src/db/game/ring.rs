@@ -0,0 +1,123 @@
+use crate::db::Db;
+use crate::engine::{plan_ring_repair, ring_violations, RingPlan, RingRepair, RingViolation};
+use crate::errors::{AppError, ErrorCode};
+use crate::models::GameStatus;
+use crate::repository::ring_must_hold;
+use tracing::{info, instrument, warn};
+
+impl Db {
+    /// Report everything wrong with a running game's target ring without
+    /// changing it. Games that are not in progress have no ring to check.
+    #[instrument(skip_all, fields(game_code = %game_code))]
+    pub async fn check_ring(&self, game_code: &str) -> Result<Vec<RingViolation>, AppError> {
+        let game = self
+            .get_game_by_code(game_code)
+            .await?
+            .ok_or(AppError::NotFound(
+                ErrorCode::GameNotFound,
+                "error.GAME_NOT_FOUND",
+            ))?;
+        if game.status != GameStatus::InProgress {
+            return Ok(Vec::new());
+        }
+        let players = self.get_players_by_game_id(&self.0, game.id).await?;
+        Ok(ring_violations(&players))
+    }
+
+    /// Validate a running game's target ring and rebuild it when broken.
+    #[instrument(skip_all, fields(game_code = %game_code))]
+    pub async fn repair_ring(&self, game_code: &str) -> Result<RingRepair, AppError> {
+        self.with_retry("repair_ring", || self.try_repair_ring(game_code))
+            .await
+    }
+
+    async fn try_repair_ring(&self, game_code: &str) -> Result<RingRepair, AppError> {
+        let mut tx = self.0.begin().await?;
+        let game = self.get_game_by_code_in_tx(&mut tx, game_code).await?;
+        let repair = self.repair_ring_in_tx(&mut tx, game.id).await?;
+        tx.commit().await?;
+
+        if !repair.violations.is_empty() {
+            info!(
+                game_code,
+                reassigned = repair.reassigned.len(),
+                "Target ring repaired"
+            );
+        }
+        Ok(repair)
+    }
+
+    /// In debug builds, fail `tx` when it leaves the ring of a running game
+    /// broken. Every transaction that changes a game ends with this, so a
+    /// bug shows up in tests instead of as a confusing target later on.
+    pub(crate) async fn assert_ring_in_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        game_id: i32,
+    ) -> Result<(), AppError> {
+        if !cfg!(debug_assertions) {
+            return Ok(());
+        }
+        let status = sqlx::query_scalar!(
+            r#"SELECT status as "status: GameStatus" FROM games WHERE id = $1"#,
+            game_id
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+        if status != Some(GameStatus::InProgress) {
+            return Ok(());
+        }
+        let players = self.get_players_by_game_id(&mut **tx, game_id).await?;
+        ring_must_hold(game_id, &players)
+    }
+
+    /// Validate the ring of a running game within `tx` and rebuild it when
+    /// broken.
+    #[instrument(skip_all, fields(game_id = game_id))]
+    async fn repair_ring_in_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        game_id: i32,
+    ) -> Result<RingRepair, AppError> {
+        let status = sqlx::query_scalar!(
+            r#"SELECT status as "status: GameStatus" FROM games WHERE id = $1"#,
+            game_id
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+        if status != Some(GameStatus::InProgress) {
+            return Ok(RingRepair::default());
+        }
+        let players = self.get_players_by_game_id(&mut **tx, game_id).await?;
+        let violations = ring_violations(&players);
+        if violations.is_empty() {
+            return Ok(RingRepair::default());
+        }
+        warn!(game_id, ?violations, "Target ring is broken, repairing");
+
+        let mut repair = RingRepair {
+            violations,
+            ..RingRepair::default()
+        };
+        match plan_ring_repair(&players) {
+            RingPlan::Finish { winner_id } => {
+                self.finish_game_in_tx(tx, game_id, winner_id).await?;
+                repair.winner_id = winner_id;
+                repair.reassigned = winner_id.map(|id| (id, None)).into_iter().collect();
+            }
+            RingPlan::Retarget(changes) => {
+                for &(pid, target_id) in &changes {
+                    sqlx::query!(
+                        "UPDATE players SET target_id = $1 WHERE id = $2",
+                        target_id,
+                        pid
+                    )
+                    .execute(&mut **tx)
+                    .await?;
+                    repair.reassigned.push((pid, Some(target_id)));
+                }
+            }
+        }
+        Ok(repair)
+    }
+}
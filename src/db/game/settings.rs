@@ -0,0 +1,34 @@
+use super::super::Db;
+use crate::errors::AppError;
+use crate::models::GameSettings;
+use sqlx::types::Json;
+use tracing::instrument;
+
+impl Db {
+    #[instrument(skip_all, fields(game_id))]
+    pub async fn get_game_settings(&self, game_id: i32) -> Result<Option<GameSettings>, AppError> {
+        let settings = sqlx::query_scalar!(
+            r#"SELECT settings AS "settings: Json<GameSettings>" FROM games WHERE id = $1"#,
+            game_id
+        )
+        .fetch_optional(&self.0)
+        .await?;
+        Ok(settings.map(|Json(settings)| settings))
+    }
+
+    #[instrument(skip_all, fields(game_id))]
+    pub async fn set_game_settings(
+        &self,
+        game_id: i32,
+        settings: &GameSettings,
+    ) -> Result<(), AppError> {
+        sqlx::query!(
+            "UPDATE games SET settings = $1 WHERE id = $2",
+            Json(settings) as _,
+            game_id
+        )
+        .execute(&self.0)
+        .await?;
+        Ok(())
+    }
+}
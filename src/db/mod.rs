@@ -1,27 +1,84 @@
-use sqlx::{postgres::PgPoolOptions, PgPool};
+use crate::config::DatabaseConfig;
+use crate::errors::{AppError, ErrorCode};
+use rand::Rng as _;
+use sqlx::{migrate::Migrator, postgres::PgPoolOptions, PgPool};
+use std::future::Future;
 use std::ops::Deref;
-use tracing::info;
+use std::time::Duration;
+use tracing::{info, warn};
+
+pub(crate) static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// How many times a transaction that lost a race against another one is
+/// retried before the error is handed to the client.
+const MAX_TX_RETRIES: u32 = 5;
 
 #[derive(Debug, Clone)]
 pub struct Db(PgPool);
 
 impl Db {
     /// Initialise a new database connection pool and run migrations.
-    pub async fn new() -> Result<Self, sqlx::Error> {
-        let db_url = dotenvy::var("DATABASE_URL").expect("DATABASE_URL must be set");
-        info!("Connecting to database at {}", db_url);
-
-        let pool = PgPoolOptions::new()
-            .max_connections(5)
-            .connect(&db_url)
-            .await?;
+    pub async fn connect(config: &DatabaseConfig) -> Result<Self, sqlx::Error> {
+        let db = Self::open(config).await?;
 
         info!("Running database migrations...");
-        sqlx::migrate!("./migrations").run(&pool).await?;
+        MIGRATOR.run(&db.0).await?;
         info!("Database migrations complete.");
 
+        Ok(db)
+    }
+
+    /// Initialise a new database connection pool, leaving the schema as it is.
+    pub async fn open(config: &DatabaseConfig) -> Result<Self, sqlx::Error> {
+        info!("Connecting to database at {}", config.url);
+
+        let pool = PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .connect(&config.url)
+            .await?;
+
         Ok(Db(pool))
     }
+
+    /// Versions of the applied migrations, oldest first.
+    pub async fn applied_migrations(&self) -> Result<Vec<i64>, sqlx::Error> {
+        // Not a checked query: the table is created by the migrator itself.
+        sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success ORDER BY version")
+            .fetch_all(&self.0)
+            .await
+    }
+
+    /// Wrap an existing pool, e.g. one handed out by `sqlx::test`.
+    pub fn from_pool(pool: PgPool) -> Self {
+        Db(pool)
+    }
+
+    /// Run a transaction, retrying it from scratch when Postgres aborts it
+    /// with a serialization failure or deadlock.
+    pub(crate) async fn with_retry<T, F, Fut>(&self, op: &str, mut run: F) -> Result<T, AppError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, AppError>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match run().await {
+                Err(err)
+                    if err.code() == ErrorCode::SerializationFailure
+                        && attempt < MAX_TX_RETRIES =>
+                {
+                    attempt += 1;
+                    let backoff = 10 * 2u64.pow(attempt) + rand::rng().random_range(0..10);
+                    warn!(
+                        op,
+                        attempt, "Transaction conflict, retrying in {}ms", backoff
+                    );
+                    tokio::time::sleep(Duration::from_millis(backoff)).await;
+                }
+                result => return result,
+            }
+        }
+    }
 }
 
 // Split implementations into focused modules.
@@ -1,13 +1,17 @@
 use sqlx::{Executor, Postgres};
 
 use super::Db;
-use crate::errors::AppError;
-use crate::models::{GameStatus, Player};
-use tracing::{debug, info};
+use crate::engine;
+use crate::errors::{AppError, ErrorCode};
+use crate::kill_token::KillTokenClaims;
+use crate::models::{Game, GameEventKind, GameStatus, NewGameEvent, Player};
+use crate::utils::generate_code;
+use tracing::{debug, info, instrument};
 
 impl Db {
     // -------- Public player APIs ---------
 
+    #[instrument(skip_all, fields(game_id = game_id))]
     pub async fn get_players_by_game_id<'e, E>(
         &self,
         executor: E,
@@ -26,6 +30,7 @@ impl Db {
                 p.secret_code,
                 p.auth_token,
                 p.is_alive,
+                p.is_ready,
                 p.target_id,
                 p.game_id,
                 COALESCE(t.name, '') as "target_name: _"
@@ -41,6 +46,7 @@ impl Db {
         Ok(players)
     }
 
+    #[instrument(skip_all)]
     pub async fn get_player_by_auth_token(
         &self,
         auth_token: &str,
@@ -56,6 +62,7 @@ impl Db {
                 p.secret_code,
                 p.auth_token,
                 p.is_alive,
+                p.is_ready,
                 p.target_id,
                 p.game_id,
                 COALESCE(t.name, '') as "target_name: _"
@@ -71,11 +78,16 @@ impl Db {
         Ok(player)
     }
 
-    pub async fn get_player_by_name(
+    #[instrument(skip_all, fields(game_id = game_id))]
+    pub async fn get_player_by_name<'e, E>(
         &self,
+        executor: E,
         game_id: i32,
         player_name: &str,
-    ) -> Result<Option<Player>, AppError> {
+    ) -> Result<Option<Player>, AppError>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
         info!(
             "Fetching player by name {} for game_id {}",
             player_name, game_id
@@ -90,6 +102,7 @@ impl Db {
                 p.secret_code,
                 p.auth_token,
                 p.is_alive,
+                p.is_ready,
                 p.target_id,
                 p.game_id,
                 COALESCE(t.name, '') as "target_name: _"
@@ -100,24 +113,30 @@ impl Db {
             game_id,
             normalised_name
         )
-        .fetch_optional(&self.0)
+        .fetch_optional(executor)
         .await
         .map_err(|e| {
             tracing::warn!(game_id, player_name, "Failed to get player by name: {}", e);
-            AppError::InternalServerError
+            AppError::from(e)
         })?;
         debug!("Found player: {:?}", player);
         Ok(player)
     }
 
+    #[instrument(skip_all, fields(game_code = %game_code))]
     pub async fn leave_game(&self, game_code: &str, auth_token: &str) -> Result<(), AppError> {
         info!(
             "Player with token {} leaving game {}",
             auth_token, game_code
         );
+        self.with_retry("leave_game", || self.try_leave_game(game_code, auth_token))
+            .await
+    }
+
+    async fn try_leave_game(&self, game_code: &str, auth_token: &str) -> Result<(), AppError> {
         let mut tx = self.0.begin().await.map_err(|e| {
             tracing::warn!(game_code, auth_token, "Failed to begin transaction: {}", e);
-            AppError::InternalServerError
+            AppError::from(e)
         })?;
 
         let game = self.get_game_by_code_in_tx(&mut tx, game_code).await?;
@@ -125,76 +144,191 @@ impl Db {
             .get_player_by_auth_token_in_tx(&mut tx, auth_token, game.id)
             .await?;
 
-        if game.status == GameStatus::Lobby {
-            let is_host = game.host_id == Some(player.id);
+        let kind = match game.status {
+            GameStatus::InProgress => GameEventKind::Forfeited,
+            _ => GameEventKind::Left,
+        };
+        let left = NewGameEvent::new(kind).by(Some(player.id), &player.name);
+        self.record_event_in_tx(&mut tx, game.id, &left).await?;
 
-            sqlx::query!("DELETE FROM players WHERE id = $1", player.id)
-                .execute(&mut *tx)
-                .await
-                .map_err(|_| AppError::InternalServerError)?;
-
-            if is_host {
-                let remaining_players: Vec<Player> = sqlx::query_as!(
-                    Player,
-                    r#"
-                    SELECT
-                        p.id as "id!",
-                        p.name,
-                        p.secret_code,
-                        p.auth_token,
-                        p.is_alive,
-                        p.target_id,
-                        p.game_id,
-                        COALESCE(t.name, '') as "target_name: _"
-                    FROM players p
-                    LEFT JOIN players t ON p.target_id = t.id
-                    WHERE p.game_id = $1 ORDER BY p.id ASC
-                    "#,
-                    game.id
-                )
-                .fetch_all(&mut *tx)
-                .await
-                .map_err(|_| AppError::InternalServerError)?;
+        match game.status {
+            GameStatus::Lobby => {
+                let is_host = game.host_id == Some(player.id);
 
-                if remaining_players.is_empty() {
-                    // Last player (the host) left, delete the game
-                    sqlx::query!("DELETE FROM games WHERE id = $1", game.id)
-                        .execute(&mut *tx)
-                        .await
-                        .map_err(|_| AppError::InternalServerError)?;
-                } else {
-                    // Assign a new host (the one who joined earliest)
-                    let new_host_id = remaining_players[0].id;
-                    sqlx::query!(
-                        "UPDATE games SET host_id = $1 WHERE id = $2",
-                        new_host_id,
+                sqlx::query!("DELETE FROM players WHERE id = $1", player.id)
+                    .execute(&mut *tx)
+                    .await?;
+
+                if is_host {
+                    let remaining_players: Vec<Player> = sqlx::query_as!(
+                        Player,
+                        r#"
+                        SELECT
+                            p.id as "id!",
+                            p.name,
+                            p.secret_code,
+                            p.auth_token,
+                            p.is_alive,
+                            p.is_ready,
+                            p.target_id,
+                            p.game_id,
+                            COALESCE(t.name, '') as "target_name: _"
+                        FROM players p
+                        LEFT JOIN players t ON p.target_id = t.id
+                        WHERE p.game_id = $1 ORDER BY p.id ASC
+                        "#,
                         game.id
                     )
-                    .execute(&mut *tx)
-                    .await
-                    .map_err(|_| AppError::InternalServerError)?;
+                    .fetch_all(&mut *tx)
+                    .await?;
+
+                    if remaining_players.is_empty() {
+                        // Last player (the host) left, delete the game
+                        sqlx::query!("DELETE FROM games WHERE id = $1", game.id)
+                            .execute(&mut *tx)
+                            .await?;
+                    } else {
+                        // Assign a new host (the one who joined earliest)
+                        let new_host_id = engine::next_host(&remaining_players);
+                        sqlx::query!(
+                            "UPDATE games SET host_id = $1 WHERE id = $2",
+                            new_host_id,
+                            game.id
+                        )
+                        .execute(&mut *tx)
+                        .await?;
+                        if let Some(host) =
+                            remaining_players.iter().find(|p| Some(p.id) == new_host_id)
+                        {
+                            let host_changed = NewGameEvent::new(GameEventKind::HostChanged)
+                                .by(Some(host.id), &host.name);
+                            self.record_event_in_tx(&mut tx, game.id, &host_changed)
+                                .await?;
+                        }
+                    }
                 }
             }
-        } else {
-            // If game is in progress or finished, just mark as not alive
-            sqlx::query!(
-                "UPDATE players SET is_alive = false WHERE id = $1",
-                player.id
-            )
-            .execute(&mut *tx)
-            .await
-            .map_err(|_| AppError::InternalServerError)?;
+            GameStatus::InProgress if player.is_alive => {
+                self.remove_from_ring_in_tx(&mut tx, &player).await?;
+            }
+            _ => {
+                // Already out of the ring (or the game is over): just mark as not alive
+                sqlx::query!(
+                    "UPDATE players SET is_alive = false WHERE id = $1",
+                    player.id
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
         }
 
-        tx.commit()
-            .await
-            .map_err(|_| AppError::InternalServerError)?;
+        self.assert_ring_in_tx(&mut tx, game.id).await?;
+        tx.commit().await?;
 
         Ok(())
     }
 
+    /// Replace a living player's secret code, invalidating the old one.
+    /// Returns the new secret and how many rotations the player has left.
+    #[instrument(skip_all, fields(game_code = %game_code))]
+    pub async fn rotate_secret(
+        &self,
+        game_code: &str,
+        auth_token: &str,
+        cooldown_secs: i64,
+        max_rotations: i32,
+    ) -> Result<(String, i32), AppError> {
+        self.with_retry("rotate_secret", || {
+            self.try_rotate_secret(game_code, auth_token, cooldown_secs, max_rotations)
+        })
+        .await
+    }
+
+    async fn try_rotate_secret(
+        &self,
+        game_code: &str,
+        auth_token: &str,
+        cooldown_secs: i64,
+        max_rotations: i32,
+    ) -> Result<(String, i32), AppError> {
+        let mut tx = self.0.begin().await.map_err(|e| {
+            tracing::warn!(game_code, "Failed to begin transaction: {}", e);
+            AppError::from(e)
+        })?;
+
+        let game = self.get_game_by_code_in_tx(&mut tx, game_code).await?;
+        let player = self
+            .get_player_by_auth_token_in_tx(&mut tx, auth_token, game.id)
+            .await?;
+        info!(game_id = game.id, player_id = player.id, "Rotating secret");
+
+        // Lock the player's row so concurrent rotations see each other's
+        // count and cannot both slip under the limit.
+        let rotation = sqlx::query!(
+            r#"
+            SELECT
+                secret_rotations,
+                GREATEST(
+                    0,
+                    CEIL($2::BIGINT - EXTRACT(EPOCH FROM NOW() - secret_rotated_at))
+                )::BIGINT AS "cooldown_left"
+            FROM players
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            player.id,
+            cooldown_secs
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        engine::check_rotation(
+            &game,
+            &player,
+            rotation.secret_rotations,
+            max_rotations,
+            rotation.cooldown_left,
+        )?;
+
+        let new_secret = generate_code(7);
+        let rotations = sqlx::query_scalar!(
+            r#"
+            UPDATE players
+            SET secret_code = $1,
+                secret_rotations = secret_rotations + 1,
+                secret_rotated_at = NOW()
+            WHERE id = $2
+            RETURNING secret_rotations
+            "#,
+            new_secret,
+            player.id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        self.assert_ring_in_tx(&mut tx, game.id).await?;
+        tx.commit().await?;
+
+        debug!("Player {} rotated secret ({} used)", player.id, rotations);
+        Ok((new_secret, max_rotations - rotations))
+    }
+
+    /// How many times a player has rotated their secret; signed kill tokens
+    /// are only valid for the current generation.
+    #[instrument(skip_all, fields(player_id = player_id))]
+    pub async fn get_secret_generation(&self, player_id: i32) -> Result<i32, AppError> {
+        let generation = sqlx::query_scalar!(
+            "SELECT secret_rotations FROM players WHERE id = $1",
+            player_id
+        )
+        .fetch_one(&self.0)
+        .await?;
+        Ok(generation)
+    }
+
     // ---------- Private helpers (within transaction) -------------
 
+    #[instrument(skip_all, fields(game_id = game_id))]
     pub(crate) async fn get_player_by_auth_token_in_tx<'a>(
         &self,
         tx: &mut sqlx::Transaction<'a, sqlx::Postgres>,
@@ -210,12 +344,14 @@ impl Db {
                 p.secret_code,
                 p.auth_token,
                 p.is_alive,
+                p.is_ready,
                 p.target_id,
                 p.game_id,
                 COALESCE(t.name, '') as "target_name: _"
             FROM players p
             LEFT JOIN players t ON p.target_id = t.id
             WHERE p.auth_token = $1 AND p.game_id = $2
+            FOR UPDATE OF p
             "#,
             killer_token,
             game_id
@@ -229,11 +365,12 @@ impl Db {
                 "Failed to query for killer by auth token: {}",
                 e
             );
-            AppError::InternalServerError
+            AppError::from(e)
         })?
         .ok_or(AppError::Unauthorized)
     }
 
+    #[instrument(skip_all, fields(game_id = game_id))]
     pub(crate) async fn get_player_by_secret_in_tx<'a>(
         &self,
         tx: &mut sqlx::Transaction<'a, sqlx::Postgres>,
@@ -249,12 +386,14 @@ impl Db {
                 p.secret_code,
                 p.auth_token,
                 p.is_alive,
+                p.is_ready,
                 p.target_id,
                 p.game_id,
                 COALESCE(t.name, '') as "target_name: _"
             FROM players p
             LEFT JOIN players t ON p.target_id = t.id
             WHERE p.secret_code = $1 AND p.game_id = $2
+            FOR UPDATE OF p
             "#,
             target_secret,
             game_id
@@ -268,10 +407,94 @@ impl Db {
                 "Failed to query for target by secret: {}",
                 e
             );
-            AppError::InternalServerError
+            AppError::from(e)
         })?
         .ok_or(AppError::NotFound(
-            "Target secret does not correspond to an active player.".to_string(),
+            ErrorCode::UnknownSecret,
+            "error.UNKNOWN_SECRET",
         ))
     }
+
+    #[instrument(skip_all, fields(game_id = game.id))]
+    pub(crate) async fn get_player_by_kill_token_in_tx<'a>(
+        &self,
+        tx: &mut sqlx::Transaction<'a, sqlx::Postgres>,
+        claims: &KillTokenClaims,
+        game: &Game,
+    ) -> Result<Player, AppError> {
+        engine::check_token_game(claims, game)?;
+
+        let target = sqlx::query_as!(
+            Player,
+            r#"
+            SELECT
+                p.id as "id!",
+                p.name,
+                p.secret_code,
+                p.auth_token,
+                p.is_alive,
+                p.is_ready,
+                p.target_id,
+                p.game_id,
+                COALESCE(t.name, '') as "target_name: _"
+            FROM players p
+            LEFT JOIN players t ON p.target_id = t.id
+            WHERE p.id = $1 AND p.game_id = $2
+            FOR UPDATE OF p
+            "#,
+            claims.victim,
+            game.id
+        )
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or(AppError::NotFound(
+            ErrorCode::UnknownSecret,
+            "error.UNKNOWN_SECRET",
+        ))?;
+
+        let generation = sqlx::query_scalar!(
+            "SELECT secret_rotations FROM players WHERE id = $1",
+            target.id
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+        engine::check_token_generation(claims, generation)?;
+
+        Ok(target)
+    }
+
+    /// Record a kill token's nonce so the same QR code cannot be used twice.
+    #[instrument(skip_all, fields(game_id = game_id))]
+    pub(crate) async fn consume_kill_token_in_tx<'a>(
+        &self,
+        tx: &mut sqlx::Transaction<'a, sqlx::Postgres>,
+        claims: &KillTokenClaims,
+        game_id: i32,
+    ) -> Result<(), AppError> {
+        sqlx::query!("DELETE FROM used_kill_tokens WHERE expires_at < NOW()")
+            .execute(&mut **tx)
+            .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO used_kill_tokens (nonce, game_id, expires_at)
+            VALUES ($1, $2, TO_TIMESTAMP($3))
+            "#,
+            claims.nonce,
+            game_id,
+            claims.exp as f64
+        )
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| {
+            if let Some(db_err) = e.as_database_error() {
+                if db_err.is_unique_violation() {
+                    return engine::kill_token_used();
+                }
+            }
+            tracing::warn!(game_id, "Failed to record kill token: {}", e);
+            AppError::from(e)
+        })?;
+        Ok(())
+    }
 }
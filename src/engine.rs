@@ -0,0 +1,621 @@
+//! The rules of the game, free of any storage. Repositories load the rows a
+//! rule needs, ask the engine whether the move is allowed and what changes,
+//! and then persist the result.
+
+use crate::errors::{AppError, ErrorCode};
+use crate::kill_token::KillTokenClaims;
+use crate::models::{
+    ChatChannel, DisputeStatus, Game, GameSettings, GameStatus, KillDispute, Player,
+    RecoveryRequest, RequestStatus,
+};
+use rand::seq::{IndexedRandom, SliceRandom};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use tracing::instrument;
+
+/// Fewest players a game can be started with.
+pub const MIN_PLAYERS: usize = 2;
+
+// ---------- Lobby ----------
+
+/// Fewest players `settings` lets a game start with.
+pub fn min_players(settings: &GameSettings) -> usize {
+    settings
+        .min_players
+        .map_or(MIN_PLAYERS, |min| (min as usize).max(MIN_PLAYERS))
+}
+
+/// Most players `settings` lets into the lobby, within the server's `cap`.
+pub fn max_players(settings: &GameSettings, cap: u32) -> usize {
+    settings.max_players.unwrap_or(cap).min(cap) as usize
+}
+
+/// Whether the host may pick `settings` on a server allowing `cap` players
+/// per lobby.
+pub fn check_settings(settings: &GameSettings, cap: u32) -> Result<(), AppError> {
+    let too_small = settings
+        .max_players
+        .is_some_and(|max| (max as usize) < MIN_PLAYERS);
+    if too_small
+        || settings.max_players > Some(cap)
+        || min_players(settings) > max_players(settings, cap)
+    {
+        return Err(AppError::UnprocessableEntity(
+            ErrorCode::InvalidLobbySize { max: cap },
+            "error.INVALID_LOBBY_SIZE",
+        ));
+    }
+    Ok(())
+}
+
+/// Whether a player may join `game`, which already has `player_count`
+/// players and takes at most `max_players`, given the player already using
+/// that name in it, if any. Running games only take newcomers when
+/// `late_join` is on.
+pub fn check_join(
+    game: &Game,
+    same_name: Option<&Player>,
+    player_count: usize,
+    max_players: usize,
+    late_join: bool,
+) -> Result<(), AppError> {
+    let open = match game.status {
+        GameStatus::Lobby => true,
+        GameStatus::InProgress => late_join,
+        GameStatus::Finished => false,
+    };
+    if !open {
+        return Err(AppError::UnprocessableEntity(
+            ErrorCode::GameAlreadyStarted,
+            "error.GAME_ALREADY_STARTED",
+        ));
+    }
+    if same_name.is_none() && player_count >= max_players {
+        return Err(AppError::UnprocessableEntity(
+            ErrorCode::LobbyFull { max: max_players },
+            "error.LOBBY_FULL",
+        ));
+    }
+    match same_name {
+        // A living player with this name is already in the lobby – reject the join attempt.
+        Some(p) if p.is_alive => Err(AppError::Conflict(ErrorCode::NameTaken, "error.NAME_TAKEN")),
+        // The player existed previously but has been eliminated – they cannot re-join.
+        Some(_) => Err(AppError::Forbidden(
+            ErrorCode::PlayerEliminated,
+            "error.PLAYER_ELIMINATED.rejoin",
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Whether `player_id` may start `game` with `player_count` players in it,
+/// when it needs at least `min_players`.
+pub fn check_start(
+    game: &Game,
+    player_id: i32,
+    player_count: usize,
+    min_players: usize,
+) -> Result<(), AppError> {
+    if game.host_id != Some(player_id) {
+        return Err(AppError::Forbidden(ErrorCode::NotHost, "error.NOT_HOST"));
+    }
+    if game.status != GameStatus::Lobby {
+        return Err(AppError::UnprocessableEntity(
+            ErrorCode::GameAlreadyStarted,
+            "error.GAME_ALREADY_STARTED.start",
+        ));
+    }
+    if player_count < min_players {
+        return Err(AppError::UnprocessableEntity(
+            ErrorCode::NotEnoughPlayers { min: min_players },
+            "error.NOT_ENOUGH_PLAYERS",
+        ));
+    }
+    Ok(())
+}
+
+/// Whether the lobby of `players` should start by itself: auto-start is on,
+/// enough players joined and every one of them is ready.
+pub fn should_auto_start(settings: &GameSettings, players: &[Player]) -> bool {
+    settings.auto_start
+        && players.len() >= min_players(settings)
+        && players.iter().all(|p| p.is_ready)
+}
+
+/// Shuffle the players into a single ring; returns `(hunter, target)` pairs.
+pub fn assign_targets(mut ids: Vec<i32>) -> Vec<(i32, i32)> {
+    ids.shuffle(&mut rand::rng());
+    (0..ids.len())
+        .map(|idx| (ids[idx], ids[(idx + 1) % ids.len()]))
+        .collect()
+}
+
+/// The player who becomes host when the current one leaves the lobby: the
+/// one who joined earliest.
+pub fn next_host(remaining: &[Player]) -> Option<i32> {
+    remaining.iter().map(|p| p.id).min()
+}
+
+// ---------- Kills ----------
+
+#[instrument(skip_all, fields(killer_id = killer.id, target_id = target.id))]
+pub fn validate_kill(killer: &Player, target: &Player, game: &Game) -> Result<(), AppError> {
+    if !killer.is_alive {
+        return Err(AppError::Forbidden(
+            ErrorCode::KillerEliminated,
+            "error.KILLER_ELIMINATED",
+        ));
+    }
+    if !target.is_alive {
+        return Err(AppError::Forbidden(
+            ErrorCode::TargetEliminated,
+            "error.TARGET_ELIMINATED",
+        ));
+    }
+    if killer.game_id != target.game_id {
+        return Err(AppError::Forbidden(
+            ErrorCode::NotSameGame,
+            "error.NOT_SAME_GAME",
+        ));
+    }
+    if killer.id == target.id {
+        return Err(AppError::Forbidden(ErrorCode::SelfKill, "error.SELF_KILL"));
+    }
+    if game.status != GameStatus::InProgress {
+        return Err(AppError::UnprocessableEntity(
+            ErrorCode::GameNotInProgress,
+            "error.GAME_NOT_IN_PROGRESS",
+        ));
+    }
+    if killer.target_id != Some(target.id) {
+        return Err(AppError::Forbidden(
+            ErrorCode::WrongTarget,
+            "error.WRONG_TARGET",
+        ));
+    }
+    Ok(())
+}
+
+/// A signed kill token only works in the game it was issued for.
+pub fn check_token_game(claims: &KillTokenClaims, game: &Game) -> Result<(), AppError> {
+    if claims.game != game.code {
+        return Err(AppError::Forbidden(
+            ErrorCode::KillTokenWrongGame,
+            "error.KILL_TOKEN_WRONG_GAME",
+        ));
+    }
+    Ok(())
+}
+
+/// A signed kill token stops working once its owner rotates their secret.
+pub fn check_token_generation(claims: &KillTokenClaims, generation: i32) -> Result<(), AppError> {
+    if generation != claims.generation {
+        return Err(AppError::Forbidden(
+            ErrorCode::KillTokenStale,
+            "error.KILL_TOKEN_STALE",
+        ));
+    }
+    Ok(())
+}
+
+pub fn kill_token_used() -> AppError {
+    AppError::Conflict(ErrorCode::KillTokenUsed, "error.KILL_TOKEN_USED")
+}
+
+// ---------- Secrets ----------
+
+/// Whether a player may rotate their secret, given how often they already
+/// did and how many seconds of the cooldown are left.
+pub fn check_rotation(
+    game: &Game,
+    player: &Player,
+    rotations: i32,
+    max_rotations: i32,
+    cooldown_left: Option<i64>,
+) -> Result<(), AppError> {
+    if game.status == GameStatus::Finished {
+        return Err(AppError::UnprocessableEntity(
+            ErrorCode::GameFinished,
+            "error.GAME_FINISHED",
+        ));
+    }
+    if !player.is_alive {
+        return Err(AppError::Forbidden(
+            ErrorCode::PlayerEliminated,
+            "error.PLAYER_ELIMINATED.rotate",
+        ));
+    }
+    if rotations >= max_rotations {
+        return Err(AppError::Forbidden(
+            ErrorCode::SecretRotationLimit {
+                limit: max_rotations,
+            },
+            "error.SECRET_ROTATION_LIMIT",
+        ));
+    }
+    if let Some(wait) = cooldown_left.filter(|&secs| secs > 0) {
+        return Err(AppError::TooManyRequests(
+            ErrorCode::SecretRotationCooldown { seconds: wait },
+            "error.SECRET_ROTATION_COOLDOWN",
+        ));
+    }
+    Ok(())
+}
+
+// ---------- Target ring ----------
+
+/// A way in which the living players of an in-progress game fail to form a
+/// single target ring.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, utoipa::ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RingViolation {
+    /// The game is still running with fewer than two players alive.
+    TooFewAlive { alive: usize },
+    /// A living player has no target at all.
+    MissingTarget { player_id: i32 },
+    /// A living player targets themselves.
+    SelfTarget { player_id: i32 },
+    /// A living player targets someone who is dead or not in the game.
+    DeadTarget { player_id: i32, target_id: i32 },
+    /// Several living players hunt the same target.
+    SharedTarget { target_id: i32, hunters: Vec<i32> },
+    /// Nobody alive is hunting this living player.
+    Unhunted { player_id: i32 },
+    /// The living players form more than one cycle.
+    SplitRing { cycles: usize },
+}
+
+/// Outcome of a repair: what was wrong and which targets were reassigned.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RingRepair {
+    pub violations: Vec<RingViolation>,
+    /// `(player_id, new_target_id)` for every target that was changed.
+    pub reassigned: Vec<(i32, Option<i32>)>,
+    /// Set when the game had to be finished because one player was left.
+    pub winner_id: Option<i32>,
+}
+
+/// Check that the living players form exactly one cycle through `target_id`.
+/// Dead players are ignored; they keep whatever target they had when they died.
+pub fn ring_violations(players: &[Player]) -> Vec<RingViolation> {
+    let alive: HashMap<i32, &Player> = players
+        .iter()
+        .filter(|p| p.is_alive)
+        .map(|p| (p.id, p))
+        .collect();
+    if alive.len() < 2 {
+        return vec![RingViolation::TooFewAlive { alive: alive.len() }];
+    }
+
+    let mut violations = Vec::new();
+    let mut hunters: HashMap<i32, Vec<i32>> = HashMap::new();
+    let mut ids: Vec<i32> = alive.keys().copied().collect();
+    ids.sort_unstable();
+
+    for &id in &ids {
+        match alive[&id].target_id {
+            None => violations.push(RingViolation::MissingTarget { player_id: id }),
+            Some(target) if target == id => {
+                violations.push(RingViolation::SelfTarget { player_id: id })
+            }
+            Some(target) if !alive.contains_key(&target) => {
+                violations.push(RingViolation::DeadTarget {
+                    player_id: id,
+                    target_id: target,
+                })
+            }
+            Some(target) => hunters.entry(target).or_default().push(id),
+        }
+    }
+    for &id in &ids {
+        match hunters.get(&id) {
+            None => violations.push(RingViolation::Unhunted { player_id: id }),
+            Some(h) if h.len() > 1 => violations.push(RingViolation::SharedTarget {
+                target_id: id,
+                hunters: h.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    // Only a clean permutation can be split into cycles; anything else has
+    // already been reported above.
+    if violations.is_empty() {
+        let mut seen = HashSet::new();
+        let mut cycles = 0;
+        for &start in &ids {
+            if seen.contains(&start) {
+                continue;
+            }
+            cycles += 1;
+            let mut current = start;
+            while seen.insert(current) {
+                current = alive[&current].target_id.unwrap_or(start);
+            }
+        }
+        if cycles > 1 {
+            violations.push(RingViolation::SplitRing { cycles });
+        }
+    }
+    violations
+}
+
+/// Order the living players into a single ring, keeping as many of the
+/// existing hunter -> target links as possible. Each player targets the next
+/// one in the returned order, and the last one targets the first.
+pub fn ring_order(players: &[Player]) -> Vec<i32> {
+    let alive: HashMap<i32, &Player> = players
+        .iter()
+        .filter(|p| p.is_alive)
+        .map(|p| (p.id, p))
+        .collect();
+    let mut ids: Vec<i32> = alive.keys().copied().collect();
+    ids.sort_unstable();
+
+    // Start chains at players nobody alive is hunting, so the chains they
+    // head are kept whole instead of being cut in the middle.
+    let hunted: HashSet<i32> = alive
+        .values()
+        .filter_map(|p| p.target_id)
+        .filter(|t| alive.contains_key(t))
+        .collect();
+    let (heads, rest): (Vec<i32>, Vec<i32>) = ids.iter().partition(|id| !hunted.contains(id));
+
+    let mut order = Vec::with_capacity(ids.len());
+    let mut visited = HashSet::new();
+    for start in heads.into_iter().chain(rest) {
+        let mut current = start;
+        while visited.insert(current) {
+            order.push(current);
+            match alive[&current].target_id {
+                Some(next) if alive.contains_key(&next) => current = next,
+                _ => break,
+            }
+        }
+    }
+    order
+}
+
+/// How to turn a broken ring back into a valid game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RingPlan {
+    /// Give these `(player_id, new_target_id)` their new targets.
+    Retarget(Vec<(i32, i32)>),
+    /// Fewer than two players are alive: the game is over.
+    Finish { winner_id: Option<i32> },
+}
+
+pub fn plan_ring_repair(players: &[Player]) -> RingPlan {
+    let order = ring_order(players);
+    if order.len() < 2 {
+        return RingPlan::Finish {
+            winner_id: order.first().copied(),
+        };
+    }
+    let current: HashMap<i32, Option<i32>> = players.iter().map(|p| (p.id, p.target_id)).collect();
+    RingPlan::Retarget(
+        order
+            .iter()
+            .enumerate()
+            .map(|(idx, &pid)| (pid, order[(idx + 1) % order.len()]))
+            .filter(|(pid, target)| current[pid] != Some(*target))
+            .collect(),
+    )
+}
+
+/// Bring `player_id` back into a running game right behind `hunter_id`, or
+/// behind the living player with the lowest id when the hunter is out: they
+/// take over the hunter's target and become the hunter's target, so nobody
+/// else's target changes. `(player_id, new_target_id)` for both changes;
+/// `None` when nobody alive could hunt them.
+pub fn plan_splice(
+    players: &[Player],
+    player_id: i32,
+    hunter_id: Option<i32>,
+) -> Option<Vec<(i32, i32)>> {
+    let alive = players.iter().filter(|p| p.is_alive && p.id != player_id);
+    let hunter = match alive.clone().find(|p| Some(p.id) == hunter_id) {
+        Some(hunter) => hunter,
+        None => alive.min_by_key(|p| p.id)?,
+    };
+    let target_id = hunter.target_id?;
+    Some(vec![(player_id, target_id), (hunter.id, player_id)])
+}
+
+/// Splice `player_id`, who just joined a running game, in behind a living
+/// player picked at random; see [`plan_splice`].
+pub fn plan_late_join(players: &[Player], player_id: i32) -> Option<Vec<(i32, i32)>> {
+    let hunter = players
+        .iter()
+        .filter(|p| p.is_alive && p.id != player_id && p.target_id.is_some())
+        .map(|p| p.id)
+        .collect::<Vec<_>>()
+        .choose(&mut rand::rng())
+        .copied();
+    plan_splice(players, player_id, hunter)
+}
+
+// ---------- Ghosts ----------
+
+/// Who gets a vote on a disputed kill: every eliminated player except the
+/// victim and the killer.
+pub fn dispute_voters(players: &[Player], victim_id: i32, killer_id: Option<i32>) -> Vec<i32> {
+    players
+        .iter()
+        .filter(|p| !p.is_alive && p.id != victim_id && Some(p.id) != killer_id)
+        .map(|p| p.id)
+        .collect()
+}
+
+/// Matching votes that settle a dispute among `voters` ghosts.
+pub fn dispute_majority(voters: usize) -> usize {
+    voters / 2 + 1
+}
+
+/// How a dispute ends given its `(player_id, overturn)` votes: as soon as a
+/// majority of `voters` agrees, or once all of them voted without one, in
+/// which case the kill stands. Votes from anyone else are ignored.
+pub fn dispute_outcome(votes: &[(i32, bool)], voters: &[i32]) -> Option<DisputeStatus> {
+    let counted: Vec<bool> = votes
+        .iter()
+        .filter(|(pid, _)| voters.contains(pid))
+        .map(|&(_, overturn)| overturn)
+        .collect();
+    let overturn = counted.iter().filter(|&&o| o).count();
+    let uphold = counted.len() - overturn;
+    let needed = dispute_majority(voters.len());
+    if voters.is_empty() {
+        None
+    } else if overturn >= needed {
+        Some(DisputeStatus::Overturned)
+    } else if uphold >= needed || counted.len() == voters.len() {
+        Some(DisputeStatus::Upheld)
+    } else {
+        None
+    }
+}
+
+/// Count `voter_id`'s vote on `dispute`, replacing their earlier one, and
+/// say how the dispute ends if this vote decides it. Only open disputes take
+/// votes, and never from the victim or the killer.
+pub fn cast_vote(
+    dispute: &mut KillDispute,
+    players: &[Player],
+    voter_id: i32,
+    overturn: bool,
+) -> Result<Option<DisputeStatus>, AppError> {
+    if dispute.status != DisputeStatus::Open {
+        return Err(AppError::Conflict(
+            ErrorCode::DisputeClosed,
+            "error.DISPUTE_CLOSED",
+        ));
+    }
+    if voter_id == dispute.victim_id || Some(voter_id) == dispute.killer_id {
+        return Err(AppError::Forbidden(
+            ErrorCode::OwnDispute,
+            "error.OWN_DISPUTE",
+        ));
+    }
+    dispute.votes.retain(|&(pid, _)| pid != voter_id);
+    dispute.votes.push((voter_id, overturn));
+    let voters = dispute_voters(players, dispute.victim_id, dispute.killer_id);
+    Ok(dispute_outcome(&dispute.votes, &voters))
+}
+
+// ---------- Chat ----------
+
+/// Whether `player` may read `channel`: announcements and the lobby chat are
+/// for everyone, the dead chat only for the eliminated.
+pub fn can_read_chat(channel: ChatChannel, player: &Player, settings: &GameSettings) -> bool {
+    match channel {
+        ChatChannel::Announcements | ChatChannel::Lobby => true,
+        ChatChannel::Dead => settings.dead_chat && !player.is_alive,
+    }
+}
+
+/// Whether spectators may read `channel`; never the dead chat.
+pub fn spectators_read_chat(channel: ChatChannel, settings: &GameSettings) -> bool {
+    settings.spectator_chat && channel != ChatChannel::Dead
+}
+
+/// Whether `player` may post to `channel` of `game` right now.
+pub fn check_chat_post(
+    game: &Game,
+    settings: &GameSettings,
+    player: &Player,
+    channel: ChatChannel,
+) -> Result<(), AppError> {
+    match channel {
+        ChatChannel::Announcements if game.host_id != Some(player.id) => Err(AppError::Forbidden(
+            ErrorCode::NotHost,
+            "error.NOT_HOST.announce",
+        )),
+        ChatChannel::Lobby if game.status != GameStatus::Lobby => Err(
+            AppError::UnprocessableEntity(ErrorCode::ChatClosed, "error.CHAT_CLOSED.lobby"),
+        ),
+        ChatChannel::Dead if !settings.dead_chat || game.status == GameStatus::Lobby => Err(
+            AppError::UnprocessableEntity(ErrorCode::ChatClosed, "error.CHAT_CLOSED.dead"),
+        ),
+        ChatChannel::Dead if player.is_alive => Err(AppError::Forbidden(
+            ErrorCode::ChatForbidden,
+            "error.CHAT_FORBIDDEN.dead",
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Whether a player who sent messages at `sent_at` (unix seconds) may send
+/// another at `now`, at most `limit` of them in any `window_secs`.
+pub fn check_chat_rate(
+    sent_at: &[i64],
+    now: i64,
+    limit: u32,
+    window_secs: i64,
+) -> Result<(), AppError> {
+    match rate_wait(sent_at, now, limit, window_secs) {
+        None => Ok(()),
+        Some(wait) => Err(AppError::TooManyRequests(
+            ErrorCode::ChatRateLimited { seconds: wait },
+            "error.CHAT_RATE_LIMITED",
+        )),
+    }
+}
+
+/// Seconds to wait at `now` before doing something again that was done at
+/// `done_at`, at most `limit` times in any `window_secs`; `None` when it may
+/// be done straight away.
+fn rate_wait(done_at: &[i64], now: i64, limit: u32, window_secs: i64) -> Option<i64> {
+    let mut recent: Vec<i64> = done_at
+        .iter()
+        .copied()
+        .filter(|&at| at > now - window_secs)
+        .collect();
+    if recent.len() < limit as usize {
+        return None;
+    }
+    // Wait until enough of them fall out of the window.
+    recent.sort_unstable();
+    let frees_up = recent[recent.len() - limit as usize] + window_secs;
+    Some((frees_up - now).max(1))
+}
+
+// ---------- Recovery ----------
+
+/// Whether someone may ask at `now` to let `player_id` back in, given the
+/// game's `requests`: one pending request per player, and at most `limit`
+/// requests per game in any `window_secs`.
+pub fn check_recovery_request(
+    requests: &[RecoveryRequest],
+    player_id: i32,
+    now: i64,
+    limit: u32,
+    window_secs: i64,
+) -> Result<(), AppError> {
+    if requests
+        .iter()
+        .any(|r| r.player_id == player_id && r.status == RequestStatus::Pending)
+    {
+        return Err(recovery_pending());
+    }
+    let requested_at: Vec<i64> = requests.iter().map(|r| r.created_at).collect();
+    match rate_wait(&requested_at, now, limit, window_secs) {
+        None => Ok(()),
+        Some(wait) => Err(AppError::TooManyRequests(
+            ErrorCode::RecoveryRateLimited { seconds: wait },
+            "error.RECOVERY_RATE_LIMITED",
+        )),
+    }
+}
+
+/// Refusal of a recovery request for a player who already has one pending.
+pub fn recovery_pending() -> AppError {
+    AppError::Conflict(ErrorCode::RecoveryPending, "error.RECOVERY_PENDING")
+}
+
+/// Whether `answerer_id` may answer a recovery request for `player_id`. The
+/// host answers everyone else's; when the host lost their own link, any
+/// other player may let them back in.
+pub fn may_answer_recovery(game: &Game, answerer_id: i32, player_id: i32) -> bool {
+    answerer_id != player_id
+        && (game.host_id == Some(answerer_id) || game.host_id == Some(player_id))
+}
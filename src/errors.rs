@@ -1,42 +1,256 @@
+use crate::{
+    i18n::{self, Locale},
+    payloads::ErrorPayload,
+    request_id,
+};
 use axum::{http::StatusCode, response::IntoResponse, Json};
-use serde_json::json;
+use serde::{Serialize, Serializer};
+use std::fmt::Display;
 use thiserror::Error;
 
+/// Stable, machine-readable error codes sent alongside the human readable
+/// message. Clients should branch on these, never on the message text.
+///
+/// Codes that need values in their message carry them, so the message can be
+/// rendered from the catalogue in any locale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    InternalError,
+    Unauthorized,
+    InvalidAuthToken,
+    InvalidSpectatorToken,
+    GameNotFound,
+    GameNotInProgress,
+    GameAlreadyStarted,
+    GameFinished,
+    NotHost,
+    NotAdmin,
+    FeatureDisabled,
+    NotEnoughPlayers { min: usize },
+    LobbyFull { max: usize },
+    InvalidLobbySize { max: u32 },
+    NameTaken,
+    InvalidName { max: usize },
+    PlayerEliminated,
+    KillerEliminated,
+    TargetEliminated,
+    NotSameGame,
+    SelfKill,
+    WrongTarget,
+    UnknownSecret,
+    InvalidKillToken,
+    KillTokenExpired,
+    KillTokenWrongGame,
+    KillTokenStale,
+    KillTokenUsed,
+    SecretRotationLimit { limit: i32 },
+    SecretRotationCooldown { seconds: i64 },
+    PlayerNotFound,
+    NotAGhost,
+    NotDisputable,
+    AlreadyDisputed,
+    DisputeNotFound,
+    DisputeClosed,
+    OwnDispute,
+    HintsDisabled,
+    HintAlreadySent,
+    HintTargetDead,
+    InvalidMessage { max: usize },
+    ChatClosed,
+    ChatForbidden,
+    ChatRateLimited { seconds: i64 },
+    RecoveryNotFound,
+    RecoveryClosed,
+    RecoveryPending,
+    RecoveryRateLimited { seconds: i64 },
+    JoinRequestNotFound,
+    JoinRequestClosed,
+    Conflict,
+    SerializationFailure,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::InternalError => "INTERNAL_ERROR",
+            ErrorCode::Unauthorized => "UNAUTHORIZED",
+            ErrorCode::InvalidAuthToken => "INVALID_AUTH_TOKEN",
+            ErrorCode::InvalidSpectatorToken => "INVALID_SPECTATOR_TOKEN",
+            ErrorCode::GameNotFound => "GAME_NOT_FOUND",
+            ErrorCode::GameNotInProgress => "GAME_NOT_IN_PROGRESS",
+            ErrorCode::GameAlreadyStarted => "GAME_ALREADY_STARTED",
+            ErrorCode::GameFinished => "GAME_FINISHED",
+            ErrorCode::NotHost => "NOT_HOST",
+            ErrorCode::NotAdmin => "NOT_ADMIN",
+            ErrorCode::FeatureDisabled => "FEATURE_DISABLED",
+            ErrorCode::NotEnoughPlayers { .. } => "NOT_ENOUGH_PLAYERS",
+            ErrorCode::LobbyFull { .. } => "LOBBY_FULL",
+            ErrorCode::InvalidLobbySize { .. } => "INVALID_LOBBY_SIZE",
+            ErrorCode::NameTaken => "NAME_TAKEN",
+            ErrorCode::InvalidName { .. } => "INVALID_NAME",
+            ErrorCode::PlayerEliminated => "PLAYER_ELIMINATED",
+            ErrorCode::KillerEliminated => "KILLER_ELIMINATED",
+            ErrorCode::TargetEliminated => "TARGET_ELIMINATED",
+            ErrorCode::NotSameGame => "NOT_SAME_GAME",
+            ErrorCode::SelfKill => "SELF_KILL",
+            ErrorCode::WrongTarget => "WRONG_TARGET",
+            ErrorCode::UnknownSecret => "UNKNOWN_SECRET",
+            ErrorCode::InvalidKillToken => "INVALID_KILL_TOKEN",
+            ErrorCode::KillTokenExpired => "KILL_TOKEN_EXPIRED",
+            ErrorCode::KillTokenWrongGame => "KILL_TOKEN_WRONG_GAME",
+            ErrorCode::KillTokenStale => "KILL_TOKEN_STALE",
+            ErrorCode::KillTokenUsed => "KILL_TOKEN_USED",
+            ErrorCode::SecretRotationLimit { .. } => "SECRET_ROTATION_LIMIT",
+            ErrorCode::SecretRotationCooldown { .. } => "SECRET_ROTATION_COOLDOWN",
+            ErrorCode::PlayerNotFound => "PLAYER_NOT_FOUND",
+            ErrorCode::NotAGhost => "NOT_A_GHOST",
+            ErrorCode::NotDisputable => "NOT_DISPUTABLE",
+            ErrorCode::AlreadyDisputed => "ALREADY_DISPUTED",
+            ErrorCode::DisputeNotFound => "DISPUTE_NOT_FOUND",
+            ErrorCode::DisputeClosed => "DISPUTE_CLOSED",
+            ErrorCode::OwnDispute => "OWN_DISPUTE",
+            ErrorCode::HintsDisabled => "HINTS_DISABLED",
+            ErrorCode::HintAlreadySent => "HINT_ALREADY_SENT",
+            ErrorCode::HintTargetDead => "HINT_TARGET_DEAD",
+            ErrorCode::InvalidMessage { .. } => "INVALID_MESSAGE",
+            ErrorCode::ChatClosed => "CHAT_CLOSED",
+            ErrorCode::ChatForbidden => "CHAT_FORBIDDEN",
+            ErrorCode::ChatRateLimited { .. } => "CHAT_RATE_LIMITED",
+            ErrorCode::RecoveryNotFound => "RECOVERY_NOT_FOUND",
+            ErrorCode::RecoveryClosed => "RECOVERY_CLOSED",
+            ErrorCode::RecoveryPending => "RECOVERY_PENDING",
+            ErrorCode::RecoveryRateLimited { .. } => "RECOVERY_RATE_LIMITED",
+            ErrorCode::JoinRequestNotFound => "JOIN_REQUEST_NOT_FOUND",
+            ErrorCode::JoinRequestClosed => "JOIN_REQUEST_CLOSED",
+            ErrorCode::Conflict => "CONFLICT",
+            ErrorCode::SerializationFailure => "SERIALIZATION_FAILURE",
+        }
+    }
+
+    /// Values substituted into the catalogue message for this code.
+    pub fn params(&self) -> Vec<(&'static str, String)> {
+        match self {
+            ErrorCode::NotEnoughPlayers { min } => vec![("min", min.to_string())],
+            ErrorCode::LobbyFull { max } => vec![("max", max.to_string())],
+            ErrorCode::InvalidLobbySize { max } => vec![("max", max.to_string())],
+            ErrorCode::SecretRotationLimit { limit } => vec![("limit", limit.to_string())],
+            ErrorCode::SecretRotationCooldown { seconds } => {
+                vec![("seconds", seconds.to_string())]
+            }
+            ErrorCode::InvalidName { max } => vec![("max", max.to_string())],
+            ErrorCode::InvalidMessage { max } => vec![("max", max.to_string())],
+            ErrorCode::ChatRateLimited { seconds } => vec![("seconds", seconds.to_string())],
+            ErrorCode::RecoveryRateLimited { seconds } => {
+                vec![("seconds", seconds.to_string())]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl Serialize for ErrorCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An error sent back to the client. Besides the status and the code, each
+/// error carries the catalogue key of its message, so two places failing with
+/// the same code can still explain themselves differently.
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error("Internal server error")]
     InternalServerError,
     #[error("Unauthorized")]
     Unauthorized,
-    #[error("Forbidden: {0}")]
-    Forbidden(String),
-    #[error("Not found: {0}")]
-    NotFound(String),
-    #[error("Unprocessable entity: {0}")]
-    UnprocessableEntity(String),
+    #[error("Forbidden ({0}): {message}", message = english(.0, .1))]
+    Forbidden(ErrorCode, &'static str),
+    #[error("Not found ({0}): {message}", message = english(.0, .1))]
+    NotFound(ErrorCode, &'static str),
+    #[error("Conflict ({0}): {message}", message = english(.0, .1))]
+    Conflict(ErrorCode, &'static str),
+    #[error("Unprocessable entity ({0}): {message}", message = english(.0, .1))]
+    UnprocessableEntity(ErrorCode, &'static str),
+    #[error("Too many requests ({0}): {message}", message = english(.0, .1))]
+    TooManyRequests(ErrorCode, &'static str),
+}
+
+fn english(code: &ErrorCode, key: &str) -> String {
+    i18n::translate(Locale::En, key, &code.params())
+}
+
+impl AppError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::InternalServerError => ErrorCode::InternalError,
+            AppError::Unauthorized => ErrorCode::Unauthorized,
+            AppError::Forbidden(code, _)
+            | AppError::NotFound(code, _)
+            | AppError::Conflict(code, _)
+            | AppError::UnprocessableEntity(code, _)
+            | AppError::TooManyRequests(code, _) => *code,
+        }
+    }
+
+    /// Catalogue key of the message explaining this error.
+    pub fn message_key(&self) -> &'static str {
+        match self {
+            AppError::InternalServerError => "error.INTERNAL_ERROR",
+            AppError::Unauthorized => "error.UNAUTHORIZED",
+            AppError::Forbidden(_, key)
+            | AppError::NotFound(_, key)
+            | AppError::Conflict(_, key)
+            | AppError::UnprocessableEntity(_, key)
+            | AppError::TooManyRequests(_, key) => key,
+        }
+    }
+
+    /// The message in `locale`, with the values the code carries filled in.
+    pub fn message(&self, locale: Locale) -> String {
+        i18n::translate(locale, self.message_key(), &self.code().params())
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AppError::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::Unauthorized => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(..) => StatusCode::FORBIDDEN,
+            AppError::NotFound(..) => StatusCode::NOT_FOUND,
+            AppError::Conflict(..) => StatusCode::CONFLICT,
+            AppError::UnprocessableEntity(..) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::TooManyRequests(..) => StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> axum::response::Response {
-        let (status, error_message) = match self {
-            AppError::InternalServerError => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Internal Server Error".to_string(),
-            ),
-            AppError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized".to_string()),
-            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
-            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
-            AppError::UnprocessableEntity(msg) => (StatusCode::UNPROCESSABLE_ENTITY, msg),
-        };
+        let status = self.status();
+        let code = self.code();
+        // Logs stay in English; the client gets the message in its own locale.
+        let error_message = self.message(Locale::En);
 
         match status.is_server_error() {
-            true => tracing::error!(status = %status, "Sending error response: {}", error_message),
-            false => tracing::info!(status = %status, "Sending error response: {}", error_message),
+            true => {
+                tracing::error!(status = %status, code = %code, "Sending error response: {}", error_message)
+            }
+            false => {
+                tracing::info!(status = %status, code = %code, "Sending error response: {}", error_message)
+            }
         }
 
-        let body = Json(json!({
-            "error": error_message,
-        }));
+        let body = Json(ErrorPayload {
+            error: self.message(i18n::current_locale()),
+            code: code.as_str().to_string(),
+            // Lets a player quote something we can find in the logs.
+            request_id: request_id::current_request_id(),
+        });
 
         (status, body).into_response()
     }
@@ -44,6 +258,20 @@ impl IntoResponse for AppError {
 
 impl From<sqlx::Error> for AppError {
     fn from(err: sqlx::Error) -> Self {
+        if let Some(db_err) = err.as_database_error() {
+            if db_err.is_unique_violation() {
+                tracing::warn!(error = ?err, "SQLx unique violation");
+                return AppError::Conflict(ErrorCode::Conflict, "error.CONFLICT");
+            }
+            // 40001: serialization_failure, 40P01: deadlock_detected
+            if matches!(db_err.code().as_deref(), Some("40001") | Some("40P01")) {
+                tracing::warn!(error = ?err, "SQLx serialization failure");
+                return AppError::Conflict(
+                    ErrorCode::SerializationFailure,
+                    "error.SERIALIZATION_FAILURE",
+                );
+            }
+        }
         tracing::error!(error = ?err, "SQLx error");
         AppError::InternalServerError
     }
@@ -0,0 +1,105 @@
+use super::ghosts::role_of;
+use super::utils::{authenticate, game_not_found};
+use crate::{
+    errors::AppError,
+    models::{GameEvent, GameEventKind, GameSettings, PlayerRole},
+    payloads::{ActivityPayload, GameEventPayload},
+    state::AppState,
+};
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+const DEFAULT_PAGE_SIZE: i64 = 20;
+const MAX_PAGE_SIZE: i64 = 100;
+
+#[derive(Deserialize, IntoParams)]
+pub struct ActivityQuery {
+    /// `next_cursor` of the previous page; leave out for the newest events.
+    pub before: Option<i64>,
+    /// At most this many events, up to 100. Defaults to 20.
+    pub limit: Option<i64>,
+}
+
+/// `event` as shown to the player with id `viewer`, or to a spectator.
+pub(crate) fn event_seen_by(
+    event: GameEvent,
+    settings: &GameSettings,
+    viewer: Option<i32>,
+) -> GameEventPayload {
+    let anonymous = event.kind == GameEventKind::Kill
+        && settings.anonymise_killer
+        && (viewer.is_none() || viewer != event.actor_id);
+    GameEventPayload {
+        id: event.id,
+        kind: event.kind,
+        actor_name: event.actor_name.filter(|_| !anonymous),
+        subject_name: event.subject_name,
+        created_at: event.created_at,
+    }
+}
+
+/// A page of the activity feed of the game the owner of `auth_token` is in.
+pub(crate) async fn feed(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+    query: ActivityQuery,
+) -> Result<ActivityPayload, AppError> {
+    let player = authenticate(state, auth_token).await?;
+    let game = state
+        .db
+        .get_game_by_id(player.game_id)
+        .await?
+        .filter(|g| g.code == game_code)
+        .ok_or_else(game_not_found)?;
+    let mut settings = state
+        .db
+        .get_game_settings(game.id)
+        .await?
+        .unwrap_or_default();
+    // Ghosts see who killed whom, so they can judge disputed kills.
+    if role_of(&player, &game, &settings) == PlayerRole::Ghost {
+        settings.anonymise_killer = false;
+    }
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    // One extra tells whether there is another page.
+    let mut events = state
+        .db
+        .get_events_page(game.id, query.before, limit + 1)
+        .await?;
+    let next_cursor = match events.len() as i64 > limit {
+        true => {
+            events.truncate(limit as usize);
+            events.last().map(|e| e.id)
+        }
+        false => None,
+    };
+    Ok(ActivityPayload {
+        events: events
+            .into_iter()
+            .map(|e| event_seen_by(e, &settings, Some(player.id)))
+            .collect(),
+        next_cursor,
+    })
+}
+
+pub async fn get_activity(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    Query(query): Query<ActivityQuery>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(feed(&state, &game_code, auth.token(), query).await?))
+}
@@ -0,0 +1,99 @@
+use super::utils::bump_game_version;
+use crate::{
+    config::Config,
+    errors::{AppError, ErrorCode},
+    payloads::{FinishedGamePayload, PurgedGamesPayload, RingCheckPayload},
+    state::AppState,
+};
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+use tracing::info;
+
+/// Admin endpoints are disabled unless an admin token is configured, and then
+/// require it as the bearer token.
+fn require_admin(config: &Config, auth: &Authorization<Bearer>) -> Result<(), AppError> {
+    let not_admin = || AppError::Forbidden(ErrorCode::NotAdmin, "error.NOT_ADMIN");
+    let expected = config
+        .admin
+        .token
+        .as_deref()
+        .filter(|t| !t.is_empty())
+        .ok_or_else(not_admin)?;
+    // Compare digests so the check takes the same time however much matches.
+    if Sha256::digest(expected.as_bytes()) != Sha256::digest(auth.token().as_bytes()) {
+        return Err(not_admin());
+    }
+    Ok(())
+}
+
+/// Validate a game's target ring and repair it if it is broken.
+pub(crate) async fn ring_check(
+    state: &AppState,
+    game_code: &str,
+    auth: &Authorization<Bearer>,
+) -> Result<RingCheckPayload, AppError> {
+    require_admin(&state.config, auth)?;
+    info!("Admin ring check for {}", game_code);
+    let repair = state.db.repair_ring(game_code).await?;
+    if !repair.reassigned.is_empty() {
+        // Hunters whose target changed pick it up through the change poll.
+        bump_game_version(state, game_code);
+    }
+    Ok(RingCheckPayload {
+        game_code: game_code.to_string(),
+        healthy: repair.violations.is_empty(),
+        violations: repair.violations,
+        reassigned: repair.reassigned,
+        winner_id: repair.winner_id,
+    })
+}
+
+/// End a game straight away, without a winner.
+pub(crate) async fn finish_game(
+    state: &AppState,
+    game_code: &str,
+    auth: &Authorization<Bearer>,
+) -> Result<FinishedGamePayload, AppError> {
+    require_admin(&state.config, auth)?;
+    info!("Admin finish of {}", game_code);
+    let finished = state.db.force_finish_game(game_code).await?;
+    if finished {
+        bump_game_version(state, game_code);
+    }
+    Ok(FinishedGamePayload {
+        game_code: game_code.to_string(),
+        finished,
+    })
+}
+
+/// Delete every game created more than `older_than` ago.
+pub(crate) async fn purge_games(
+    state: &AppState,
+    older_than: Duration,
+    auth: &Authorization<Bearer>,
+) -> Result<PurgedGamesPayload, AppError> {
+    require_admin(&state.config, auth)?;
+    info!(?older_than, "Admin purge of old games");
+    let game_codes = state.db.purge_games(older_than).await?;
+    for code in &game_codes {
+        state.versions.remove(code);
+    }
+    Ok(PurgedGamesPayload { game_codes })
+}
+
+pub async fn check_ring(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(ring_check(&state, &game_code, &auth).await?))
+}
@@ -1,38 +1,55 @@
-use crate::{errors::AppError, state::AppState};
+use crate::{errors::AppError, payloads::ChangesPayload, state::AppState};
 use axum::{
     extract::{Path, Query, State},
     response::IntoResponse,
     Json,
 };
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
 use serde::Deserialize;
-use serde::Serialize;
 use tracing::info;
+use utoipa::IntoParams;
 
-#[derive(Serialize)]
-pub struct ChangedResponse {
-    pub changed: bool,
-    pub current_version: i64,
-}
-
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct VersionQuery {
+    /// The last version the client has seen.
     pub version: Option<i64>,
 }
 
-pub async fn check_for_changes(
-    State(state): State<AppState>,
-    Path(game_code): Path<String>,
-    Query(query): Query<VersionQuery>,
-) -> Result<impl IntoResponse, AppError> {
-    let client_version = query.version.unwrap_or(0);
-    let current_version = state.get_game_version(&game_code);
+pub(crate) fn changes(
+    state: &AppState,
+    game_code: &str,
+    client_version: i64,
+    auth_token: Option<&str>,
+) -> ChangesPayload {
+    if let Some(token) = auth_token {
+        state.metrics.subscribed(token);
+    }
+    let current_version = state.get_game_version(game_code);
     let changed = current_version > client_version;
     info!(
         "Client version {} vs server {}, changed => {}",
         client_version, current_version, changed
     );
-    Ok(Json(ChangedResponse {
+    ChangesPayload {
         changed,
         current_version,
-    }))
+    }
+}
+
+pub async fn check_for_changes(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    Query(query): Query<VersionQuery>,
+    auth: Option<TypedHeader<Authorization<Bearer>>>,
+) -> Result<impl IntoResponse, AppError> {
+    let token = auth.as_ref().map(|TypedHeader(auth)| auth.token());
+    Ok(Json(changes(
+        &state,
+        &game_code,
+        query.version.unwrap_or(0),
+        token,
+    )))
 }
@@ -0,0 +1,158 @@
+use super::utils::{authenticate, bump_game_version, game_not_found, message_text};
+use crate::{
+    engine,
+    errors::AppError,
+    kill_token::unix_now,
+    models::{ChatChannel, ChatMessage, Game, GameSettings, Player},
+    payloads::{ChatMessagePayload, PostMessagePayload},
+    state::AppState,
+};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+use serde::Deserialize;
+use tracing::info;
+use utoipa::IntoParams;
+
+/// Longest chat message, in characters.
+pub(crate) const MAX_MESSAGE_LENGTH: usize = 500;
+
+#[derive(Deserialize, IntoParams)]
+pub struct ChatQuery {
+    /// Only messages newer than this message id; leave out for all of them.
+    pub after: Option<i64>,
+}
+
+pub(crate) fn message_payload(message: ChatMessage) -> ChatMessagePayload {
+    ChatMessagePayload {
+        id: message.id,
+        channel: message.channel,
+        sender_id: message.sender_id,
+        sender_name: message.sender_name,
+        body: message.body,
+        created_at: message.created_at,
+    }
+}
+
+/// The channels `player` may post in right now.
+pub(crate) fn postable_channels(
+    game: &Game,
+    settings: &GameSettings,
+    player: &Player,
+) -> Vec<ChatChannel> {
+    [
+        ChatChannel::Announcements,
+        ChatChannel::Lobby,
+        ChatChannel::Dead,
+    ]
+    .into_iter()
+    .filter(|&channel| engine::check_chat_post(game, settings, player, channel).is_ok())
+    .collect()
+}
+
+/// The owner of `auth_token`, their game and its settings.
+async fn member(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+) -> Result<(Player, Game, GameSettings), AppError> {
+    let player = authenticate(state, auth_token).await?;
+    let game = state
+        .db
+        .get_game_by_id(player.game_id)
+        .await?
+        .filter(|g| g.code == game_code)
+        .ok_or_else(game_not_found)?;
+    let settings = state
+        .db
+        .get_game_settings(game.id)
+        .await?
+        .unwrap_or_default();
+    Ok((player, game, settings))
+}
+
+/// The messages the owner of `auth_token` may read, oldest first.
+pub(crate) async fn messages(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+    query: ChatQuery,
+) -> Result<Vec<ChatMessagePayload>, AppError> {
+    let (player, game, settings) = member(state, game_code, auth_token).await?;
+    let after = query.after.unwrap_or(0);
+    Ok(state
+        .db
+        .get_messages(game.id)
+        .await?
+        .into_iter()
+        .filter(|m| m.id > after && engine::can_read_chat(m.channel, &player, &settings))
+        .map(message_payload)
+        .collect())
+}
+
+/// Post to one of the game's channels, within the configured rate limit.
+pub(crate) async fn post(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+    payload: PostMessagePayload,
+) -> Result<ChatMessagePayload, AppError> {
+    let (player, game, settings) = member(state, game_code, auth_token).await?;
+    let body = message_text(&payload.body, MAX_MESSAGE_LENGTH)?;
+    engine::check_chat_post(&game, &settings, &player, payload.channel)?;
+    let sent_at: Vec<i64> = state
+        .db
+        .get_messages(game.id)
+        .await?
+        .into_iter()
+        .filter(|m| m.sender_id == Some(player.id))
+        .map(|m| m.created_at)
+        .collect();
+    let limits = &state.config.rate_limits;
+    engine::check_chat_rate(
+        &sent_at,
+        unix_now(),
+        limits.chat_message_limit,
+        limits.chat_window_secs,
+    )?;
+    let message = state
+        .db
+        .post_message(payload.channel, &player, body)
+        .await?;
+    info!(
+        game_code,
+        channel = payload.channel.as_str(),
+        "Chat message posted"
+    );
+    // Everyone's chat picks the message up through the change poll.
+    bump_game_version(state, game_code);
+    Ok(message_payload(message))
+}
+
+pub async fn get_chat(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    Query(query): Query<ChatQuery>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        messages(&state, &game_code, auth.token(), query).await?,
+    ))
+}
+
+pub async fn post_message(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Json(payload): Json<PostMessagePayload>,
+) -> Result<impl IntoResponse, AppError> {
+    let message = post(&state, &game_code, auth.token(), payload).await?;
+    Ok((StatusCode::CREATED, Json(message)))
+}
@@ -0,0 +1,319 @@
+use super::utils::{authenticate, bump_game_version, game_not_found, message_text};
+use crate::{
+    engine,
+    errors::{AppError, ErrorCode},
+    models::{
+        DisputeStatus, Game, GameEvent, GameEventKind, GameSettings, GameStatus, KillDispute,
+        Player, PlayerRole,
+    },
+    payloads::{
+        DisputePayload, DisputeVotePayload, GhostPayload, HintPayload, OpenDisputePayload,
+        SendHintPayload,
+    },
+    state::AppState,
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+use tracing::info;
+
+/// Longest hint a ghost can send, in characters.
+pub(crate) const MAX_HINT_LENGTH: usize = 140;
+
+/// What `player` can still do in `game`.
+pub(crate) fn role_of(player: &Player, game: &Game, settings: &GameSettings) -> PlayerRole {
+    if player.is_alive {
+        PlayerRole::Alive
+    } else if settings.ghosts && game.status == GameStatus::InProgress {
+        PlayerRole::Ghost
+    } else {
+        PlayerRole::Eliminated
+    }
+}
+
+/// The owner of `auth_token`, their game and its settings, provided they
+/// are a ghost in `game_code`.
+async fn ghost(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+) -> Result<(Player, Game, GameSettings), AppError> {
+    let player = authenticate(state, auth_token).await?;
+    let game = state
+        .db
+        .get_game_by_id(player.game_id)
+        .await?
+        .filter(|g| g.code == game_code)
+        .ok_or_else(game_not_found)?;
+    let settings = state
+        .db
+        .get_game_settings(game.id)
+        .await?
+        .unwrap_or_default();
+    if role_of(&player, &game, &settings) != PlayerRole::Ghost {
+        return Err(AppError::Forbidden(
+            ErrorCode::NotAGhost,
+            "error.NOT_A_GHOST",
+        ));
+    }
+    Ok((player, game, settings))
+}
+
+/// The kill that eliminated the player with id `victim_id`: the latest one.
+fn fatal_kill(events: &[GameEvent], victim_id: i32) -> Option<&GameEvent> {
+    events
+        .iter()
+        .rev()
+        .find(|e| e.kind == GameEventKind::Kill && e.subject_id == Some(victim_id))
+}
+
+/// `dispute` as shown to the ghost with id `viewer`: tallies, not voters.
+fn dispute_seen_by(
+    dispute: &KillDispute,
+    events: &[GameEvent],
+    players: &[Player],
+    viewer: i32,
+) -> DisputePayload {
+    let kill = events.iter().find(|e| e.id == dispute.event_id);
+    let voters = engine::dispute_voters(players, dispute.victim_id, dispute.killer_id);
+    let counted = || dispute.votes.iter().filter(|(pid, _)| voters.contains(pid));
+    DisputePayload {
+        id: dispute.id,
+        event_id: dispute.event_id,
+        victim_name: kill.and_then(|k| k.subject_name.clone()),
+        killer_name: kill.and_then(|k| k.actor_name.clone()),
+        status: dispute.status,
+        overturn_votes: counted().filter(|(_, overturn)| *overturn).count() as u32,
+        uphold_votes: counted().filter(|(_, overturn)| !*overturn).count() as u32,
+        votes_needed: engine::dispute_majority(voters.len()) as u32,
+        can_vote: dispute.status == DisputeStatus::Open
+            && viewer != dispute.victim_id
+            && Some(viewer) != dispute.killer_id,
+        my_vote: dispute
+            .votes
+            .iter()
+            .find(|(pid, _)| *pid == viewer)
+            .map(|&(_, overturn)| overturn),
+    }
+}
+
+/// Everything the ghost owning `auth_token` can do.
+pub(crate) async fn overview(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+) -> Result<GhostPayload, AppError> {
+    let (player, game, settings) = ghost(state, game_code, auth_token).await?;
+    let events = state.db.get_events(game.id).await?;
+    let players = state.db.get_players_by_game_id(game.id).await?;
+    let disputes = state.db.get_disputes(game.id).await?;
+    let hints = state.db.get_hints(game.id).await?;
+    let disputable_event_id = fatal_kill(&events, player.id)
+        .filter(|kill| !disputes.iter().any(|d| d.event_id == kill.id))
+        .map(|kill| kill.id);
+    Ok(GhostPayload {
+        disputes: disputes
+            .iter()
+            .map(|d| dispute_seen_by(d, &events, &players, player.id))
+            .collect(),
+        disputable_event_id,
+        can_hint: settings.ghost_hints && !hints.iter().any(|h| h.sender_id == player.id),
+    })
+}
+
+/// Ask the other ghosts to overturn the kill that eliminated you.
+pub(crate) async fn open(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+    event_id: i64,
+) -> Result<DisputePayload, AppError> {
+    let (player, game, _) = ghost(state, game_code, auth_token).await?;
+    let events = state.db.get_events(game.id).await?;
+    let kill = fatal_kill(&events, player.id)
+        .filter(|kill| kill.id == event_id)
+        .ok_or(AppError::UnprocessableEntity(
+            ErrorCode::NotDisputable,
+            "error.NOT_DISPUTABLE",
+        ))?;
+    let disputes = state.db.get_disputes(game.id).await?;
+    if disputes.iter().any(|d| d.event_id == event_id) {
+        return Err(AppError::Conflict(
+            ErrorCode::AlreadyDisputed,
+            "error.ALREADY_DISPUTED",
+        ));
+    }
+    let id = state
+        .db
+        .open_dispute(game.id, event_id, player.id, kill.actor_id)
+        .await?;
+    info!(game_code, dispute_id = id, "Kill disputed");
+    bump_game_version(state, game_code);
+    let dispute = KillDispute {
+        id,
+        game_id: game.id,
+        event_id,
+        victim_id: player.id,
+        killer_id: kill.actor_id,
+        status: DisputeStatus::Open,
+        votes: Vec::new(),
+    };
+    let players = state.db.get_players_by_game_id(game.id).await?;
+    Ok(dispute_seen_by(&dispute, &events, &players, player.id))
+}
+
+/// Vote on an open dispute. The dispute is settled as soon as the vote
+/// decides it; overturning puts the victim back into the game.
+pub(crate) async fn vote(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+    dispute_id: i64,
+    overturn: bool,
+) -> Result<DisputePayload, AppError> {
+    let (player, game, _) = ghost(state, game_code, auth_token).await?;
+    let dispute = state
+        .db
+        .vote_on_dispute(game_code, dispute_id, player.id, overturn)
+        .await?;
+    bump_game_version(state, game_code);
+    let players = state.db.get_players_by_game_id(game.id).await?;
+    let events = state.db.get_events(game.id).await?;
+    Ok(dispute_seen_by(&dispute, &events, &players, player.id))
+}
+
+/// Send the one anonymous hint a ghost gets to a living player.
+pub(crate) async fn hint(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+    payload: SendHintPayload,
+) -> Result<(), AppError> {
+    let (player, game, settings) = ghost(state, game_code, auth_token).await?;
+    if !settings.ghost_hints {
+        return Err(AppError::Forbidden(
+            ErrorCode::HintsDisabled,
+            "error.HINTS_DISABLED",
+        ));
+    }
+    let message = message_text(&payload.message, MAX_HINT_LENGTH)?;
+    let recipient = state
+        .db
+        .get_players_by_game_id(game.id)
+        .await?
+        .into_iter()
+        .find(|p| p.id == payload.player_id)
+        .ok_or(AppError::NotFound(
+            ErrorCode::PlayerNotFound,
+            "error.PLAYER_NOT_FOUND",
+        ))?;
+    if !recipient.is_alive {
+        return Err(AppError::UnprocessableEntity(
+            ErrorCode::HintTargetDead,
+            "error.HINT_TARGET_DEAD",
+        ));
+    }
+    let hints = state.db.get_hints(game.id).await?;
+    if hints.iter().any(|h| h.sender_id == player.id) {
+        return Err(AppError::Conflict(
+            ErrorCode::HintAlreadySent,
+            "error.HINT_ALREADY_SENT",
+        ));
+    }
+    state
+        .db
+        .send_hint(game.id, player.id, recipient.id, message)
+        .await?;
+    info!(game_code, "Ghost sent a hint");
+    bump_game_version(state, game_code);
+    Ok(())
+}
+
+/// The hints sent to the owner of `auth_token`, oldest first. Senders stay
+/// anonymous.
+pub(crate) async fn received(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+) -> Result<Vec<HintPayload>, AppError> {
+    let player = authenticate(state, auth_token).await?;
+    let game = state
+        .db
+        .get_game_by_id(player.game_id)
+        .await?
+        .filter(|g| g.code == game_code)
+        .ok_or_else(game_not_found)?;
+    Ok(state
+        .db
+        .get_hints(game.id)
+        .await?
+        .into_iter()
+        .filter(|h| h.recipient_id == player.id)
+        .map(|h| HintPayload {
+            id: h.id,
+            message: h.message,
+            created_at: h.created_at,
+        })
+        .collect())
+}
+
+pub async fn get_ghost(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(overview(&state, &game_code, auth.token()).await?))
+}
+
+pub async fn open_dispute(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Json(payload): Json<OpenDisputePayload>,
+) -> Result<impl IntoResponse, AppError> {
+    let dispute = open(&state, &game_code, auth.token(), payload.event_id).await?;
+    Ok((StatusCode::CREATED, Json(dispute)))
+}
+
+pub async fn vote_on_dispute(
+    State(state): State<AppState>,
+    Path((game_code, dispute_id)): Path<(String, i64)>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Json(payload): Json<DisputeVotePayload>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        vote(
+            &state,
+            &game_code,
+            auth.token(),
+            dispute_id,
+            payload.overturn,
+        )
+        .await?,
+    ))
+}
+
+pub async fn send_hint(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Json(payload): Json<SendHintPayload>,
+) -> Result<impl IntoResponse, AppError> {
+    hint(&state, &game_code, auth.token(), payload).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn get_hints(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(received(&state, &game_code, auth.token()).await?))
+}
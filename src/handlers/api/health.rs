@@ -0,0 +1,24 @@
+use crate::{payloads::HealthPayload, state::AppState};
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+
+fn health(status: StatusCode, text: &str) -> impl IntoResponse {
+    (
+        status,
+        Json(HealthPayload {
+            status: text.to_string(),
+        }),
+    )
+}
+
+/// Liveness: the process is up and serving requests.
+pub async fn healthz() -> impl IntoResponse {
+    health(StatusCode::OK, "ok")
+}
+
+/// Readiness: the database is reachable and its migrations are applied.
+pub async fn readyz(State(state): State<AppState>) -> impl IntoResponse {
+    match state.db.check_ready().await {
+        Ok(()) => health(StatusCode::OK, "ready"),
+        Err(_) => health(StatusCode::SERVICE_UNAVAILABLE, "unavailable"),
+    }
+}
@@ -1,5 +1,10 @@
 use super::utils::bump_game_version;
-use crate::{errors::AppError, payloads::KillResponsePayload, state::AppState};
+use crate::{
+    errors::AppError,
+    metrics,
+    payloads::{KillPayload, KillResponsePayload},
+    state::AppState,
+};
 use axum::{
     extract::{Path, State},
     http::StatusCode,
@@ -10,12 +15,29 @@ use axum_extra::{
     headers::{authorization::Bearer, Authorization},
     TypedHeader,
 };
-use serde::Deserialize;
 use tracing::info;
 
-#[derive(Deserialize, Debug)]
-pub struct KillPayload {
-    secret_code: String,
+/// Eliminate the player whose secret code or kill token was presented.
+pub(crate) async fn kill(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+    secret_code: &str,
+) -> Result<KillResponsePayload, AppError> {
+    let outcome = match state.kill_tokens.parse_proof(secret_code) {
+        Ok(proof) => state.db.process_kill(game_code, auth_token, &proof).await,
+        Err(err) => Err(err),
+    };
+    let (_, killer_name, eliminated, new_target) =
+        outcome.inspect_err(metrics::record_failed_kill)?;
+    metrics::record_kill();
+    bump_game_version(state, game_code);
+    Ok(KillResponsePayload {
+        eliminated_player_name: eliminated,
+        killer_name,
+        game_over: new_target.is_none(),
+        new_target_name: new_target,
+    })
 }
 
 pub async fn kill_handler(
@@ -25,18 +47,6 @@ pub async fn kill_handler(
     Json(payload): Json<KillPayload>,
 ) -> Result<impl IntoResponse, AppError> {
     info!("kill_handler {}", game_code);
-    let (_killer_id, killer_name, eliminated, new_target) = state
-        .db
-        .process_kill(&game_code, auth.token(), &payload.secret_code)
-        .await?;
-    if state.db.get_game_by_code(&game_code).await?.is_some() {
-        bump_game_version(&state, &game_code);
-    }
-    let resp = KillResponsePayload {
-        eliminated_player_name: eliminated,
-        killer_name,
-        game_over: new_target.is_none(),
-        new_target_name: new_target,
-    };
+    let resp = kill(&state, &game_code, auth.token(), &payload.secret_code).await?;
     Ok((StatusCode::OK, Json(resp)))
 }
@@ -0,0 +1,190 @@
+use super::utils::{authenticate, bump_game_version, game_not_found};
+use crate::{
+    engine,
+    errors::{AppError, ErrorCode},
+    models::{Game, JoinRequest, RequestStatus},
+    payloads::{DecideJoinPayload, JoinRequestPayload, PendingJoinPayload},
+    remember::remember,
+    state::AppState,
+    utils::normalise_name,
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+use tracing::info;
+
+fn join_request_payload(request: JoinRequest) -> JoinRequestPayload {
+    JoinRequestPayload {
+        claim_token: request.claim_token,
+        status: request.status,
+        auth_token: request.auth_token,
+    }
+}
+
+fn join_request_not_found() -> AppError {
+    AppError::NotFound(
+        ErrorCode::JoinRequestNotFound,
+        "error.JOIN_REQUEST_NOT_FOUND",
+    )
+}
+
+/// The owner of `auth_token` and their game, provided they host `game_code`.
+async fn host(state: &AppState, game_code: &str, auth_token: &str) -> Result<Game, AppError> {
+    let player = authenticate(state, auth_token).await?;
+    let game = state
+        .db
+        .get_game_by_id(player.game_id)
+        .await?
+        .filter(|g| g.code == game_code)
+        .ok_or_else(game_not_found)?;
+    if game.host_id != Some(player.id) {
+        return Err(AppError::Forbidden(
+            ErrorCode::NotHost,
+            "error.NOT_HOST.late_join",
+        ));
+    }
+    Ok(game)
+}
+
+/// Ask the host of the running `game` to let `player_name` in. Names that
+/// could never be let in are turned away straight away.
+pub(crate) async fn request(
+    state: &AppState,
+    game: &Game,
+    player_name: &str,
+    max_players: usize,
+) -> Result<JoinRequestPayload, AppError> {
+    let players = state.db.get_players_by_game_id(game.id).await?;
+    let name = normalise_name(player_name);
+    let same_name = players.iter().find(|p| normalise_name(&p.name) == name);
+    engine::check_join(game, same_name, players.len(), max_players, true)?;
+    let request = state.db.request_join(game.id, player_name).await?;
+    info!(game_code = %game.code, request_id = request.id, "Late join requested");
+    // The host's page picks the request up through the change poll.
+    bump_game_version(state, &game.code);
+    Ok(join_request_payload(request))
+}
+
+/// Where the request behind `claim_token` stands.
+pub(crate) async fn claim(
+    state: &AppState,
+    game_code: &str,
+    claim_token: &str,
+) -> Result<JoinRequestPayload, AppError> {
+    let game = state
+        .db
+        .get_game_by_code(game_code)
+        .await?
+        .ok_or_else(game_not_found)?;
+    state
+        .db
+        .get_join_request(claim_token)
+        .await?
+        .filter(|r| r.game_id == game.id)
+        .map(join_request_payload)
+        .ok_or_else(join_request_not_found)
+}
+
+/// The requests waiting for the host owning `auth_token`, oldest first.
+pub(crate) async fn pending(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+) -> Result<Vec<PendingJoinPayload>, AppError> {
+    let game = host(state, game_code, auth_token).await?;
+    Ok(state
+        .db
+        .get_join_requests(game.id)
+        .await?
+        .into_iter()
+        .filter(|r| r.status == RequestStatus::Pending)
+        .map(|r| PendingJoinPayload {
+            id: r.id,
+            player_name: r.player_name,
+            created_at: r.created_at,
+        })
+        .collect())
+}
+
+/// Let a newcomer into the running game, or turn them away.
+pub(crate) async fn decide(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+    request_id: i64,
+    approve: bool,
+) -> Result<(), AppError> {
+    let game = host(state, game_code, auth_token).await?;
+    let request = state
+        .db
+        .get_join_requests(game.id)
+        .await?
+        .into_iter()
+        .find(|r| r.id == request_id)
+        .ok_or_else(join_request_not_found)?;
+    let answered = || AppError::Conflict(ErrorCode::JoinRequestClosed, "error.JOIN_REQUEST_CLOSED");
+    if request.status != RequestStatus::Pending {
+        return Err(answered());
+    }
+    let settings = state
+        .db
+        .get_game_settings(game.id)
+        .await?
+        .unwrap_or_default();
+    let max_players = engine::max_players(&settings, state.config.game.max_players);
+    if !state
+        .db
+        .decide_join_request(game_code, request_id, approve, max_players)
+        .await?
+    {
+        return Err(answered());
+    }
+    info!(game_code, request_id, approve, "Join request answered");
+    bump_game_version(state, game_code);
+    Ok(())
+}
+
+/// Also remembers the newcomer on this browser once let in.
+pub async fn claim_join_request(
+    State(state): State<AppState>,
+    Path((game_code, claim_token)): Path<(String, String)>,
+) -> Result<impl IntoResponse, AppError> {
+    let request = claim(&state, &game_code, &claim_token).await?;
+    let cookie = request
+        .auth_token
+        .as_deref()
+        .map(|token| remember(&state.config, token));
+    Ok((cookie, Json(request)))
+}
+
+pub async fn get_join_requests(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(pending(&state, &game_code, auth.token()).await?))
+}
+
+pub async fn decide_join_request(
+    State(state): State<AppState>,
+    Path((game_code, request_id)): Path<(String, i64)>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Json(payload): Json<DecideJoinPayload>,
+) -> Result<impl IntoResponse, AppError> {
+    decide(
+        &state,
+        &game_code,
+        auth.token(),
+        request_id,
+        payload.approve,
+    )
+    .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,6 +1,15 @@
+use super::late_join;
+use super::utils::{authenticate, game_not_found, player_name};
 use crate::{
-    errors::AppError,
-    payloads::{CreateGamePayload, GameCreatedPayload, GameJoinedPayload, JoinGamePayload},
+    engine,
+    errors::{AppError, ErrorCode},
+    models::{Game, GameStatus, Player},
+    payloads::{
+        hide_targets, CreateGamePayload, GameSessionPayload, JoinGamePayload, JoinRequestPayload,
+        ReadyPayload,
+    },
+    remember::remember,
+    repository::NewPlayer,
     state::AppState,
     utils::generate_code,
 };
@@ -16,41 +25,203 @@ use axum_extra::{
 };
 use tracing::info;
 
-pub async fn create_game(
-    State(state): State<AppState>,
-    Json(payload): Json<CreateGamePayload>,
-) -> Result<impl IntoResponse, AppError> {
-    info!("Received create_game: {:?}", payload);
-    let game_code_len: usize = dotenvy::var("GAME_CODE_LENGTH")
-        .unwrap_or_else(|_| "4".into())
-        .parse()
-        .expect("GAME_CODE_LENGTH must be number");
-    let game_code = generate_code(game_code_len);
-    let (game_id, player_id, player_secret, auth_token) = state
+/// Longest player name, in characters.
+pub(crate) const MAX_NAME_LENGTH: usize = 32;
+
+/// A freshly created or joined player together with their game.
+pub(crate) struct Session {
+    pub player: Player,
+    pub game: Game,
+    pub players: Vec<Player>,
+    pub version: i64,
+}
+
+impl From<Session> for GameSessionPayload {
+    fn from(session: Session) -> Self {
+        GameSessionPayload {
+            game_code: session.game.code.clone(),
+            player_id: session.player.id,
+            player_secret: session.player.secret_code,
+            auth_token: session.player.auth_token,
+            players: hide_targets(session.players, session.player.id),
+            game: session.game,
+            version: session.version,
+        }
+    }
+}
+
+async fn session(
+    state: &AppState,
+    game_code: &str,
+    (game_id, player_id, secret_code, auth_token): NewPlayer,
+) -> Result<Session, AppError> {
+    let version = state.bump_game_version(game_code);
+    let players = state.db.get_players_by_game_id(game_id).await?;
+    let player = players
+        .iter()
+        .find(|p| p.id == player_id)
+        .map(|p| Player {
+            secret_code,
+            auth_token,
+            ..p.clone()
+        })
+        .ok_or(AppError::InternalServerError)?;
+    let game = state
         .db
-        .create_game(payload.player_name, game_code.clone())
+        .get_game_by_code(game_code)
+        .await?
+        .ok_or_else(game_not_found)?;
+    Ok(Session {
+        player,
+        game,
+        players,
+        version,
+    })
+}
+
+pub(crate) async fn create(state: &AppState, name: String) -> Result<Session, AppError> {
+    let name = player_name(&name, MAX_NAME_LENGTH)?;
+    let game_code = generate_code(state.config.game.code_length);
+    let created = state.db.create_game(name, game_code.clone()).await?;
+    session(state, &game_code, created).await
+}
+
+/// What asking to join a game led to.
+pub(crate) enum Joined {
+    Player(Session),
+    /// The game already runs and its host lets late joiners in by hand.
+    Pending(JoinRequestPayload),
+}
+
+pub(crate) async fn join(
+    state: &AppState,
+    game_code: &str,
+    name: String,
+) -> Result<Joined, AppError> {
+    let name = player_name(&name, MAX_NAME_LENGTH)?;
+    let game = state
+        .db
+        .get_game_by_code(game_code)
+        .await?
+        .ok_or_else(game_not_found)?;
+    let settings = state
+        .db
+        .get_game_settings(game.id)
+        .await?
+        .unwrap_or_default();
+    let max_players = engine::max_players(&settings, state.config.game.max_players);
+    if settings.late_join && settings.late_join_approval && game.status == GameStatus::InProgress {
+        let request = late_join::request(state, &game, &name, max_players).await?;
+        return Ok(Joined::Pending(request));
+    }
+    // Without approval a game that started meanwhile takes the newcomer
+    // straight into its ring.
+    let late_join = settings.late_join && !settings.late_join_approval;
+    let joined = state
+        .db
+        .join_game(game_code.to_string(), name, max_players, late_join)
         .await?;
+    Ok(Joined::Player(session(state, game_code, joined).await?))
+}
 
+/// Start the game on behalf of `starter`, who must be its host.
+async fn begin(
+    state: &AppState,
+    game_code: &str,
+    starter: &Player,
+) -> Result<Vec<Player>, AppError> {
+    let settings = state
+        .db
+        .get_game_settings(starter.game_id)
+        .await?
+        .unwrap_or_default();
     let players = state
         .db
-        .get_players_by_game_id(&*state.db, game_id)
+        .start_game(game_code, starter.id, engine::min_players(&settings))
         .await?;
-    let game = state
+    state.bump_game_version(game_code);
+    Ok(players)
+}
+
+/// Start the game as the host; returns everyone, with only the host's own
+/// first target.
+pub(crate) async fn start(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+) -> Result<Vec<Player>, AppError> {
+    let player = authenticate(state, auth_token).await?;
+    let players = begin(state, game_code, &player).await?;
+    Ok(hide_targets(players, player.id))
+}
+
+/// Start the lobby of `game_code` in its host's name when its settings ask
+/// for that and everyone is ready. Called after anything that may have made
+/// the lobby ready.
+pub(crate) async fn auto_start(state: &AppState, game_code: &str) -> Result<(), AppError> {
+    let Some((game, players)) = state.db.get_game_state(game_code).await? else {
+        return Ok(());
+    };
+    let settings = state
         .db
-        .get_game_by_code(&game_code)
+        .get_game_settings(game.id)
         .await?
-        .ok_or(AppError::InternalServerError)?;
-    let version = state.bump_game_version(&game_code);
-    let response = GameCreatedPayload {
-        game_code,
-        player_id,
-        player_secret,
-        auth_token,
-        players,
-        game,
-        version,
+        .unwrap_or_default();
+    if game.status != GameStatus::Lobby || !engine::should_auto_start(&settings, &players) {
+        return Ok(());
+    }
+    let Some(host) = players.iter().find(|p| Some(p.id) == game.host_id) else {
+        return Ok(());
     };
-    Ok((StatusCode::CREATED, Json(response)))
+    info!(game_code, "Everyone is ready, starting the game");
+    match begin(state, game_code, host).await {
+        // Someone else's ready toggle got there first.
+        Err(err) if err.code() == ErrorCode::GameAlreadyStarted => Ok(()),
+        result => result.map(|_| ()),
+    }
+}
+
+/// Mark the owner of `auth_token` ready to start, or not; starts the game
+/// when that made everyone ready and the host asked for it.
+pub(crate) async fn ready(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+    ready: bool,
+) -> Result<(), AppError> {
+    let player = authenticate(state, auth_token).await?;
+    let game = state
+        .db
+        .get_game_by_id(player.game_id)
+        .await?
+        .filter(|g| g.code == game_code)
+        .ok_or_else(game_not_found)?;
+    if game.status != GameStatus::Lobby {
+        return Err(AppError::UnprocessableEntity(
+            ErrorCode::GameAlreadyStarted,
+            "error.GAME_ALREADY_STARTED.ready",
+        ));
+    }
+    state.db.set_ready(player.id, ready).await?;
+    state.bump_game_version(game_code);
+    if ready {
+        auto_start(state, game_code).await?;
+    }
+    Ok(())
+}
+
+pub async fn create_game(
+    State(state): State<AppState>,
+    Json(payload): Json<CreateGamePayload>,
+) -> Result<impl IntoResponse, AppError> {
+    info!("Received create_game: {:?}", payload);
+    let session = create(&state, payload.player_name).await?;
+    let cookie = remember(&state.config, &session.player.auth_token);
+    Ok((
+        StatusCode::CREATED,
+        cookie,
+        Json(GameSessionPayload::from(session)),
+    ))
 }
 
 pub async fn join_game(
@@ -59,30 +230,13 @@ pub async fn join_game(
     Json(payload): Json<JoinGamePayload>,
 ) -> Result<impl IntoResponse, AppError> {
     info!("Received join_game {}: {:?}", game_code, payload);
-    let (game_id, player_id, player_secret, auth_token) = state
-        .db
-        .join_game(game_code.clone(), payload.player_name)
-        .await?;
-    let players = state
-        .db
-        .get_players_by_game_id(&*state.db, game_id)
-        .await?;
-    let game = state
-        .db
-        .get_game_by_code(&game_code)
-        .await?
-        .ok_or(AppError::NotFound("Game not found".into()))?;
-    let version = state.bump_game_version(&game_code);
-    let response = GameJoinedPayload {
-        game_code,
-        player_id,
-        player_secret,
-        auth_token,
-        players,
-        game,
-        version,
-    };
-    Ok(Json(response))
+    Ok(match join(&state, &game_code, payload.player_name).await? {
+        Joined::Player(session) => {
+            let cookie = remember(&state.config, &session.player.auth_token);
+            (cookie, Json(GameSessionPayload::from(session))).into_response()
+        }
+        Joined::Pending(request) => (StatusCode::ACCEPTED, Json(request)).into_response(),
+    })
 }
 
 pub async fn start_game(
@@ -91,12 +245,15 @@ pub async fn start_game(
     TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
 ) -> Result<impl IntoResponse, AppError> {
     info!("Received start_game for {}", game_code);
-    let player = state
-        .db
-        .get_player_by_auth_token(auth.token())
-        .await?
-        .ok_or(AppError::Forbidden("Invalid auth token.".into()))?;
-    let players = state.db.start_game(&game_code, player.id).await?;
-    state.bump_game_version(&game_code);
-    Ok(Json(players))
+    Ok(Json(start(&state, &game_code, auth.token()).await?))
+}
+
+pub async fn set_ready(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Json(payload): Json<ReadyPayload>,
+) -> Result<impl IntoResponse, AppError> {
+    ready(&state, &game_code, auth.token(), payload.ready).await?;
+    Ok(StatusCode::NO_CONTENT)
 }
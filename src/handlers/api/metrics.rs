@@ -0,0 +1,14 @@
+use crate::{errors::AppError, state::AppState};
+use axum::{extract::State, http::header, response::IntoResponse};
+
+/// Prometheus scrape endpoint.
+pub async fn metrics(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    state.metrics.record_games(&state.db.get_all_games().await?);
+    if let Some(pool) = state.db.pool_stats() {
+        state.metrics.record_pool(pool);
+    }
+    Ok((
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render(),
+    ))
+}
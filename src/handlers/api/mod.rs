@@ -1,10 +1,33 @@
+pub mod activity;
+pub mod admin;
 pub mod change;
+pub mod chat;
+pub mod ghosts;
+pub mod health;
 pub mod kill;
+pub mod late_join;
 pub mod lobby;
+pub mod metrics;
+pub mod recovery;
+pub mod secret;
+pub mod settings;
+pub mod spectate;
 pub mod state;
 pub mod utils;
+pub mod v1;
 
+pub use activity::get_activity;
+pub use admin::check_ring;
 pub use change::check_for_changes;
+pub use chat::{get_chat, post_message};
+pub use ghosts::{get_ghost, get_hints, open_dispute, send_hint, vote_on_dispute};
+pub use health::{healthz, readyz};
 pub use kill::kill_handler;
-pub use lobby::{create_game, join_game, start_game};
+pub use late_join::{claim_join_request, decide_join_request, get_join_requests};
+pub use lobby::{create_game, join_game, set_ready, start_game};
+pub use metrics::metrics;
+pub use recovery::{claim_recovery, decide_recovery, get_recoveries, request_recovery};
+pub use secret::{issue_kill_token, rotate_secret};
+pub use settings::update_settings;
+pub use spectate::{spectate, spectator_link};
 pub use state::{get_game_state, leave_game};
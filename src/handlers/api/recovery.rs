@@ -0,0 +1,229 @@
+use super::utils::{authenticate, bump_game_version, game_not_found};
+use crate::{
+    engine,
+    errors::{AppError, ErrorCode},
+    kill_token::unix_now,
+    models::{Game, Player, RecoveryRequest, RequestStatus},
+    payloads::{DecideRecoveryPayload, PendingRecoveryPayload, RecoverPayload, RecoveryPayload},
+    remember::remember,
+    state::AppState,
+    utils::normalise_name,
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+use tracing::info;
+
+fn recovery_payload(request: RecoveryRequest) -> RecoveryPayload {
+    RecoveryPayload {
+        claim_token: request.claim_token,
+        status: request.status,
+        auth_token: request.auth_token,
+    }
+}
+
+fn recovery_not_found() -> AppError {
+    AppError::NotFound(ErrorCode::RecoveryNotFound, "error.RECOVERY_NOT_FOUND")
+}
+
+/// The owner of `auth_token` and their game, provided it is `game_code`.
+async fn member(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+) -> Result<(Player, Game), AppError> {
+    let player = authenticate(state, auth_token).await?;
+    let game = state
+        .db
+        .get_game_by_id(player.game_id)
+        .await?
+        .filter(|g| g.code == game_code)
+        .ok_or_else(game_not_found)?;
+    Ok((player, game))
+}
+
+/// Ask the host to let the player called `player_name` back in, or the other
+/// players when it is the host who lost their link. Whoever holds the
+/// returned claim token collects the new auth token.
+pub(crate) async fn request(
+    state: &AppState,
+    game_code: &str,
+    player_name: &str,
+) -> Result<RecoveryPayload, AppError> {
+    let game = state
+        .db
+        .get_game_by_code(game_code)
+        .await?
+        .ok_or_else(game_not_found)?;
+    let name = normalise_name(player_name);
+    let player: Player = state
+        .db
+        .get_players_by_game_id(game.id)
+        .await?
+        .into_iter()
+        .find(|p| normalise_name(&p.name) == name)
+        .ok_or(AppError::NotFound(
+            ErrorCode::PlayerNotFound,
+            "error.PLAYER_NOT_FOUND",
+        ))?;
+    // Every request wakes up the whole game, so a game only takes so many.
+    let limits = &state.config.rate_limits;
+    engine::check_recovery_request(
+        &state.db.get_recovery_requests(game.id).await?,
+        player.id,
+        unix_now(),
+        limits.recovery_request_limit,
+        limits.recovery_window_secs,
+    )?;
+    let request = state.db.request_recovery(game.id, player.id).await?;
+    info!(game_code, player_id = player.id, "Recovery requested");
+    // The host's page picks the request up through the change poll.
+    bump_game_version(state, game_code);
+    Ok(recovery_payload(request))
+}
+
+/// Where the request behind `claim_token` stands.
+pub(crate) async fn claim(
+    state: &AppState,
+    game_code: &str,
+    claim_token: &str,
+) -> Result<RecoveryPayload, AppError> {
+    let game = state
+        .db
+        .get_game_by_code(game_code)
+        .await?
+        .ok_or_else(game_not_found)?;
+    state
+        .db
+        .get_recovery_request(claim_token)
+        .await?
+        .filter(|r| r.game_id == game.id)
+        .map(recovery_payload)
+        .ok_or_else(recovery_not_found)
+}
+
+/// The pending requests the owner of `auth_token` may answer, oldest first:
+/// everyone else's for the host, the host's own for the other players.
+pub(crate) async fn pending(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+) -> Result<Vec<PendingRecoveryPayload>, AppError> {
+    let (me, game) = member(state, game_code, auth_token).await?;
+    let players = state.db.get_players_by_game_id(game.id).await?;
+    Ok(state
+        .db
+        .get_recovery_requests(game.id)
+        .await?
+        .into_iter()
+        .filter(|r| r.status == RequestStatus::Pending)
+        .filter(|r| engine::may_answer_recovery(&game, me.id, r.player_id))
+        .filter_map(|r| {
+            let player = players.iter().find(|p| p.id == r.player_id)?;
+            Some(PendingRecoveryPayload {
+                id: r.id,
+                player_id: r.player_id,
+                player_name: player.name.clone(),
+                created_at: r.created_at,
+            })
+        })
+        .collect())
+}
+
+/// Let a player back in with a fresh auth token, or turn them away.
+pub(crate) async fn decide(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+    request_id: i64,
+    approve: bool,
+) -> Result<(), AppError> {
+    let (me, game) = member(state, game_code, auth_token).await?;
+    let request = state
+        .db
+        .get_recovery_requests(game.id)
+        .await?
+        .into_iter()
+        .find(|r| r.id == request_id)
+        .ok_or_else(recovery_not_found)?;
+    if !engine::may_answer_recovery(&game, me.id, request.player_id) {
+        return Err(AppError::Forbidden(
+            ErrorCode::NotHost,
+            "error.NOT_HOST.recovery",
+        ));
+    }
+    let answered = || AppError::Conflict(ErrorCode::RecoveryClosed, "error.RECOVERY_CLOSED");
+    if request.status != RequestStatus::Pending {
+        return Err(answered());
+    }
+    if !state
+        .db
+        .decide_recovery(game.id, request_id, approve)
+        .await?
+    {
+        return Err(answered());
+    }
+    info!(
+        game_code,
+        request_id,
+        player_id = request.player_id,
+        approve,
+        "Recovery request answered"
+    );
+    bump_game_version(state, game_code);
+    Ok(())
+}
+
+pub async fn request_recovery(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    Json(payload): Json<RecoverPayload>,
+) -> Result<impl IntoResponse, AppError> {
+    let recovery = request(&state, &game_code, &payload.player_name).await?;
+    Ok((StatusCode::CREATED, Json(recovery)))
+}
+
+/// Also remembers the recovered player on this browser once approved.
+pub async fn claim_recovery(
+    State(state): State<AppState>,
+    Path((game_code, claim_token)): Path<(String, String)>,
+) -> Result<impl IntoResponse, AppError> {
+    let recovery = claim(&state, &game_code, &claim_token).await?;
+    let cookie = recovery
+        .auth_token
+        .as_deref()
+        .map(|token| remember(&state.config, token));
+    Ok((cookie, Json(recovery)))
+}
+
+pub async fn get_recoveries(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(pending(&state, &game_code, auth.token()).await?))
+}
+
+pub async fn decide_recovery(
+    State(state): State<AppState>,
+    Path((game_code, request_id)): Path<(String, i64)>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Json(payload): Json<DecideRecoveryPayload>,
+) -> Result<impl IntoResponse, AppError> {
+    decide(
+        &state,
+        &game_code,
+        auth.token(),
+        request_id,
+        payload.approve,
+    )
+    .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,101 @@
+use super::utils::{authenticate, bump_game_version, feature_disabled, game_not_found};
+use crate::{
+    errors::{AppError, ErrorCode},
+    models::GameStatus,
+    payloads::{KillTokenPayload, SecretRotatedPayload},
+    state::AppState,
+};
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+use tracing::info;
+
+/// Replace the player's secret code, within the configured limits.
+pub(crate) async fn rotate(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+) -> Result<SecretRotatedPayload, AppError> {
+    if !state.config.features.secret_rotation {
+        return Err(feature_disabled());
+    }
+    let limits = &state.config.rate_limits;
+    let (secret_code, rotations_left) = state
+        .db
+        .rotate_secret(
+            game_code,
+            auth_token,
+            limits.secret_rotation_cooldown_secs,
+            limits.secret_rotation_limit,
+        )
+        .await?;
+    // The player's page picks up the new code (and QR) through the change poll.
+    bump_game_version(state, game_code);
+    Ok(SecretRotatedPayload {
+        secret_code,
+        rotations_left,
+    })
+}
+
+/// A short-lived signed token for a living player to show as their QR code.
+pub(crate) async fn kill_token(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+) -> Result<KillTokenPayload, AppError> {
+    if !state.config.features.kill_tokens {
+        return Err(feature_disabled());
+    }
+    let player = authenticate(state, auth_token).await?;
+    let game = state
+        .db
+        .get_game_by_id(player.game_id)
+        .await?
+        .filter(|g| g.code == game_code)
+        .ok_or_else(game_not_found)?;
+    if game.status != GameStatus::InProgress {
+        return Err(AppError::UnprocessableEntity(
+            ErrorCode::GameNotInProgress,
+            "error.GAME_NOT_IN_PROGRESS.kill_code",
+        ));
+    }
+    if !player.is_alive {
+        return Err(AppError::Forbidden(
+            ErrorCode::PlayerEliminated,
+            "error.PLAYER_ELIMINATED.kill_code",
+        ));
+    }
+
+    let generation = state.db.get_secret_generation(player.id).await?;
+    let (token, claims) = state.kill_tokens.issue(&game.code, player.id, generation);
+    Ok(KillTokenPayload {
+        token,
+        expires_at: claims.exp,
+        // Refresh well before expiry so a scan never races the clock.
+        refresh_in_secs: (state.kill_tokens.ttl_secs() / 2).max(1),
+    })
+}
+
+pub async fn rotate_secret(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<impl IntoResponse, AppError> {
+    info!("Received rotate_secret for {}", game_code);
+    Ok(Json(rotate(&state, &game_code, auth.token()).await?))
+}
+
+/// Hand a living player a short-lived signed token to show as their QR code.
+pub async fn issue_kill_token(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(kill_token(&state, &game_code, auth.token()).await?))
+}
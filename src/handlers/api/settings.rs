@@ -0,0 +1,84 @@
+use super::lobby;
+use super::utils::{authenticate, bump_game_version, game_not_found};
+use crate::{
+    engine,
+    errors::{AppError, ErrorCode},
+    models::{GameSettings, GameStatus},
+    state::AppState,
+};
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+use tracing::info;
+
+/// The settings of the game the owner of `auth_token` is in.
+pub(crate) async fn get(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+) -> Result<GameSettings, AppError> {
+    let player = authenticate(state, auth_token).await?;
+    let game = state
+        .db
+        .get_game_by_id(player.game_id)
+        .await?
+        .filter(|g| g.code == game_code)
+        .ok_or_else(game_not_found)?;
+    state
+        .db
+        .get_game_settings(game.id)
+        .await?
+        .ok_or_else(game_not_found)
+}
+
+/// Replace a game's settings; only its host may, and only in the lobby.
+/// Turning on auto-start in a lobby where everyone is ready starts it.
+pub(crate) async fn update(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+    settings: GameSettings,
+) -> Result<GameSettings, AppError> {
+    let player = authenticate(state, auth_token).await?;
+    let game = state
+        .db
+        .get_game_by_id(player.game_id)
+        .await?
+        .filter(|g| g.code == game_code)
+        .ok_or_else(game_not_found)?;
+    if game.host_id != Some(player.id) {
+        return Err(AppError::Forbidden(
+            ErrorCode::NotHost,
+            "error.NOT_HOST.settings",
+        ));
+    }
+    if game.status != GameStatus::Lobby {
+        return Err(AppError::UnprocessableEntity(
+            ErrorCode::GameAlreadyStarted,
+            "error.GAME_ALREADY_STARTED.settings",
+        ));
+    }
+    engine::check_settings(&settings, state.config.game.max_players)?;
+    info!(game_code, ?settings, "Host changed the game settings");
+    state.db.set_game_settings(game.id, &settings).await?;
+    bump_game_version(state, game_code);
+    lobby::auto_start(state, game_code).await?;
+    Ok(settings)
+}
+
+pub async fn update_settings(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Json(settings): Json<GameSettings>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        update(&state, &game_code, auth.token(), settings).await?,
+    ))
+}
@@ -0,0 +1,154 @@
+use super::activity::event_seen_by;
+use super::chat::message_payload;
+use super::utils::{authenticate, feature_disabled, game_not_found};
+use crate::{
+    engine,
+    errors::{AppError, ErrorCode},
+    models::{GameEventKind, Player},
+    payloads::{SpectatedPlayerPayload, SpectatorLinkPayload, SpectatorPayload},
+    state::AppState,
+};
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+pub struct SpectatorQuery {
+    /// The game's spectator token, from its spectator link.
+    pub token: String,
+}
+
+/// Where the spectator page of a game lives.
+pub(crate) fn spectator_path(game_code: &str, spectator_token: &str) -> String {
+    format!("/game/{game_code}/spectate?token={spectator_token}")
+}
+
+/// What spectators see of a player: whether they are still in, nothing else.
+fn spectated(player: Player) -> SpectatedPlayerPayload {
+    SpectatedPlayerPayload {
+        id: player.id,
+        name: player.name,
+        is_alive: player.is_alive,
+    }
+}
+
+/// The scoreboard of a game, for whoever holds its spectator token.
+pub(crate) async fn scoreboard(
+    state: &AppState,
+    game_code: &str,
+    spectator_token: &str,
+) -> Result<SpectatorPayload, AppError> {
+    if !state.config.features.spectators {
+        return Err(feature_disabled());
+    }
+    let (game, players) = state
+        .db
+        .get_game_state(game_code)
+        .await?
+        .ok_or_else(game_not_found)?;
+    let expected = state.db.get_spectator_token(game.id).await?;
+    // Compare digests so the check takes the same time however much matches.
+    let valid = expected.is_some_and(|expected| {
+        Sha256::digest(expected.as_bytes()) == Sha256::digest(spectator_token.as_bytes())
+    });
+    if !valid {
+        return Err(AppError::Forbidden(
+            ErrorCode::InvalidSpectatorToken,
+            "error.INVALID_SPECTATOR_TOKEN",
+        ));
+    }
+    let events = state.db.get_events(game.id).await?;
+    let settings = state
+        .db
+        .get_game_settings(game.id)
+        .await?
+        .unwrap_or_default();
+    let last = |kind: GameEventKind| {
+        events
+            .iter()
+            .rev()
+            .find(|e| e.kind == kind)
+            .map(|e| e.created_at)
+    };
+    let messages = state
+        .db
+        .get_messages(game.id)
+        .await?
+        .into_iter()
+        .filter(|m| engine::spectators_read_chat(m.channel, &settings))
+        .map(message_payload)
+        .collect();
+    let started_at = last(GameEventKind::Started);
+    let finished_at = last(GameEventKind::Finished);
+    Ok(SpectatorPayload {
+        version: state.get_game_version(game_code),
+        code: game.code,
+        status: game.status,
+        players: players.into_iter().map(spectated).collect(),
+        events: events
+            .into_iter()
+            .map(|e| event_seen_by(e, &settings, None))
+            .collect(),
+        messages,
+        started_at,
+        finished_at,
+    })
+}
+
+/// The spectator link of a game; only its host may share it.
+pub(crate) async fn link(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+) -> Result<SpectatorLinkPayload, AppError> {
+    if !state.config.features.spectators {
+        return Err(feature_disabled());
+    }
+    let player = authenticate(state, auth_token).await?;
+    let game = state
+        .db
+        .get_game_by_id(player.game_id)
+        .await?
+        .filter(|g| g.code == game_code)
+        .ok_or_else(game_not_found)?;
+    if game.host_id != Some(player.id) {
+        return Err(AppError::Forbidden(
+            ErrorCode::NotHost,
+            "error.NOT_HOST.spectate",
+        ));
+    }
+    let spectator_token = state
+        .db
+        .get_spectator_token(game.id)
+        .await?
+        .ok_or_else(game_not_found)?;
+    Ok(SpectatorLinkPayload {
+        path: spectator_path(game_code, &spectator_token),
+        spectator_token,
+    })
+}
+
+pub async fn spectate(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    Query(query): Query<SpectatorQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(scoreboard(&state, &game_code, &query.token).await?))
+}
+
+pub async fn spectator_link(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(link(&state, &game_code, auth.token()).await?))
+}
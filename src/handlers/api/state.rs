@@ -1,5 +1,13 @@
-use super::utils::bump_game_version;
-use crate::{errors::AppError, models::Game, state::AppState};
+use super::ghosts::role_of;
+use super::lobby;
+use super::utils::{authenticate, bump_game_version, game_not_found};
+use crate::{
+    errors::AppError,
+    models::{Game, GameStatus, Player, PlayerRole},
+    payloads::{player_seen_by, PlayerPayload},
+    remember::forget,
+    state::AppState,
+};
 use axum::{
     extract::{Path, State},
     http::StatusCode,
@@ -13,55 +21,84 @@ use axum_extra::{
 use serde::Serialize;
 
 #[derive(Serialize)]
-pub struct PlayerGameState {
-    pub id: i32,
-    pub name: String,
-    pub is_alive: bool,
-    pub target_name: Option<String>,
-    pub secret_code: Option<String>,
+pub struct GameStateResponse {
+    pub game: Game,
+    pub players: Vec<PlayerPayload>,
+    pub role: PlayerRole,
+    pub version: i64,
 }
 
-#[derive(Serialize)]
-pub struct GameStateResponse {
+/// A game as seen by one of its players.
+pub(crate) struct GameView {
     pub game: Game,
-    pub players: Vec<PlayerGameState>,
+    pub players: Vec<PlayerPayload>,
+    pub role: PlayerRole,
     pub version: i64,
 }
 
-pub async fn get_game_state(
-    State(state): State<AppState>,
-    Path(game_code): Path<String>,
-    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
-) -> Result<impl IntoResponse, AppError> {
-    let requesting = state
+/// A game and its players as seen by the owner of `auth_token`.
+pub(crate) async fn view(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+) -> Result<GameView, AppError> {
+    let requesting = authenticate(state, auth_token).await?;
+    let (game, players) = state
         .db
-        .get_player_by_auth_token(auth.token())
+        .get_game_state(game_code)
         .await?
-        .ok_or(AppError::Forbidden("Invalid auth token.".into()))?;
-    let (game, players) = state
+        .filter(|(g, _)| g.id == requesting.game_id)
+        .ok_or_else(game_not_found)?;
+    let settings = state
         .db
-        .get_game_state(&game_code)
+        .get_game_settings(game.id)
         .await?
-        .ok_or(AppError::NotFound("Game not found".into()))?;
-    let players_conv: Vec<PlayerGameState> = players
+        .unwrap_or_default();
+    Ok(GameView {
+        role: role_of(&requesting, &game, &settings),
+        players: seen_by(players, requesting.id),
+        version: state.get_game_version(game_code),
+        game,
+    })
+}
+
+pub(crate) fn seen_by(players: Vec<Player>, viewer: i32) -> Vec<PlayerPayload> {
+    players
         .into_iter()
-        .map(|p| PlayerGameState {
-            id: p.id,
-            name: p.name,
-            is_alive: p.is_alive,
-            target_name: p.target_name,
-            secret_code: if p.id == requesting.id {
-                Some(p.secret_code)
-            } else {
-                None
-            },
-        })
-        .collect();
-    let version = state.get_game_version(&game_code);
+        .map(|p| player_seen_by(p, viewer))
+        .collect()
+}
+
+pub(crate) async fn leave(
+    state: &AppState,
+    game_code: &str,
+    auth_token: &str,
+) -> Result<(), AppError> {
+    authenticate(state, auth_token).await?;
+    state.db.leave_game(game_code, auth_token).await?;
+    bump_game_version(state, game_code);
+    // The game is gone when the last player left its lobby.
+    let Some(game) = state.db.get_game_by_code(game_code).await? else {
+        return Ok(());
+    };
+    // The last player who was not ready may just have left the lobby.
+    if game.status == GameStatus::Lobby {
+        lobby::auto_start(state, game_code).await?;
+    }
+    Ok(())
+}
+
+pub async fn get_game_state(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<impl IntoResponse, AppError> {
+    let view = view(&state, &game_code, auth.token()).await?;
     Ok(Json(GameStateResponse {
-        game,
-        players: players_conv,
-        version,
+        game: view.game,
+        players: view.players,
+        role: view.role,
+        version: view.version,
     }))
 }
 
@@ -70,9 +107,6 @@ pub async fn leave_game(
     Path(game_code): Path<String>,
     TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
 ) -> Result<impl IntoResponse, AppError> {
-    state.db.leave_game(&game_code, auth.token()).await?;
-    if state.db.get_game_by_code(&game_code).await?.is_some() {
-        bump_game_version(&state, &game_code);
-    }
-    Ok(StatusCode::NO_CONTENT)
+    leave(&state, &game_code, auth.token()).await?;
+    Ok((StatusCode::NO_CONTENT, forget(&state.config)))
 }
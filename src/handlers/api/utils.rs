@@ -1,6 +1,61 @@
-use crate::state::AppState;
+use crate::{
+    errors::{AppError, ErrorCode},
+    models::Player,
+    request_id,
+    state::AppState,
+};
 
 /// Bump the version counter for a game whenever a significant event occurs.
 pub fn bump_game_version(state: &AppState, game_code: &str) {
     state.bump_game_version(game_code);
 }
+
+/// The player owning a bearer token.
+pub(crate) async fn authenticate(state: &AppState, auth_token: &str) -> Result<Player, AppError> {
+    let player =
+        state
+            .db
+            .get_player_by_auth_token(auth_token)
+            .await?
+            .ok_or(AppError::Forbidden(
+                ErrorCode::InvalidAuthToken,
+                "error.INVALID_AUTH_TOKEN",
+            ))?;
+    state.metrics.player_active(player.id);
+    request_id::record_player(player.id);
+    Ok(player)
+}
+
+pub(crate) fn game_not_found() -> AppError {
+    AppError::NotFound(ErrorCode::GameNotFound, "error.GAME_NOT_FOUND")
+}
+
+pub(crate) fn feature_disabled() -> AppError {
+    AppError::NotFound(ErrorCode::FeatureDisabled, "error.FEATURE_DISABLED")
+}
+
+/// `name` without surrounding whitespace, provided that leaves between 1 and
+/// `max` characters.
+pub(crate) fn player_name(name: &str, max: usize) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > max {
+        return Err(AppError::UnprocessableEntity(
+            ErrorCode::InvalidName { max },
+            "error.INVALID_NAME",
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// `text` without surrounding whitespace, provided that leaves between 1 and
+/// `max` characters.
+pub(crate) fn message_text(text: &str, max: usize) -> Result<&str, AppError> {
+    let text = text.trim();
+    if text.is_empty() || text.chars().count() > max {
+        return Err(AppError::UnprocessableEntity(
+            ErrorCode::InvalidMessage { max },
+            "error.INVALID_MESSAGE",
+        ));
+    }
+    Ok(text)
+}
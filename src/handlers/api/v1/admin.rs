@@ -0,0 +1,80 @@
+use crate::handlers::api::admin;
+use crate::{
+    errors::AppError,
+    payloads::{
+        ErrorPayload, FinishedGamePayload, PurgeGamesPayload, PurgedGamesPayload, RingCheckPayload,
+    },
+    state::AppState,
+};
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+use std::time::Duration;
+
+/// Validate a running game's target ring and repair it if it is broken.
+#[utoipa::path(
+    post,
+    path = "/admin/games/{game_code}/ring",
+    tag = "admin",
+    params(("game_code" = String, Path, description = "Code of the game")),
+    security(("admin" = [])),
+    responses(
+        (status = OK, body = RingCheckPayload),
+        (status = FORBIDDEN, description = "Not the admin token, or none is configured", body = ErrorPayload),
+        (status = NOT_FOUND, description = "No such game", body = ErrorPayload),
+    )
+)]
+pub async fn check_ring(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<Json<RingCheckPayload>, AppError> {
+    Ok(Json(admin::ring_check(&state, &game_code, &auth).await?))
+}
+
+/// End a game straight away, without a winner, whatever state it is in.
+#[utoipa::path(
+    post,
+    path = "/admin/games/{game_code}/finish",
+    tag = "admin",
+    params(("game_code" = String, Path, description = "Code of the game")),
+    security(("admin" = [])),
+    responses(
+        (status = OK, body = FinishedGamePayload),
+        (status = FORBIDDEN, description = "Not the admin token, or none is configured", body = ErrorPayload),
+        (status = NOT_FOUND, description = "No such game", body = ErrorPayload),
+    )
+)]
+pub async fn finish_game(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<Json<FinishedGamePayload>, AppError> {
+    Ok(Json(admin::finish_game(&state, &game_code, &auth).await?))
+}
+
+/// Delete old games together with their players.
+#[utoipa::path(
+    post,
+    path = "/admin/purge",
+    tag = "admin",
+    request_body = PurgeGamesPayload,
+    security(("admin" = [])),
+    responses(
+        (status = OK, body = PurgedGamesPayload),
+        (status = FORBIDDEN, description = "Not the admin token, or none is configured", body = ErrorPayload),
+    )
+)]
+pub async fn purge_games(
+    State(state): State<AppState>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Json(payload): Json<PurgeGamesPayload>,
+) -> Result<Json<PurgedGamesPayload>, AppError> {
+    let older_than = Duration::from_secs(payload.older_than_hours.saturating_mul(3600));
+    Ok(Json(admin::purge_games(&state, older_than, &auth).await?))
+}
@@ -0,0 +1,70 @@
+use crate::handlers::api::chat;
+use crate::{
+    errors::AppError,
+    payloads::{ChatMessagePayload, ErrorPayload, PostMessagePayload},
+    state::AppState,
+};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+
+/// The chat messages you may read, oldest first: the host's announcements,
+/// the lobby chat and, once you are out, the dead players' chat.
+#[utoipa::path(
+    get,
+    path = "/games/{game_code}/messages",
+    tag = "chat",
+    params(
+        ("game_code" = String, Path, description = "Code of the game"),
+        chat::ChatQuery,
+    ),
+    security(("player" = [])),
+    responses(
+        (status = OK, body = Vec<ChatMessagePayload>),
+        (status = FORBIDDEN, description = "Unknown auth token", body = ErrorPayload),
+        (status = NOT_FOUND, description = "No such game", body = ErrorPayload),
+    )
+)]
+pub async fn get_messages(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    Query(query): Query<chat::ChatQuery>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<Json<Vec<ChatMessagePayload>>, AppError> {
+    Ok(Json(
+        chat::messages(&state, &game_code, auth.token(), query).await?,
+    ))
+}
+
+/// Post a message. Only the host makes announcements, the lobby chat closes
+/// when the game starts and the dead chat is for eliminated players.
+#[utoipa::path(
+    post,
+    path = "/games/{game_code}/messages",
+    tag = "chat",
+    params(("game_code" = String, Path, description = "Code of the game")),
+    request_body = PostMessagePayload,
+    security(("player" = [])),
+    responses(
+        (status = CREATED, body = ChatMessagePayload),
+        (status = FORBIDDEN, description = "Not yours to post in, or unknown auth token", body = ErrorPayload),
+        (status = NOT_FOUND, description = "No such game", body = ErrorPayload),
+        (status = UNPROCESSABLE_ENTITY, description = "Empty or too long, or the channel is closed", body = ErrorPayload),
+        (status = TOO_MANY_REQUESTS, description = "Too many messages in a short time", body = ErrorPayload),
+    )
+)]
+pub async fn post_message(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Json(payload): Json<PostMessagePayload>,
+) -> Result<(StatusCode, Json<ChatMessagePayload>), AppError> {
+    let message = chat::post(&state, &game_code, auth.token(), payload).await?;
+    Ok((StatusCode::CREATED, Json(message)))
+}
@@ -0,0 +1,213 @@
+use crate::handlers::api::{activity, change, lobby, settings, state as game_state};
+use crate::{
+    errors::AppError,
+    models::{GameSettings, PlayerRole},
+    payloads::{
+        player_seen_by, ActivityPayload, ChangesPayload, CreateGamePayload, ErrorPayload,
+        GamePayload, PlayerSessionPayload,
+    },
+    state::AppState,
+};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+
+pub(crate) fn game_payload(view: game_state::GameView) -> GamePayload {
+    GamePayload {
+        code: view.game.code,
+        status: view.game.status,
+        host_id: view.game.host_id,
+        players: view.players,
+        role: view.role,
+        version: view.version,
+    }
+}
+
+pub(crate) fn session_payload(session: lobby::Session) -> PlayerSessionPayload {
+    let viewer = session.player.id;
+    PlayerSessionPayload {
+        auth_token: session.player.auth_token.clone(),
+        player: player_seen_by(session.player, viewer),
+        game: game_payload(game_state::GameView {
+            game: session.game,
+            players: game_state::seen_by(session.players, viewer),
+            // New players are always alive, in the lobby or late.
+            role: PlayerRole::Alive,
+            version: session.version,
+        }),
+    }
+}
+
+/// Create a game with yourself as its host.
+#[utoipa::path(
+    post,
+    path = "/games",
+    tag = "games",
+    request_body = CreateGamePayload,
+    responses(
+        (status = CREATED, description = "The game and your session as its host", body = PlayerSessionPayload),
+        (status = UNPROCESSABLE_ENTITY, description = "The name is not acceptable", body = ErrorPayload),
+    )
+)]
+pub async fn create_game(
+    State(state): State<AppState>,
+    Json(payload): Json<CreateGamePayload>,
+) -> Result<(StatusCode, Json<PlayerSessionPayload>), AppError> {
+    let session = lobby::create(&state, payload.player_name).await?;
+    Ok((StatusCode::CREATED, Json(session_payload(session))))
+}
+
+/// The game as you see it: only your own secret code and target are
+/// included.
+#[utoipa::path(
+    get,
+    path = "/games/{game_code}",
+    tag = "games",
+    params(("game_code" = String, Path, description = "Code of the game")),
+    security(("player" = [])),
+    responses(
+        (status = OK, body = GamePayload),
+        (status = FORBIDDEN, description = "Unknown auth token", body = ErrorPayload),
+        (status = NOT_FOUND, description = "No such game, or not yours", body = ErrorPayload),
+    )
+)]
+pub async fn get_game(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<Json<GamePayload>, AppError> {
+    let view = game_state::view(&state, &game_code, auth.token()).await?;
+    Ok(Json(game_payload(view)))
+}
+
+/// Start the game and hand out targets. Only the host may do this.
+#[utoipa::path(
+    post,
+    path = "/games/{game_code}/start",
+    tag = "games",
+    params(("game_code" = String, Path, description = "Code of the game")),
+    security(("player" = [])),
+    responses(
+        (status = OK, description = "The running game", body = GamePayload),
+        (status = FORBIDDEN, description = "Not the host, or unknown auth token", body = ErrorPayload),
+        (status = NOT_FOUND, description = "No such game", body = ErrorPayload),
+        (status = UNPROCESSABLE_ENTITY, description = "Already started, or not enough players", body = ErrorPayload),
+    )
+)]
+pub async fn start_game(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<Json<GamePayload>, AppError> {
+    lobby::start(&state, &game_code, auth.token()).await?;
+    let view = game_state::view(&state, &game_code, auth.token()).await?;
+    Ok(Json(game_payload(view)))
+}
+
+/// Whether the game changed since `version`. Cheap enough to poll every
+/// couple of seconds.
+#[utoipa::path(
+    get,
+    path = "/games/{game_code}/changes",
+    tag = "games",
+    params(
+        ("game_code" = String, Path, description = "Code of the game"),
+        change::VersionQuery,
+    ),
+    responses((status = OK, body = ChangesPayload))
+)]
+pub async fn changes(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    Query(query): Query<change::VersionQuery>,
+    auth: Option<TypedHeader<Authorization<Bearer>>>,
+) -> Json<ChangesPayload> {
+    let token = auth.as_ref().map(|TypedHeader(auth)| auth.token());
+    Json(change::changes(
+        &state,
+        &game_code,
+        query.version.unwrap_or(0),
+        token,
+    ))
+}
+
+/// What happened in the game, newest first: joins, the start, kills,
+/// players leaving and host changes.
+#[utoipa::path(
+    get,
+    path = "/games/{game_code}/activity",
+    tag = "games",
+    params(
+        ("game_code" = String, Path, description = "Code of the game"),
+        activity::ActivityQuery,
+    ),
+    security(("player" = [])),
+    responses(
+        (status = OK, body = ActivityPayload),
+        (status = FORBIDDEN, description = "Unknown auth token", body = ErrorPayload),
+        (status = NOT_FOUND, description = "No such game", body = ErrorPayload),
+    )
+)]
+pub async fn get_activity(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    Query(query): Query<activity::ActivityQuery>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<Json<ActivityPayload>, AppError> {
+    Ok(Json(
+        activity::feed(&state, &game_code, auth.token(), query).await?,
+    ))
+}
+
+/// The rules the host picked for this game.
+#[utoipa::path(
+    get,
+    path = "/games/{game_code}/settings",
+    tag = "games",
+    params(("game_code" = String, Path, description = "Code of the game")),
+    security(("player" = [])),
+    responses(
+        (status = OK, body = GameSettings),
+        (status = FORBIDDEN, description = "Unknown auth token", body = ErrorPayload),
+        (status = NOT_FOUND, description = "No such game", body = ErrorPayload),
+    )
+)]
+pub async fn get_settings(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<Json<GameSettings>, AppError> {
+    Ok(Json(settings::get(&state, &game_code, auth.token()).await?))
+}
+
+/// Replace the game's settings. Only the host may, before the game starts.
+#[utoipa::path(
+    put,
+    path = "/games/{game_code}/settings",
+    tag = "games",
+    params(("game_code" = String, Path, description = "Code of the game")),
+    request_body = GameSettings,
+    security(("player" = [])),
+    responses(
+        (status = OK, description = "The new settings", body = GameSettings),
+        (status = FORBIDDEN, description = "Not the host, or unknown auth token", body = ErrorPayload),
+        (status = NOT_FOUND, description = "No such game", body = ErrorPayload),
+        (status = UNPROCESSABLE_ENTITY, description = "The game already started, or the player limits do not fit", body = ErrorPayload),
+    )
+)]
+pub async fn update_settings(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Json(payload): Json<GameSettings>,
+) -> Result<Json<GameSettings>, AppError> {
+    Ok(Json(
+        settings::update(&state, &game_code, auth.token(), payload).await?,
+    ))
+}
@@ -0,0 +1,154 @@
+use crate::handlers::api::ghosts;
+use crate::{
+    errors::AppError,
+    payloads::{
+        DisputePayload, DisputeVotePayload, ErrorPayload, GhostPayload, HintPayload,
+        OpenDisputePayload, SendHintPayload,
+    },
+    state::AppState,
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+
+/// What you can do as a ghost: the open disputes, whether you can dispute
+/// your own kill and whether your hint is still unsent.
+#[utoipa::path(
+    get,
+    path = "/games/{game_code}/ghost",
+    tag = "ghosts",
+    params(("game_code" = String, Path, description = "Code of the game")),
+    security(("player" = [])),
+    responses(
+        (status = OK, body = GhostPayload),
+        (status = FORBIDDEN, description = "Not a ghost, or unknown auth token", body = ErrorPayload),
+        (status = NOT_FOUND, description = "No such game", body = ErrorPayload),
+    )
+)]
+pub async fn get_ghost(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<Json<GhostPayload>, AppError> {
+    Ok(Json(
+        ghosts::overview(&state, &game_code, auth.token()).await?,
+    ))
+}
+
+/// Ask the other ghosts to overturn the kill that eliminated you.
+#[utoipa::path(
+    post,
+    path = "/games/{game_code}/disputes",
+    tag = "ghosts",
+    params(("game_code" = String, Path, description = "Code of the game")),
+    request_body = OpenDisputePayload,
+    security(("player" = [])),
+    responses(
+        (status = CREATED, body = DisputePayload),
+        (status = FORBIDDEN, description = "Not a ghost, or unknown auth token", body = ErrorPayload),
+        (status = NOT_FOUND, description = "No such game", body = ErrorPayload),
+        (status = CONFLICT, description = "The kill is already disputed", body = ErrorPayload),
+        (status = UNPROCESSABLE_ENTITY, description = "Not the kill that eliminated you", body = ErrorPayload),
+    )
+)]
+pub async fn open_dispute(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Json(payload): Json<OpenDisputePayload>,
+) -> Result<(StatusCode, Json<DisputePayload>), AppError> {
+    let dispute = ghosts::open(&state, &game_code, auth.token(), payload.event_id).await?;
+    Ok((StatusCode::CREATED, Json(dispute)))
+}
+
+/// Vote on a disputed kill, replacing your earlier vote. A majority of the
+/// ghosts settles it; an overturned kill puts the victim back in the game.
+#[utoipa::path(
+    post,
+    path = "/games/{game_code}/disputes/{dispute_id}/votes",
+    tag = "ghosts",
+    params(
+        ("game_code" = String, Path, description = "Code of the game"),
+        ("dispute_id" = i64, Path, description = "The dispute to vote on"),
+    ),
+    request_body = DisputeVotePayload,
+    security(("player" = [])),
+    responses(
+        (status = OK, description = "The dispute after your vote", body = DisputePayload),
+        (status = FORBIDDEN, description = "Not a ghost, your own kill, or unknown auth token", body = ErrorPayload),
+        (status = NOT_FOUND, description = "No such game or dispute", body = ErrorPayload),
+        (status = CONFLICT, description = "The dispute is already settled", body = ErrorPayload),
+    )
+)]
+pub async fn vote_on_dispute(
+    State(state): State<AppState>,
+    Path((game_code, dispute_id)): Path<(String, i64)>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Json(payload): Json<DisputeVotePayload>,
+) -> Result<Json<DisputePayload>, AppError> {
+    Ok(Json(
+        ghosts::vote(
+            &state,
+            &game_code,
+            auth.token(),
+            dispute_id,
+            payload.overturn,
+        )
+        .await?,
+    ))
+}
+
+/// Send your one anonymous hint to a living player.
+#[utoipa::path(
+    post,
+    path = "/games/{game_code}/hints",
+    tag = "ghosts",
+    params(("game_code" = String, Path, description = "Code of the game")),
+    request_body = SendHintPayload,
+    security(("player" = [])),
+    responses(
+        (status = NO_CONTENT, description = "The hint is on its way"),
+        (status = FORBIDDEN, description = "Not a ghost, hints are off, or unknown auth token", body = ErrorPayload),
+        (status = NOT_FOUND, description = "No such game or player", body = ErrorPayload),
+        (status = CONFLICT, description = "You already sent your hint", body = ErrorPayload),
+        (status = UNPROCESSABLE_ENTITY, description = "Empty or too long, or the player is out", body = ErrorPayload),
+    )
+)]
+pub async fn send_hint(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Json(payload): Json<SendHintPayload>,
+) -> Result<StatusCode, AppError> {
+    ghosts::hint(&state, &game_code, auth.token(), payload).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The hints ghosts sent you, oldest first. They never say who sent them.
+#[utoipa::path(
+    get,
+    path = "/games/{game_code}/hints",
+    tag = "ghosts",
+    params(("game_code" = String, Path, description = "Code of the game")),
+    security(("player" = [])),
+    responses(
+        (status = OK, body = Vec<HintPayload>),
+        (status = FORBIDDEN, description = "Unknown auth token", body = ErrorPayload),
+        (status = NOT_FOUND, description = "No such game", body = ErrorPayload),
+    )
+)]
+pub async fn get_hints(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<Json<Vec<HintPayload>>, AppError> {
+    Ok(Json(
+        ghosts::received(&state, &game_code, auth.token()).await?,
+    ))
+}
@@ -0,0 +1,99 @@
+use crate::handlers::api::late_join;
+use crate::{
+    errors::AppError,
+    payloads::{DecideJoinPayload, ErrorPayload, JoinRequestPayload, PendingJoinPayload},
+    state::AppState,
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+
+/// Where your request to join a running game stands; includes your auth
+/// token once the host let you in.
+#[utoipa::path(
+    get,
+    path = "/games/{game_code}/join-claims/{claim_token}",
+    tag = "late-join",
+    params(
+        ("game_code" = String, Path, description = "Code of the game"),
+        ("claim_token" = String, Path, description = "Returned when you asked to join"),
+    ),
+    responses(
+        (status = OK, body = JoinRequestPayload),
+        (status = NOT_FOUND, description = "No such game or request", body = ErrorPayload),
+    )
+)]
+pub async fn claim_join_request(
+    State(state): State<AppState>,
+    Path((game_code, claim_token)): Path<(String, String)>,
+) -> Result<Json<JoinRequestPayload>, AppError> {
+    Ok(Json(
+        late_join::claim(&state, &game_code, &claim_token).await?,
+    ))
+}
+
+/// The newcomers waiting for you to let them in, oldest first. Host only.
+#[utoipa::path(
+    get,
+    path = "/games/{game_code}/join-requests",
+    tag = "late-join",
+    params(("game_code" = String, Path, description = "Code of the game")),
+    security(("player" = [])),
+    responses(
+        (status = OK, body = Vec<PendingJoinPayload>),
+        (status = FORBIDDEN, description = "Not the host, or unknown auth token", body = ErrorPayload),
+        (status = NOT_FOUND, description = "No such game", body = ErrorPayload),
+    )
+)]
+pub async fn get_join_requests(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<Json<Vec<PendingJoinPayload>>, AppError> {
+    Ok(Json(
+        late_join::pending(&state, &game_code, auth.token()).await?,
+    ))
+}
+
+/// Let a newcomer into the running game or turn them away. Approving
+/// splices them into the ring at a random point. Host only.
+#[utoipa::path(
+    post,
+    path = "/games/{game_code}/join-requests/{request_id}",
+    tag = "late-join",
+    params(
+        ("game_code" = String, Path, description = "Code of the game"),
+        ("request_id" = i64, Path, description = "The request to answer"),
+    ),
+    request_body = DecideJoinPayload,
+    security(("player" = [])),
+    responses(
+        (status = NO_CONTENT, description = "The request is answered"),
+        (status = FORBIDDEN, description = "Not the host, or unknown auth token", body = ErrorPayload),
+        (status = NOT_FOUND, description = "No such game or request", body = ErrorPayload),
+        (status = CONFLICT, description = "The request was already answered, or the name got taken", body = ErrorPayload),
+        (status = UNPROCESSABLE_ENTITY, description = "The game is full or over", body = ErrorPayload),
+    )
+)]
+pub async fn decide_join_request(
+    State(state): State<AppState>,
+    Path((game_code, request_id)): Path<(String, i64)>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Json(payload): Json<DecideJoinPayload>,
+) -> Result<StatusCode, AppError> {
+    late_join::decide(
+        &state,
+        &game_code,
+        auth.token(),
+        request_id,
+        payload.approve,
+    )
+    .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,122 @@
+//! `/api/v1`: the versioned public API.
+//!
+//! Every game is a resource under `/games/{game_code}` and every response
+//! uses the representations from `payloads.rs`, which also generate the
+//! OpenAPI document served at `/api/v1/openapi.json`. The unversioned
+//! `/api/game` routes stay as they are for the bundled frontend.
+
+use crate::payloads::ErrorPayload;
+use crate::state::AppState;
+use axum::{routing::get, Json, Router};
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+pub mod admin;
+pub mod chat;
+pub mod games;
+pub mod ghosts;
+pub mod late_join;
+pub mod players;
+pub mod recovery;
+pub mod spectators;
+
+pub const PREFIX: &str = "/api/v1";
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Hitman API"),
+    modifiers(&BearerAuth),
+    components(schemas(ErrorPayload)),
+    tags(
+        (name = "games", description = "Creating, starting and following games"),
+        (name = "players", description = "What a player does in a game"),
+        (name = "chat", description = "Announcements and chat within a game"),
+        (name = "recovery", description = "Getting back into a game after losing your auth token"),
+        (name = "late-join", description = "Letting newcomers into games that already run"),
+        (name = "ghosts", description = "What eliminated players can still do in games with ghosts on"),
+        (name = "spectators", description = "Following a game without playing in it"),
+        (name = "admin", description = "Server administration, needs the admin token"),
+    )
+)]
+pub struct ApiDoc;
+
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        let bearer = |description: &str| {
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .description(Some(description))
+                    .build(),
+            )
+        };
+        components.add_security_scheme(
+            "player",
+            bearer("The `auth_token` returned when creating or joining a game."),
+        );
+        components.add_security_scheme(
+            "spectator",
+            bearer("The `spectator_token` the host shares with spectators."),
+        );
+        components.add_security_scheme("admin", bearer("The server's `ADMIN_TOKEN`."));
+    }
+}
+
+fn api() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(games::create_game))
+        .routes(routes!(games::get_game))
+        .routes(routes!(games::start_game))
+        .routes(routes!(games::changes))
+        .routes(routes!(games::get_activity))
+        .routes(routes!(games::get_settings, games::update_settings))
+        .routes(routes!(players::join_game))
+        .routes(routes!(players::leave_game))
+        .routes(routes!(players::set_ready))
+        .routes(routes!(players::kill))
+        .routes(routes!(players::kill_token))
+        .routes(routes!(players::rotate_secret))
+        .routes(routes!(chat::get_messages, chat::post_message))
+        .routes(routes!(
+            recovery::request_recovery,
+            recovery::get_recoveries
+        ))
+        .routes(routes!(recovery::claim_recovery))
+        .routes(routes!(recovery::decide_recovery))
+        .routes(routes!(late_join::claim_join_request))
+        .routes(routes!(late_join::get_join_requests))
+        .routes(routes!(late_join::decide_join_request))
+        .routes(routes!(ghosts::get_ghost))
+        .routes(routes!(ghosts::open_dispute))
+        .routes(routes!(ghosts::vote_on_dispute))
+        .routes(routes!(ghosts::send_hint, ghosts::get_hints))
+        .routes(routes!(spectators::spectate))
+        .routes(routes!(spectators::spectator_link))
+        .routes(routes!(admin::check_ring))
+        .routes(routes!(admin::finish_game))
+        .routes(routes!(admin::purge_games))
+}
+
+/// The OpenAPI document for everything under [`PREFIX`].
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    router().1
+}
+
+fn router() -> (Router<AppState>, utoipa::openapi::OpenApi) {
+    OpenApiRouter::with_openapi(ApiDoc::openapi())
+        .nest(PREFIX, api())
+        .split_for_parts()
+}
+
+/// The `/api/v1` routes, including the OpenAPI document itself.
+pub fn routes() -> Router<AppState> {
+    let (router, openapi) = router();
+    router.route(
+        &format!("{PREFIX}/openapi.json"),
+        get(move || async move { Json(openapi) }),
+    )
+}
@@ -0,0 +1,176 @@
+use super::games::session_payload;
+use crate::handlers::api::{kill as kills, lobby, secret, state as game_state};
+use crate::{
+    errors::AppError,
+    payloads::{
+        ErrorPayload, JoinGamePayload, JoinRequestPayload, KillPayload, KillResponsePayload,
+        KillTokenPayload, PlayerSessionPayload, ReadyPayload, SecretRotatedPayload,
+    },
+    state::AppState,
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+
+/// Join a game that is still in its lobby, or a running one that takes
+/// late joiners. When its host lets late joiners in by hand you get a join
+/// request to poll instead.
+#[utoipa::path(
+    post,
+    path = "/games/{game_code}/players",
+    tag = "players",
+    params(("game_code" = String, Path, description = "Code of the game")),
+    request_body = JoinGamePayload,
+    responses(
+        (status = CREATED, description = "The game and your session in it", body = PlayerSessionPayload),
+        (status = ACCEPTED, description = "Waiting for the host to let you into the running game", body = JoinRequestPayload),
+        (status = NOT_FOUND, description = "No such game", body = ErrorPayload),
+        (status = CONFLICT, description = "Name taken", body = ErrorPayload),
+        (status = UNPROCESSABLE_ENTITY, description = "The lobby is full, the game already started, or the name is not acceptable", body = ErrorPayload),
+    )
+)]
+pub async fn join_game(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    Json(payload): Json<JoinGamePayload>,
+) -> Result<Response, AppError> {
+    Ok(
+        match lobby::join(&state, &game_code, payload.player_name).await? {
+            lobby::Joined::Player(session) => {
+                (StatusCode::CREATED, Json(session_payload(session))).into_response()
+            }
+            lobby::Joined::Pending(request) => {
+                (StatusCode::ACCEPTED, Json(request)).into_response()
+            }
+        },
+    )
+}
+
+/// Leave the game. In the lobby you are removed; in a running game your
+/// hunter inherits your target.
+#[utoipa::path(
+    delete,
+    path = "/games/{game_code}/players/me",
+    tag = "players",
+    params(("game_code" = String, Path, description = "Code of the game")),
+    security(("player" = [])),
+    responses(
+        (status = NO_CONTENT, description = "You left"),
+        (status = UNAUTHORIZED, description = "Not a player of this game", body = ErrorPayload),
+        (status = NOT_FOUND, description = "No such game", body = ErrorPayload),
+    )
+)]
+pub async fn leave_game(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<StatusCode, AppError> {
+    game_state::leave(&state, &game_code, auth.token()).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Tell the lobby whether you are ready. With auto-start on, the game starts
+/// once everyone is.
+#[utoipa::path(
+    put,
+    path = "/games/{game_code}/players/me/ready",
+    tag = "players",
+    params(("game_code" = String, Path, description = "Code of the game")),
+    request_body = ReadyPayload,
+    security(("player" = [])),
+    responses(
+        (status = NO_CONTENT, description = "Your readiness is recorded"),
+        (status = FORBIDDEN, description = "Unknown auth token", body = ErrorPayload),
+        (status = NOT_FOUND, description = "No such game", body = ErrorPayload),
+        (status = UNPROCESSABLE_ENTITY, description = "The game already started", body = ErrorPayload),
+    )
+)]
+pub async fn set_ready(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Json(payload): Json<ReadyPayload>,
+) -> Result<StatusCode, AppError> {
+    lobby::ready(&state, &game_code, auth.token(), payload.ready).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Eliminate your target with their secret code or a scanned kill token.
+#[utoipa::path(
+    post,
+    path = "/games/{game_code}/kills",
+    tag = "players",
+    params(("game_code" = String, Path, description = "Code of the game")),
+    request_body = KillPayload,
+    security(("player" = [])),
+    responses(
+        (status = CREATED, description = "Your target is out", body = KillResponsePayload),
+        (status = FORBIDDEN, description = "Not your target, or you are out", body = ErrorPayload),
+        (status = NOT_FOUND, description = "No such game or secret", body = ErrorPayload),
+        (status = UNPROCESSABLE_ENTITY, description = "Invalid or expired kill token, or the game is not running", body = ErrorPayload),
+    )
+)]
+pub async fn kill(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Json(payload): Json<KillPayload>,
+) -> Result<(StatusCode, Json<KillResponsePayload>), AppError> {
+    let outcome = kills::kill(&state, &game_code, auth.token(), &payload.secret_code).await?;
+    Ok((StatusCode::CREATED, Json(outcome)))
+}
+
+/// A short-lived signed token to show as your QR code instead of your
+/// secret.
+#[utoipa::path(
+    get,
+    path = "/games/{game_code}/kill-token",
+    tag = "players",
+    params(("game_code" = String, Path, description = "Code of the game")),
+    security(("player" = [])),
+    responses(
+        (status = OK, body = KillTokenPayload),
+        (status = FORBIDDEN, description = "You are out, or unknown auth token", body = ErrorPayload),
+        (status = NOT_FOUND, description = "No such game, or kill tokens are turned off", body = ErrorPayload),
+        (status = UNPROCESSABLE_ENTITY, description = "The game is not running", body = ErrorPayload),
+    )
+)]
+pub async fn kill_token(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<Json<KillTokenPayload>, AppError> {
+    Ok(Json(
+        secret::kill_token(&state, &game_code, auth.token()).await?,
+    ))
+}
+
+/// Replace your secret code, e.g. after someone saw it.
+#[utoipa::path(
+    post,
+    path = "/games/{game_code}/secret/rotate",
+    tag = "players",
+    params(("game_code" = String, Path, description = "Code of the game")),
+    security(("player" = [])),
+    responses(
+        (status = OK, body = SecretRotatedPayload),
+        (status = NOT_FOUND, description = "No such game, or rotation is turned off", body = ErrorPayload),
+        (status = TOO_MANY_REQUESTS, description = "Rotated too recently or too often", body = ErrorPayload),
+    )
+)]
+pub async fn rotate_secret(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<Json<SecretRotatedPayload>, AppError> {
+    Ok(Json(
+        secret::rotate(&state, &game_code, auth.token()).await?,
+    ))
+}
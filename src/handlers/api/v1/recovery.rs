@@ -0,0 +1,128 @@
+use crate::handlers::api::recovery;
+use crate::{
+    errors::AppError,
+    payloads::{
+        DecideRecoveryPayload, ErrorPayload, PendingRecoveryPayload, RecoverPayload,
+        RecoveryPayload,
+    },
+    state::AppState,
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+
+/// Lost your auth token? Ask the host to let you back in under your name,
+/// then poll the claim token for the outcome. When the host asks, the other
+/// players answer.
+#[utoipa::path(
+    post,
+    path = "/games/{game_code}/recoveries",
+    tag = "recovery",
+    params(("game_code" = String, Path, description = "Code of the game")),
+    request_body = RecoverPayload,
+    responses(
+        (status = CREATED, description = "The request, waiting for the host", body = RecoveryPayload),
+        (status = NOT_FOUND, description = "No such game or player", body = ErrorPayload),
+        (status = CONFLICT, description = "A request for this player is still pending", body = ErrorPayload),
+        (status = TOO_MANY_REQUESTS, description = "The game took too many requests lately", body = ErrorPayload),
+    )
+)]
+pub async fn request_recovery(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    Json(payload): Json<RecoverPayload>,
+) -> Result<(StatusCode, Json<RecoveryPayload>), AppError> {
+    let recovery = recovery::request(&state, &game_code, &payload.player_name).await?;
+    Ok((StatusCode::CREATED, Json(recovery)))
+}
+
+/// Where your request stands; includes your new auth token once the host
+/// approved it.
+#[utoipa::path(
+    get,
+    path = "/games/{game_code}/recovery-claims/{claim_token}",
+    tag = "recovery",
+    params(
+        ("game_code" = String, Path, description = "Code of the game"),
+        ("claim_token" = String, Path, description = "Returned when you asked to recover"),
+    ),
+    responses(
+        (status = OK, body = RecoveryPayload),
+        (status = NOT_FOUND, description = "No such game or request", body = ErrorPayload),
+    )
+)]
+pub async fn claim_recovery(
+    State(state): State<AppState>,
+    Path((game_code, claim_token)): Path<(String, String)>,
+) -> Result<Json<RecoveryPayload>, AppError> {
+    Ok(Json(
+        recovery::claim(&state, &game_code, &claim_token).await?,
+    ))
+}
+
+/// The requests waiting for you to answer, oldest first: everyone else's
+/// for the host, the host's own for the other players.
+#[utoipa::path(
+    get,
+    path = "/games/{game_code}/recoveries",
+    tag = "recovery",
+    params(("game_code" = String, Path, description = "Code of the game")),
+    security(("player" = [])),
+    responses(
+        (status = OK, body = Vec<PendingRecoveryPayload>),
+        (status = FORBIDDEN, description = "Unknown auth token", body = ErrorPayload),
+        (status = NOT_FOUND, description = "No such game", body = ErrorPayload),
+    )
+)]
+pub async fn get_recoveries(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<Json<Vec<PendingRecoveryPayload>>, AppError> {
+    Ok(Json(
+        recovery::pending(&state, &game_code, auth.token()).await?,
+    ))
+}
+
+/// Let a player back in or turn them away. Approving gives them a fresh auth
+/// token; their old one stops working. The host answers everyone else's
+/// requests; the other players answer the host's.
+#[utoipa::path(
+    post,
+    path = "/games/{game_code}/recoveries/{request_id}",
+    tag = "recovery",
+    params(
+        ("game_code" = String, Path, description = "Code of the game"),
+        ("request_id" = i64, Path, description = "The request to answer"),
+    ),
+    request_body = DecideRecoveryPayload,
+    security(("player" = [])),
+    responses(
+        (status = NO_CONTENT, description = "The request is answered"),
+        (status = FORBIDDEN, description = "Not yours to answer, or unknown auth token", body = ErrorPayload),
+        (status = NOT_FOUND, description = "No such game or request", body = ErrorPayload),
+        (status = CONFLICT, description = "The request was already answered", body = ErrorPayload),
+    )
+)]
+pub async fn decide_recovery(
+    State(state): State<AppState>,
+    Path((game_code, request_id)): Path<(String, i64)>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+    Json(payload): Json<DecideRecoveryPayload>,
+) -> Result<StatusCode, AppError> {
+    recovery::decide(
+        &state,
+        &game_code,
+        auth.token(),
+        request_id,
+        payload.approve,
+    )
+    .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,61 @@
+use crate::handlers::api::spectate as scoreboards;
+use crate::{
+    errors::AppError,
+    payloads::{ErrorPayload, SpectatorLinkPayload, SpectatorPayload},
+    state::AppState,
+};
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
+
+/// The public scoreboard: who is still in, the kill feed and when the game
+/// started and ended. Never shows targets or secrets.
+#[utoipa::path(
+    get,
+    path = "/games/{game_code}/spectate",
+    tag = "spectators",
+    params(("game_code" = String, Path, description = "Code of the game")),
+    security(("spectator" = [])),
+    responses(
+        (status = OK, description = "The scoreboard", body = SpectatorPayload),
+        (status = FORBIDDEN, description = "Wrong spectator token", body = ErrorPayload),
+        (status = NOT_FOUND, description = "No such game, or spectating is turned off", body = ErrorPayload),
+    )
+)]
+pub async fn spectate(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<Json<SpectatorPayload>, AppError> {
+    Ok(Json(
+        scoreboards::scoreboard(&state, &game_code, auth.token()).await?,
+    ))
+}
+
+/// The spectator token and page of a game, for its host to share.
+#[utoipa::path(
+    get,
+    path = "/games/{game_code}/spectator-link",
+    tag = "spectators",
+    params(("game_code" = String, Path, description = "Code of the game")),
+    security(("player" = [])),
+    responses(
+        (status = OK, description = "The spectator link", body = SpectatorLinkPayload),
+        (status = FORBIDDEN, description = "Not the host of this game", body = ErrorPayload),
+        (status = NOT_FOUND, description = "No such game, or spectating is turned off", body = ErrorPayload),
+    )
+)]
+pub async fn spectator_link(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+) -> Result<Json<SpectatorLinkPayload>, AppError> {
+    Ok(Json(
+        scoreboards::link(&state, &game_code, auth.token()).await?,
+    ))
+}
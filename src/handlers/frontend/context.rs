@@ -1,3 +1,4 @@
+use crate::models::{ChatChannel, GameSettings};
 use serde::Serialize;
 
 #[derive(Serialize, Default, Clone)]
@@ -14,4 +15,13 @@ pub struct IndexContext {
     pub player_id: Option<i32>,
     pub player_name: Option<String>,
     pub rejoin_link: Option<String>,
+    /// Path of the spectator page, only ever given to the host.
+    pub spectator_link: Option<String>,
+    pub spectator_token: Option<String>,
+    /// The game's settings, for the host to change in the lobby.
+    pub settings: Option<GameSettings>,
+    /// The server's largest lobby, the most the host may allow.
+    pub max_players: Option<u32>,
+    /// The chat channels the player may post in.
+    pub chat_channels: Vec<ChatChannel>,
 }
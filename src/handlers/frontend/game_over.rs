@@ -1,5 +1,5 @@
 use super::context::IndexContext;
-use crate::state::AppState;
+use crate::{i18n::Locale, request_id, state::AppState};
 use axum::{
     extract::{Path, State},
     http::StatusCode,
@@ -10,6 +10,7 @@ use tera::Context;
 pub async fn game_over_page(
     State(state): State<AppState>,
     Path((game_code, auth_token)): Path<(String, String)>,
+    locale: Locale,
 ) -> impl IntoResponse {
     let mut context = Context::new();
     let mut index_context = IndexContext {
@@ -22,6 +23,7 @@ pub async fn game_over_page(
     };
 
     if let Ok(Some(player)) = state.db.get_player_by_auth_token(&auth_token).await {
+        request_id::record_player(player.id);
         if let Ok(Some(game)) = state.db.get_game_by_id(player.game_id).await {
             if game.code == game_code {
                 index_context.game_exists = Some(true);
@@ -40,6 +42,8 @@ pub async fn game_over_page(
     }
 
     context.insert("ctx", &index_context);
+    context.insert("lang", &locale);
+    context.insert("locales", &Locale::ALL);
     match state.tera.render("game_over.tera.html", &context) {
         Ok(s) => Html(s).into_response(),
         Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
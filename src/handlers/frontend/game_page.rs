@@ -1,5 +1,5 @@
 use super::context::IndexContext;
-use crate::state::AppState;
+use crate::{i18n::Locale, state::AppState};
 use axum::http::StatusCode;
 use axum::{
     extract::{Path, State},
@@ -10,13 +10,14 @@ use tera::Context;
 pub async fn game_page(
     State(state): State<AppState>,
     Path(game_code): Path<String>,
+    locale: Locale,
 ) -> impl IntoResponse {
     let mut context = Context::new();
     let (game_exists, game_name, player_count) = match state.db.get_game_by_code(&game_code).await {
         Ok(Some(game)) => {
             let players = state
                 .db
-                .get_players_by_game_id(&*state.db, game.id)
+                .get_players_by_game_id(game.id)
                 .await
                 .unwrap_or_default();
             (
@@ -38,6 +39,8 @@ pub async fn game_page(
         ..Default::default()
     };
     context.insert("ctx", &index_ctx);
+    context.insert("lang", &locale);
+    context.insert("locales", &Locale::ALL);
     match state.tera.render("welcome.tera.html", &context) {
         Ok(s) => Html(s).into_response(),
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
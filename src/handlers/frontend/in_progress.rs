@@ -1,5 +1,6 @@
 use super::context::IndexContext;
-use crate::state::AppState;
+use crate::handlers::api::chat::postable_channels;
+use crate::{i18n::Locale, request_id, state::AppState};
 use axum::{
     extract::{Path, State},
     http::StatusCode,
@@ -10,6 +11,7 @@ use tera::Context;
 pub async fn game_in_progress_page(
     State(state): State<AppState>,
     Path((game_code, auth_token)): Path<(String, String)>,
+    locale: Locale,
 ) -> impl IntoResponse {
     let mut context = Context::new();
 
@@ -23,9 +25,18 @@ pub async fn game_in_progress_page(
     };
 
     if let Ok(Some(player)) = state.db.get_player_by_auth_token(&auth_token).await {
+        request_id::record_player(player.id);
         if let Ok(Some(game)) = state.db.get_game_by_id(player.game_id).await {
             if game.code == game_code {
                 index_context.game_exists = Some(true);
+                let settings = state
+                    .db
+                    .get_game_settings(game.id)
+                    .await
+                    .ok()
+                    .flatten()
+                    .unwrap_or_default();
+                index_context.chat_channels = postable_channels(&game, &settings, &player);
                 index_context.player_id = Some(player.id);
                 index_context.player_name = Some(player.name);
                 index_context.rejoin_link =
@@ -41,6 +52,9 @@ pub async fn game_in_progress_page(
     }
 
     context.insert("ctx", &index_context);
+    context.insert("lang", &locale);
+    context.insert("locales", &Locale::ALL);
+    context.insert("features", &state.config.features);
     match state.tera.render("game.tera.html", &context) {
         Ok(s) => Html(s).into_response(),
         Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
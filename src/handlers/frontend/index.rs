@@ -1,13 +1,37 @@
 use super::context::IndexContext;
-use crate::state::AppState;
-use axum::http::StatusCode;
+use crate::models::GameStatus;
+use crate::remember::{forget, remembered};
+use crate::{i18n::Locale, state::AppState};
+use axum::http::{HeaderMap, StatusCode};
 use axum::{
     extract::State,
-    response::{Html, IntoResponse},
+    response::{Html, IntoResponse, Redirect},
 };
 use tera::Context;
 
-pub async fn index(State(state): State<AppState>) -> impl IntoResponse {
+/// The rejoin link of the player owning `auth_token`, while their game is
+/// not over.
+async fn rejoin_path(state: &AppState, auth_token: &str) -> Option<String> {
+    let player = state.db.get_player_by_auth_token(auth_token).await.ok()??;
+    let game = state.db.get_game_by_id(player.game_id).await.ok()??;
+    (game.status != GameStatus::Finished)
+        .then(|| format!("/game/{}/player/{}", game.code, auth_token))
+}
+
+pub async fn index(
+    State(state): State<AppState>,
+    locale: Locale,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let auth_token = remembered(&headers);
+    if let Some(auth_token) = auth_token {
+        if let Some(path) = rejoin_path(&state, auth_token).await {
+            return Redirect::to(&path).into_response();
+        }
+    }
+    // Whoever was remembered has nothing left to go back to.
+    let cookie = auth_token.map(|_| forget(&state.config));
+
     let mut ctx = Context::new();
     let index_ctx = IndexContext {
         is_game_page: false,
@@ -16,8 +40,10 @@ pub async fn index(State(state): State<AppState>) -> impl IntoResponse {
         ..Default::default()
     };
     ctx.insert("ctx", &index_ctx);
+    ctx.insert("lang", &locale);
+    ctx.insert("locales", &Locale::ALL);
     match state.tera.render("welcome.tera.html", &ctx) {
-        Ok(s) => Html(s).into_response(),
+        Ok(s) => (cookie, Html(s)).into_response(),
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
 }
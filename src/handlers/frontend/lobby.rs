@@ -1,5 +1,7 @@
 use super::context::IndexContext;
-use crate::state::AppState;
+use crate::handlers::api::chat::postable_channels;
+use crate::handlers::api::spectate::spectator_path;
+use crate::{i18n::Locale, request_id, state::AppState};
 use axum::{
     extract::{Path, State},
     http::StatusCode,
@@ -10,6 +12,7 @@ use tera::Context;
 pub async fn lobby_page(
     State(state): State<AppState>,
     Path((game_code, auth_token)): Path<(String, String)>,
+    locale: Locale,
 ) -> impl IntoResponse {
     let mut context = Context::new();
 
@@ -23,13 +26,33 @@ pub async fn lobby_page(
     };
 
     if let Ok(Some(player)) = state.db.get_player_by_auth_token(&auth_token).await {
+        request_id::record_player(player.id);
         if let Ok(Some(game)) = state.db.get_game_by_id(player.game_id).await {
             if game.code == game_code {
                 index_context.game_exists = Some(true);
+                let settings = state
+                    .db
+                    .get_game_settings(game.id)
+                    .await
+                    .ok()
+                    .flatten()
+                    .unwrap_or_default();
+                index_context.chat_channels = postable_channels(&game, &settings, &player);
                 index_context.player_id = Some(player.id);
                 index_context.player_name = Some(player.name);
                 index_context.rejoin_link =
                     Some(format!("/game/{}/player/{}", game_code, auth_token));
+                if game.host_id == Some(player.id) {
+                    if let Ok(settings) = state.db.get_game_settings(game.id).await {
+                        index_context.settings = settings;
+                    }
+                    index_context.max_players = Some(state.config.game.max_players);
+                    if state.config.features.spectators {
+                        if let Ok(Some(token)) = state.db.get_spectator_token(game.id).await {
+                            index_context.spectator_link = Some(spectator_path(&game_code, &token));
+                        }
+                    }
+                }
             } else {
                 index_context.game_exists = Some(false);
             }
@@ -41,6 +64,8 @@ pub async fn lobby_page(
     }
 
     context.insert("ctx", &index_context);
+    context.insert("lang", &locale);
+    context.insert("locales", &Locale::ALL);
 
     match state.tera.render("lobby.tera.html", &context) {
         Ok(s) => Html(s).into_response(),
@@ -6,6 +6,7 @@ pub mod in_progress;
 pub mod index;
 pub mod lobby;
 pub mod rejoin;
+pub mod spectate;
 // Additional page modules would follow similarly (lobby, in_progress, eliminated, game_over)
 
 pub use context::IndexContext;
@@ -16,3 +17,4 @@ pub use in_progress::game_in_progress_page;
 pub use index::index;
 pub use lobby::lobby_page;
 pub use rejoin::rejoin_page;
+pub use spectate::spectate_page;
@@ -1,3 +1,5 @@
+use crate::remember::remember;
+use crate::request_id;
 use crate::state::AppState;
 use axum::response::Redirect;
 use axum::{
@@ -10,6 +12,7 @@ pub async fn rejoin_page(
     Path((game_code, auth_token)): Path<(String, String)>,
 ) -> impl IntoResponse {
     if let Ok(Some(player)) = state.db.get_player_by_auth_token(&auth_token).await {
+        request_id::record_player(player.id);
         if let Ok(Some(game)) = state.db.get_game_by_id(player.game_id).await {
             if game.code == game_code {
                 let url = match game.status.to_string().as_str() {
@@ -24,7 +27,9 @@ pub async fn rejoin_page(
                     "FINISHED" => format!("/game/{}/player/{}/game_over", game.code, auth_token),
                     _ => "/".into(),
                 };
-                return Redirect::to(&url).into_response();
+                // Opening the site again brings the player back here.
+                let cookie = remember(&state.config, &auth_token);
+                return (cookie, Redirect::to(&url)).into_response();
             }
         }
     }
@@ -0,0 +1,46 @@
+use super::context::IndexContext;
+use crate::handlers::api::spectate::scoreboard;
+use crate::{i18n::Locale, state::AppState};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{Html, IntoResponse},
+};
+use serde::Deserialize;
+use tera::Context;
+
+#[derive(Deserialize)]
+pub struct SpectatePageQuery {
+    token: Option<String>,
+}
+
+pub async fn spectate_page(
+    State(state): State<AppState>,
+    Path(game_code): Path<String>,
+    Query(query): Query<SpectatePageQuery>,
+    locale: Locale,
+) -> impl IntoResponse {
+    let mut context = Context::new();
+    let token = query.token;
+    // The page only gets the skeleton; spectate.js fetches the scoreboard.
+    let game_exists = match &token {
+        Some(token) => scoreboard(&state, &game_code, token).await.is_ok(),
+        None => false,
+    };
+    let index_context = IndexContext {
+        page_name: Some("spectate".to_string()),
+        is_game_page: true,
+        game_code: Some(game_code),
+        game_exists: Some(game_exists),
+        spectator_token: token.filter(|_| game_exists),
+        ..Default::default()
+    };
+
+    context.insert("ctx", &index_context);
+    context.insert("lang", &locale);
+    context.insert("locales", &Locale::ALL);
+    match state.tera.render("spectate.tera.html", &context) {
+        Ok(s) => Html(s).into_response(),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    }
+}
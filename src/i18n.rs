@@ -0,0 +1,151 @@
+use crate::utils::cookie;
+use axum::{
+    extract::{FromRequestParts, Request},
+    http::{header, request::Parts, HeaderMap},
+    middleware::Next,
+    response::Response,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::LazyLock;
+use tera::{Tera, Value};
+
+/// Name of the cookie the language picker stores the chosen locale in.
+pub const LOCALE_COOKIE: &str = "lang";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Nl,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::Nl];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Nl => "nl",
+        }
+    }
+
+    /// Match a language tag such as `nl`, `nl-BE` or `EN_gb`.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.trim().split(['-', '_']).next()?.to_lowercase();
+        Locale::ALL.into_iter().find(|l| l.as_str() == primary)
+    }
+
+    /// Pick a locale from the `lang` cookie first, then `Accept-Language`.
+    pub fn negotiate(headers: &HeaderMap) -> Self {
+        let from_cookie = cookie(headers, LOCALE_COOKIE).and_then(Locale::from_tag);
+        if let Some(locale) = from_cookie {
+            return locale;
+        }
+
+        let accept = headers
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        let mut ranges: Vec<(&str, f32)> = accept
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let tag = parts.next()?.trim();
+                let quality = parts
+                    .find_map(|p| p.trim().strip_prefix("q="))
+                    .and_then(|q| q.parse().ok())
+                    .unwrap_or(1.0);
+                Some((tag, quality))
+            })
+            .collect();
+        ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranges
+            .into_iter()
+            .find_map(|(tag, _)| Locale::from_tag(tag))
+            .unwrap_or_default()
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Locale {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Locale::negotiate(&parts.headers))
+    }
+}
+
+static CATALOGUES: LazyLock<HashMap<Locale, HashMap<String, String>>> = LazyLock::new(|| {
+    let parse = |raw: &str| -> HashMap<String, String> {
+        serde_json::from_str(raw).expect("locale catalogue must be a flat JSON object")
+    };
+    HashMap::from([
+        (Locale::En, parse(include_str!("../locales/en.json"))),
+        (Locale::Nl, parse(include_str!("../locales/nl.json"))),
+    ])
+});
+
+/// Look up `key` in the catalogue for `locale`, falling back to English.
+pub fn lookup(locale: Locale, key: &str) -> Option<&'static str> {
+    CATALOGUES
+        .get(&locale)
+        .and_then(|c| c.get(key))
+        .or_else(|| CATALOGUES.get(&Locale::En).and_then(|c| c.get(key)))
+        .map(String::as_str)
+}
+
+/// Translate `key`, replacing `{name}` placeholders. Unknown keys render as
+/// the key itself so missing translations are easy to spot.
+pub fn translate(locale: Locale, key: &str, params: &[(&str, String)]) -> String {
+    let mut message = lookup(locale, key).unwrap_or(key).to_string();
+    for (name, value) in params {
+        message = message.replace(&format!("{{{name}}}"), value);
+    }
+    message
+}
+
+tokio::task_local! {
+    static CURRENT_LOCALE: Locale;
+}
+
+/// Locale of the request currently being handled, used where no extractor is
+/// available (e.g. when an `AppError` turns itself into a response).
+pub fn current_locale() -> Locale {
+    CURRENT_LOCALE.try_with(|l| *l).unwrap_or_default()
+}
+
+/// Middleware that negotiates the locale once and scopes it to the request.
+pub async fn locale_middleware(req: Request, next: Next) -> Response {
+    let locale = Locale::negotiate(req.headers());
+    CURRENT_LOCALE.scope(locale, next.run(req)).await
+}
+
+/// Register the `t` function: `{{ t(key="lobby.title", lang=lang) }}`.
+/// Any extra arguments fill placeholders of the same name.
+pub fn register_tera_functions(tera: &mut Tera) {
+    tera.register_function("t", |args: &HashMap<String, Value>| {
+        let key = args
+            .get("key")
+            .and_then(Value::as_str)
+            .ok_or_else(|| tera::Error::msg("t() requires a `key` argument"))?;
+        let locale = args
+            .get("lang")
+            .and_then(Value::as_str)
+            .and_then(Locale::from_tag)
+            .unwrap_or_default();
+        let params: Vec<(&str, String)> = args
+            .iter()
+            .filter(|(name, _)| name.as_str() != "key" && name.as_str() != "lang")
+            .map(|(name, value)| {
+                let value = match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                (name.as_str(), value)
+            })
+            .collect();
+        Ok(Value::String(translate(locale, key, &params)))
+    });
+}
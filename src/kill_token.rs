@@ -0,0 +1,148 @@
+use crate::config::KillTokenConfig;
+use crate::errors::{AppError, ErrorCode};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use hmac::{Hmac, Mac};
+use rand::Rng as _;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Prefix that tells a signed kill token apart from a typed secret code.
+pub const TOKEN_PREFIX: &str = "hk1.";
+
+/// What a signed kill token vouches for: "this player's QR code was on their
+/// screen in this game until `exp`".
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KillTokenClaims {
+    pub game: String,
+    pub victim: i32,
+    /// Number of times the victim had rotated their secret when the token was
+    /// issued; rotating again invalidates every older token.
+    pub generation: i32,
+    pub exp: i64,
+    pub nonce: String,
+}
+
+/// The proof a hunter presents when claiming a kill.
+#[derive(Debug, Clone)]
+pub enum KillProof {
+    Secret(String),
+    Token(KillTokenClaims),
+}
+
+/// Issues and verifies HMAC-signed kill tokens.
+///
+/// The first key signs new tokens; every key is accepted when verifying so
+/// keys can be rotated without invalidating the QR codes currently on screen.
+#[derive(Clone)]
+pub struct KillTokenSigner {
+    keys: Arc<Vec<Vec<u8>>>,
+    ttl_secs: i64,
+}
+
+impl KillTokenSigner {
+    pub fn new(keys: Vec<Vec<u8>>, ttl_secs: i64) -> Self {
+        assert!(!keys.is_empty(), "at least one kill token key is required");
+        Self {
+            keys: Arc::new(keys),
+            ttl_secs,
+        }
+    }
+
+    /// Build a signer from the configured keys (newest first). Without keys a
+    /// random one is used, which means tokens do not survive a restart.
+    pub fn from_config(config: &KillTokenConfig) -> Self {
+        let keys: Vec<Vec<u8>> = config.keys.iter().map(|k| k.as_bytes().to_vec()).collect();
+        if keys.is_empty() {
+            tracing::warn!("KILL_TOKEN_KEYS not set, using a random signing key");
+            let mut key = vec![0u8; 32];
+            rand::rng().fill(&mut key[..]);
+            return Self::new(vec![key], config.ttl_secs);
+        }
+        Self::new(keys, config.ttl_secs)
+    }
+
+    pub fn ttl_secs(&self) -> i64 {
+        self.ttl_secs
+    }
+
+    /// Issue a fresh token for `victim` in `game_code`.
+    pub fn issue(
+        &self,
+        game_code: &str,
+        victim: i32,
+        generation: i32,
+    ) -> (String, KillTokenClaims) {
+        let claims = KillTokenClaims {
+            game: game_code.to_string(),
+            victim,
+            generation,
+            exp: unix_now() + self.ttl_secs,
+            nonce: Uuid::new_v4().simple().to_string(),
+        };
+        let payload =
+            URL_SAFE_NO_PAD.encode(serde_json::to_vec(&claims).expect("claims always serialise"));
+        let signature = URL_SAFE_NO_PAD.encode(self.sign(&self.keys[0], &payload));
+        (format!("{TOKEN_PREFIX}{payload}.{signature}"), claims)
+    }
+
+    /// Check the signature and expiry of a token and return its claims.
+    pub fn verify(&self, token: &str) -> Result<KillTokenClaims, AppError> {
+        let invalid =
+            || AppError::Forbidden(ErrorCode::InvalidKillToken, "error.INVALID_KILL_TOKEN");
+
+        let (payload, signature) = token
+            .strip_prefix(TOKEN_PREFIX)
+            .and_then(|rest| rest.split_once('.'))
+            .ok_or_else(invalid)?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| invalid())?;
+        let signed_by_us = self.keys.iter().any(|key| {
+            let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts any key length");
+            mac.update(payload.as_bytes());
+            mac.verify_slice(&signature).is_ok()
+        });
+        if !signed_by_us {
+            return Err(invalid());
+        }
+
+        let claims: KillTokenClaims = URL_SAFE_NO_PAD
+            .decode(payload)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(invalid)?;
+        if claims.exp < unix_now() {
+            return Err(AppError::Forbidden(
+                ErrorCode::KillTokenExpired,
+                "error.KILL_TOKEN_EXPIRED",
+            ));
+        }
+        Ok(claims)
+    }
+
+    /// Turn whatever the hunter submitted into a kill proof.
+    pub fn parse_proof(&self, submitted: &str) -> Result<KillProof, AppError> {
+        let submitted = submitted.trim();
+        if submitted.starts_with(TOKEN_PREFIX) {
+            Ok(KillProof::Token(self.verify(submitted)?))
+        } else {
+            Ok(KillProof::Secret(submitted.to_string()))
+        }
+    }
+
+    fn sign(&self, key: &[u8], payload: &str) -> Vec<u8> {
+        let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts any key length");
+        mac.update(payload.as_bytes());
+        mac.finalize().into_bytes().to_vec()
+    }
+}
+
+pub fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock before 1970")
+        .as_secs() as i64
+}
@@ -2,16 +2,27 @@ use axum::{
     routing::{get, post},
     Router,
 };
+use std::sync::Arc;
 use tower_http::services::ServeDir;
 use tower_http::trace::TraceLayer;
-use uuid::Uuid;
 
+pub mod config;
 pub mod db;
+pub mod engine;
 pub mod errors;
 pub mod handlers;
+pub mod i18n;
+pub mod kill_token;
+pub mod metrics;
 pub mod models;
 pub mod payloads;
+pub mod remember;
+pub mod repository;
+pub mod request_id;
+pub mod retention;
+pub mod security;
 pub mod state;
+pub mod telemetry;
 pub mod utils;
 
 use handlers::api;
@@ -22,6 +33,7 @@ pub fn create_router(app_state: AppState) -> axum::Router {
     tracing::info!("Creating router");
     let mut static_path = dotenvy::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string());
     static_path.push_str("/static");
+    let server_config = Arc::new(app_state.config.server.clone());
 
     Router::new()
         // Frontend
@@ -47,26 +59,105 @@ pub fn create_router(app_state: AppState) -> axum::Router {
             "/game/{game_code}/player/{auth_token}/game_over",
             get(fh::game_over_page),
         )
+        .route("/game/{game_code}/spectate", get(fh::spectate_page))
+        // Probes
+        .route("/healthz", get(api::healthz))
+        .route("/readyz", get(api::readyz))
+        .route("/metrics", get(api::metrics))
         // API
         .route("/api/game/{game_code}/changed", get(api::check_for_changes))
-        .nest_service("/static", ServeDir::new(static_path))
         .route("/api/game", post(api::create_game))
         .route("/api/game/{game_code}", get(api::get_game_state))
         .route("/api/game/{game_code}/join", post(api::join_game))
         .route("/api/game/{game_code}/start", post(api::start_game))
+        .route("/api/game/{game_code}/ready", post(api::set_ready))
         .route("/api/game/{game_code}/eliminate", post(api::kill_handler))
         .route("/api/game/{game_code}/leave", post(api::leave_game))
+        .route(
+            "/api/game/{game_code}/kill-token",
+            get(api::issue_kill_token),
+        )
+        .route(
+            "/api/game/{game_code}/secret/rotate",
+            post(api::rotate_secret),
+        )
+        .route("/api/game/{game_code}/activity", get(api::get_activity))
+        .route("/api/game/{game_code}/settings", post(api::update_settings))
+        .route("/api/game/{game_code}/spectate", get(api::spectate))
+        .route(
+            "/api/game/{game_code}/spectator-link",
+            get(api::spectator_link),
+        )
+        .route("/api/game/{game_code}/ghost", get(api::get_ghost))
+        .route(
+            "/api/game/{game_code}/ghost/disputes",
+            post(api::open_dispute),
+        )
+        .route(
+            "/api/game/{game_code}/ghost/disputes/{dispute_id}/votes",
+            post(api::vote_on_dispute),
+        )
+        .route("/api/game/{game_code}/ghost/hint", post(api::send_hint))
+        .route("/api/game/{game_code}/hints", get(api::get_hints))
+        .route(
+            "/api/game/{game_code}/chat",
+            get(api::get_chat).post(api::post_message),
+        )
+        .route("/api/game/{game_code}/recover", post(api::request_recovery))
+        .route(
+            "/api/game/{game_code}/recover/{claim_token}",
+            get(api::claim_recovery),
+        )
+        .route("/api/game/{game_code}/recoveries", get(api::get_recoveries))
+        .route(
+            "/api/game/{game_code}/recoveries/{request_id}",
+            post(api::decide_recovery),
+        )
+        .route(
+            "/api/game/{game_code}/join/{claim_token}",
+            get(api::claim_join_request),
+        )
+        .route(
+            "/api/game/{game_code}/join-requests",
+            get(api::get_join_requests),
+        )
+        .route(
+            "/api/game/{game_code}/join-requests/{request_id}",
+            post(api::decide_join_request),
+        )
+        .route("/api/admin/game/{game_code}/ring", post(api::check_ring))
+        .merge(api::v1::routes())
+        .route_layer(axum::middleware::from_fn(
+            request_id::span_fields_middleware,
+        ))
+        .route_layer(axum::middleware::from_fn(metrics::track_requests))
+        .nest_service("/static", ServeDir::new(static_path))
         .with_state(app_state)
+        .layer(axum::middleware::from_fn(i18n::locale_middleware))
+        .layer(security::cors_layer(&server_config))
+        .layer(axum::middleware::from_fn_with_state(
+            server_config,
+            security::security_headers,
+        ))
         .layer(
             TraceLayer::new_for_http().make_span_with(|request: &axum::http::Request<_>| {
-                let request_id = Uuid::new_v4();
-                tracing::info_span!(
+                let request_id = request
+                    .extensions()
+                    .get::<request_id::RequestId>()
+                    .map(|id| id.0.as_str())
+                    .unwrap_or_default();
+                let span = tracing::info_span!(
                     "request",
                     method = %request.method(),
                     uri = %request.uri(),
                     version = ?request.version(),
                     request_id = %request_id,
-                )
+                    game_code = tracing::field::Empty,
+                    player_id = tracing::field::Empty,
+                );
+                telemetry::continue_trace(&span, request.headers());
+                span
             }),
         )
+        .layer(axum::middleware::from_fn(request_id::request_id_middleware))
 }
@@ -1,8 +1,18 @@
 use dashmap::DashMap;
-use hitman::{create_router, db::Db, state::AppState};
+use hitman::{
+    config::{Config, LogFormat},
+    create_router,
+    kill_token::KillTokenSigner,
+    metrics::Metrics,
+    state::AppState,
+};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
 use std::sync::Arc;
+use std::time::Duration;
 use tera::Tera;
-use tower_http::cors::{Any, CorsLayer};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::Notify;
 use tracing_subscriber::prelude::*;
 
 #[tokio::main]
@@ -10,35 +20,152 @@ async fn main() {
     dotenvy::dotenv().ok();
     sqlx::any::install_default_drivers();
 
+    let config = Config::load().unwrap_or_else(|err| {
+        eprintln!("Invalid configuration: {err}");
+        std::process::exit(2);
+    });
+
+    let filter = tracing_subscriber::EnvFilter::new(
+        std::env::var("RUST_LOG").unwrap_or_else(|_| "info".into()),
+    );
+    let fmt = tracing_subscriber::fmt::layer();
+    // One JSON object per line. The span list keeps the request's fields
+    // (request id, game code, player id) on events from nested spans too.
+    let fmt = match config.log.format {
+        LogFormat::Text => fmt.boxed(),
+        LogFormat::Json => fmt
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(true)
+            .boxed(),
+    };
+    let tracer_provider =
+        hitman::telemetry::tracer_provider(&config.telemetry).unwrap_or_else(|err| {
+            eprintln!("Cannot set up trace export: {err}");
+            std::process::exit(2);
+        });
     tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::new(
-            std::env::var("RUST_LOG").unwrap_or_else(|_| "info".into()),
-        ))
-        .with(tracing_subscriber::fmt::layer())
+        .with(filter)
+        .with(fmt)
+        .with(tracer_provider.as_ref().map(hitman::telemetry::layer))
         .init();
+    if std::env::args().nth(1).as_deref() == Some("healthcheck") {
+        std::process::exit(healthcheck(config.server.bind).await);
+    }
 
-    let db = Db::new().await.expect("Failed to create database pool");
+    let db = hitman::repository::connect(&config.database)
+        .await
+        .expect("Failed to open the database");
     let mut template_path = dotenvy::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string());
     template_path.push_str("/templates/**/*");
 
-    let tera = Tera::new(&template_path).expect("Failed to create Tera instance");
+    let mut tera = Tera::new(&template_path).expect("Failed to create Tera instance");
+    hitman::i18n::register_tera_functions(&mut tera);
 
     let app_state = AppState {
         db,
         tera,
         versions: Arc::new(DashMap::new()),
+        kill_tokens: KillTokenSigner::from_config(&config.kill_tokens),
+        config: Arc::new(config),
+        metrics: Metrics::new(),
     };
+    tokio::spawn(hitman::retention::run_purge_task(app_state.clone()));
 
-    let app = create_router(app_state).layer(
-        CorsLayer::new()
-            .allow_origin(Any)
-            .allow_methods(Any)
-            .allow_headers(Any),
-    );
+    let addr = app_state.config.server.bind;
+    let shutdown_timeout = Duration::from_secs(app_state.config.server.shutdown_timeout_secs);
+    let db = app_state.db.clone();
+    let app = create_router(app_state);
 
-    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], 3000));
     tracing::info!("Server listening on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    let stopping = Arc::new(Notify::new());
+    let server = axum::serve(listener, app).with_graceful_shutdown({
+        let stopping = stopping.clone();
+        async move {
+            shutdown_signal().await;
+            tracing::info!("Shutting down, waiting for in-flight requests");
+            stopping.notify_one();
+        }
+    });
+    tokio::select! {
+        result = server => result.unwrap(),
+        _ = async {
+            stopping.notified().await;
+            tokio::time::sleep(shutdown_timeout).await;
+        } => {
+            tracing::warn!(
+                "Requests still running after {}s, exiting anyway",
+                shutdown_timeout.as_secs()
+            );
+        }
+    }
+    db.close().await;
+    tracing::info!("Server stopped");
+    if let Some(provider) = tracer_provider {
+        // Flushes the last batch with a blocking HTTP client.
+        let _ = tokio::task::spawn_blocking(move || provider.shutdown()).await;
+    }
+}
+
+/// Resolves on Ctrl+C, or on SIGTERM from `docker stop` and friends.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+/// `hitman healthcheck`: ask the running server whether it is ready and exit
+/// with 0 if so. The runtime image has no curl, so container healthchecks
+/// run this instead.
+async fn healthcheck(bind: SocketAddr) -> i32 {
+    let mut addr = bind;
+    if addr.ip().is_unspecified() {
+        addr.set_ip(match addr {
+            SocketAddr::V4(_) => Ipv4Addr::LOCALHOST.into(),
+            SocketAddr::V6(_) => Ipv6Addr::LOCALHOST.into(),
+        });
+    }
+    let probe = async {
+        let mut stream = TcpStream::connect(addr).await?;
+        stream
+            .write_all(b"GET /readyz HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await?;
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).await?;
+        Ok::<_, std::io::Error>(String::from_utf8_lossy(&response).into_owned())
+    };
+    match tokio::time::timeout(Duration::from_secs(5), probe).await {
+        Ok(Ok(response)) if response.starts_with("HTTP/1.1 200") => 0,
+        Ok(Ok(response)) => {
+            eprintln!("not ready: {}", response.lines().next().unwrap_or(""));
+            1
+        }
+        Ok(Err(err)) => {
+            eprintln!("cannot reach {addr}: {err}");
+            1
+        }
+        Err(_) => {
+            eprintln!("no answer from {addr} within 5s");
+            1
+        }
+    }
 }
@@ -0,0 +1,224 @@
+//! Prometheus metrics, scraped from `/metrics`.
+//!
+//! Counters and the request latency histogram are recorded as things happen.
+//! Gauges describing the current state (games, the connection pool, who is
+//! online) are refreshed on every scrape instead, so they can never drift.
+
+use crate::errors::AppError;
+use crate::models::{GameInfo, GameStatus};
+use crate::repository::PoolStats;
+use ::metrics::{counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram};
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use dashmap::DashMap;
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+const REQUEST_DURATION: &str = "http_request_duration_seconds";
+const REQUEST_DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// A player counts as online for this long after their last authenticated
+/// API request.
+const ONLINE_WINDOW: Duration = Duration::from_secs(60);
+/// Clients poll `/changed` every two seconds; one that has not polled for
+/// this long has closed the page.
+const SUBSCRIBER_WINDOW: Duration = Duration::from_secs(10);
+/// Change polls are not authenticated, so neither map may grow past this.
+/// Once it is reached, stale entries are dropped at most once per window and
+/// new ones are ignored in between.
+const MAX_TRACKED: usize = 10_000;
+
+#[derive(Clone)]
+pub struct Metrics {
+    handle: PrometheusHandle,
+    /// Player ids, by the time of the player's last authenticated request.
+    online: Arc<Presence<i32>>,
+    /// Auth tokens, by the time of the client's last change poll.
+    subscribers: Arc<Presence<String>>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Metrics {
+            handle: recorder().clone(),
+            online: Arc::new(Presence::new(ONLINE_WINDOW)),
+            subscribers: Arc::new(Presence::new(SUBSCRIBER_WINDOW)),
+        }
+    }
+
+    /// A player's auth token was accepted.
+    pub fn player_active(&self, player_id: i32) {
+        self.online.touch(player_id);
+    }
+
+    /// A client polled for changes to a game.
+    pub fn subscribed(&self, auth_token: &str) {
+        self.subscribers.touch(auth_token.to_string());
+    }
+
+    pub fn record_games(&self, games: &[GameInfo]) {
+        for status in [
+            GameStatus::Lobby,
+            GameStatus::InProgress,
+            GameStatus::Finished,
+        ] {
+            let count = games.iter().filter(|g| g.status == status).count();
+            gauge!("hitman_games", "status" => status.to_string()).set(count as f64);
+        }
+    }
+
+    pub fn record_pool(&self, pool: PoolStats) {
+        let in_use = pool.size.saturating_sub(pool.idle);
+        gauge!("hitman_db_pool_connections", "state" => "in_use").set(in_use);
+        gauge!("hitman_db_pool_connections", "state" => "idle").set(pool.idle);
+        gauge!("hitman_db_pool_max_connections").set(pool.max);
+    }
+
+    /// Refresh the presence gauges and render everything in the Prometheus
+    /// text format.
+    pub fn render(&self) -> String {
+        gauge!("hitman_players_online").set(self.online.count() as f64);
+        gauge!("hitman_change_subscribers").set(self.subscribers.count() as f64);
+        self.handle.render()
+    }
+}
+
+/// Who was seen within the last `window`, up to [`MAX_TRACKED`] of them.
+struct Presence<K> {
+    seen: DashMap<K, Instant>,
+    window: Duration,
+    /// When a full map was last swept for stale entries.
+    swept: Mutex<Option<Instant>>,
+}
+
+impl<K: Eq + Hash> Presence<K> {
+    fn new(window: Duration) -> Self {
+        Presence {
+            seen: DashMap::new(),
+            window,
+            swept: Mutex::new(None),
+        }
+    }
+
+    fn touch(&self, key: K) {
+        if self.seen.contains_key(&key) || self.has_room() {
+            self.seen.insert(key, Instant::now());
+        }
+    }
+
+    /// Whether a new entry fits. A full map is swept at most once per
+    /// window, so a flood of new keys cannot make every request pay for it.
+    fn has_room(&self) -> bool {
+        if self.seen.len() < MAX_TRACKED {
+            return true;
+        }
+        let Ok(mut swept) = self.swept.try_lock() else {
+            return false;
+        };
+        if swept.is_some_and(|at| at.elapsed() < self.window) {
+            return false;
+        }
+        *swept = Some(Instant::now());
+        self.count() < MAX_TRACKED
+    }
+
+    /// Drop stale entries and count the rest.
+    fn count(&self) -> usize {
+        self.seen.retain(|_, at| at.elapsed() < self.window);
+        self.seen.len()
+    }
+}
+
+/// The recorder is process-wide, so every `Metrics` (one per router, several
+/// in tests) shares the one installed on first use.
+fn recorder() -> &'static PrometheusHandle {
+    static HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+    HANDLE.get_or_init(|| {
+        let handle = PrometheusBuilder::new()
+            .set_buckets_for_metric(
+                Matcher::Full(REQUEST_DURATION.to_string()),
+                REQUEST_DURATION_BUCKETS,
+            )
+            .expect("buckets are not empty")
+            .install_recorder()
+            .expect("no other metrics recorder is installed");
+        describe();
+        handle
+    })
+}
+
+fn describe() {
+    describe_histogram!(
+        REQUEST_DURATION,
+        ::metrics::Unit::Seconds,
+        "Time to answer a request, by route"
+    );
+    describe_gauge!("hitman_games", "Games by status");
+    describe_gauge!(
+        "hitman_db_pool_connections",
+        "Database connections, in use or idle"
+    );
+    describe_gauge!(
+        "hitman_db_pool_max_connections",
+        "Size limit of the database pool"
+    );
+    describe_gauge!(
+        "hitman_players_online",
+        "Players that made an authenticated request in the last minute"
+    );
+    describe_gauge!(
+        "hitman_change_subscribers",
+        "Clients currently polling for game changes"
+    );
+    describe_counter!(
+        "hitman_kills_total",
+        "Successful kills; rate() gives kills per minute"
+    );
+    describe_counter!(
+        "hitman_kill_failures_total",
+        "Rejected kills, by error code"
+    );
+}
+
+pub fn record_kill() {
+    counter!("hitman_kills_total").increment(1);
+}
+
+pub fn record_failed_kill(err: &AppError) {
+    counter!("hitman_kill_failures_total", "code" => err.code().as_str()).increment(1);
+}
+
+/// Time every routed request. Installed with `route_layer`, so the route
+/// template is known and unmatched paths cannot blow up the label set.
+pub async fn track_requests(request: Request, next: Next) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_default();
+    let method = request.method().to_string();
+
+    let started = Instant::now();
+    let response = next.run(request).await;
+    histogram!(
+        REQUEST_DURATION,
+        "method" => method,
+        "route" => route,
+        "status" => response.status().as_u16().to_string(),
+    )
+    .record(started.elapsed());
+    response
+}
@@ -1,24 +1,7 @@
+pub use hitman_types::{
+    ChatChannel, DisputeStatus, GameEventKind, GameSettings, GameStatus, PlayerRole, RequestStatus,
+};
 use serde::{Deserialize, Serialize};
-use std::fmt::Display;
-
-#[derive(Debug, Clone, sqlx::Type, Serialize, Deserialize, PartialEq)]
-#[sqlx(type_name = "game_status", rename_all = "lowercase")]
-pub enum GameStatus {
-    Lobby,
-    #[sqlx(rename = "in_progress")]
-    InProgress,
-    Finished,
-}
-
-impl Display for GameStatus {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            GameStatus::Lobby => write!(f, "LOBBY"),
-            GameStatus::InProgress => write!(f, "IN_PROGRESS"),
-            GameStatus::Finished => write!(f, "FINISHED"),
-        }
-    }
-}
 
 #[derive(Debug, Clone, sqlx::FromRow, Serialize, Deserialize, PartialEq)]
 pub struct Player {
@@ -29,6 +12,8 @@ pub struct Player {
     #[serde(skip)]
     pub auth_token: String,
     pub is_alive: bool,
+    #[serde(default)]
+    pub is_ready: bool,
     #[serde(skip)]
     pub target_id: Option<i32>,
     #[serde(skip)]
@@ -51,3 +36,124 @@ pub struct GameInfo {
     pub status: GameStatus,
     pub player_count: i64,
 }
+
+/// An entry of a game's event log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameEvent {
+    pub id: i64,
+    pub game_id: i32,
+    pub kind: GameEventKind,
+    pub actor_id: Option<i32>,
+    pub actor_name: Option<String>,
+    pub subject_id: Option<i32>,
+    pub subject_name: Option<String>,
+    /// Unix seconds.
+    pub created_at: i64,
+}
+
+/// An event about to be recorded; see [`GameEvent`] for the fields.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewGameEvent {
+    pub kind: GameEventKind,
+    pub actor_id: Option<i32>,
+    pub actor_name: Option<String>,
+    pub subject_id: Option<i32>,
+    pub subject_name: Option<String>,
+}
+
+impl NewGameEvent {
+    pub fn new(kind: GameEventKind) -> Self {
+        NewGameEvent {
+            kind,
+            actor_id: None,
+            actor_name: None,
+            subject_id: None,
+            subject_name: None,
+        }
+    }
+
+    /// Who did it.
+    pub fn by(mut self, id: Option<i32>, name: impl Into<String>) -> Self {
+        self.actor_id = id;
+        self.actor_name = Some(name.into());
+        self
+    }
+
+    /// Who it was done to.
+    pub fn to(mut self, id: Option<i32>, name: impl Into<String>) -> Self {
+        self.subject_id = id;
+        self.subject_name = Some(name.into());
+        self
+    }
+}
+
+/// A kill its victim asked the ghosts to overturn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KillDispute {
+    pub id: i64,
+    pub game_id: i32,
+    /// The kill in the game's event log.
+    pub event_id: i64,
+    pub victim_id: i32,
+    pub killer_id: Option<i32>,
+    pub status: DisputeStatus,
+    /// `(player_id, overturn)` of every vote cast so far.
+    pub votes: Vec<(i32, bool)>,
+}
+
+/// A ghost's one anonymous message to a living player.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GhostHint {
+    pub id: i64,
+    pub game_id: i32,
+    pub sender_id: i32,
+    pub recipient_id: i32,
+    pub message: String,
+    /// Unix seconds.
+    pub created_at: i64,
+}
+
+/// A message in one of a game's chat channels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatMessage {
+    pub id: i64,
+    pub game_id: i32,
+    pub channel: ChatChannel,
+    /// `None` once the sender left the game.
+    pub sender_id: Option<i32>,
+    pub sender_name: String,
+    pub body: String,
+    /// Unix seconds.
+    pub created_at: i64,
+}
+
+/// A newcomer asking the host to let them into a game that already runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JoinRequest {
+    pub id: i64,
+    pub game_id: i32,
+    pub player_name: String,
+    /// Known only to whoever made the request; they poll the outcome with it.
+    pub claim_token: String,
+    pub status: RequestStatus,
+    /// The newcomer's auth token once approved.
+    pub auth_token: Option<String>,
+    /// Unix seconds.
+    pub created_at: i64,
+}
+
+/// A player asking the host to let them back into their game after losing
+/// their link.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecoveryRequest {
+    pub id: i64,
+    pub game_id: i32,
+    pub player_id: i32,
+    /// Known only to whoever made the request; they poll the outcome with it.
+    pub claim_token: String,
+    pub status: RequestStatus,
+    /// The player's new auth token once approved.
+    pub auth_token: Option<String>,
+    /// Unix seconds.
+    pub created_at: i64,
+}
@@ -1,22 +1,49 @@
+use crate::engine::RingViolation;
 use crate::models::{Game, Player};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-// --- Client-to-Server Payloads ---
+// The versioned API's payloads live in `hitman-types` so clients share them.
+pub use hitman_types::{
+    ActivityPayload, ChangesPayload, ChatMessagePayload, CreateGamePayload, DecideJoinPayload,
+    DecideRecoveryPayload, DisputePayload, DisputeVotePayload, ErrorPayload, FinishedGamePayload,
+    GameEventPayload, GamePayload, GhostPayload, HealthPayload, HintPayload, JoinGamePayload,
+    JoinRequestPayload, KillPayload, KillResponsePayload, KillTokenPayload, OpenDisputePayload,
+    PendingJoinPayload, PendingRecoveryPayload, PlayerPayload, PlayerSessionPayload,
+    PostMessagePayload, PurgeGamesPayload, PurgedGamesPayload, ReadyPayload, RecoverPayload,
+    RecoveryPayload, SecretRotatedPayload, SendHintPayload, SpectatedPlayerPayload,
+    SpectatorLinkPayload, SpectatorPayload,
+};
 
-#[derive(Debug, Deserialize, Serialize)]
-pub struct CreateGamePayload {
-    pub player_name: String,
+/// `player` as shown to the player with id `viewer`: only their own secret
+/// code and target are included.
+pub fn player_seen_by(player: Player, viewer: i32) -> PlayerPayload {
+    let own = player.id == viewer;
+    PlayerPayload {
+        secret_code: own.then_some(player.secret_code),
+        target_name: player.target_name.filter(|_| own),
+        id: player.id,
+        name: player.name,
+        is_alive: player.is_alive,
+        is_ready: player.is_ready,
+    }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-pub struct JoinGamePayload {
-    pub player_name: String,
+/// `players` with everyone's target but `viewer`'s left out, for the
+/// unversioned endpoints that return [`Player`]s.
+pub fn hide_targets(players: Vec<Player>, viewer: i32) -> Vec<Player> {
+    players
+        .into_iter()
+        .map(|p| Player {
+            target_name: p.target_name.filter(|_| p.id == viewer),
+            ..p
+        })
+        .collect()
 }
 
-// --- Server-to-Client Payloads ---
-
+/// Returned by the unversioned create and join endpoints.
 #[derive(Debug, Serialize, Deserialize)]
-pub struct GameCreatedPayload {
+pub struct GameSessionPayload {
     pub game_code: String,
     pub player_id: i32,
     pub player_secret: String,
@@ -26,21 +53,13 @@ pub struct GameCreatedPayload {
     pub version: i64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct GameJoinedPayload {
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RingCheckPayload {
     pub game_code: String,
-    pub player_id: i32,
-    pub player_secret: String,
-    pub auth_token: String,
-    pub players: Vec<Player>,
-    pub game: Game,
-    pub version: i64,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct KillResponsePayload {
-    pub eliminated_player_name: String,
-    pub killer_name: String,
-    pub new_target_name: Option<String>,
-    pub game_over: bool,
+    pub healthy: bool,
+    pub violations: Vec<RingViolation>,
+    /// `[player_id, new_target_id]` for every target that was changed.
+    #[schema(value_type = Vec<Vec<Option<i32>>>)]
+    pub reassigned: Vec<(i32, Option<i32>)>,
+    pub winner_id: Option<i32>,
 }
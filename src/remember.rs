@@ -0,0 +1,48 @@
+//! "Remember me": a cookie with the auth token of the player last seen on a
+//! browser, so opening the site again takes them straight back to their game.
+
+use crate::config::Config;
+use crate::utils::cookie;
+use axum::http::{header, HeaderMap, HeaderName};
+use axum::response::AppendHeaders;
+
+/// Name of the cookie holding the remembered auth token.
+pub const PLAYER_COOKIE: &str = "player";
+
+/// How long a player is remembered on a server that keeps games forever.
+const FOREVER_MAX_AGE_SECS: u64 = 30 * 24 * 3600;
+
+pub type SetCookie = AppendHeaders<[(HeaderName, String); 1]>;
+
+/// Remember the player owning `auth_token` for as long as their game is kept.
+pub fn remember(config: &Config, auth_token: &str) -> SetCookie {
+    let max_age = config
+        .retention
+        .game_ttl()
+        .map_or(FOREVER_MAX_AGE_SECS, |ttl| ttl.as_secs());
+    set_cookie(config, auth_token, max_age)
+}
+
+/// Stop remembering whoever was remembered.
+pub fn forget(config: &Config) -> SetCookie {
+    set_cookie(config, "", 0)
+}
+
+fn set_cookie(config: &Config, value: &str, max_age: u64) -> SetCookie {
+    let secure = if config.server.behind_tls {
+        "; Secure"
+    } else {
+        ""
+    };
+    AppendHeaders([(
+        header::SET_COOKIE,
+        format!(
+            "{PLAYER_COOKIE}={value}; Path=/; Max-Age={max_age}; HttpOnly; SameSite=Lax{secure}"
+        ),
+    )])
+}
+
+/// The auth token remembered on the browser that sent `headers`.
+pub fn remembered(headers: &HeaderMap) -> Option<&str> {
+    cookie(headers, PLAYER_COOKIE).filter(|token| !token.is_empty())
+}
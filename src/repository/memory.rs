@@ -0,0 +1,1012 @@
+use super::{ring_must_hold, GameRepository, KillOutcome, NewPlayer};
+use crate::engine::{self, RingPlan, RingRepair, RingViolation};
+use crate::errors::{AppError, ErrorCode};
+use crate::kill_token::{unix_now, KillProof};
+use crate::models::{
+    ChatChannel, ChatMessage, DisputeStatus, Game, GameEvent, GameEventKind, GameInfo,
+    GameSettings, GameStatus, GhostHint, JoinRequest, KillDispute, NewGameEvent, Player,
+    RecoveryRequest, RequestStatus,
+};
+use crate::utils::{generate_code, normalise_name};
+use async_trait::async_trait;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Keeps every game in a single process-local map. Nothing survives a
+/// restart; meant for tests and trying the game without a database.
+#[derive(Default)]
+pub struct MemoryRepository {
+    store: Mutex<Store>,
+}
+
+struct PlayerRow {
+    player: Player,
+    secret_rotations: i32,
+    secret_rotated_at: Option<Instant>,
+}
+
+#[derive(Default)]
+struct Store {
+    games: BTreeMap<i32, Game>,
+    game_created_at: HashMap<i32, Instant>,
+    spectator_tokens: HashMap<i32, String>,
+    settings: HashMap<i32, GameSettings>,
+    events: Vec<GameEvent>,
+    disputes: Vec<KillDispute>,
+    hints: Vec<GhostHint>,
+    messages: Vec<ChatMessage>,
+    recoveries: Vec<RecoveryRequest>,
+    join_requests: Vec<JoinRequest>,
+    players: BTreeMap<i32, PlayerRow>,
+    /// Nonces of used kill tokens with their expiry.
+    used_kill_tokens: HashMap<String, i64>,
+    last_game_id: i32,
+    last_player_id: i32,
+    last_event_id: i64,
+    last_dispute_id: i64,
+    last_hint_id: i64,
+    last_message_id: i64,
+    last_recovery_id: i64,
+    last_join_request_id: i64,
+}
+
+fn game_not_found() -> AppError {
+    AppError::NotFound(ErrorCode::GameNotFound, "error.GAME_NOT_FOUND")
+}
+
+/// What Postgres and SQLite report for a unique violation.
+fn already_exists() -> AppError {
+    AppError::Conflict(ErrorCode::Conflict, "error.CONFLICT")
+}
+
+fn unknown_secret() -> AppError {
+    AppError::NotFound(ErrorCode::UnknownSecret, "error.UNKNOWN_SECRET")
+}
+
+impl MemoryRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn store(&self) -> MutexGuard<'_, Store> {
+        self.store.lock().expect("memory store lock poisoned")
+    }
+}
+
+impl Store {
+    fn game_by_code(&self, code: &str) -> Result<Game, AppError> {
+        self.games
+            .values()
+            .find(|g| g.code == code)
+            .cloned()
+            .ok_or_else(game_not_found)
+    }
+
+    /// A player as the queries return it, with the target's name filled in.
+    fn view(&self, row: &PlayerRow) -> Player {
+        let target_name = row
+            .player
+            .target_id
+            .and_then(|id| self.players.get(&id))
+            .map(|t| t.player.name.clone())
+            .unwrap_or_default();
+        Player {
+            target_name: Some(target_name),
+            ..row.player.clone()
+        }
+    }
+
+    fn players_of(&self, game_id: i32) -> Vec<Player> {
+        self.players
+            .values()
+            .filter(|row| row.player.game_id == game_id)
+            .map(|row| self.view(row))
+            .collect()
+    }
+
+    fn find_in_game(&self, game_id: i32, pred: impl Fn(&Player) -> bool) -> Option<&PlayerRow> {
+        self.players
+            .values()
+            .find(|row| row.player.game_id == game_id && pred(&row.player))
+    }
+
+    fn row_mut(&mut self, player_id: i32) -> &mut PlayerRow {
+        self.players
+            .get_mut(&player_id)
+            .expect("player rows are only looked up by known ids")
+    }
+
+    fn set_status(&mut self, game_id: i32, status: GameStatus) {
+        if let Some(game) = self.games.get_mut(&game_id) {
+            game.status = status;
+        }
+    }
+
+    fn insert_player(&mut self, game_id: i32, name: &str) -> (i32, String, String) {
+        self.last_player_id += 1;
+        let id = self.last_player_id;
+        let secret = generate_code(7);
+        let auth_token = Uuid::new_v4().to_string();
+        self.players.insert(
+            id,
+            PlayerRow {
+                player: Player {
+                    id,
+                    name: name.to_string(),
+                    secret_code: secret.clone(),
+                    auth_token: auth_token.clone(),
+                    is_alive: true,
+                    is_ready: false,
+                    target_id: None,
+                    game_id,
+                    target_name: None,
+                },
+                secret_rotations: 0,
+                secret_rotated_at: None,
+            },
+        );
+        (id, secret, auth_token)
+    }
+
+    /// Add `name` to `game`, splicing them into the ring if it is already
+    /// running.
+    fn join(
+        &mut self,
+        game: &Game,
+        name: &str,
+        max_players: usize,
+        late_join: bool,
+    ) -> Result<(i32, String, String), AppError> {
+        let normalised = normalise_name(name);
+        let same_name = self
+            .find_in_game(game.id, |p| normalise_name(&p.name) == normalised)
+            .map(|row| row.player.clone());
+        let player_count = self.players_of(game.id).len();
+        engine::check_join(
+            game,
+            same_name.as_ref(),
+            player_count,
+            max_players,
+            late_join,
+        )?;
+
+        let (player_id, secret, auth_token) = self.insert_player(game.id, name);
+        if game.status == GameStatus::InProgress {
+            let changes = engine::plan_late_join(&self.players_of(game.id), player_id)
+                .ok_or(AppError::InternalServerError)?;
+            for (pid, target_id) in changes {
+                self.row_mut(pid).player.target_id = Some(target_id);
+            }
+            info!(
+                game_id = game.id,
+                player_id, "Late joiner spliced into the ring"
+            );
+        }
+        let joined = NewGameEvent::new(GameEventKind::Joined).by(Some(player_id), name);
+        self.record(game.id, &joined);
+        Ok((player_id, secret, auth_token))
+    }
+
+    /// Append to a game's event log as part of the change it describes.
+    fn record(&mut self, game_id: i32, event: &NewGameEvent) {
+        self.last_event_id += 1;
+        self.events.push(GameEvent {
+            id: self.last_event_id,
+            game_id,
+            kind: event.kind,
+            actor_id: event.actor_id,
+            actor_name: event.actor_name.clone(),
+            subject_id: event.subject_id,
+            subject_name: event.subject_name.clone(),
+            created_at: unix_now(),
+        });
+    }
+
+    /// Close a game with `winner_id` as its winner, clear every target and
+    /// log the end of the game.
+    fn finish(&mut self, game_id: i32, winner_id: Option<i32>) {
+        self.set_status(game_id, GameStatus::Finished);
+        for row in self.players.values_mut() {
+            if row.player.game_id == game_id {
+                row.player.target_id = None;
+            }
+        }
+        let mut finished = NewGameEvent::new(GameEventKind::Finished);
+        if let Some(winner) = winner_id.and_then(|id| self.players.get(&id)) {
+            finished = finished.by(winner_id, &winner.player.name);
+        }
+        self.record(game_id, &finished);
+    }
+
+    /// Take a living player out of the ring without a kill: their hunter
+    /// inherits their target and the game ends once one player is left.
+    fn remove_from_ring(&mut self, player: &Player) {
+        let row = self.row_mut(player.id);
+        row.player.is_alive = false;
+        row.player.target_id = None;
+        for row in self.players.values_mut() {
+            let p = &mut row.player;
+            if p.game_id == player.game_id && p.is_alive && p.target_id == Some(player.id) {
+                p.target_id = player.target_id;
+            }
+        }
+        let survivors: Vec<i32> = self
+            .players
+            .values()
+            .filter(|row| row.player.game_id == player.game_id && row.player.is_alive)
+            .map(|row| row.player.id)
+            .collect();
+        if survivors.len() <= 1 {
+            self.finish(player.game_id, survivors.first().copied());
+        }
+    }
+
+    /// Put the victim of an overturned kill back into the ring behind their
+    /// killer, see [`engine::plan_splice`].
+    fn revive(&mut self, players: &[Player], dispute: &KillDispute) {
+        let Some(victim) = players
+            .iter()
+            .find(|p| p.id == dispute.victim_id && !p.is_alive)
+        else {
+            return;
+        };
+        let Some(changes) = engine::plan_splice(players, victim.id, dispute.killer_id) else {
+            return;
+        };
+        self.row_mut(victim.id).player.is_alive = true;
+        for (pid, target_id) in changes {
+            self.row_mut(pid).player.target_id = Some(target_id);
+        }
+        let revived = NewGameEvent::new(GameEventKind::Revived).by(Some(victim.id), &victim.name);
+        self.record(dispute.game_id, &revived);
+        info!(
+            game_id = dispute.game_id,
+            dispute_id = dispute.id,
+            "Kill overturned, victim is back"
+        );
+    }
+
+    /// In debug builds, fail a change that left the ring of a running game
+    /// broken, as the Postgres repository does before every commit. There is
+    /// no transaction to roll back here, so the change itself stays.
+    fn assert_ring(&self, game_id: i32) -> Result<(), AppError> {
+        if !cfg!(debug_assertions)
+            || self.games.get(&game_id).map(|g| &g.status) != Some(&GameStatus::InProgress)
+        {
+            return Ok(());
+        }
+        ring_must_hold(game_id, &self.players_of(game_id))
+    }
+
+    /// Validate the ring of a running game and rebuild it when broken.
+    fn repair_ring(&mut self, game_id: i32) -> RingRepair {
+        if self.games.get(&game_id).map(|g| &g.status) != Some(&GameStatus::InProgress) {
+            return RingRepair::default();
+        }
+        let players = self.players_of(game_id);
+        let violations = engine::ring_violations(&players);
+        if violations.is_empty() {
+            return RingRepair::default();
+        }
+        warn!(game_id, ?violations, "Target ring is broken, repairing");
+
+        let mut repair = RingRepair {
+            violations,
+            ..RingRepair::default()
+        };
+        match engine::plan_ring_repair(&players) {
+            RingPlan::Finish { winner_id } => {
+                self.finish(game_id, winner_id);
+                repair.winner_id = winner_id;
+                repair.reassigned = winner_id.map(|id| (id, None)).into_iter().collect();
+            }
+            RingPlan::Retarget(changes) => {
+                for (pid, target_id) in changes {
+                    self.row_mut(pid).player.target_id = Some(target_id);
+                    repair.reassigned.push((pid, Some(target_id)));
+                }
+            }
+        }
+        repair
+    }
+}
+
+#[async_trait]
+impl GameRepository for MemoryRepository {
+    async fn create_game(
+        &self,
+        player_name: String,
+        game_code: String,
+    ) -> Result<NewPlayer, AppError> {
+        let player_name = player_name.trim();
+        info!(
+            "Creating game with code {} for player {}",
+            game_code, player_name
+        );
+        let mut store = self.store();
+        if store.games.values().any(|g| g.code == game_code) {
+            return Err(AppError::Conflict(ErrorCode::Conflict, "error.CONFLICT"));
+        }
+        store.last_game_id += 1;
+        let game_id = store.last_game_id;
+        store.game_created_at.insert(game_id, Instant::now());
+        store
+            .spectator_tokens
+            .insert(game_id, Uuid::new_v4().simple().to_string());
+        store.games.insert(
+            game_id,
+            Game {
+                id: game_id,
+                status: GameStatus::Lobby,
+                host_id: None,
+                code: game_code,
+            },
+        );
+        let (player_id, secret, auth_token) = store.insert_player(game_id, player_name);
+        store.games.get_mut(&game_id).unwrap().host_id = Some(player_id);
+        let joined = NewGameEvent::new(GameEventKind::Joined).by(Some(player_id), player_name);
+        store.record(game_id, &joined);
+        Ok((game_id, player_id, secret, auth_token))
+    }
+
+    async fn join_game(
+        &self,
+        game_code: String,
+        player_name: String,
+        max_players: usize,
+        late_join: bool,
+    ) -> Result<NewPlayer, AppError> {
+        let player_name = player_name.trim();
+        info!("Player {} joining game {}", player_name, game_code);
+        let mut store = self.store();
+        let game = store.game_by_code(&game_code)?;
+        let (player_id, secret, auth_token) =
+            store.join(&game, player_name, max_players, late_join)?;
+        store.assert_ring(game.id)?;
+        Ok((game.id, player_id, secret, auth_token))
+    }
+
+    async fn set_ready(&self, player_id: i32, ready: bool) -> Result<(), AppError> {
+        if let Some(row) = self.store().players.get_mut(&player_id) {
+            row.player.is_ready = ready;
+        }
+        Ok(())
+    }
+
+    async fn start_game(
+        &self,
+        game_code: &str,
+        player_id: i32,
+        min_players: usize,
+    ) -> Result<Vec<Player>, AppError> {
+        info!("Starting game {} by player {}", game_code, player_id);
+        let mut store = self.store();
+        let game = store.game_by_code(game_code)?;
+        let players = store.players_of(game.id);
+        engine::check_start(&game, player_id, players.len(), min_players)?;
+        let starter = players
+            .iter()
+            .find(|p| p.id == player_id)
+            .ok_or(AppError::InternalServerError)?;
+        let started = NewGameEvent::new(GameEventKind::Started).by(Some(starter.id), &starter.name);
+        store.record(game.id, &started);
+
+        let ids = players.iter().map(|p| p.id).collect();
+        for (pid, target_id) in engine::assign_targets(ids) {
+            store.row_mut(pid).player.target_id = Some(target_id);
+        }
+        store.set_status(game.id, GameStatus::InProgress);
+        store.assert_ring(game.id)?;
+        Ok(store.players_of(game.id))
+    }
+
+    async fn process_kill(
+        &self,
+        game_code: &str,
+        killer_token: &str,
+        proof: &KillProof,
+    ) -> Result<KillOutcome, AppError> {
+        let mut store = self.store();
+        let game = store.game_by_code(game_code)?;
+        let killer = store
+            .find_in_game(game.id, |p| p.auth_token == killer_token)
+            .map(|row| row.player.clone())
+            .ok_or(AppError::Unauthorized)?;
+        let target = match proof {
+            KillProof::Secret(secret) => store
+                .find_in_game(game.id, |p| &p.secret_code == secret)
+                .map(|row| row.player.clone())
+                .ok_or_else(unknown_secret)?,
+            KillProof::Token(claims) => {
+                engine::check_token_game(claims, &game)?;
+                let row = store
+                    .find_in_game(game.id, |p| p.id == claims.victim)
+                    .ok_or_else(unknown_secret)?;
+                engine::check_token_generation(claims, row.secret_rotations)?;
+                row.player.clone()
+            }
+        };
+
+        engine::validate_kill(&killer, &target, &game)?;
+
+        if let KillProof::Token(claims) = proof {
+            let now = unix_now();
+            store.used_kill_tokens.retain(|_, exp| *exp >= now);
+            if store.used_kill_tokens.contains_key(&claims.nonce) {
+                return Err(engine::kill_token_used());
+            }
+            store
+                .used_kill_tokens
+                .insert(claims.nonce.clone(), claims.exp);
+        }
+
+        let kill = NewGameEvent::new(GameEventKind::Kill)
+            .by(Some(killer.id), &killer.name)
+            .to(Some(target.id), &target.name);
+        store.record(game.id, &kill);
+        store.row_mut(target.id).player.is_alive = false;
+        let alive = store
+            .players
+            .values()
+            .filter(|row| row.player.game_id == game.id && row.player.is_alive)
+            .count();
+        let new_target_name = if alive <= 1 {
+            store.finish(game.id, Some(killer.id));
+            None
+        } else {
+            store.row_mut(killer.id).player.target_id = target.target_id;
+            target
+                .target_id
+                .and_then(|id| store.players.get(&id))
+                .map(|row| row.player.name.clone())
+        };
+        store.assert_ring(game.id)?;
+
+        Ok((killer.id, killer.name, target.name, new_target_name))
+    }
+
+    async fn leave_game(&self, game_code: &str, auth_token: &str) -> Result<(), AppError> {
+        let mut store = self.store();
+        let game = store.game_by_code(game_code)?;
+        let player = store
+            .find_in_game(game.id, |p| p.auth_token == auth_token)
+            .map(|row| row.player.clone())
+            .ok_or(AppError::Unauthorized)?;
+        info!(
+            game_id = game.id,
+            player_id = player.id,
+            "Player leaving game"
+        );
+        let kind = match game.status {
+            GameStatus::InProgress => GameEventKind::Forfeited,
+            _ => GameEventKind::Left,
+        };
+        store.record(
+            game.id,
+            &NewGameEvent::new(kind).by(Some(player.id), &player.name),
+        );
+
+        match game.status {
+            GameStatus::Lobby => {
+                store.players.remove(&player.id);
+                store.recoveries.retain(|r| r.player_id != player.id);
+                for message in store.messages.iter_mut() {
+                    if message.sender_id == Some(player.id) {
+                        message.sender_id = None;
+                    }
+                }
+                if game.host_id == Some(player.id) {
+                    let remaining = store.players_of(game.id);
+                    if remaining.is_empty() {
+                        // Last player (the host) left, delete the game
+                        store.games.remove(&game.id);
+                        store.game_created_at.remove(&game.id);
+                        store.events.retain(|event| event.game_id != game.id);
+                    } else {
+                        let new_host_id = engine::next_host(&remaining);
+                        if let Some(game) = store.games.get_mut(&game.id) {
+                            game.host_id = new_host_id;
+                        }
+                        if let Some(host) = remaining.iter().find(|p| Some(p.id) == new_host_id) {
+                            let host_changed = NewGameEvent::new(GameEventKind::HostChanged)
+                                .by(Some(host.id), &host.name);
+                            store.record(game.id, &host_changed);
+                        }
+                    }
+                }
+            }
+            GameStatus::InProgress if player.is_alive => store.remove_from_ring(&player),
+            _ => store.row_mut(player.id).player.is_alive = false,
+        }
+        store.assert_ring(game.id)?;
+        Ok(())
+    }
+
+    async fn rotate_secret(
+        &self,
+        game_code: &str,
+        auth_token: &str,
+        cooldown_secs: i64,
+        max_rotations: i32,
+    ) -> Result<(String, i32), AppError> {
+        let mut store = self.store();
+        let game = store.game_by_code(game_code)?;
+        let row = store
+            .find_in_game(game.id, |p| p.auth_token == auth_token)
+            .ok_or(AppError::Unauthorized)?;
+        let cooldown_left = row.secret_rotated_at.map(|at| {
+            let left = cooldown_secs as f64 - at.elapsed().as_secs_f64();
+            left.ceil().max(0.0) as i64
+        });
+        engine::check_rotation(
+            &game,
+            &row.player,
+            row.secret_rotations,
+            max_rotations,
+            cooldown_left,
+        )?;
+
+        let player_id = row.player.id;
+        let new_secret = generate_code(7);
+        let row = store.row_mut(player_id);
+        row.player.secret_code = new_secret.clone();
+        row.secret_rotations += 1;
+        row.secret_rotated_at = Some(Instant::now());
+        Ok((new_secret, max_rotations - row.secret_rotations))
+    }
+
+    async fn get_secret_generation(&self, player_id: i32) -> Result<i32, AppError> {
+        self.store()
+            .players
+            .get(&player_id)
+            .map(|row| row.secret_rotations)
+            .ok_or(AppError::InternalServerError)
+    }
+
+    async fn get_all_games(&self) -> Result<Vec<GameInfo>, AppError> {
+        let store = self.store();
+        Ok(store
+            .games
+            .values()
+            .map(|g| GameInfo {
+                code: g.code.clone(),
+                status: g.status.clone(),
+                player_count: store.players_of(g.id).len() as i64,
+            })
+            .collect())
+    }
+
+    async fn get_game_by_code(&self, code: &str) -> Result<Option<Game>, AppError> {
+        Ok(self.store().game_by_code(code).ok())
+    }
+
+    async fn get_game_by_id(&self, game_id: i32) -> Result<Option<Game>, AppError> {
+        Ok(self.store().games.get(&game_id).cloned())
+    }
+
+    async fn get_game_state(
+        &self,
+        game_code: &str,
+    ) -> Result<Option<(Game, Vec<Player>)>, AppError> {
+        let store = self.store();
+        Ok(store.game_by_code(game_code).ok().map(|game| {
+            let players = store.players_of(game.id);
+            (game, players)
+        }))
+    }
+
+    async fn get_players_by_game_id(&self, game_id: i32) -> Result<Vec<Player>, AppError> {
+        Ok(self.store().players_of(game_id))
+    }
+
+    async fn get_player_by_auth_token(&self, auth_token: &str) -> Result<Option<Player>, AppError> {
+        let store = self.store();
+        Ok(store
+            .players
+            .values()
+            .find(|row| row.player.auth_token == auth_token)
+            .map(|row| store.view(row)))
+    }
+
+    async fn check_ring(&self, game_code: &str) -> Result<Vec<RingViolation>, AppError> {
+        let store = self.store();
+        let game = store.game_by_code(game_code)?;
+        if game.status != GameStatus::InProgress {
+            return Ok(Vec::new());
+        }
+        Ok(engine::ring_violations(&store.players_of(game.id)))
+    }
+
+    async fn repair_ring(&self, game_code: &str) -> Result<RingRepair, AppError> {
+        let mut store = self.store();
+        let game = store.game_by_code(game_code)?;
+        let repair = store.repair_ring(game.id);
+        if !repair.violations.is_empty() {
+            info!(
+                game_code,
+                reassigned = repair.reassigned.len(),
+                "Target ring repaired"
+            );
+        }
+        Ok(repair)
+    }
+
+    async fn get_events(&self, game_id: i32) -> Result<Vec<GameEvent>, AppError> {
+        Ok(self
+            .store()
+            .events
+            .iter()
+            .filter(|event| event.game_id == game_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_events_page(
+        &self,
+        game_id: i32,
+        before: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<GameEvent>, AppError> {
+        Ok(self
+            .store()
+            .events
+            .iter()
+            .rev()
+            .filter(|event| event.game_id == game_id)
+            .filter(|event| before.is_none_or(|before| event.id < before))
+            .take(limit.max(0) as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_spectator_token(&self, game_id: i32) -> Result<Option<String>, AppError> {
+        Ok(self.store().spectator_tokens.get(&game_id).cloned())
+    }
+
+    async fn get_game_settings(&self, game_id: i32) -> Result<Option<GameSettings>, AppError> {
+        let store = self.store();
+        if !store.games.contains_key(&game_id) {
+            return Ok(None);
+        }
+        Ok(Some(
+            store.settings.get(&game_id).cloned().unwrap_or_default(),
+        ))
+    }
+
+    async fn set_game_settings(
+        &self,
+        game_id: i32,
+        settings: &GameSettings,
+    ) -> Result<(), AppError> {
+        let mut store = self.store();
+        if store.games.contains_key(&game_id) {
+            store.settings.insert(game_id, settings.clone());
+        }
+        Ok(())
+    }
+
+    async fn open_dispute(
+        &self,
+        game_id: i32,
+        event_id: i64,
+        victim_id: i32,
+        killer_id: Option<i32>,
+    ) -> Result<i64, AppError> {
+        let mut store = self.store();
+        if store.disputes.iter().any(|d| d.event_id == event_id) {
+            return Err(already_exists());
+        }
+        store.last_dispute_id += 1;
+        let id = store.last_dispute_id;
+        store.disputes.push(KillDispute {
+            id,
+            game_id,
+            event_id,
+            victim_id,
+            killer_id,
+            status: DisputeStatus::Open,
+            votes: Vec::new(),
+        });
+        Ok(id)
+    }
+
+    async fn get_disputes(&self, game_id: i32) -> Result<Vec<KillDispute>, AppError> {
+        Ok(self
+            .store()
+            .disputes
+            .iter()
+            .filter(|d| d.game_id == game_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn vote_on_dispute(
+        &self,
+        game_code: &str,
+        dispute_id: i64,
+        voter_id: i32,
+        overturn: bool,
+    ) -> Result<KillDispute, AppError> {
+        let mut store = self.store();
+        let game = store.game_by_code(game_code)?;
+        let index = store
+            .disputes
+            .iter()
+            .position(|d| d.id == dispute_id && d.game_id == game.id)
+            .ok_or(AppError::NotFound(
+                ErrorCode::DisputeNotFound,
+                "error.DISPUTE_NOT_FOUND",
+            ))?;
+        let players = store.players_of(game.id);
+        let mut dispute = store.disputes[index].clone();
+        let outcome = engine::cast_vote(&mut dispute, &players, voter_id, overturn)?;
+        if let Some(outcome) = outcome {
+            dispute.status = outcome;
+            if outcome == DisputeStatus::Overturned && game.status == GameStatus::InProgress {
+                store.revive(&players, &dispute);
+            }
+            info!(game_code, dispute_id, ?outcome, "Dispute settled");
+        }
+        store.disputes[index] = dispute.clone();
+        store.assert_ring(game.id)?;
+        Ok(dispute)
+    }
+
+    async fn send_hint(
+        &self,
+        game_id: i32,
+        sender_id: i32,
+        recipient_id: i32,
+        message: &str,
+    ) -> Result<(), AppError> {
+        let mut store = self.store();
+        if store.hints.iter().any(|h| h.sender_id == sender_id) {
+            return Err(already_exists());
+        }
+        store.last_hint_id += 1;
+        let id = store.last_hint_id;
+        store.hints.push(GhostHint {
+            id,
+            game_id,
+            sender_id,
+            recipient_id,
+            message: message.to_string(),
+            created_at: unix_now(),
+        });
+        Ok(())
+    }
+
+    async fn get_hints(&self, game_id: i32) -> Result<Vec<GhostHint>, AppError> {
+        Ok(self
+            .store()
+            .hints
+            .iter()
+            .filter(|h| h.game_id == game_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn post_message(
+        &self,
+        channel: ChatChannel,
+        sender: &Player,
+        body: &str,
+    ) -> Result<ChatMessage, AppError> {
+        let mut store = self.store();
+        store.last_message_id += 1;
+        let message = ChatMessage {
+            id: store.last_message_id,
+            game_id: sender.game_id,
+            channel,
+            sender_id: Some(sender.id),
+            sender_name: sender.name.clone(),
+            body: body.to_string(),
+            created_at: unix_now(),
+        };
+        store.messages.push(message.clone());
+        Ok(message)
+    }
+
+    async fn get_messages(&self, game_id: i32) -> Result<Vec<ChatMessage>, AppError> {
+        Ok(self
+            .store()
+            .messages
+            .iter()
+            .filter(|m| m.game_id == game_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn request_recovery(
+        &self,
+        game_id: i32,
+        player_id: i32,
+    ) -> Result<RecoveryRequest, AppError> {
+        let mut store = self.store();
+        let pending = |r: &RecoveryRequest| r.status == RequestStatus::Pending;
+        if store
+            .recoveries
+            .iter()
+            .any(|r| r.player_id == player_id && pending(r))
+        {
+            return Err(engine::recovery_pending());
+        }
+        store
+            .recoveries
+            .retain(|r| r.player_id != player_id || pending(r));
+        store.last_recovery_id += 1;
+        let request = RecoveryRequest {
+            id: store.last_recovery_id,
+            game_id,
+            player_id,
+            claim_token: Uuid::new_v4().simple().to_string(),
+            status: RequestStatus::Pending,
+            auth_token: None,
+            created_at: unix_now(),
+        };
+        store.recoveries.push(request.clone());
+        Ok(request)
+    }
+
+    async fn get_recovery_requests(&self, game_id: i32) -> Result<Vec<RecoveryRequest>, AppError> {
+        Ok(self
+            .store()
+            .recoveries
+            .iter()
+            .filter(|r| r.game_id == game_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_recovery_request(
+        &self,
+        claim_token: &str,
+    ) -> Result<Option<RecoveryRequest>, AppError> {
+        Ok(self
+            .store()
+            .recoveries
+            .iter()
+            .find(|r| r.claim_token == claim_token)
+            .cloned())
+    }
+
+    async fn decide_recovery(
+        &self,
+        game_id: i32,
+        request_id: i64,
+        approve: bool,
+    ) -> Result<bool, AppError> {
+        let mut store = self.store();
+        let Some(request) = store.recoveries.iter_mut().find(|r| {
+            r.id == request_id && r.game_id == game_id && r.status == RequestStatus::Pending
+        }) else {
+            return Ok(false);
+        };
+        if !approve {
+            request.status = RequestStatus::Denied;
+            return Ok(true);
+        }
+        let auth_token = Uuid::new_v4().to_string();
+        request.status = RequestStatus::Approved;
+        request.auth_token = Some(auth_token.clone());
+        let player_id = request.player_id;
+        if let Some(row) = store.players.get_mut(&player_id) {
+            row.player.auth_token = auth_token;
+        }
+        info!(game_id, player_id, "Player recovered with a new auth token");
+        Ok(true)
+    }
+
+    async fn request_join(&self, game_id: i32, player_name: &str) -> Result<JoinRequest, AppError> {
+        let mut store = self.store();
+        store.last_join_request_id += 1;
+        let request = JoinRequest {
+            id: store.last_join_request_id,
+            game_id,
+            player_name: player_name.trim().to_string(),
+            claim_token: Uuid::new_v4().simple().to_string(),
+            status: RequestStatus::Pending,
+            auth_token: None,
+            created_at: unix_now(),
+        };
+        store.join_requests.push(request.clone());
+        Ok(request)
+    }
+
+    async fn get_join_requests(&self, game_id: i32) -> Result<Vec<JoinRequest>, AppError> {
+        Ok(self
+            .store()
+            .join_requests
+            .iter()
+            .filter(|r| r.game_id == game_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_join_request(&self, claim_token: &str) -> Result<Option<JoinRequest>, AppError> {
+        Ok(self
+            .store()
+            .join_requests
+            .iter()
+            .find(|r| r.claim_token == claim_token)
+            .cloned())
+    }
+
+    async fn decide_join_request(
+        &self,
+        game_code: &str,
+        request_id: i64,
+        approve: bool,
+        max_players: usize,
+    ) -> Result<bool, AppError> {
+        let mut store = self.store();
+        let game = store.game_by_code(game_code)?;
+        let Some(index) = store.join_requests.iter().position(|r| {
+            r.id == request_id && r.game_id == game.id && r.status == RequestStatus::Pending
+        }) else {
+            return Ok(false);
+        };
+        if !approve {
+            store.join_requests[index].status = RequestStatus::Denied;
+            return Ok(true);
+        }
+        let name = store.join_requests[index].player_name.clone();
+        let (player_id, _, auth_token) = store.join(&game, &name, max_players, true)?;
+        let request = &mut store.join_requests[index];
+        request.status = RequestStatus::Approved;
+        request.auth_token = Some(auth_token);
+        store.assert_ring(game.id)?;
+        info!(game_code, player_id, "Host let a late joiner in");
+        Ok(true)
+    }
+
+    async fn check_ready(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    async fn force_finish_game(&self, game_code: &str) -> Result<bool, AppError> {
+        let mut store = self.store();
+        let game = store.game_by_code(game_code)?;
+        if game.status == GameStatus::Finished {
+            return Ok(false);
+        }
+        store.finish(game.id, None);
+        info!(game_code, "Game finished by an administrator");
+        Ok(true)
+    }
+
+    async fn purge_games(&self, older_than: Duration) -> Result<Vec<String>, AppError> {
+        let mut store = self.store();
+        let now = unix_now();
+        store.used_kill_tokens.retain(|_, exp| *exp >= now);
+        let expired: Vec<i32> = store
+            .game_created_at
+            .iter()
+            .filter(|(_, created)| created.elapsed() > older_than)
+            .map(|(&id, _)| id)
+            .collect();
+        let mut codes = Vec::new();
+        for game_id in expired {
+            store.game_created_at.remove(&game_id);
+            if let Some(game) = store.games.remove(&game_id) {
+                codes.push(game.code);
+            }
+            store.spectator_tokens.remove(&game_id);
+            store.settings.remove(&game_id);
+            store.players.retain(|_, row| row.player.game_id != game_id);
+            store.events.retain(|event| event.game_id != game_id);
+            store.disputes.retain(|d| d.game_id != game_id);
+            store.hints.retain(|h| h.game_id != game_id);
+            store.messages.retain(|m| m.game_id != game_id);
+            store.recoveries.retain(|r| r.game_id != game_id);
+            store.join_requests.retain(|r| r.game_id != game_id);
+        }
+        if !codes.is_empty() {
+            info!(count = codes.len(), "Purged old games");
+        }
+        Ok(codes)
+    }
+}
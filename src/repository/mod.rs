@@ -0,0 +1,395 @@
+//! Storage behind the game. Handlers only talk to a [`GameRepository`], so
+//! the same rules run on Postgres in production, on SQLite for small
+//! self-hosted setups and in memory for tests and quick local play.
+//!
+//! Postgres keeps its `query!` macros, checked against the schema at compile
+//! time (see `.sqlx`), and serialises writes with `SELECT … FOR UPDATE`.
+//! [`sqlite`] writes its statements in the SQL both databases understand but
+//! runs them as runtime queries, because the macros check against a single
+//! database, and serialises writes with `BEGIN IMMEDIATE` since it has no
+//! row locks.
+//!
+//! Anything touching storage is therefore written once per backend: a trait
+//! method here, the queries in `crate::db` (forwarded by [`postgres`]), the
+//! SQLite and in-memory versions, and a migration in both `migrations/` and
+//! `migrations_sqlite/`. Game rules belong in `crate::engine` so the three
+//! only move rows around. HTTP tests are written once as an `async fn`
+//! taking a `TestApp` and run against each backend by `on_every_backend!`
+//! in `tests/common`.
+
+use crate::config::DatabaseConfig;
+use crate::db::Db;
+use crate::engine::{RingRepair, RingViolation};
+use crate::errors::AppError;
+use crate::kill_token::KillProof;
+use crate::models::{
+    ChatChannel, ChatMessage, DisputeStatus, Game, GameEvent, GameEventKind, GameInfo,
+    GameSettings, GhostHint, JoinRequest, KillDispute, Player, RecoveryRequest, RequestStatus,
+};
+use async_trait::async_trait;
+use sqlx::migrate::Migrator;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub mod memory;
+pub mod postgres;
+pub mod sqlite;
+
+pub use memory::MemoryRepository;
+pub use sqlite::SqliteRepository;
+
+/// Open the storage `config.url` points at, running migrations.
+///
+/// `memory` runs without a database; games vanish on restart. A `sqlite:`
+/// URL keeps everything in a single file; anything else is Postgres.
+pub async fn connect(config: &DatabaseConfig) -> Result<Arc<dyn GameRepository>, sqlx::Error> {
+    Ok(match config.url.as_str() {
+        "memory" => {
+            tracing::warn!("Using the in-memory repository, nothing will be persisted");
+            Arc::new(MemoryRepository::new())
+        }
+        url if url.starts_with("sqlite:") => {
+            Arc::new(SqliteRepository::connect(url, config.max_connections).await?)
+        }
+        _ => Arc::new(Db::connect(config).await?),
+    })
+}
+
+/// Open the database `config.url` points at without touching its schema, for
+/// tools that inspect the database of a running server. Refuses `memory`,
+/// which would only ever show an empty store.
+pub async fn open(config: &DatabaseConfig) -> Result<Arc<dyn GameRepository>, sqlx::Error> {
+    Ok(match config.url.as_str() {
+        "memory" => {
+            return Err(sqlx::Error::Configuration(
+                "the in-memory store lives inside the server and cannot be opened".into(),
+            ))
+        }
+        url if url.starts_with("sqlite:") => {
+            Arc::new(SqliteRepository::open(url, config.max_connections).await?)
+        }
+        _ => Arc::new(Db::open(config).await?),
+    })
+}
+
+/// Versions of `migrator` that are missing from `applied`.
+pub(crate) fn pending_migrations(migrator: &Migrator, applied: &[i64]) -> Vec<i64> {
+    migrator
+        .iter()
+        .map(|m| m.version)
+        .filter(|v| !applied.contains(v))
+        .collect()
+}
+
+/// Fail a change that leaves the living players of a running game outside a
+/// single target ring. Backends call this before committing in debug builds
+/// only; a ring found broken in production is mended by `repair_ring`.
+pub(crate) fn ring_must_hold(game_id: i32, players: &[Player]) -> Result<(), AppError> {
+    let violations = crate::engine::ring_violations(players);
+    if violations.is_empty() {
+        return Ok(());
+    }
+    tracing::error!(game_id, ?violations, "Change would break the target ring");
+    Err(AppError::InternalServerError)
+}
+
+/// Read back a stored [`GameEventKind`]; anything else means the table was
+/// written by something other than this server.
+pub(crate) fn parse_event_kind(kind: &str) -> Result<GameEventKind, AppError> {
+    kind.parse().map_err(|err| {
+        tracing::error!("Corrupt game event: {err}");
+        AppError::InternalServerError
+    })
+}
+
+/// Read back a stored [`DisputeStatus`], like [`parse_event_kind`].
+pub(crate) fn parse_dispute_status(status: &str) -> Result<DisputeStatus, AppError> {
+    status.parse().map_err(|err| {
+        tracing::error!("Corrupt kill dispute: {err}");
+        AppError::InternalServerError
+    })
+}
+
+/// Read back a stored [`ChatChannel`], like [`parse_event_kind`].
+pub(crate) fn parse_chat_channel(channel: &str) -> Result<ChatChannel, AppError> {
+    channel.parse().map_err(|err| {
+        tracing::error!("Corrupt chat message: {err}");
+        AppError::InternalServerError
+    })
+}
+
+/// Read back a stored [`RequestStatus`], like [`parse_event_kind`].
+pub(crate) fn parse_request_status(status: &str) -> Result<RequestStatus, AppError> {
+    status.parse().map_err(|err| {
+        tracing::error!("Corrupt request to the host: {err}");
+        AppError::InternalServerError
+    })
+}
+
+/// `(game_id, player_id, player_secret, auth_token)` of a freshly created or
+/// joined player.
+pub type NewPlayer = (i32, i32, String, String);
+
+/// `(killer_id, killer_name, eliminated_name, new_target_name)`; the new
+/// target is `None` when the kill ended the game.
+pub type KillOutcome = (i32, String, String, Option<String>);
+
+/// Connection pool usage, for the metrics endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolStats {
+    pub size: u32,
+    pub idle: u32,
+    pub max: u32,
+}
+
+impl PoolStats {
+    fn of<DB: sqlx::Database>(pool: &sqlx::Pool<DB>) -> Self {
+        PoolStats {
+            size: pool.size(),
+            idle: pool.num_idle() as u32,
+            max: pool.options().get_max_connections(),
+        }
+    }
+}
+
+#[async_trait]
+pub trait GameRepository: Send + Sync {
+    // ---------- Lobby ----------
+
+    /// Create a new game and the first (host) player.
+    async fn create_game(
+        &self,
+        player_name: String,
+        game_code: String,
+    ) -> Result<NewPlayer, AppError>;
+
+    /// Add a player to a game that is still in its lobby and has fewer than
+    /// `max_players` in it. With `late_join` a running game takes them too,
+    /// spliced into the ring at a random point in the same transaction.
+    async fn join_game(
+        &self,
+        game_code: String,
+        player_name: String,
+        max_players: usize,
+        late_join: bool,
+    ) -> Result<NewPlayer, AppError>;
+
+    /// Mark a player in the lobby ready to start, or not.
+    async fn set_ready(&self, player_id: i32, ready: bool) -> Result<(), AppError>;
+
+    /// Host starts the game – assigns targets and flips status. Needs at
+    /// least `min_players` in the lobby.
+    async fn start_game(
+        &self,
+        game_code: &str,
+        player_id: i32,
+        min_players: usize,
+    ) -> Result<Vec<Player>, AppError>;
+
+    // ---------- Playing ----------
+
+    async fn process_kill(
+        &self,
+        game_code: &str,
+        killer_token: &str,
+        proof: &KillProof,
+    ) -> Result<KillOutcome, AppError>;
+
+    async fn leave_game(&self, game_code: &str, auth_token: &str) -> Result<(), AppError>;
+
+    /// Replace a living player's secret code, invalidating the old one.
+    /// Returns the new secret and how many rotations the player has left.
+    async fn rotate_secret(
+        &self,
+        game_code: &str,
+        auth_token: &str,
+        cooldown_secs: i64,
+        max_rotations: i32,
+    ) -> Result<(String, i32), AppError>;
+
+    /// How many times a player has rotated their secret; signed kill tokens
+    /// are only valid for the current generation.
+    async fn get_secret_generation(&self, player_id: i32) -> Result<i32, AppError>;
+
+    // ---------- Queries ----------
+
+    async fn get_all_games(&self) -> Result<Vec<GameInfo>, AppError>;
+
+    async fn get_game_by_code(&self, code: &str) -> Result<Option<Game>, AppError>;
+
+    async fn get_game_by_id(&self, game_id: i32) -> Result<Option<Game>, AppError>;
+
+    async fn get_game_state(
+        &self,
+        game_code: &str,
+    ) -> Result<Option<(Game, Vec<Player>)>, AppError>;
+
+    async fn get_players_by_game_id(&self, game_id: i32) -> Result<Vec<Player>, AppError>;
+
+    async fn get_player_by_auth_token(&self, auth_token: &str) -> Result<Option<Player>, AppError>;
+
+    // ---------- Events ----------
+
+    /// A game's event log, oldest first. The operations above append to it
+    /// in the same transaction as the change an event describes.
+    async fn get_events(&self, game_id: i32) -> Result<Vec<GameEvent>, AppError>;
+
+    /// Up to `limit` events of a game older than the event `before`, newest
+    /// first.
+    async fn get_events_page(
+        &self,
+        game_id: i32,
+        before: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<GameEvent>, AppError>;
+
+    /// The token that lets spectators watch a game; `None` when there is no
+    /// such game.
+    async fn get_spectator_token(&self, game_id: i32) -> Result<Option<String>, AppError>;
+
+    // ---------- Settings ----------
+
+    /// `None` when there is no such game.
+    async fn get_game_settings(&self, game_id: i32) -> Result<Option<GameSettings>, AppError>;
+
+    async fn set_game_settings(
+        &self,
+        game_id: i32,
+        settings: &GameSettings,
+    ) -> Result<(), AppError>;
+
+    // ---------- Ghosts ----------
+
+    /// Open a dispute over the kill `event_id`; returns its id.
+    async fn open_dispute(
+        &self,
+        game_id: i32,
+        event_id: i64,
+        victim_id: i32,
+        killer_id: Option<i32>,
+    ) -> Result<i64, AppError>;
+
+    /// A game's disputes with their votes, oldest first.
+    async fn get_disputes(&self, game_id: i32) -> Result<Vec<KillDispute>, AppError>;
+
+    /// Cast a ghost's vote on an open dispute, replacing their earlier one,
+    /// and settle the dispute once the votes decide it, see
+    /// [`engine::cast_vote`]. Overturning puts the victim of a running game
+    /// back into the ring behind their killer, see [`engine::plan_splice`].
+    /// Reading the tally, voting and settling share one transaction that
+    /// holds the dispute's row. Returns the dispute after the vote.
+    ///
+    /// [`engine::cast_vote`]: crate::engine::cast_vote
+    /// [`engine::plan_splice`]: crate::engine::plan_splice
+    async fn vote_on_dispute(
+        &self,
+        game_code: &str,
+        dispute_id: i64,
+        voter_id: i32,
+        overturn: bool,
+    ) -> Result<KillDispute, AppError>;
+
+    /// Store a ghost's hint; a second one from the same ghost is a conflict.
+    async fn send_hint(
+        &self,
+        game_id: i32,
+        sender_id: i32,
+        recipient_id: i32,
+        message: &str,
+    ) -> Result<(), AppError>;
+
+    /// Every hint sent in a game, oldest first.
+    async fn get_hints(&self, game_id: i32) -> Result<Vec<GhostHint>, AppError>;
+
+    // ---------- Chat ----------
+
+    /// Store a message from `sender` and return it as stored.
+    async fn post_message(
+        &self,
+        channel: ChatChannel,
+        sender: &Player,
+        body: &str,
+    ) -> Result<ChatMessage, AppError>;
+
+    /// A game's chat messages in every channel, oldest first.
+    async fn get_messages(&self, game_id: i32) -> Result<Vec<ChatMessage>, AppError>;
+
+    // ---------- Recovery ----------
+
+    /// Ask the host to let a player who lost their link back in, replacing
+    /// any answered request for the same player. Fails while an earlier one
+    /// is still pending.
+    async fn request_recovery(
+        &self,
+        game_id: i32,
+        player_id: i32,
+    ) -> Result<RecoveryRequest, AppError>;
+
+    /// A game's recovery requests, oldest first.
+    async fn get_recovery_requests(&self, game_id: i32) -> Result<Vec<RecoveryRequest>, AppError>;
+
+    async fn get_recovery_request(
+        &self,
+        claim_token: &str,
+    ) -> Result<Option<RecoveryRequest>, AppError>;
+
+    /// Answer a pending recovery request. Approving gives the player a fresh
+    /// auth token, so their old link stops working. Returns `false` when the
+    /// request had already been answered.
+    async fn decide_recovery(
+        &self,
+        game_id: i32,
+        request_id: i64,
+        approve: bool,
+    ) -> Result<bool, AppError>;
+
+    // ---------- Late joins ----------
+
+    /// Ask the host to let a newcomer into a running game.
+    async fn request_join(&self, game_id: i32, player_name: &str) -> Result<JoinRequest, AppError>;
+
+    /// A game's join requests, oldest first.
+    async fn get_join_requests(&self, game_id: i32) -> Result<Vec<JoinRequest>, AppError>;
+
+    async fn get_join_request(&self, claim_token: &str) -> Result<Option<JoinRequest>, AppError>;
+
+    /// Answer a pending join request. Approving joins the newcomer as
+    /// [`join_game`](Self::join_game) does with `late_join`, in the same
+    /// transaction, and hands them its auth token. Returns `false` when the
+    /// request had already been answered.
+    async fn decide_join_request(
+        &self,
+        game_code: &str,
+        request_id: i64,
+        approve: bool,
+        max_players: usize,
+    ) -> Result<bool, AppError>;
+
+    // ---------- Maintenance ----------
+
+    /// Whether the storage is reachable and its schema is up to date.
+    async fn check_ready(&self) -> Result<(), AppError>;
+
+    /// Release connections before the process exits.
+    async fn close(&self) {}
+
+    /// Connection pool usage; `None` for storage without a pool.
+    fn pool_stats(&self) -> Option<PoolStats> {
+        None
+    }
+
+    /// Report everything wrong with a running game's target ring.
+    async fn check_ring(&self, game_code: &str) -> Result<Vec<RingViolation>, AppError>;
+
+    /// Validate a running game's target ring and rebuild it when broken.
+    async fn repair_ring(&self, game_code: &str) -> Result<RingRepair, AppError>;
+
+    /// End a game without a winner, whatever state it is in. Returns `false`
+    /// when it had already finished.
+    async fn force_finish_game(&self, game_code: &str) -> Result<bool, AppError>;
+
+    /// Delete every game created more than `older_than` ago, together with
+    /// its players, and drop expired kill token nonces. Returns the codes of
+    /// the deleted games.
+    async fn purge_games(&self, older_than: Duration) -> Result<Vec<String>, AppError>;
+}
@@ -0,0 +1,266 @@
+use super::{pending_migrations, GameRepository, KillOutcome, NewPlayer, PoolStats};
+use crate::db::{Db, MIGRATOR};
+use crate::engine::{RingRepair, RingViolation};
+use crate::errors::AppError;
+use crate::kill_token::KillProof;
+use crate::models::{
+    ChatChannel, ChatMessage, Game, GameEvent, GameInfo, GameSettings, GhostHint, JoinRequest,
+    KillDispute, Player, RecoveryRequest,
+};
+use async_trait::async_trait;
+use std::time::Duration;
+use tracing::warn;
+
+/// The queries themselves live in `impl Db` blocks under `crate::db`; this
+/// only forwards to them.
+#[async_trait]
+impl GameRepository for Db {
+    async fn create_game(
+        &self,
+        player_name: String,
+        game_code: String,
+    ) -> Result<NewPlayer, AppError> {
+        Db::create_game(self, player_name, game_code).await
+    }
+
+    async fn join_game(
+        &self,
+        game_code: String,
+        player_name: String,
+        max_players: usize,
+        late_join: bool,
+    ) -> Result<NewPlayer, AppError> {
+        Db::join_game(self, game_code, player_name, max_players, late_join).await
+    }
+
+    async fn set_ready(&self, player_id: i32, ready: bool) -> Result<(), AppError> {
+        Db::set_ready(self, player_id, ready).await
+    }
+
+    async fn start_game(
+        &self,
+        game_code: &str,
+        player_id: i32,
+        min_players: usize,
+    ) -> Result<Vec<Player>, AppError> {
+        Db::start_game(self, game_code, player_id, min_players).await
+    }
+
+    async fn process_kill(
+        &self,
+        game_code: &str,
+        killer_token: &str,
+        proof: &KillProof,
+    ) -> Result<KillOutcome, AppError> {
+        Db::process_kill(self, game_code, killer_token, proof).await
+    }
+
+    async fn leave_game(&self, game_code: &str, auth_token: &str) -> Result<(), AppError> {
+        Db::leave_game(self, game_code, auth_token).await
+    }
+
+    async fn rotate_secret(
+        &self,
+        game_code: &str,
+        auth_token: &str,
+        cooldown_secs: i64,
+        max_rotations: i32,
+    ) -> Result<(String, i32), AppError> {
+        Db::rotate_secret(self, game_code, auth_token, cooldown_secs, max_rotations).await
+    }
+
+    async fn get_secret_generation(&self, player_id: i32) -> Result<i32, AppError> {
+        Db::get_secret_generation(self, player_id).await
+    }
+
+    async fn get_all_games(&self) -> Result<Vec<GameInfo>, AppError> {
+        Ok(Db::get_all_games(self).await?)
+    }
+
+    async fn get_game_by_code(&self, code: &str) -> Result<Option<Game>, AppError> {
+        Db::get_game_by_code(self, code).await
+    }
+
+    async fn get_game_by_id(&self, game_id: i32) -> Result<Option<Game>, AppError> {
+        Ok(Db::get_game_by_id(self, game_id).await?)
+    }
+
+    async fn get_game_state(
+        &self,
+        game_code: &str,
+    ) -> Result<Option<(Game, Vec<Player>)>, AppError> {
+        Db::get_game_state(self, game_code).await
+    }
+
+    async fn get_players_by_game_id(&self, game_id: i32) -> Result<Vec<Player>, AppError> {
+        Ok(Db::get_players_by_game_id(self, &**self, game_id).await?)
+    }
+
+    async fn get_player_by_auth_token(&self, auth_token: &str) -> Result<Option<Player>, AppError> {
+        Ok(Db::get_player_by_auth_token(self, auth_token).await?)
+    }
+
+    async fn check_ring(&self, game_code: &str) -> Result<Vec<RingViolation>, AppError> {
+        Db::check_ring(self, game_code).await
+    }
+
+    async fn repair_ring(&self, game_code: &str) -> Result<RingRepair, AppError> {
+        Db::repair_ring(self, game_code).await
+    }
+
+    async fn get_events(&self, game_id: i32) -> Result<Vec<GameEvent>, AppError> {
+        Db::get_events(self, game_id).await
+    }
+
+    async fn get_events_page(
+        &self,
+        game_id: i32,
+        before: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<GameEvent>, AppError> {
+        Db::get_events_page(self, game_id, before, limit).await
+    }
+
+    async fn get_spectator_token(&self, game_id: i32) -> Result<Option<String>, AppError> {
+        Db::get_spectator_token(self, game_id).await
+    }
+
+    async fn get_game_settings(&self, game_id: i32) -> Result<Option<GameSettings>, AppError> {
+        Db::get_game_settings(self, game_id).await
+    }
+
+    async fn set_game_settings(
+        &self,
+        game_id: i32,
+        settings: &GameSettings,
+    ) -> Result<(), AppError> {
+        Db::set_game_settings(self, game_id, settings).await
+    }
+
+    async fn open_dispute(
+        &self,
+        game_id: i32,
+        event_id: i64,
+        victim_id: i32,
+        killer_id: Option<i32>,
+    ) -> Result<i64, AppError> {
+        Db::open_dispute(self, game_id, event_id, victim_id, killer_id).await
+    }
+
+    async fn get_disputes(&self, game_id: i32) -> Result<Vec<KillDispute>, AppError> {
+        Db::get_disputes(self, game_id).await
+    }
+
+    async fn vote_on_dispute(
+        &self,
+        game_code: &str,
+        dispute_id: i64,
+        voter_id: i32,
+        overturn: bool,
+    ) -> Result<KillDispute, AppError> {
+        Db::vote_on_dispute(self, game_code, dispute_id, voter_id, overturn).await
+    }
+
+    async fn send_hint(
+        &self,
+        game_id: i32,
+        sender_id: i32,
+        recipient_id: i32,
+        message: &str,
+    ) -> Result<(), AppError> {
+        Db::send_hint(self, game_id, sender_id, recipient_id, message).await
+    }
+
+    async fn get_hints(&self, game_id: i32) -> Result<Vec<GhostHint>, AppError> {
+        Db::get_hints(self, game_id).await
+    }
+
+    async fn post_message(
+        &self,
+        channel: ChatChannel,
+        sender: &Player,
+        body: &str,
+    ) -> Result<ChatMessage, AppError> {
+        Db::post_message(self, channel, sender, body).await
+    }
+
+    async fn get_messages(&self, game_id: i32) -> Result<Vec<ChatMessage>, AppError> {
+        Db::get_messages(self, game_id).await
+    }
+
+    async fn request_recovery(
+        &self,
+        game_id: i32,
+        player_id: i32,
+    ) -> Result<RecoveryRequest, AppError> {
+        Db::request_recovery(self, game_id, player_id).await
+    }
+
+    async fn get_recovery_requests(&self, game_id: i32) -> Result<Vec<RecoveryRequest>, AppError> {
+        Db::get_recovery_requests(self, game_id).await
+    }
+
+    async fn get_recovery_request(
+        &self,
+        claim_token: &str,
+    ) -> Result<Option<RecoveryRequest>, AppError> {
+        Db::get_recovery_request(self, claim_token).await
+    }
+
+    async fn decide_recovery(
+        &self,
+        game_id: i32,
+        request_id: i64,
+        approve: bool,
+    ) -> Result<bool, AppError> {
+        Db::decide_recovery(self, game_id, request_id, approve).await
+    }
+
+    async fn request_join(&self, game_id: i32, player_name: &str) -> Result<JoinRequest, AppError> {
+        Db::request_join(self, game_id, player_name).await
+    }
+
+    async fn get_join_requests(&self, game_id: i32) -> Result<Vec<JoinRequest>, AppError> {
+        Db::get_join_requests(self, game_id).await
+    }
+
+    async fn get_join_request(&self, claim_token: &str) -> Result<Option<JoinRequest>, AppError> {
+        Db::get_join_request(self, claim_token).await
+    }
+
+    async fn decide_join_request(
+        &self,
+        game_code: &str,
+        request_id: i64,
+        approve: bool,
+        max_players: usize,
+    ) -> Result<bool, AppError> {
+        Db::decide_join_request(self, game_code, request_id, approve, max_players).await
+    }
+
+    async fn check_ready(&self) -> Result<(), AppError> {
+        let pending = pending_migrations(&MIGRATOR, &self.applied_migrations().await?);
+        if !pending.is_empty() {
+            warn!(?pending, "Database migrations have not been applied");
+            return Err(AppError::InternalServerError);
+        }
+        Ok(())
+    }
+
+    async fn close(&self) {
+        // `Db` derefs to its pool.
+        (**self).close().await;
+    }
+
+    fn pool_stats(&self) -> Option<PoolStats> {
+        Some(PoolStats::of(self))
+    }
+
+    async fn force_finish_game(&self, game_code: &str) -> Result<bool, AppError> {
+        Db::force_finish_game(self, game_code).await
+    }
+
+    async fn purge_games(&self, older_than: Duration) -> Result<Vec<String>, AppError> {
+        Ok(Db::purge_games(self, older_than).await?)
+    }
+}
@@ -0,0 +1,1394 @@
+use super::{
+    parse_chat_channel, parse_dispute_status, parse_event_kind, parse_request_status,
+    pending_migrations, ring_must_hold, GameRepository, KillOutcome, NewPlayer, PoolStats,
+};
+use crate::engine::{self, RingPlan, RingRepair, RingViolation};
+use crate::errors::{AppError, ErrorCode};
+use crate::kill_token::{unix_now, KillProof};
+use crate::models::{
+    ChatChannel, ChatMessage, DisputeStatus, Game, GameEvent, GameEventKind, GameInfo,
+    GameSettings, GameStatus, GhostHint, JoinRequest, KillDispute, NewGameEvent, Player,
+    RecoveryRequest, RequestStatus,
+};
+use crate::utils::{generate_code, normalise_name};
+use async_trait::async_trait;
+use sqlx::migrate::Migrator;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
+use sqlx::types::Json;
+use sqlx::{Executor, Sqlite, SqlitePool, Transaction};
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+/// Stores games in a single SQLite file, for small self-hosted deployments
+/// where running Postgres next to the app is overkill.
+///
+/// The SQL sticks to what Postgres and SQLite both understand (`$n`
+/// placeholders, `RETURNING`, `TRUE`/`FALSE`); only row locking differs.
+/// SQLite has no `FOR UPDATE`, so every mutating transaction starts with
+/// `BEGIN IMMEDIATE` and holds the database write lock instead.
+#[derive(Debug, Clone)]
+pub struct SqliteRepository(SqlitePool);
+
+type Tx = Transaction<'static, Sqlite>;
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations_sqlite");
+
+/// Every column of a player plus the name of their target, as all player
+/// queries return it.
+const SELECT_PLAYER: &str = "
+    SELECT
+        p.id,
+        p.name,
+        p.secret_code,
+        p.auth_token,
+        p.is_alive,
+        p.is_ready,
+        p.target_id,
+        p.game_id,
+        COALESCE(t.name, '') AS target_name
+    FROM players p
+    LEFT JOIN players t ON p.target_id = t.id";
+
+fn game_not_found() -> AppError {
+    AppError::NotFound(ErrorCode::GameNotFound, "error.GAME_NOT_FOUND")
+}
+
+fn unknown_secret() -> AppError {
+    AppError::NotFound(ErrorCode::UnknownSecret, "error.UNKNOWN_SECRET")
+}
+
+impl SqliteRepository {
+    /// Open (creating if needed) the database at `url` and run migrations.
+    pub async fn connect(url: &str, max_connections: u32) -> Result<Self, sqlx::Error> {
+        info!("Opening SQLite database at {}", url);
+        let options = SqliteConnectOptions::from_str(url)?
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal);
+        // Every connection to `:memory:` would get its own empty database.
+        let in_memory = url.contains(":memory:") || url.contains("mode=memory");
+        let pool = SqlitePoolOptions::new()
+            .max_connections(if in_memory { 1 } else { max_connections })
+            .connect_with(options)
+            .await?;
+        Self::from_pool(pool).await
+    }
+
+    /// Open the existing database at `url`, leaving its schema as it is.
+    pub async fn open(url: &str, max_connections: u32) -> Result<Self, sqlx::Error> {
+        info!("Opening SQLite database at {}", url);
+        let options = SqliteConnectOptions::from_str(url)?;
+        let pool = SqlitePoolOptions::new()
+            .max_connections(max_connections)
+            .connect_with(options)
+            .await?;
+        Ok(SqliteRepository(pool))
+    }
+
+    /// Wrap an existing pool and bring its schema up to date.
+    pub async fn from_pool(pool: SqlitePool) -> Result<Self, sqlx::Error> {
+        info!("Running SQLite migrations...");
+        MIGRATOR.run(&pool).await?;
+        info!("SQLite migrations complete.");
+        Ok(SqliteRepository(pool))
+    }
+
+    /// Start a transaction that takes the write lock straight away, so two
+    /// writers queue up instead of failing halfway with `SQLITE_BUSY`.
+    async fn begin_write(&self) -> Result<Tx, AppError> {
+        Ok(self.0.begin_with("BEGIN IMMEDIATE").await?)
+    }
+
+    async fn players_of<'e, E>(&self, executor: E, game_id: i32) -> Result<Vec<Player>, AppError>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let sql = format!("{SELECT_PLAYER} WHERE p.game_id = $1 ORDER BY p.id");
+        Ok(sqlx::query_as(&sql)
+            .bind(game_id)
+            .fetch_all(executor)
+            .await?)
+    }
+
+    async fn game_by_code<'e, E>(&self, executor: E, code: &str) -> Result<Option<Game>, AppError>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        Ok(
+            sqlx::query_as("SELECT id, status, host_id, code FROM games WHERE code = $1")
+                .bind(code)
+                .fetch_optional(executor)
+                .await?,
+        )
+    }
+
+    async fn player_in_game(
+        &self,
+        tx: &mut Tx,
+        game_id: i32,
+        column: &str,
+        value: &str,
+    ) -> Result<Option<Player>, AppError> {
+        let sql = format!("{SELECT_PLAYER} WHERE p.game_id = $1 AND p.{column} = $2");
+        Ok(sqlx::query_as(&sql)
+            .bind(game_id)
+            .bind(value)
+            .fetch_optional(&mut **tx)
+            .await?)
+    }
+
+    async fn insert_player(
+        &self,
+        tx: &mut Tx,
+        game_id: i32,
+        name: &str,
+    ) -> Result<(i32, String, String), sqlx::Error> {
+        let secret = generate_code(7);
+        let auth_token = Uuid::new_v4().to_string();
+        let id = sqlx::query_scalar(
+            "INSERT INTO players (game_id, name, secret_code, auth_token) VALUES ($1, $2, $3, $4) RETURNING id",
+        )
+        .bind(game_id)
+        .bind(name)
+        .bind(&secret)
+        .bind(&auth_token)
+        .fetch_one(&mut **tx)
+        .await?;
+        Ok((id, secret, auth_token))
+    }
+
+    /// Add `name` to `game`, splicing them into the ring if it is already
+    /// running.
+    async fn join(
+        &self,
+        tx: &mut Tx,
+        game: &Game,
+        name: &str,
+        max_players: usize,
+        late_join: bool,
+    ) -> Result<(i32, String, String), AppError> {
+        // SQLite's LOWER only folds ASCII, so compare names in Rust.
+        let normalised = normalise_name(name);
+        let players = self.players_of(&mut **tx, game.id).await?;
+        let same_name = players
+            .iter()
+            .find(|p| normalise_name(&p.name) == normalised);
+        engine::check_join(game, same_name, players.len(), max_players, late_join)?;
+
+        let (player_id, secret, auth_token) =
+            self.insert_player(tx, game.id, name).await.map_err(|e| {
+                match e.as_database_error() {
+                    Some(db_err) if db_err.is_unique_violation() => {
+                        AppError::Conflict(ErrorCode::NameTaken, "error.NAME_TAKEN")
+                    }
+                    _ => AppError::from(e),
+                }
+            })?;
+        if game.status == GameStatus::InProgress {
+            let players = self.players_of(&mut **tx, game.id).await?;
+            let changes =
+                engine::plan_late_join(&players, player_id).ok_or(AppError::InternalServerError)?;
+            for (pid, target_id) in changes {
+                sqlx::query("UPDATE players SET target_id = $1 WHERE id = $2")
+                    .bind(target_id)
+                    .bind(pid)
+                    .execute(&mut **tx)
+                    .await?;
+            }
+            info!(
+                game_id = game.id,
+                player_id, "Late joiner spliced into the ring"
+            );
+        }
+        let joined = NewGameEvent::new(GameEventKind::Joined).by(Some(player_id), name);
+        self.record_event(tx, game.id, &joined).await?;
+        Ok((player_id, secret, auth_token))
+    }
+
+    async fn finish_game(
+        &self,
+        tx: &mut Tx,
+        game_id: i32,
+        winner_id: Option<i32>,
+    ) -> Result<(), AppError> {
+        sqlx::query("UPDATE games SET status = 'finished', winner_id = $1 WHERE id = $2")
+            .bind(winner_id)
+            .bind(game_id)
+            .execute(&mut **tx)
+            .await?;
+        sqlx::query("UPDATE players SET target_id = NULL WHERE game_id = $1")
+            .bind(game_id)
+            .execute(&mut **tx)
+            .await?;
+
+        let mut finished = NewGameEvent::new(GameEventKind::Finished);
+        if let Some(winner_id) = winner_id {
+            let name: String = sqlx::query_scalar("SELECT name FROM players WHERE id = $1")
+                .bind(winner_id)
+                .fetch_one(&mut **tx)
+                .await?;
+            finished = finished.by(Some(winner_id), name);
+        }
+        self.record_event(tx, game_id, &finished).await
+    }
+
+    /// Append to a game's event log as part of the change it describes.
+    async fn record_event(
+        &self,
+        tx: &mut Tx,
+        game_id: i32,
+        event: &NewGameEvent,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            "INSERT INTO game_events
+                 (game_id, kind, actor_id, actor_name, subject_id, subject_name, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(game_id)
+        .bind(event.kind.as_str())
+        .bind(event.actor_id)
+        .bind(&event.actor_name)
+        .bind(event.subject_id)
+        .bind(&event.subject_name)
+        .bind(unix_now())
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+
+    /// Take a living player out of the ring without a kill: their hunter
+    /// inherits their target and the game ends once one player is left.
+    async fn remove_from_ring(&self, tx: &mut Tx, player: &Player) -> Result<(), AppError> {
+        sqlx::query("UPDATE players SET is_alive = FALSE, target_id = NULL WHERE id = $1")
+            .bind(player.id)
+            .execute(&mut **tx)
+            .await?;
+        sqlx::query(
+            "UPDATE players SET target_id = $1 WHERE game_id = $2 AND target_id = $3 AND is_alive = TRUE",
+        )
+        .bind(player.target_id)
+        .bind(player.game_id)
+        .bind(player.id)
+        .execute(&mut **tx)
+        .await?;
+
+        let survivors: Vec<i32> =
+            sqlx::query_scalar("SELECT id FROM players WHERE game_id = $1 AND is_alive = TRUE")
+                .bind(player.game_id)
+                .fetch_all(&mut **tx)
+                .await?;
+        if survivors.len() <= 1 {
+            self.finish_game(tx, player.game_id, survivors.first().copied())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Put the victim of an overturned kill back into the ring behind their
+    /// killer, see [`engine::plan_splice`].
+    async fn revive(
+        &self,
+        tx: &mut Tx,
+        game: &Game,
+        players: &[Player],
+        dispute: &KillDispute,
+    ) -> Result<(), AppError> {
+        let Some(victim) = players
+            .iter()
+            .find(|p| p.id == dispute.victim_id && !p.is_alive)
+        else {
+            return Ok(());
+        };
+        let Some(changes) = engine::plan_splice(players, victim.id, dispute.killer_id) else {
+            return Ok(());
+        };
+        sqlx::query("UPDATE players SET is_alive = TRUE WHERE id = $1")
+            .bind(victim.id)
+            .execute(&mut **tx)
+            .await?;
+        for (pid, target_id) in changes {
+            sqlx::query("UPDATE players SET target_id = $1 WHERE id = $2")
+                .bind(target_id)
+                .bind(pid)
+                .execute(&mut **tx)
+                .await?;
+        }
+        let revived = NewGameEvent::new(GameEventKind::Revived).by(Some(victim.id), &victim.name);
+        self.record_event(tx, game.id, &revived).await?;
+        info!(
+            game_code = %game.code,
+            dispute_id = dispute.id,
+            "Kill overturned, victim is back"
+        );
+        Ok(())
+    }
+
+    /// In debug builds, fail `tx` when it leaves the ring of a running game
+    /// broken, as the Postgres repository does before every commit.
+    async fn assert_ring_in_tx(&self, tx: &mut Tx, game_id: i32) -> Result<(), AppError> {
+        if !cfg!(debug_assertions) {
+            return Ok(());
+        }
+        let status: Option<GameStatus> =
+            sqlx::query_scalar("SELECT status FROM games WHERE id = $1")
+                .bind(game_id)
+                .fetch_optional(&mut **tx)
+                .await?;
+        if status != Some(GameStatus::InProgress) {
+            return Ok(());
+        }
+        let players = self.players_of(&mut **tx, game_id).await?;
+        ring_must_hold(game_id, &players)
+    }
+
+    /// Validate the ring of a running game and rebuild it when broken.
+    async fn repair_ring_in_tx(&self, tx: &mut Tx, game_id: i32) -> Result<RingRepair, AppError> {
+        let status: Option<GameStatus> =
+            sqlx::query_scalar("SELECT status FROM games WHERE id = $1")
+                .bind(game_id)
+                .fetch_optional(&mut **tx)
+                .await?;
+        if status != Some(GameStatus::InProgress) {
+            return Ok(RingRepair::default());
+        }
+        let players = self.players_of(&mut **tx, game_id).await?;
+        let violations = engine::ring_violations(&players);
+        if violations.is_empty() {
+            return Ok(RingRepair::default());
+        }
+        warn!(game_id, ?violations, "Target ring is broken, repairing");
+
+        let mut repair = RingRepair {
+            violations,
+            ..RingRepair::default()
+        };
+        match engine::plan_ring_repair(&players) {
+            RingPlan::Finish { winner_id } => {
+                self.finish_game(tx, game_id, winner_id).await?;
+                repair.winner_id = winner_id;
+                repair.reassigned = winner_id.map(|id| (id, None)).into_iter().collect();
+            }
+            RingPlan::Retarget(changes) => {
+                for (pid, target_id) in changes {
+                    sqlx::query("UPDATE players SET target_id = $1 WHERE id = $2")
+                        .bind(target_id)
+                        .bind(pid)
+                        .execute(&mut **tx)
+                        .await?;
+                    repair.reassigned.push((pid, Some(target_id)));
+                }
+            }
+        }
+        Ok(repair)
+    }
+}
+
+/// `(id, kind, actor_id, actor_name, subject_id, subject_name, created_at)`
+type EventRow = (
+    i64,
+    String,
+    Option<i32>,
+    Option<String>,
+    Option<i32>,
+    Option<String>,
+    i64,
+);
+
+fn event_from_row(
+    game_id: i32,
+    (id, kind, actor_id, actor_name, subject_id, subject_name, created_at): EventRow,
+) -> Result<GameEvent, AppError> {
+    Ok(GameEvent {
+        id,
+        game_id,
+        kind: parse_event_kind(&kind)?,
+        actor_id,
+        actor_name,
+        subject_id,
+        subject_name,
+        created_at,
+    })
+}
+
+#[async_trait]
+impl GameRepository for SqliteRepository {
+    async fn create_game(
+        &self,
+        player_name: String,
+        game_code: String,
+    ) -> Result<NewPlayer, AppError> {
+        let player_name = player_name.trim();
+        info!(
+            "Creating game with code {} for player {}",
+            game_code, player_name
+        );
+        let mut tx = self.begin_write().await?;
+        let game_id: i32 = sqlx::query_scalar(
+            "INSERT INTO games (code, spectator_token) VALUES ($1, $2) RETURNING id",
+        )
+        .bind(&game_code)
+        .bind(Uuid::new_v4().simple().to_string())
+        .fetch_one(&mut *tx)
+        .await?;
+        let (player_id, secret, auth_token) =
+            self.insert_player(&mut tx, game_id, player_name).await?;
+        sqlx::query("UPDATE games SET host_id = $1 WHERE id = $2")
+            .bind(player_id)
+            .bind(game_id)
+            .execute(&mut *tx)
+            .await?;
+        let joined = NewGameEvent::new(GameEventKind::Joined).by(Some(player_id), player_name);
+        self.record_event(&mut tx, game_id, &joined).await?;
+        self.assert_ring_in_tx(&mut tx, game_id).await?;
+        tx.commit().await?;
+        Ok((game_id, player_id, secret, auth_token))
+    }
+
+    async fn join_game(
+        &self,
+        game_code: String,
+        player_name: String,
+        max_players: usize,
+        late_join: bool,
+    ) -> Result<NewPlayer, AppError> {
+        let player_name = player_name.trim();
+        info!("Player {} joining game {}", player_name, game_code);
+        let mut tx = self.begin_write().await?;
+        let game = self
+            .game_by_code(&mut *tx, &game_code)
+            .await?
+            .ok_or_else(game_not_found)?;
+        let (player_id, secret, auth_token) = self
+            .join(&mut tx, &game, player_name, max_players, late_join)
+            .await?;
+        self.assert_ring_in_tx(&mut tx, game.id).await?;
+        tx.commit().await?;
+        Ok((game.id, player_id, secret, auth_token))
+    }
+
+    async fn set_ready(&self, player_id: i32, ready: bool) -> Result<(), AppError> {
+        sqlx::query("UPDATE players SET is_ready = $1 WHERE id = $2")
+            .bind(ready)
+            .bind(player_id)
+            .execute(&self.0)
+            .await?;
+        Ok(())
+    }
+
+    async fn start_game(
+        &self,
+        game_code: &str,
+        player_id: i32,
+        min_players: usize,
+    ) -> Result<Vec<Player>, AppError> {
+        info!("Starting game {} by player {}", game_code, player_id);
+        let mut tx = self.begin_write().await?;
+        let game = self
+            .game_by_code(&mut *tx, game_code)
+            .await?
+            .ok_or_else(game_not_found)?;
+        let players = self.players_of(&mut *tx, game.id).await?;
+        engine::check_start(&game, player_id, players.len(), min_players)?;
+        let starter = players
+            .iter()
+            .find(|p| p.id == player_id)
+            .ok_or(AppError::InternalServerError)?;
+        let started = NewGameEvent::new(GameEventKind::Started).by(Some(starter.id), &starter.name);
+        self.record_event(&mut tx, game.id, &started).await?;
+
+        let ids = players.iter().map(|p| p.id).collect();
+        for (pid, target_id) in engine::assign_targets(ids) {
+            sqlx::query("UPDATE players SET target_id = $1 WHERE id = $2")
+                .bind(target_id)
+                .bind(pid)
+                .execute(&mut *tx)
+                .await?;
+        }
+        sqlx::query("UPDATE games SET status = 'in_progress' WHERE id = $1")
+            .bind(game.id)
+            .execute(&mut *tx)
+            .await?;
+        self.assert_ring_in_tx(&mut tx, game.id).await?;
+        tx.commit().await?;
+
+        self.players_of(&self.0, game.id).await
+    }
+
+    async fn process_kill(
+        &self,
+        game_code: &str,
+        killer_token: &str,
+        proof: &KillProof,
+    ) -> Result<KillOutcome, AppError> {
+        let mut tx = self.begin_write().await?;
+        debug!("Transaction started for process_kill");
+        let game = self
+            .game_by_code(&mut *tx, game_code)
+            .await?
+            .ok_or_else(game_not_found)?;
+        let killer = self
+            .player_in_game(&mut tx, game.id, "auth_token", killer_token)
+            .await?
+            .ok_or(AppError::Unauthorized)?;
+        let target = match proof {
+            KillProof::Secret(secret) => self
+                .player_in_game(&mut tx, game.id, "secret_code", secret)
+                .await?
+                .ok_or_else(unknown_secret)?,
+            KillProof::Token(claims) => {
+                engine::check_token_game(claims, &game)?;
+                let sql = format!("{SELECT_PLAYER} WHERE p.game_id = $1 AND p.id = $2");
+                let target: Player = sqlx::query_as(&sql)
+                    .bind(game.id)
+                    .bind(claims.victim)
+                    .fetch_optional(&mut *tx)
+                    .await?
+                    .ok_or_else(unknown_secret)?;
+                let generation: i32 =
+                    sqlx::query_scalar("SELECT secret_rotations FROM players WHERE id = $1")
+                        .bind(target.id)
+                        .fetch_one(&mut *tx)
+                        .await?;
+                engine::check_token_generation(claims, generation)?;
+                target
+            }
+        };
+
+        engine::validate_kill(&killer, &target, &game)?;
+
+        if let KillProof::Token(claims) = proof {
+            sqlx::query("DELETE FROM used_kill_tokens WHERE expires_at < $1")
+                .bind(unix_now())
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query(
+                "INSERT INTO used_kill_tokens (nonce, game_id, expires_at) VALUES ($1, $2, $3)",
+            )
+            .bind(&claims.nonce)
+            .bind(game.id)
+            .bind(claims.exp)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| match e.as_database_error() {
+                Some(db_err) if db_err.is_unique_violation() => engine::kill_token_used(),
+                _ => AppError::from(e),
+            })?;
+        }
+
+        let kill = NewGameEvent::new(GameEventKind::Kill)
+            .by(Some(killer.id), &killer.name)
+            .to(Some(target.id), &target.name);
+        self.record_event(&mut tx, game.id, &kill).await?;
+        sqlx::query("UPDATE players SET is_alive = FALSE WHERE id = $1")
+            .bind(target.id)
+            .execute(&mut *tx)
+            .await?;
+        let alive: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM players WHERE game_id = $1 AND is_alive = TRUE",
+        )
+        .bind(game.id)
+        .fetch_one(&mut *tx)
+        .await?;
+        let new_target_name = if alive <= 1 {
+            self.finish_game(&mut tx, game.id, Some(killer.id)).await?;
+            None
+        } else {
+            sqlx::query("UPDATE players SET target_id = $1 WHERE id = $2")
+                .bind(target.target_id)
+                .bind(killer.id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query_scalar("SELECT name FROM players WHERE id = $1")
+                .bind(target.target_id)
+                .fetch_optional(&mut *tx)
+                .await?
+        };
+        self.assert_ring_in_tx(&mut tx, game.id).await?;
+        tx.commit().await?;
+
+        Ok((killer.id, killer.name, target.name, new_target_name))
+    }
+
+    async fn leave_game(&self, game_code: &str, auth_token: &str) -> Result<(), AppError> {
+        let mut tx = self.begin_write().await?;
+        let game = self
+            .game_by_code(&mut *tx, game_code)
+            .await?
+            .ok_or_else(game_not_found)?;
+        let player = self
+            .player_in_game(&mut tx, game.id, "auth_token", auth_token)
+            .await?
+            .ok_or(AppError::Unauthorized)?;
+        info!(
+            game_id = game.id,
+            player_id = player.id,
+            "Player leaving game"
+        );
+        let kind = match game.status {
+            GameStatus::InProgress => GameEventKind::Forfeited,
+            _ => GameEventKind::Left,
+        };
+        let left = NewGameEvent::new(kind).by(Some(player.id), &player.name);
+        self.record_event(&mut tx, game.id, &left).await?;
+
+        match game.status {
+            GameStatus::Lobby => {
+                sqlx::query("DELETE FROM players WHERE id = $1")
+                    .bind(player.id)
+                    .execute(&mut *tx)
+                    .await?;
+                if game.host_id == Some(player.id) {
+                    let remaining = self.players_of(&mut *tx, game.id).await?;
+                    if remaining.is_empty() {
+                        // Last player (the host) left, delete the game
+                        sqlx::query("DELETE FROM games WHERE id = $1")
+                            .bind(game.id)
+                            .execute(&mut *tx)
+                            .await?;
+                    } else {
+                        let new_host_id = engine::next_host(&remaining);
+                        sqlx::query("UPDATE games SET host_id = $1 WHERE id = $2")
+                            .bind(new_host_id)
+                            .bind(game.id)
+                            .execute(&mut *tx)
+                            .await?;
+                        if let Some(host) = remaining.iter().find(|p| Some(p.id) == new_host_id) {
+                            let host_changed = NewGameEvent::new(GameEventKind::HostChanged)
+                                .by(Some(host.id), &host.name);
+                            self.record_event(&mut tx, game.id, &host_changed).await?;
+                        }
+                    }
+                }
+            }
+            GameStatus::InProgress if player.is_alive => {
+                self.remove_from_ring(&mut tx, &player).await?;
+            }
+            _ => {
+                sqlx::query("UPDATE players SET is_alive = FALSE WHERE id = $1")
+                    .bind(player.id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+        self.assert_ring_in_tx(&mut tx, game.id).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn rotate_secret(
+        &self,
+        game_code: &str,
+        auth_token: &str,
+        cooldown_secs: i64,
+        max_rotations: i32,
+    ) -> Result<(String, i32), AppError> {
+        let mut tx = self.begin_write().await?;
+        let game = self
+            .game_by_code(&mut *tx, game_code)
+            .await?
+            .ok_or_else(game_not_found)?;
+        let player = self
+            .player_in_game(&mut tx, game.id, "auth_token", auth_token)
+            .await?
+            .ok_or(AppError::Unauthorized)?;
+        info!(game_id = game.id, player_id = player.id, "Rotating secret");
+        let (rotations, rotated_at): (i32, Option<i64>) =
+            sqlx::query_as("SELECT secret_rotations, secret_rotated_at FROM players WHERE id = $1")
+                .bind(player.id)
+                .fetch_one(&mut *tx)
+                .await?;
+        let now = unix_now();
+        let cooldown_left = rotated_at.map(|at| (cooldown_secs - (now - at)).max(0));
+        engine::check_rotation(&game, &player, rotations, max_rotations, cooldown_left)?;
+
+        let new_secret = generate_code(7);
+        let rotations: i32 = sqlx::query_scalar(
+            "UPDATE players SET secret_code = $1, secret_rotations = secret_rotations + 1, secret_rotated_at = $2 WHERE id = $3 RETURNING secret_rotations",
+        )
+        .bind(&new_secret)
+        .bind(now)
+        .bind(player.id)
+        .fetch_one(&mut *tx)
+        .await?;
+        self.assert_ring_in_tx(&mut tx, game.id).await?;
+        tx.commit().await?;
+
+        debug!("Player {} rotated secret ({} used)", player.id, rotations);
+        Ok((new_secret, max_rotations - rotations))
+    }
+
+    async fn get_secret_generation(&self, player_id: i32) -> Result<i32, AppError> {
+        Ok(
+            sqlx::query_scalar("SELECT secret_rotations FROM players WHERE id = $1")
+                .bind(player_id)
+                .fetch_one(&self.0)
+                .await?,
+        )
+    }
+
+    async fn get_all_games(&self) -> Result<Vec<GameInfo>, AppError> {
+        Ok(sqlx::query_as(
+            "SELECT g.code, g.status, (SELECT COUNT(*) FROM players p WHERE p.game_id = g.id) AS player_count FROM games g",
+        )
+        .fetch_all(&self.0)
+        .await?)
+    }
+
+    async fn get_game_by_code(&self, code: &str) -> Result<Option<Game>, AppError> {
+        self.game_by_code(&self.0, code).await
+    }
+
+    async fn get_game_by_id(&self, game_id: i32) -> Result<Option<Game>, AppError> {
+        Ok(
+            sqlx::query_as("SELECT id, status, host_id, code FROM games WHERE id = $1")
+                .bind(game_id)
+                .fetch_optional(&self.0)
+                .await?,
+        )
+    }
+
+    async fn get_game_state(
+        &self,
+        game_code: &str,
+    ) -> Result<Option<(Game, Vec<Player>)>, AppError> {
+        match self.game_by_code(&self.0, game_code).await? {
+            Some(game) => {
+                let players = self.players_of(&self.0, game.id).await?;
+                Ok(Some((game, players)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn get_players_by_game_id(&self, game_id: i32) -> Result<Vec<Player>, AppError> {
+        self.players_of(&self.0, game_id).await
+    }
+
+    async fn get_player_by_auth_token(&self, auth_token: &str) -> Result<Option<Player>, AppError> {
+        let sql = format!("{SELECT_PLAYER} WHERE p.auth_token = $1");
+        Ok(sqlx::query_as(&sql)
+            .bind(auth_token)
+            .fetch_optional(&self.0)
+            .await?)
+    }
+
+    async fn check_ring(&self, game_code: &str) -> Result<Vec<RingViolation>, AppError> {
+        let game = self
+            .game_by_code(&self.0, game_code)
+            .await?
+            .ok_or_else(game_not_found)?;
+        if game.status != GameStatus::InProgress {
+            return Ok(Vec::new());
+        }
+        Ok(engine::ring_violations(
+            &self.players_of(&self.0, game.id).await?,
+        ))
+    }
+
+    async fn repair_ring(&self, game_code: &str) -> Result<RingRepair, AppError> {
+        let mut tx = self.begin_write().await?;
+        let game = self
+            .game_by_code(&mut *tx, game_code)
+            .await?
+            .ok_or_else(game_not_found)?;
+        let repair = self.repair_ring_in_tx(&mut tx, game.id).await?;
+        tx.commit().await?;
+
+        if !repair.violations.is_empty() {
+            info!(
+                game_code,
+                reassigned = repair.reassigned.len(),
+                "Target ring repaired"
+            );
+        }
+        Ok(repair)
+    }
+
+    async fn get_events(&self, game_id: i32) -> Result<Vec<GameEvent>, AppError> {
+        let rows: Vec<EventRow> = sqlx::query_as(
+            "SELECT id, kind, actor_id, actor_name, subject_id, subject_name, created_at
+             FROM game_events WHERE game_id = $1 ORDER BY id",
+        )
+        .bind(game_id)
+        .fetch_all(&self.0)
+        .await?;
+        rows.into_iter()
+            .map(|row| event_from_row(game_id, row))
+            .collect()
+    }
+
+    async fn get_events_page(
+        &self,
+        game_id: i32,
+        before: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<GameEvent>, AppError> {
+        let rows: Vec<EventRow> = sqlx::query_as(
+            "SELECT id, kind, actor_id, actor_name, subject_id, subject_name, created_at
+             FROM game_events WHERE game_id = $1 AND ($2 IS NULL OR id < $2)
+             ORDER BY id DESC LIMIT $3",
+        )
+        .bind(game_id)
+        .bind(before)
+        .bind(limit)
+        .fetch_all(&self.0)
+        .await?;
+        rows.into_iter()
+            .map(|row| event_from_row(game_id, row))
+            .collect()
+    }
+
+    async fn get_spectator_token(&self, game_id: i32) -> Result<Option<String>, AppError> {
+        Ok(
+            sqlx::query_scalar("SELECT spectator_token FROM games WHERE id = $1")
+                .bind(game_id)
+                .fetch_optional(&self.0)
+                .await?,
+        )
+    }
+
+    async fn get_game_settings(&self, game_id: i32) -> Result<Option<GameSettings>, AppError> {
+        let settings: Option<Json<GameSettings>> =
+            sqlx::query_scalar("SELECT settings FROM games WHERE id = $1")
+                .bind(game_id)
+                .fetch_optional(&self.0)
+                .await?;
+        Ok(settings.map(|Json(settings)| settings))
+    }
+
+    async fn set_game_settings(
+        &self,
+        game_id: i32,
+        settings: &GameSettings,
+    ) -> Result<(), AppError> {
+        sqlx::query("UPDATE games SET settings = $1 WHERE id = $2")
+            .bind(Json(settings))
+            .bind(game_id)
+            .execute(&self.0)
+            .await?;
+        Ok(())
+    }
+
+    async fn open_dispute(
+        &self,
+        game_id: i32,
+        event_id: i64,
+        victim_id: i32,
+        killer_id: Option<i32>,
+    ) -> Result<i64, AppError> {
+        Ok(sqlx::query_scalar(
+            "INSERT INTO kill_disputes (game_id, event_id, victim_id, killer_id, created_at)
+             VALUES ($1, $2, $3, $4, $5) RETURNING id",
+        )
+        .bind(game_id)
+        .bind(event_id)
+        .bind(victim_id)
+        .bind(killer_id)
+        .bind(unix_now())
+        .fetch_one(&self.0)
+        .await?)
+    }
+
+    async fn get_disputes(&self, game_id: i32) -> Result<Vec<KillDispute>, AppError> {
+        let rows: Vec<(i64, i64, i32, Option<i32>, String)> = sqlx::query_as(
+            "SELECT id, event_id, victim_id, killer_id, status
+             FROM kill_disputes WHERE game_id = $1 ORDER BY id",
+        )
+        .bind(game_id)
+        .fetch_all(&self.0)
+        .await?;
+        let votes: Vec<(i64, i32, bool)> = sqlx::query_as(
+            "SELECT v.dispute_id, v.player_id, v.overturn
+             FROM dispute_votes v JOIN kill_disputes d ON d.id = v.dispute_id
+             WHERE d.game_id = $1",
+        )
+        .bind(game_id)
+        .fetch_all(&self.0)
+        .await?;
+        rows.into_iter()
+            .map(|(id, event_id, victim_id, killer_id, status)| {
+                Ok(KillDispute {
+                    id,
+                    game_id,
+                    event_id,
+                    victim_id,
+                    killer_id,
+                    status: parse_dispute_status(&status)?,
+                    votes: votes
+                        .iter()
+                        .filter(|(dispute_id, _, _)| *dispute_id == id)
+                        .map(|&(_, pid, overturn)| (pid, overturn))
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+
+    async fn vote_on_dispute(
+        &self,
+        game_code: &str,
+        dispute_id: i64,
+        voter_id: i32,
+        overturn: bool,
+    ) -> Result<KillDispute, AppError> {
+        let mut tx = self.begin_write().await?;
+        let game = self
+            .game_by_code(&mut *tx, game_code)
+            .await?
+            .ok_or_else(game_not_found)?;
+        let (event_id, victim_id, killer_id, status): (i64, i32, Option<i32>, String) =
+            sqlx::query_as(
+                "SELECT event_id, victim_id, killer_id, status
+                 FROM kill_disputes WHERE id = $1 AND game_id = $2",
+            )
+            .bind(dispute_id)
+            .bind(game.id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(AppError::NotFound(
+                ErrorCode::DisputeNotFound,
+                "error.DISPUTE_NOT_FOUND",
+            ))?;
+        let votes: Vec<(i32, bool)> =
+            sqlx::query_as("SELECT player_id, overturn FROM dispute_votes WHERE dispute_id = $1")
+                .bind(dispute_id)
+                .fetch_all(&mut *tx)
+                .await?;
+        let mut dispute = KillDispute {
+            id: dispute_id,
+            game_id: game.id,
+            event_id,
+            victim_id,
+            killer_id,
+            status: parse_dispute_status(&status)?,
+            votes,
+        };
+        let players = self.players_of(&mut *tx, game.id).await?;
+        let outcome = engine::cast_vote(&mut dispute, &players, voter_id, overturn)?;
+
+        sqlx::query(
+            "INSERT INTO dispute_votes (dispute_id, player_id, overturn) VALUES ($1, $2, $3)
+             ON CONFLICT (dispute_id, player_id) DO UPDATE SET overturn = excluded.overturn",
+        )
+        .bind(dispute_id)
+        .bind(voter_id)
+        .bind(overturn)
+        .execute(&mut *tx)
+        .await?;
+        if let Some(outcome) = outcome {
+            sqlx::query("UPDATE kill_disputes SET status = $1 WHERE id = $2")
+                .bind(outcome.as_str())
+                .bind(dispute_id)
+                .execute(&mut *tx)
+                .await?;
+            dispute.status = outcome;
+            if outcome == DisputeStatus::Overturned && game.status == GameStatus::InProgress {
+                self.revive(&mut tx, &game, &players, &dispute).await?;
+            }
+            info!(game_code, dispute_id, ?outcome, "Dispute settled");
+        }
+        self.assert_ring_in_tx(&mut tx, game.id).await?;
+        tx.commit().await?;
+        Ok(dispute)
+    }
+
+    async fn send_hint(
+        &self,
+        game_id: i32,
+        sender_id: i32,
+        recipient_id: i32,
+        message: &str,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            "INSERT INTO ghost_hints (game_id, sender_id, recipient_id, message, created_at)
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(game_id)
+        .bind(sender_id)
+        .bind(recipient_id)
+        .bind(message)
+        .bind(unix_now())
+        .execute(&self.0)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_hints(&self, game_id: i32) -> Result<Vec<GhostHint>, AppError> {
+        let rows: Vec<(i64, i32, i32, String, i64)> = sqlx::query_as(
+            "SELECT id, sender_id, recipient_id, message, created_at
+             FROM ghost_hints WHERE game_id = $1 ORDER BY id",
+        )
+        .bind(game_id)
+        .fetch_all(&self.0)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(
+                |(id, sender_id, recipient_id, message, created_at)| GhostHint {
+                    id,
+                    game_id,
+                    sender_id,
+                    recipient_id,
+                    message,
+                    created_at,
+                },
+            )
+            .collect())
+    }
+
+    async fn post_message(
+        &self,
+        channel: ChatChannel,
+        sender: &Player,
+        body: &str,
+    ) -> Result<ChatMessage, AppError> {
+        let created_at = unix_now();
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO chat_messages (game_id, channel, sender_id, sender_name, body, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6) RETURNING id",
+        )
+        .bind(sender.game_id)
+        .bind(channel.as_str())
+        .bind(sender.id)
+        .bind(&sender.name)
+        .bind(body)
+        .bind(created_at)
+        .fetch_one(&self.0)
+        .await?;
+        Ok(ChatMessage {
+            id,
+            game_id: sender.game_id,
+            channel,
+            sender_id: Some(sender.id),
+            sender_name: sender.name.clone(),
+            body: body.to_string(),
+            created_at,
+        })
+    }
+
+    async fn get_messages(&self, game_id: i32) -> Result<Vec<ChatMessage>, AppError> {
+        let rows: Vec<(i64, String, Option<i32>, String, String, i64)> = sqlx::query_as(
+            "SELECT id, channel, sender_id, sender_name, body, created_at
+             FROM chat_messages WHERE game_id = $1 ORDER BY id",
+        )
+        .bind(game_id)
+        .fetch_all(&self.0)
+        .await?;
+        rows.into_iter()
+            .map(|(id, channel, sender_id, sender_name, body, created_at)| {
+                Ok(ChatMessage {
+                    id,
+                    game_id,
+                    channel: parse_chat_channel(&channel)?,
+                    sender_id,
+                    sender_name,
+                    body,
+                    created_at,
+                })
+            })
+            .collect()
+    }
+
+    async fn request_recovery(
+        &self,
+        game_id: i32,
+        player_id: i32,
+    ) -> Result<RecoveryRequest, AppError> {
+        let claim_token = Uuid::new_v4().simple().to_string();
+        let created_at = unix_now();
+        let mut tx = self.begin_write().await?;
+        sqlx::query("DELETE FROM recovery_requests WHERE player_id = $1 AND status <> 'pending'")
+            .bind(player_id)
+            .execute(&mut *tx)
+            .await?;
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO recovery_requests (game_id, player_id, claim_token, created_at)
+             VALUES ($1, $2, $3, $4) RETURNING id",
+        )
+        .bind(game_id)
+        .bind(player_id)
+        .bind(&claim_token)
+        .bind(created_at)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| match e.as_database_error() {
+            Some(db_err) if db_err.is_unique_violation() => engine::recovery_pending(),
+            _ => AppError::from(e),
+        })?;
+        tx.commit().await?;
+        Ok(RecoveryRequest {
+            id,
+            game_id,
+            player_id,
+            claim_token,
+            status: RequestStatus::Pending,
+            auth_token: None,
+            created_at,
+        })
+    }
+
+    async fn get_recovery_requests(&self, game_id: i32) -> Result<Vec<RecoveryRequest>, AppError> {
+        let rows: Vec<(i64, i32, String, String, Option<String>, i64)> = sqlx::query_as(
+            "SELECT id, player_id, claim_token, status, auth_token, created_at
+             FROM recovery_requests WHERE game_id = $1 ORDER BY id",
+        )
+        .bind(game_id)
+        .fetch_all(&self.0)
+        .await?;
+        rows.into_iter()
+            .map(
+                |(id, player_id, claim_token, status, auth_token, created_at)| {
+                    Ok(RecoveryRequest {
+                        id,
+                        game_id,
+                        player_id,
+                        claim_token,
+                        status: parse_request_status(&status)?,
+                        auth_token,
+                        created_at,
+                    })
+                },
+            )
+            .collect()
+    }
+
+    async fn get_recovery_request(
+        &self,
+        claim_token: &str,
+    ) -> Result<Option<RecoveryRequest>, AppError> {
+        let row: Option<(i64, i32, i32, String, Option<String>, i64)> = sqlx::query_as(
+            "SELECT id, game_id, player_id, status, auth_token, created_at
+             FROM recovery_requests WHERE claim_token = $1",
+        )
+        .bind(claim_token)
+        .fetch_optional(&self.0)
+        .await?;
+        row.map(|(id, game_id, player_id, status, auth_token, created_at)| {
+            Ok(RecoveryRequest {
+                id,
+                game_id,
+                player_id,
+                claim_token: claim_token.to_string(),
+                status: parse_request_status(&status)?,
+                auth_token,
+                created_at,
+            })
+        })
+        .transpose()
+    }
+
+    async fn decide_recovery(
+        &self,
+        game_id: i32,
+        request_id: i64,
+        approve: bool,
+    ) -> Result<bool, AppError> {
+        let (status, auth_token) = match approve {
+            true => (RequestStatus::Approved, Some(Uuid::new_v4().to_string())),
+            false => (RequestStatus::Denied, None),
+        };
+        let mut tx = self.begin_write().await?;
+        let Some(player_id): Option<i32> = sqlx::query_scalar(
+            "UPDATE recovery_requests SET status = $1, auth_token = $2
+             WHERE id = $3 AND game_id = $4 AND status = 'pending'
+             RETURNING player_id",
+        )
+        .bind(status.as_str())
+        .bind(&auth_token)
+        .bind(request_id)
+        .bind(game_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(false);
+        };
+        if let Some(auth_token) = &auth_token {
+            sqlx::query("UPDATE players SET auth_token = $1 WHERE id = $2")
+                .bind(auth_token)
+                .bind(player_id)
+                .execute(&mut *tx)
+                .await?;
+            info!(game_id, player_id, "Player recovered with a new auth token");
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn request_join(&self, game_id: i32, player_name: &str) -> Result<JoinRequest, AppError> {
+        let player_name = player_name.trim().to_string();
+        let claim_token = Uuid::new_v4().simple().to_string();
+        let created_at = unix_now();
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO join_requests (game_id, player_name, claim_token, created_at)
+             VALUES ($1, $2, $3, $4) RETURNING id",
+        )
+        .bind(game_id)
+        .bind(&player_name)
+        .bind(&claim_token)
+        .bind(created_at)
+        .fetch_one(&self.0)
+        .await?;
+        Ok(JoinRequest {
+            id,
+            game_id,
+            player_name,
+            claim_token,
+            status: RequestStatus::Pending,
+            auth_token: None,
+            created_at,
+        })
+    }
+
+    async fn get_join_requests(&self, game_id: i32) -> Result<Vec<JoinRequest>, AppError> {
+        let rows: Vec<(i64, String, String, String, Option<String>, i64)> = sqlx::query_as(
+            "SELECT id, player_name, claim_token, status, auth_token, created_at
+             FROM join_requests WHERE game_id = $1 ORDER BY id",
+        )
+        .bind(game_id)
+        .fetch_all(&self.0)
+        .await?;
+        rows.into_iter()
+            .map(
+                |(id, player_name, claim_token, status, auth_token, created_at)| {
+                    Ok(JoinRequest {
+                        id,
+                        game_id,
+                        player_name,
+                        claim_token,
+                        status: parse_request_status(&status)?,
+                        auth_token,
+                        created_at,
+                    })
+                },
+            )
+            .collect()
+    }
+
+    async fn get_join_request(&self, claim_token: &str) -> Result<Option<JoinRequest>, AppError> {
+        let row: Option<(i64, i32, String, String, Option<String>, i64)> = sqlx::query_as(
+            "SELECT id, game_id, player_name, status, auth_token, created_at
+             FROM join_requests WHERE claim_token = $1",
+        )
+        .bind(claim_token)
+        .fetch_optional(&self.0)
+        .await?;
+        row.map(
+            |(id, game_id, player_name, status, auth_token, created_at)| {
+                Ok(JoinRequest {
+                    id,
+                    game_id,
+                    player_name,
+                    claim_token: claim_token.to_string(),
+                    status: parse_request_status(&status)?,
+                    auth_token,
+                    created_at,
+                })
+            },
+        )
+        .transpose()
+    }
+
+    async fn decide_join_request(
+        &self,
+        game_code: &str,
+        request_id: i64,
+        approve: bool,
+        max_players: usize,
+    ) -> Result<bool, AppError> {
+        let mut tx = self.begin_write().await?;
+        let game = self
+            .game_by_code(&mut *tx, game_code)
+            .await?
+            .ok_or_else(game_not_found)?;
+        let Some(player_name): Option<String> = sqlx::query_scalar(
+            "SELECT player_name FROM join_requests
+             WHERE id = $1 AND game_id = $2 AND status = 'pending'",
+        )
+        .bind(request_id)
+        .bind(game.id)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(false);
+        };
+        let (status, auth_token) = match approve {
+            true => {
+                let (player_id, _, auth_token) = self
+                    .join(&mut tx, &game, &player_name, max_players, true)
+                    .await?;
+                info!(game_code, player_id, "Host let a late joiner in");
+                (RequestStatus::Approved, Some(auth_token))
+            }
+            false => (RequestStatus::Denied, None),
+        };
+        sqlx::query("UPDATE join_requests SET status = $1, auth_token = $2 WHERE id = $3")
+            .bind(status.as_str())
+            .bind(&auth_token)
+            .bind(request_id)
+            .execute(&mut *tx)
+            .await?;
+        self.assert_ring_in_tx(&mut tx, game.id).await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn check_ready(&self) -> Result<(), AppError> {
+        let applied: Vec<i64> = sqlx::query_scalar(
+            "SELECT version FROM _sqlx_migrations WHERE success ORDER BY version",
+        )
+        .fetch_all(&self.0)
+        .await?;
+        let pending = pending_migrations(&MIGRATOR, &applied);
+        if !pending.is_empty() {
+            warn!(?pending, "SQLite migrations have not been applied");
+            return Err(AppError::InternalServerError);
+        }
+        Ok(())
+    }
+
+    async fn close(&self) {
+        self.0.close().await;
+    }
+
+    fn pool_stats(&self) -> Option<PoolStats> {
+        Some(PoolStats::of(&self.0))
+    }
+
+    async fn force_finish_game(&self, game_code: &str) -> Result<bool, AppError> {
+        let mut tx = self.begin_write().await?;
+        let game = self
+            .game_by_code(&mut *tx, game_code)
+            .await?
+            .ok_or_else(game_not_found)?;
+        if game.status == GameStatus::Finished {
+            return Ok(false);
+        }
+        self.finish_game(&mut tx, game.id, None).await?;
+        tx.commit().await?;
+
+        info!(game_code, "Game finished by an administrator");
+        Ok(true)
+    }
+
+    async fn purge_games(&self, older_than: Duration) -> Result<Vec<String>, AppError> {
+        let mut tx = self.begin_write().await?;
+        sqlx::query("DELETE FROM used_kill_tokens WHERE expires_at < $1")
+            .bind(unix_now())
+            .execute(&mut *tx)
+            .await?;
+        // `created_at` holds `CURRENT_TIMESTAMP`, which is UTC text.
+        let codes: Vec<String> = sqlx::query_scalar(
+            "DELETE FROM games WHERE created_at < datetime('now', $1) RETURNING code",
+        )
+        .bind(format!("-{} seconds", older_than.as_secs()))
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        if !codes.is_empty() {
+            info!(count = codes.len(), "Purged old games");
+        }
+        Ok(codes)
+    }
+}
@@ -0,0 +1,78 @@
+//! Request ids, so a player reporting a problem can hand us something to
+//! search the logs for.
+//!
+//! A well-formed `X-Request-Id` from the client or a proxy in front of us is
+//! kept, otherwise a fresh one is generated. Either way it is echoed on the
+//! response, recorded on the request span and included in error bodies.
+
+use axum::{
+    extract::{rejection::RawPathParamsRejection, RawPathParams, Request},
+    http::{HeaderMap, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use uuid::Uuid;
+
+pub const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest id accepted from a client.
+const MAX_LEN: usize = 128;
+
+/// The id of a request, also available as a request extension.
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+tokio::task_local! {
+    static CURRENT_REQUEST_ID: String;
+}
+
+/// Id of the request currently being handled, used where no extractor is
+/// available (e.g. when an `AppError` turns itself into a response).
+pub fn current_request_id() -> Option<String> {
+    CURRENT_REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+/// Only short, printable ids are taken over, so they are safe to log and to
+/// send back as a header.
+fn from_headers(headers: &HeaderMap) -> Option<String> {
+    let id = headers.get(X_REQUEST_ID)?.to_str().ok()?.trim();
+    let well_formed = !id.is_empty()
+        && id.len() <= MAX_LEN
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"-_.:/+=".contains(&b));
+    well_formed.then(|| id.to_string())
+}
+
+/// Middleware that picks the request id and scopes it to the request. Must
+/// wrap the trace layer, which reads it from the request extensions.
+pub async fn request_id_middleware(mut req: Request, next: Next) -> Response {
+    let id = from_headers(req.headers()).unwrap_or_else(|| Uuid::new_v4().to_string());
+    req.extensions_mut().insert(RequestId(id.clone()));
+    let mut response = CURRENT_REQUEST_ID.scope(id.clone(), next.run(req)).await;
+    response.headers_mut().insert(
+        X_REQUEST_ID,
+        HeaderValue::from_str(&id).expect("request ids are printable ASCII"),
+    );
+    response
+}
+
+/// Record the game code on the request span.
+pub async fn span_fields_middleware(
+    params: Result<RawPathParams, RawPathParamsRejection>,
+    req: Request,
+    next: Next,
+) -> Response {
+    for (key, value) in params.iter().flatten() {
+        if key == "game_code" {
+            tracing::Span::current().record("game_code", value);
+        }
+    }
+    next.run(req).await
+}
+
+/// Record the acting player on the request span, once their token has been
+/// looked up anyway.
+pub fn record_player(player_id: i32) {
+    tracing::Span::current().record("player_id", player_id);
+}
@@ -0,0 +1,30 @@
+//! Background clean-up of old games, driven by the `retention` settings.
+
+use crate::state::AppState;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Purge expired games every `purge_interval_secs` for as long as the server
+/// runs. Returns straight away when retention is off.
+pub async fn run_purge_task(state: AppState) {
+    let retention = state.config.retention.clone();
+    let Some(ttl) = retention.game_ttl() else {
+        return;
+    };
+    info!(
+        ttl_hours = retention.game_ttl_hours,
+        "Games older than the retention period will be purged"
+    );
+    let mut interval = tokio::time::interval(Duration::from_secs(retention.purge_interval_secs));
+    loop {
+        interval.tick().await;
+        match state.db.purge_games(ttl).await {
+            Ok(codes) => {
+                for code in codes {
+                    state.versions.remove(&code);
+                }
+            }
+            Err(err) => warn!(error = %err, "Purging old games failed"),
+        }
+    }
+}
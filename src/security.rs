@@ -0,0 +1,77 @@
+//! Cross-origin policy and the security headers sent with every response.
+
+use crate::config::ServerConfig;
+use crate::request_id::X_REQUEST_ID;
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderName, HeaderValue, Method},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+/// Scripts are all served from `/static` (including the bundled `qrcode` and
+/// `html5-qrcode` libraries), so no inline or remote script is allowed.
+/// Styles stay inline-friendly because the templates and the QR scanner use
+/// `style` attributes; `data:`/`blob:` cover the rendered QR codes and the
+/// scanner's icons.
+const CONTENT_SECURITY_POLICY: &str = "default-src 'self'; \
+    script-src 'self'; \
+    style-src 'self' 'unsafe-inline' https://unpkg.com; \
+    img-src 'self' data: blob: https://unpkg.com https://www.newegg.com; \
+    font-src 'self' data: https://unpkg.com; \
+    connect-src 'self'; \
+    media-src 'self' blob:; \
+    object-src 'none'; \
+    base-uri 'self'; \
+    form-action 'self'; \
+    frame-ancestors 'none'";
+
+const STRICT_TRANSPORT_SECURITY: &str = "max-age=31536000; includeSubDomains";
+
+/// Only the configured origins may call the API from a browser; with none
+/// configured no CORS headers are sent and only same-origin pages work.
+pub fn cors_layer(config: &ServerConfig) -> CorsLayer {
+    let origins = config
+        .cors_origins
+        .iter()
+        .map(|origin| HeaderValue::from_str(origin).expect("origins are validated on load"));
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_methods([Method::GET, Method::POST])
+        .allow_headers([
+            header::AUTHORIZATION,
+            header::CONTENT_TYPE,
+            header::ACCEPT_LANGUAGE,
+            X_REQUEST_ID,
+        ])
+        .expose_headers([X_REQUEST_ID])
+}
+
+pub async fn security_headers(
+    State(config): State<Arc<ServerConfig>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    let mut set = |name: HeaderName, value: &'static str| {
+        headers
+            .entry(name)
+            .or_insert(HeaderValue::from_static(value));
+    };
+    set(header::CONTENT_SECURITY_POLICY, CONTENT_SECURITY_POLICY);
+    set(header::X_FRAME_OPTIONS, "DENY");
+    // Player URLs carry their auth token; never leak them to other sites.
+    set(header::REFERRER_POLICY, "no-referrer");
+    set(header::X_CONTENT_TYPE_OPTIONS, "nosniff");
+    set(
+        HeaderName::from_static("permissions-policy"),
+        "camera=(self), microphone=(), geolocation=()",
+    );
+    if config.behind_tls {
+        set(header::STRICT_TRANSPORT_SECURITY, STRICT_TRANSPORT_SECURITY);
+    }
+    response
+}
@@ -1,13 +1,19 @@
-use crate::db::Db;
+use crate::config::Config;
+use crate::kill_token::KillTokenSigner;
+use crate::metrics::Metrics;
+use crate::repository::GameRepository;
 use dashmap::DashMap;
 use std::sync::Arc;
 use tera::Tera;
 
 #[derive(Clone)]
 pub struct AppState {
-    pub db: Db,
+    pub db: Arc<dyn GameRepository>,
     pub tera: Tera,
     pub versions: Arc<DashMap<String, i64>>,
+    pub kill_tokens: KillTokenSigner,
+    pub config: Arc<Config>,
+    pub metrics: Metrics,
 }
 
 impl AppState {
@@ -1,3 +1,4 @@
+use axum::http::{header, HeaderMap};
 use rand::Rng as _;
 
 pub fn generate_code(len: usize) -> String {
@@ -16,3 +17,15 @@ pub fn generate_code(len: usize) -> String {
 pub fn normalise_name(name: &str) -> String {
     name.trim().to_lowercase()
 }
+
+/// The value of the cookie `name` sent with a request.
+pub fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
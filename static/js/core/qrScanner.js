@@ -1,4 +1,5 @@
 import { showToast } from "../utils/ui.js";
+import { strings } from "../utils/strings.js";
 
 let html5QrScanner = null;
 
@@ -78,7 +79,7 @@ export function startScanner(onScanSuccess) {
         )
         .catch((err) => {
             console.error("Failed to start QR scanner:", err);
-            showToast("Unable to start camera for scanning.", "error");
+            showToast(strings("game").scanner_failed, "error");
             stopScanner();
         });
 
@@ -1,6 +1,7 @@
 import { showScreen } from "../utils/ui.js";
 import { gameState } from "./state.js";
 import { route } from "./router.js";
+import { strings } from "../utils/strings.js";
 
 export class ViewManager {
 	#views = new Map();
@@ -15,12 +16,12 @@ export class ViewManager {
 		return this.#views.get(name);
 	}
 
-	update(game, players) {
+	update(game, players, role) {
 		const { playerId } = gameState.getGameDetails();
 		const me = players.find((p) => p.id === playerId);
 
 		if (!me) {
-			alert("You have been removed from the game.");
+			alert(strings("session").removed);
 			// The service will handle the leave logic
 			return;
 		}
@@ -40,7 +41,7 @@ export class ViewManager {
 			} else {
 				const killer = players.find((p) => p.id === me.killed_by);
 				view = this.getView("eliminated");
-				viewData = { killer };
+				viewData = { game, killer, role, players };
 			}
 		} else if (gameStatus === "finished") {
 			const winner = players.find((p) => p.is_alive);
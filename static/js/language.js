@@ -0,0 +1,12 @@
+// Persist the chosen language in a cookie the server negotiates on, then
+// reload so the page renders in it.
+document.addEventListener("DOMContentLoaded", () => {
+	const picker = document.getElementById("languagePicker");
+	if (!picker) return;
+
+	picker.addEventListener("change", () => {
+		const maxAge = 60 * 60 * 24 * 365;
+		document.cookie = `lang=${picker.value}; path=/; max-age=${maxAge}; samesite=lax`;
+		window.location.reload();
+	});
+});
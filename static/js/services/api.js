@@ -3,10 +3,12 @@ import { gameState } from "../core/state.js";
 const API_BASE_URL = `${window.location.protocol}//${window.location.host}`;
 
 class ApiError extends Error {
-	constructor(message, status) {
+	constructor(message, status, code) {
 		super(message);
 		this.name = "ApiError";
 		this.status = status;
+		// Stable machine-readable code from the server, e.g. "WRONG_TARGET".
+		this.code = code;
 	}
 }
 
@@ -26,17 +28,24 @@ async function fetchApi(url, options = {}) {
 
 	if (!response.ok) {
 		let message = `API request failed with status ${response.status}`;
+		let code = null;
 		try {
 			const data = await response.clone().json();
 			if (data && data.error) {
 				message = data.error;
 			}
+			if (data && data.code) {
+				code = data.code;
+			}
 		} catch (_) {
 			// Ignore and fall back to default message
 		}
-		throw new ApiError(message, response.status);
+		throw new ApiError(message, response.status, code);
 	}
 
+	if (response.status === 204) {
+		return null;
+	}
 	return response.json();
 }
 
@@ -68,6 +77,12 @@ export const leaveGame = (gameCode) =>
 		body: JSON.stringify({}),
 	});
 
+export const setReady = (gameCode, ready) =>
+	fetchApi(`${API_BASE_URL}/api/game/${gameCode}/ready`, {
+		method: "POST",
+		body: JSON.stringify({ ready }),
+	});
+
 export const startGame = (gameCode) =>
 	fetchApi(`${API_BASE_URL}/api/game/${gameCode}/start`, {
 		method: "POST",
@@ -78,4 +93,95 @@ export const eliminateTarget = (gameCode, secretCode) =>
 	fetchApi(`${API_BASE_URL}/api/game/${gameCode}/eliminate`, {
 		method: "POST",
 		body: JSON.stringify({ secret_code: secretCode }),
-	}); 
\ No newline at end of file
+	});
+
+export const fetchKillToken = (gameCode) =>
+	fetchApi(`${API_BASE_URL}/api/game/${gameCode}/kill-token`);
+
+export const rotateSecret = (gameCode) =>
+	fetchApi(`${API_BASE_URL}/api/game/${gameCode}/secret/rotate`, {
+		method: "POST",
+		body: JSON.stringify({}),
+	});
+
+export const fetchScoreboard = (gameCode, spectatorToken) =>
+	fetchApi(
+		`${API_BASE_URL}/api/game/${gameCode}/spectate?token=${encodeURIComponent(spectatorToken)}`,
+	);
+
+export const fetchActivity = (gameCode, before) =>
+	fetchApi(
+		`${API_BASE_URL}/api/game/${gameCode}/activity${before ? `?before=${before}` : ""}`,
+	);
+
+export const updateSettings = (gameCode, settings) =>
+	fetchApi(`${API_BASE_URL}/api/game/${gameCode}/settings`, {
+		method: "POST",
+		body: JSON.stringify(settings),
+	});
+
+export const fetchGhost = (gameCode) =>
+	fetchApi(`${API_BASE_URL}/api/game/${gameCode}/ghost`);
+
+export const disputeKill = (gameCode, eventId) =>
+	fetchApi(`${API_BASE_URL}/api/game/${gameCode}/ghost/disputes`, {
+		method: "POST",
+		body: JSON.stringify({ event_id: eventId }),
+	});
+
+export const voteOnDispute = (gameCode, disputeId, overturn) =>
+	fetchApi(
+		`${API_BASE_URL}/api/game/${gameCode}/ghost/disputes/${disputeId}/votes`,
+		{
+			method: "POST",
+			body: JSON.stringify({ overturn }),
+		},
+	);
+
+export const sendHint = (gameCode, playerId, message) =>
+	fetchApi(`${API_BASE_URL}/api/game/${gameCode}/ghost/hint`, {
+		method: "POST",
+		body: JSON.stringify({ player_id: playerId, message }),
+	});
+
+export const fetchHints = (gameCode) =>
+	fetchApi(`${API_BASE_URL}/api/game/${gameCode}/hints`);
+
+export const fetchMessages = (gameCode) =>
+	fetchApi(`${API_BASE_URL}/api/game/${gameCode}/chat`);
+
+export const postMessage = (gameCode, channel, body) =>
+	fetchApi(`${API_BASE_URL}/api/game/${gameCode}/chat`, {
+		method: "POST",
+		body: JSON.stringify({ channel, body }),
+	});
+
+export const requestRecovery = (gameCode, playerName) =>
+	fetchApi(`${API_BASE_URL}/api/game/${gameCode}/recover`, {
+		method: "POST",
+		body: JSON.stringify({ player_name: playerName }),
+	});
+
+export const fetchRecovery = (gameCode, claimToken) =>
+	fetchApi(`${API_BASE_URL}/api/game/${gameCode}/recover/${claimToken}`);
+
+export const fetchRecoveries = (gameCode) =>
+	fetchApi(`${API_BASE_URL}/api/game/${gameCode}/recoveries`);
+
+export const decideRecovery = (gameCode, requestId, approve) =>
+	fetchApi(`${API_BASE_URL}/api/game/${gameCode}/recoveries/${requestId}`, {
+		method: "POST",
+		body: JSON.stringify({ approve }),
+	});
+
+export const fetchJoinRequest = (gameCode, claimToken) =>
+	fetchApi(`${API_BASE_URL}/api/game/${gameCode}/join/${claimToken}`);
+
+export const fetchJoinRequests = (gameCode) =>
+	fetchApi(`${API_BASE_URL}/api/game/${gameCode}/join-requests`);
+
+export const decideJoinRequest = (gameCode, requestId, approve) =>
+	fetchApi(`${API_BASE_URL}/api/game/${gameCode}/join-requests/${requestId}`, {
+		method: "POST",
+		body: JSON.stringify({ approve }),
+	});
@@ -1,6 +1,7 @@
 import { gameState } from "../core/state.js";
 import * as api from "./api.js";
 import { showToast } from "../utils/ui.js";
+import { format, strings } from "../utils/strings.js";
 
 export class GameService {
 	#pollingIntervalId = null;
@@ -44,15 +45,12 @@ export class GameService {
 			// Handle reconnection message if needed
 		} catch (error) {
 			console.error("Error polling for changes:", error);
-			if (error.status === 404) {
-				showToast(
-					"This game session no longer exists. Returning to the home page.",
-					"error",
-				);
+			if (error.code === "GAME_NOT_FOUND") {
+				showToast(strings("session").gone, "error");
 				setTimeout(() => this.leave(), 3000);
 			} else {
 				showToast(
-					`Connection issue: ${error.message}. Will keep trying.`,
+					format(strings("session").connection_issue, { error: error.message }),
 					"error",
 				);
 			}
@@ -62,7 +60,7 @@ export class GameService {
 	async fetchGameState() {
 		const { gameCode } = gameState.getGameDetails();
 		try {
-			const { game, players, version } = await api.fetchGameState(
+			const { game, players, role, version } = await api.fetchGameState(
 				gameCode,
 			);
 			if (typeof version === "number") {
@@ -70,11 +68,11 @@ export class GameService {
 				gameState.setVersion(version);
 			}
 			// Dispatch the new state to the view manager
-			this.viewManager.update(game, players);
+			this.viewManager.update(game, players, role);
 		} catch (error) {
 			console.error("Error fetching game state:", error);
 			showToast(
-				`Failed to update game state: ${error.message}`,
+				format(strings("session").update_failed, { error: error.message }),
 				"error",
 			);
 		}
@@ -108,9 +106,24 @@ export class GameService {
 		const { gameCode } = gameState.getGameDetails();
 		try {
 			await api.eliminateTarget(gameCode, secretCode);
-			showToast("Target elimination attempted!", "info");
+			showToast(strings("game").kill_attempted, "info");
+		} catch (error) {
+			showToast(error.message, "error");
+		}
+	}
+
+	async fetchKillToken() {
+		const { gameCode } = gameState.getGameDetails();
+		return api.fetchKillToken(gameCode);
+	}
+
+	async rotateSecret() {
+		const { gameCode } = gameState.getGameDetails();
+		try {
+			const { rotations_left } = await api.rotateSecret(gameCode);
+			showToast(format(strings("game").rotated, { left: rotations_left }), "info");
 		} catch (error) {
 			showToast(error.message, "error");
 		}
 	}
-} 
\ No newline at end of file
+}
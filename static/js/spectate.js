@@ -0,0 +1,90 @@
+import * as api from "./services/api.js";
+import { eventItem } from "./utils/activity.js";
+import { strings } from "./utils/strings.js";
+import { messageItem } from "./views/chatPanel.js";
+
+const context = JSON.parse(document.getElementById("server-context").textContent);
+const text = strings("spectate");
+const { game_code: gameCode, spectator_token: spectatorToken } = context;
+
+let version = 0;
+let startedAt = null;
+let finishedAt = null;
+
+function clock(seconds) {
+	const h = Math.floor(seconds / 3600);
+	const m = String(Math.floor((seconds % 3600) / 60)).padStart(2, "0");
+	const s = String(seconds % 60).padStart(2, "0");
+	return h > 0 ? `${h}:${m}:${s}` : `${m}:${s}`;
+}
+
+function tick() {
+	const timer = document.getElementById("spectateTimer");
+	if (!startedAt) {
+		timer.textContent = "";
+		return;
+	}
+	const end = finishedAt ?? Math.floor(Date.now() / 1000);
+	timer.textContent = clock(Math.max(0, end - startedAt));
+}
+
+function render(scoreboard) {
+	startedAt = scoreboard.started_at;
+	finishedAt = scoreboard.finished_at;
+
+	const status = {
+		Lobby: text.status_lobby,
+		InProgress: text.status_in_progress,
+		Finished: text.status_finished,
+	}[scoreboard.status];
+	document.getElementById("spectateStatus").textContent = status ?? scoreboard.status;
+
+	const players = document.getElementById("spectatePlayers");
+	players.innerHTML = "";
+	scoreboard.players.forEach((p) => {
+		const li = document.createElement("li");
+		li.textContent = p.is_alive ? p.name : `${p.name} (${text.eliminated})`;
+		if (!p.is_alive) {
+			li.style.textDecoration = "line-through";
+		}
+		players.appendChild(li);
+	});
+
+	const feed = document.getElementById("spectateFeed");
+	feed.innerHTML = "";
+	// Newest first.
+	[...scoreboard.events]
+		.reverse()
+		.forEach((event) => feed.appendChild(eventItem(event)));
+
+	// Only filled when the host lets spectators read along.
+	const chat = document.getElementById("spectateChat");
+	chat.innerHTML = "";
+	scoreboard.messages.forEach((m) => chat.appendChild(messageItem(m, text)));
+	document.getElementById("spectateChatContainer").style.display =
+		scoreboard.messages.length ? "block" : "none";
+	tick();
+}
+
+async function refresh() {
+	const scoreboard = await api.fetchScoreboard(gameCode, spectatorToken);
+	version = scoreboard.version;
+	render(scoreboard);
+}
+
+async function poll() {
+	try {
+		const data = await api.checkChanges(gameCode, version);
+		if (data.changed) {
+			await refresh();
+		}
+	} catch (error) {
+		console.error("Error polling for changes:", error);
+	}
+}
+
+document.addEventListener("DOMContentLoaded", async () => {
+	await refresh();
+	setInterval(poll, 2000);
+	setInterval(tick, 1000);
+});
@@ -0,0 +1,23 @@
+import { format, strings } from "./strings.js";
+
+// One line of text for a game event.
+export function describeEvent(event) {
+	const s = strings("activity");
+	const values = { actor: event.actor_name, subject: event.subject_name };
+	switch (event.kind) {
+		case "kill":
+			// Games can hide who made the kill.
+			return format(event.actor_name ? s.kill : s.kill_anonymous, values);
+		case "finished":
+			return format(event.actor_name ? s.won : s.finished, values);
+		default:
+			return s[event.kind] ? format(s[event.kind], values) : event.kind;
+	}
+}
+
+export function eventItem(event) {
+	const li = document.createElement("li");
+	const time = new Date(event.created_at * 1000).toLocaleTimeString();
+	li.textContent = `${time} ${describeEvent(event)}`;
+	return li;
+}
@@ -0,0 +1,15 @@
+const cache = new Map();
+
+// Translated strings a template rendered into <script id="{name}-strings">.
+export function strings(name) {
+	if (!cache.has(name)) {
+		const element = document.getElementById(`${name}-strings`);
+		cache.set(name, element ? JSON.parse(element.textContent) : {});
+	}
+	return cache.get(name);
+}
+
+// Fill the `{placeholder}`s of a translated template.
+export function format(template, values) {
+	return (template ?? "").replace(/\{(\w+)\}/g, (_, name) => values[name] ?? "");
+}
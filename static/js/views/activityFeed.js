@@ -0,0 +1,48 @@
+import { gameState } from "../core/state.js";
+import * as api from "../services/api.js";
+import { eventItem } from "../utils/activity.js";
+
+let nextCursor = null;
+
+function showOlderButton() {
+	const button = document.getElementById("activityOlderBtn");
+	if (button) {
+		button.style.display = nextCursor ? "block" : "none";
+	}
+}
+
+async function loadOlder() {
+	const { gameCode } = gameState.getGameDetails();
+	if (!nextCursor) return;
+	try {
+		const page = await api.fetchActivity(gameCode, nextCursor);
+		const feed = document.getElementById("activityFeed");
+		page.events.forEach((event) => feed.appendChild(eventItem(event)));
+		nextCursor = page.next_cursor;
+		showOlderButton();
+	} catch (error) {
+		console.error("Failed to load older activity:", error);
+	}
+}
+
+// Re-render the newest page of the feed; older pages load on demand.
+export async function refreshActivity() {
+	const feed = document.getElementById("activityFeed");
+	if (!feed) return;
+	const { gameCode } = gameState.getGameDetails();
+	try {
+		const page = await api.fetchActivity(gameCode);
+		feed.innerHTML = "";
+		page.events.forEach((event) => feed.appendChild(eventItem(event)));
+		nextCursor = page.next_cursor;
+		showOlderButton();
+	} catch (error) {
+		console.error("Failed to load activity:", error);
+	}
+}
+
+export function initActivity() {
+	document
+		.getElementById("activityOlderBtn")
+		?.addEventListener("click", loadOlder);
+}
@@ -0,0 +1,60 @@
+import { gameState } from "../core/state.js";
+import * as api from "../services/api.js";
+import { showToast } from "../utils/ui.js";
+import { strings } from "../utils/strings.js";
+
+export function messageItem(message, s) {
+	const li = document.createElement("li");
+	const time = new Date(message.created_at * 1000).toLocaleTimeString();
+	const sender =
+		message.sender_id === null
+			? `${message.sender_name} ${s.left ?? ""}`.trim()
+			: message.sender_name;
+	li.textContent = `${time} [${s[message.channel] ?? message.channel}] ${sender}: ${message.body}`;
+	if (message.channel === "announcements") {
+		li.style.fontWeight = "bold";
+	}
+	return li;
+}
+
+// Re-render every message the player may read; called on each game change.
+export async function refreshChat() {
+	const list = document.getElementById("chatMessages");
+	if (!list) return;
+	const { gameCode } = gameState.getGameDetails();
+	try {
+		const messages = await api.fetchMessages(gameCode);
+		const atBottom =
+			list.scrollTop + list.clientHeight >= list.scrollHeight - 5;
+		list.innerHTML = "";
+		messages.forEach((m) => list.appendChild(messageItem(m, strings("chat"))));
+		if (atBottom) {
+			list.scrollTop = list.scrollHeight;
+		}
+	} catch (error) {
+		console.error("Failed to load chat:", error);
+	}
+}
+
+async function send() {
+	const { gameCode } = gameState.getGameDetails();
+	const channel = document.getElementById("chatChannel");
+	const body = document.getElementById("chatBody");
+	if (!body.value.trim()) return;
+	try {
+		await api.postMessage(gameCode, channel.value, body.value);
+		body.value = "";
+		await refreshChat();
+	} catch (error) {
+		showToast(error.message, "error");
+	}
+}
+
+export function initChat() {
+	document.getElementById("sendChatBtn")?.addEventListener("click", send);
+	document.getElementById("chatBody")?.addEventListener("keydown", (event) => {
+		if (event.key === "Enter") {
+			send();
+		}
+	});
+}
@@ -1,8 +1,22 @@
-function updateEliminatedUI({ killer }) {
+import { initActivity, refreshActivity } from "./activityFeed.js";
+import { initChat, refreshChat } from "./chatPanel.js";
+import { initGhost, refreshGhost } from "./ghostPanel.js";
+import { refreshRecoveries } from "./recoveryPanel.js";
+import { refreshJoinRequests } from "./joinRequestsPanel.js";
+
+function updateEliminatedUI({ game, killer, role, players }) {
     document.getElementById('killerName').textContent = killer ? killer.name : "an unknown player";
+    refreshGhost(role, players);
+    refreshChat();
+    refreshRecoveries();
+    refreshJoinRequests(game);
+    refreshActivity();
 }
 
 function initEliminated(gameService) {
+    initActivity();
+    initGhost();
+    initChat();
     document.querySelector('.title-bar-controls button[aria-label="Close"]')?.addEventListener('click', () => gameService.leave());
     document.getElementById('backToMenuBtn')?.addEventListener('click', () => gameService.leave());
 }
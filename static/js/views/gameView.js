@@ -1,11 +1,20 @@
 import { gameState } from "../core/state.js";
+import * as api from "../services/api.js";
 import { showToast } from "../utils/ui.js";
+import { format, strings } from "../utils/strings.js";
+import { initActivity, refreshActivity } from "./activityFeed.js";
+import { initChat, refreshChat } from "./chatPanel.js";
+import { refreshRecoveries } from "./recoveryPanel.js";
+import { refreshJoinRequests } from "./joinRequestsPanel.js";
 import {
 	startScanner,
 	stopScanner,
 } from "../core/qrScanner.js";
 
 let lastRenderedSecret = null;
+let lastRenderedTarget = null;
+let killTokenService = null;
+let killTokenTimeoutId = null;
 
 function renderSecretQr(secret) {
 	const container = document.getElementById("qrCode");
@@ -23,20 +32,75 @@ function renderSecretQr(secret) {
 	}
 }
 
+// The QR code holds a short-lived signed token rather than the bare secret,
+// so a screenshot of it stops working after a minute or so.
+async function refreshKillToken() {
+	clearTimeout(killTokenTimeoutId);
+	if (!killTokenService) return;
+	try {
+		const { token, refresh_in_secs } =
+			await killTokenService.fetchKillToken();
+		renderSecretQr(token);
+		killTokenTimeoutId = setTimeout(
+			refreshKillToken,
+			refresh_in_secs * 1000,
+		);
+	} catch (error) {
+		// Signed kill codes are turned off: show the plain secret instead.
+		if (error.code === "FEATURE_DISABLED") {
+			renderSecretQr(lastRenderedSecret);
+			return;
+		}
+		console.error("Failed to refresh kill code:", error);
+		// Eliminated or game over: the view will switch on the next poll.
+		if (
+			error.code !== "PLAYER_ELIMINATED" &&
+			error.code !== "GAME_NOT_IN_PROGRESS"
+		) {
+			killTokenTimeoutId = setTimeout(refreshKillToken, 5000);
+		}
+	}
+}
+
+// Anonymous hints from ghosts; the fieldset stays hidden until one arrives.
+async function refreshHints() {
+	const container = document.getElementById("hintsContainer");
+	if (!container) return;
+	const { gameCode } = gameState.getGameDetails();
+	try {
+		const hints = await api.fetchHints(gameCode);
+		const list = document.getElementById("hintList");
+		list.innerHTML = "";
+		hints.forEach((hint) => {
+			const item = document.createElement("li");
+			const time = new Date(hint.created_at * 1000).toLocaleTimeString();
+			item.textContent = `${time} ${hint.message}`;
+			list.appendChild(item);
+		});
+		container.style.display = hints.length ? "block" : "none";
+	} catch (error) {
+		console.error("Failed to load hints:", error);
+	}
+}
+
 function updateGameUI({ game, players, me }) {
-	document.getElementById("gameViewTitle").textContent = "Game in Progress";
 	document.getElementById("playerSecretCode").textContent =
 		me.secret_code || "...";
 
 	if (me.secret_code && me.secret_code !== lastRenderedSecret) {
 		lastRenderedSecret = me.secret_code;
-		renderSecretQr(me.secret_code);
+		refreshKillToken();
+	}
+
+	// A late joiner spliced in behind us, or a kill, changed our target.
+	if (lastRenderedTarget && me.target_name && me.target_name !== lastRenderedTarget) {
+		showToast(format(strings("game").target_changed, { name: me.target_name }));
 	}
+	lastRenderedTarget = me.target_name || null;
 
-	const targetInfo = document.getElementById("targetInfo");
-	targetInfo.innerHTML = me.target_name
-		? `<legend>Your Target</legend><p>Your target is: <strong>${me.target_name}</strong></p>`
-		: `<legend>Your Target</legend><p>Waiting for target...</p>`;
+	document.getElementById("targetName").textContent = me.target_name || "";
+	document.getElementById("targetKnown").style.display = me.target_name ? "" : "none";
+	document.getElementById("targetWaiting").style.display = me.target_name ? "none" : "";
 
 	const gamePlayerList = document.getElementById("gamePlayerList");
 	gamePlayerList.innerHTML = "";
@@ -47,25 +111,39 @@ function updateGameUI({ game, players, me }) {
 			let text = p.name;
 			if (p.id === gameState.getGameDetails().playerId) {
 				item.style.fontWeight = "bold";
-				text += " (You)";
+				text += ` ${strings("game").you}`;
 			}
 			item.textContent = text;
 			gamePlayerList.appendChild(item);
 		});
+
+	refreshHints();
+	refreshChat();
+	refreshRecoveries();
+	refreshJoinRequests(game);
+	refreshActivity();
 }
 
 function initGame(gameService) {
+	killTokenService = gameService;
+	initActivity();
+	initChat();
+
 	document
 		.getElementById("assassinateBtn")
 		?.addEventListener("click", () => {
 			const code = document.getElementById("assassinationCode").value;
 			if (!code) {
-				showToast("Please enter your target's secret code.", "error");
+				showToast(strings("game").enter_code, "error");
 				return;
 			}
 			gameService.eliminateTarget(code);
 		});
 
+	document
+		.getElementById("rotateSecretBtn")
+		?.addEventListener("click", () => gameService.rotateSecret());
+
 	document.getElementById("scanQrButton")?.addEventListener("click", () => {
 		startScanner(async (decodedText) => {
 			await gameService.eliminateTarget(decodedText);
@@ -0,0 +1,124 @@
+import { gameState } from "../core/state.js";
+import * as api from "../services/api.js";
+import { showToast } from "../utils/ui.js";
+import { format, strings } from "../utils/strings.js";
+
+let disputableEventId = null;
+
+function disputeItem(dispute) {
+	const s = strings("ghost");
+	const values = {
+		victim: dispute.victim_name,
+		killer: dispute.killer_name ?? "?",
+		overturn: dispute.overturn_votes,
+		uphold: dispute.uphold_votes,
+		needed: dispute.votes_needed,
+	};
+	const li = document.createElement("li");
+	const text = document.createElement("span");
+	li.appendChild(text);
+	if (dispute.status !== "open") {
+		text.textContent = format(s[dispute.status], values);
+		return li;
+	}
+	text.textContent = format(s.dispute, values);
+	// The victim and the killer have no say.
+	if (dispute.can_vote) {
+		for (const overturn of [true, false]) {
+			const button = document.createElement("button");
+			button.type = "button";
+			button.textContent = overturn ? s.overturn : s.uphold;
+			button.disabled = dispute.my_vote === overturn;
+			button.style.marginLeft = "5px";
+			button.addEventListener("click", () => vote(dispute.id, overturn));
+			li.appendChild(button);
+		}
+	}
+	return li;
+}
+
+async function vote(disputeId, overturn) {
+	const { gameCode } = gameState.getGameDetails();
+	try {
+		await api.voteOnDispute(gameCode, disputeId, overturn);
+		await refreshGhost();
+	} catch (error) {
+		showToast(error.message, "error");
+	}
+}
+
+async function dispute() {
+	const { gameCode } = gameState.getGameDetails();
+	if (!disputableEventId) return;
+	try {
+		await api.disputeKill(gameCode, disputableEventId);
+		await refreshGhost();
+	} catch (error) {
+		showToast(error.message, "error");
+	}
+}
+
+async function sendHint() {
+	const { gameCode } = gameState.getGameDetails();
+	const recipient = document.getElementById("hintRecipient");
+	const message = document.getElementById("hintMessage");
+	try {
+		await api.sendHint(gameCode, Number(recipient.value), message.value);
+		message.value = "";
+		showToast(strings("ghost").hint_sent, "success");
+		await refreshGhost();
+	} catch (error) {
+		showToast(error.message, "error");
+	}
+}
+
+function fillRecipients(players) {
+	const select = document.getElementById("hintRecipient");
+	if (!select) return;
+	const selected = select.value;
+	select.innerHTML = "";
+	players
+		.filter((p) => p.is_alive)
+		.forEach((p) => {
+			const option = document.createElement("option");
+			option.value = p.id;
+			option.textContent = p.name;
+			select.appendChild(option);
+		});
+	select.value = selected || select.value;
+}
+
+// Show what the player can do as a ghost, or hide the panel when they are
+// simply out.
+export async function refreshGhost(role, players) {
+	const container = document.getElementById("ghostContainer");
+	if (!container) return;
+	if (role !== undefined) {
+		container.style.display = role === "ghost" ? "block" : "none";
+	}
+	if (players) {
+		fillRecipients(players);
+	}
+	if (container.style.display === "none") return;
+
+	const { gameCode } = gameState.getGameDetails();
+	try {
+		const ghost = await api.fetchGhost(gameCode);
+		disputableEventId = ghost.disputable_event_id;
+		document.getElementById("disputeKillBtn").style.display =
+			disputableEventId ? "inline-block" : "none";
+		document.getElementById("hintForm").style.display = ghost.can_hint
+			? "block"
+			: "none";
+		const list = document.getElementById("disputeList");
+		list.innerHTML = "";
+		ghost.disputes.forEach((d) => list.appendChild(disputeItem(d)));
+	} catch (error) {
+		console.error("Failed to load ghost actions:", error);
+	}
+}
+
+export function initGhost() {
+	document.getElementById("disputeKillBtn")?.addEventListener("click", dispute);
+	document.getElementById("sendHintBtn")?.addEventListener("click", sendHint);
+}
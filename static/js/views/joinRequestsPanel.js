@@ -0,0 +1,44 @@
+import { gameState } from "../core/state.js";
+import * as api from "../services/api.js";
+import { showToast } from "../utils/ui.js";
+
+async function decide(requestId, approve) {
+	const { gameCode } = gameState.getGameDetails();
+	try {
+		await api.decideJoinRequest(gameCode, requestId, approve);
+	} catch (error) {
+		showToast(error.message, "error");
+	}
+	await refreshJoinRequests();
+}
+
+// Show the host who is asking to join the running game; called on each
+// game change.
+export async function refreshJoinRequests(game) {
+	const container = document.getElementById("joinRequestsContainer");
+	if (!container) return;
+	const { gameCode, playerId } = gameState.getGameDetails();
+	if (game && game.host_id !== playerId) {
+		container.style.display = "none";
+		return;
+	}
+	try {
+		const requests = await api.fetchJoinRequests(gameCode);
+		const list = document.getElementById("joinRequestsList");
+		const template = document.getElementById("joinRequestItem");
+		list.innerHTML = "";
+		requests.forEach((request) => {
+			const item = template.content.firstElementChild.cloneNode(true);
+			item.querySelector(".join-request-name").textContent = request.player_name;
+			item.querySelector(".join-request-approve").onclick = () =>
+				decide(request.id, true);
+			item.querySelector(".join-request-deny").onclick = () =>
+				decide(request.id, false);
+			list.appendChild(item);
+		});
+		container.style.display = requests.length ? "block" : "none";
+	} catch (error) {
+		// Not the host (any more); nothing to show.
+		container.style.display = "none";
+	}
+}
@@ -1,21 +1,34 @@
 import { gameState } from "../core/state.js";
-import { copyToClipboard } from "../utils/ui.js";
+import { copyToClipboard, showToast } from "../utils/ui.js";
+import { format, strings } from "../utils/strings.js";
+import * as api from "../services/api.js";
+import { initChat, refreshChat } from "./chatPanel.js";
+import { refreshRecoveries } from "./recoveryPanel.js";
 
 function updateLobbyUI({ game, players }) {
 	const { playerId } = gameState.getGameDetails();
-	document.getElementById("lobbyGameName").textContent = `Game Lobby: ${game.code}`;
+	document.getElementById("lobbyGameName").textContent = format(strings("lobby").heading, { code: game.code });
 
 	const shareLink = `${window.location.origin}/game/${game.code}`;
 	document.getElementById("shareLink").value = shareLink;
 	document.getElementById("copyLinkBtn").onclick = () =>
-		copyToClipboard(shareLink, "Game link copied to clipboard!");
+		copyToClipboard(shareLink, strings("lobby").link_copied);
 
 	const rejoinLink = `${window.location.origin}/game/${game.code}/player/${
 		gameState.getGameDetails().authToken
 	}`;
 	document.getElementById("rejoinLink").value = rejoinLink;
 	document.getElementById("copyRejoinLinkBtn").onclick = () =>
-		copyToClipboard(rejoinLink, "Rejoin link copied to clipboard!");
+		copyToClipboard(rejoinLink, strings("lobby").rejoin_copied);
+
+	// Only rendered for the host.
+	const spectatorInput = document.getElementById("spectatorLink");
+	if (spectatorInput) {
+		const spectatorLink = `${window.location.origin}${spectatorInput.dataset.path}`;
+		spectatorInput.value = spectatorLink;
+		document.getElementById("copySpectatorLinkBtn").onclick = () =>
+			copyToClipboard(spectatorLink, strings("lobby").spectator_copied);
+	}
 
 	const qrContainer = document.getElementById("qrCode");
 	if (
@@ -36,7 +49,11 @@ function updateLobbyUI({ game, players }) {
 	playerList.innerHTML = "";
 	players.forEach((p) => {
 		const li = document.createElement("li");
-		li.textContent = `${p.name} ${p.id === game.host_id ? "(Host)" : ""}`;
+		const notes = [
+			p.id === game.host_id ? strings("lobby").host : "",
+			p.is_ready ? playerList.dataset.ready : "",
+		];
+		li.textContent = `${p.name} ${notes.join(" ")}`.trim();
 		if (p.id === playerId) {
 			li.style.fontWeight = "bold";
 		}
@@ -44,20 +61,88 @@ function updateLobbyUI({ game, players }) {
 	});
 
 	const me = players.find((p) => p.id === playerId);
+	const readyBtn = document.getElementById("readyBtn");
+	readyBtn.dataset.isReady = me?.is_ready ? "true" : "";
+	readyBtn.textContent = me?.is_ready
+		? readyBtn.dataset.unready
+		: readyBtn.dataset.ready;
+
 	const startGameBtn = document.getElementById("startGameBtn");
 	if (me && me.id === game.host_id) {
+		const minPlayers =
+			Number(document.getElementById("minPlayers")?.value) || 2;
 		startGameBtn.style.display = "block";
-		startGameBtn.disabled = players.length < 2;
+		startGameBtn.disabled = players.length < minPlayers;
 		startGameBtn.title =
-			players.length < 2
-				? "Need at least 2 players to start the game"
+			players.length < minPlayers
+				? format(strings("lobby").need_players, { min: minPlayers })
 				: "";
 	} else {
 		startGameBtn.style.display = "none";
 	}
+
+	refreshChat();
+	refreshRecoveries();
 }
 
 function initLobby(gameService) {
+	initChat();
+	// Only rendered for the host.
+	const checkboxes = {
+		anonymise_killer: document.getElementById("anonymiseKiller"),
+		ghosts: document.getElementById("ghosts"),
+		ghost_hints: document.getElementById("ghostHints"),
+		dead_chat: document.getElementById("deadChat"),
+		spectator_chat: document.getElementById("spectatorChat"),
+		auto_start: document.getElementById("autoStart"),
+		late_join: document.getElementById("lateJoin"),
+		late_join_approval: document.getElementById("lateJoinApproval"),
+	};
+	// Left empty, these fall back to the server's limits.
+	const numbers = {
+		min_players: document.getElementById("minPlayers"),
+		max_players: document.getElementById("maxPlayers"),
+	};
+	const saved = new Map(
+		Object.values(numbers).map((input) => [input, input?.value]),
+	);
+	// Send every setting: the ones left out would be reset to their default.
+	const save = async (input) => {
+		const { gameCode } = gameState.getGameDetails();
+		const settings = {
+			...Object.fromEntries(
+				Object.entries(checkboxes).map(([key, box]) => [key, !!box?.checked]),
+			),
+			...Object.fromEntries(
+				Object.entries(numbers).map(([key, field]) => [
+					key,
+					field?.value ? Number(field.value) : null,
+				]),
+			),
+		};
+		try {
+			await api.updateSettings(gameCode, settings);
+			if (saved.has(input)) saved.set(input, input.value);
+		} catch (error) {
+			if (saved.has(input)) {
+				input.value = saved.get(input);
+			} else {
+				input.checked = !input.checked;
+			}
+			showToast(format(strings("lobby").settings_failed, { error: error.message }), "error");
+		}
+	};
+	[...Object.values(checkboxes), ...Object.values(numbers)].forEach((input) =>
+		input?.addEventListener("change", () => save(input)),
+	);
+	document.getElementById("readyBtn")?.addEventListener("click", async (event) => {
+		const { gameCode } = gameState.getGameDetails();
+		try {
+			await api.setReady(gameCode, !event.target.dataset.isReady);
+		} catch (error) {
+			showToast(error.message, "error");
+		}
+	});
 	document
 		.getElementById("leaveGameBtn")
 		?.addEventListener("click", () => gameService.leave());
@@ -0,0 +1,42 @@
+import { gameState } from "../core/state.js";
+import * as api from "../services/api.js";
+import { showToast } from "../utils/ui.js";
+
+async function decide(requestId, approve) {
+	const { gameCode } = gameState.getGameDetails();
+	try {
+		await api.decideRecovery(gameCode, requestId, approve);
+	} catch (error) {
+		showToast(error.message, "error");
+	}
+	await refreshRecoveries();
+}
+
+// Show who is asking to be let back in: everyone else to the host, and the
+// host to the other players. Called on each game change.
+export async function refreshRecoveries() {
+	const container = document.getElementById("recoveryContainer");
+	if (!container) return;
+	const { gameCode } = gameState.getGameDetails();
+	try {
+		const requests = await api.fetchRecoveries(gameCode);
+		const list = document.getElementById("recoveryList");
+		const template = document.getElementById("recoveryItem");
+		list.innerHTML = "";
+		requests.forEach((request) => {
+			const item = template.content.firstElementChild.cloneNode(true);
+			item.querySelector(".recovery-name").textContent = request.player_name;
+			const time = new Date(request.created_at * 1000).toLocaleTimeString();
+			item.querySelector(".recovery-meta").textContent = `#${request.id} · ${time}`;
+			item.querySelector(".recovery-approve").onclick = () =>
+				decide(request.id, true);
+			item.querySelector(".recovery-deny").onclick = () =>
+				decide(request.id, false);
+			list.appendChild(item);
+		});
+		container.style.display = requests.length ? "block" : "none";
+	} catch (error) {
+		// Not in the game (any more); nothing to show.
+		container.style.display = "none";
+	}
+}
@@ -1,6 +1,37 @@
 import { showModal, hideModal } from './utils/ui.js';
 import { showToast } from './utils/ui.js';
-import { createGame, joinGame } from './services/api.js';
+import { strings } from './utils/strings.js';
+import { createGame, joinGame, requestRecovery, fetchRecovery, fetchJoinRequest } from './services/api.js';
+
+// How often to ask whether the host let us in.
+const HOST_POLL_MS = 3000;
+
+let hostTimer = null;
+
+// Poll `fetchRequest` until the host answers, showing `status` meanwhile;
+// approved players go to their game.
+function waitForHost(gameId, status, fetchRequest) {
+    status.classList.remove('hidden');
+    clearInterval(hostTimer);
+    hostTimer = setInterval(async () => {
+        try {
+            const request = await fetchRequest();
+            if (request.status === 'approved') {
+                clearInterval(hostTimer);
+                window.location.href = `/game/${gameId}/player/${request.auth_token}`;
+            } else if (request.status === 'denied') {
+                clearInterval(hostTimer);
+                status.classList.add('hidden');
+                showToast(status.dataset.denied, 'error');
+            }
+        } catch (error) {
+            // Superseded by a newer request, or the game is gone.
+            clearInterval(hostTimer);
+            status.classList.add('hidden');
+            showToast(error.message, 'error');
+        }
+    }, HOST_POLL_MS);
+}
 
 document.addEventListener('DOMContentLoaded', () => {
     const serverContextElement = document.getElementById('server-context');
@@ -34,7 +65,7 @@ document.addEventListener('DOMContentLoaded', () => {
         const creatorNameInput = document.getElementById('creatorName');
         const creatorName = creatorNameInput ? creatorNameInput.value : '';
         if (!creatorName) {
-            showToast('Please enter your name.', 'error');
+            showToast(strings('welcome').enter_name, 'error');
             return;
         }
 
@@ -54,15 +85,40 @@ document.addEventListener('DOMContentLoaded', () => {
         const playerName = playerNameInput ? playerNameInput.value : '';
 
         if (!gameId || !playerName) {
-            showToast('Please enter both game ID and your name.', 'error');
+            showToast(strings('welcome').enter_game_and_name, 'error');
             return;
         }
 
         try {
             const data = await joinGame(gameId, playerName);
-            window.location.href = `/game/${gameId}/player/${data.auth_token}/lobby`;
+            if (data.claim_token) {
+                // The game already runs and its host lets newcomers in.
+                waitForHost(gameId, document.getElementById('lateJoinStatus'), () =>
+                    fetchJoinRequest(gameId, data.claim_token));
+            } else if (data.game.status === 'Lobby') {
+                window.location.href = `/game/${gameId}/player/${data.auth_token}/lobby`;
+            } else {
+                window.location.href = `/game/${gameId}/player/${data.auth_token}`;
+            }
+        } catch (error) {
+            showToast(error.message, 'error');
+        }
+    });
+
+    document.getElementById('joinGameRecover')?.addEventListener('click', async () => {
+        const gameId = document.getElementById('gameId')?.value ?? '';
+        const playerName = document.getElementById('playerName')?.value ?? '';
+        if (!gameId || !playerName) {
+            showToast(strings('welcome').enter_game_and_name, 'error');
+            return;
+        }
+
+        try {
+            const { claim_token } = await requestRecovery(gameId, playerName);
+            waitForHost(gameId, document.getElementById('recoveryStatus'), () =>
+                fetchRecovery(gameId, claim_token));
         } catch (error) {
             showToast(error.message, 'error');
         }
     });
-}); 
\ No newline at end of file
+});
@@ -0,0 +1,309 @@
+//! The activity feed players see while the game runs, and the setting that
+//! hides who made each kill.
+
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{on_every_backend, TestApp, TestPlayer};
+use serde_json::{json, Value};
+
+async fn activity(app: &TestApp, code: &str, player: &TestPlayer, query: &str) -> Value {
+    app.get(
+        &format!("/api/game/{code}/activity{query}"),
+        Some(&player.token),
+    )
+    .await
+    .assert_ok()
+    .clone()
+}
+
+/// `(kind, actor_name)` of every event of a page.
+fn entries(page: &Value) -> Vec<(String, Option<String>)> {
+    page["events"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| {
+            (
+                e["kind"].as_str().unwrap().to_string(),
+                e["actor_name"].as_str().map(str::to_string),
+            )
+        })
+        .collect()
+}
+
+async fn pages_follow_the_cursor(app: TestApp) {
+    let (code, players) = app.started_game(&["alice", "bob", "carol", "dave"]).await;
+    let targets = app.targets(&code).await;
+    let victim = players.iter().find(|p| p.name == targets["alice"]).unwrap();
+    app.kill(&code, &players[0], &victim.secret)
+        .await
+        .assert_ok();
+
+    let mut ids = Vec::new();
+    let mut kinds = Vec::new();
+    let mut query = "?limit=2".to_string();
+    loop {
+        let page = activity(&app, &code, &players[0], &query).await;
+        assert!(page["events"].as_array().unwrap().len() <= 2);
+        for event in page["events"].as_array().unwrap() {
+            ids.push(event["id"].as_i64().unwrap());
+            kinds.push(event["kind"].as_str().unwrap().to_string());
+        }
+        match page["next_cursor"].as_i64() {
+            Some(cursor) => query = format!("?limit=2&before={cursor}"),
+            None => break,
+        }
+    }
+    assert!(ids.windows(2).all(|w| w[0] > w[1]), "newest first: {ids:?}");
+    assert_eq!(
+        kinds,
+        ["kill", "started", "joined", "joined", "joined", "joined"]
+    );
+
+    let newest = activity(&app, &code, &players[1], "").await;
+    assert_eq!(newest["events"].as_array().unwrap().len(), 6);
+    assert!(newest["next_cursor"].is_null());
+    assert_eq!(newest["events"][0]["actor_name"], "alice");
+    assert_eq!(newest["events"][0]["subject_name"], victim.name.as_str());
+}
+
+on_every_backend!(pages_follow_the_cursor);
+
+async fn killers_can_be_anonymised(app: TestApp) {
+    let (code, alice) = app.create_game("alice").await;
+    let bob = app.join(&code, "bob").await;
+    let carol = app.join(&code, "carol").await;
+    let settings = json!({ "anonymise_killer": true });
+
+    let not_host = app
+        .post(
+            &format!("/api/game/{code}/settings"),
+            Some(&bob.token),
+            Some(settings.clone()),
+        )
+        .await;
+    not_host.assert_error(StatusCode::FORBIDDEN, "NOT_HOST");
+    assert!(not_host.body["error"]
+        .as_str()
+        .unwrap()
+        .contains("settings"));
+    let saved = app
+        .post(
+            &format!("/api/game/{code}/settings"),
+            Some(&alice.token),
+            Some(settings.clone()),
+        )
+        .await;
+    assert_eq!(saved.assert_ok()["anonymise_killer"], true);
+
+    app.start(&code, &alice).await.assert_ok();
+    let locked = app
+        .post(
+            &format!("/api/game/{code}/settings"),
+            Some(&alice.token),
+            Some(json!({ "anonymise_killer": false })),
+        )
+        .await;
+    locked.assert_error(StatusCode::UNPROCESSABLE_ENTITY, "GAME_ALREADY_STARTED");
+    assert!(locked.body["error"].as_str().unwrap().contains("Settings"));
+
+    let players = [&alice, &bob, &carol];
+    let targets = app.targets(&code).await;
+    let victim = *players.iter().find(|p| p.name == targets["alice"]).unwrap();
+    let bystander = *players
+        .iter()
+        .find(|p| p.id != alice.id && p.id != victim.id)
+        .unwrap();
+    app.kill(&code, &alice, &victim.secret).await.assert_ok();
+
+    for viewer in [victim, bystander] {
+        let page = activity(&app, &code, viewer, "").await;
+        assert_eq!(entries(&page)[0], ("kill".to_string(), None));
+        assert_eq!(page["events"][0]["subject_name"], victim.name.as_str());
+    }
+    let own = activity(&app, &code, &alice, "").await;
+    assert_eq!(
+        entries(&own)[0],
+        ("kill".to_string(), Some("alice".to_string()))
+    );
+
+    let token = app
+        .get(
+            &format!("/api/game/{code}/spectator-link"),
+            Some(&alice.token),
+        )
+        .await
+        .assert_ok()["spectator_token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let board = app
+        .get(&format!("/api/game/{code}/spectate?token={token}"), None)
+        .await;
+    let kill = board.assert_ok()["events"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["kind"] == "kill")
+        .unwrap()
+        .clone();
+    assert!(kill["actor_name"].is_null(), "{kill}");
+}
+
+on_every_backend!(killers_can_be_anonymised);
+
+/// Every `target_name` anywhere in `body`.
+fn target_names(body: &Value) -> Vec<&str> {
+    match body {
+        Value::Array(items) => items.iter().flat_map(target_names).collect(),
+        Value::Object(fields) => fields
+            .iter()
+            .flat_map(|(key, value)| match (key.as_str(), value.as_str()) {
+                ("target_name", Some(name)) => vec![name],
+                _ => target_names(value),
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[tokio::test]
+async fn anonymous_killers_stay_hidden_from_bystanders() {
+    let app = TestApp::in_memory();
+    let (code, alice) = app.create_game("alice").await;
+    app.post(
+        &format!("/api/game/{code}/settings"),
+        Some(&alice.token),
+        Some(json!({ "anonymise_killer": true })),
+    )
+    .await
+    .assert_ok();
+    let mut players = vec![alice];
+    for name in ["bob", "carol", "dave"] {
+        players.push(app.join(&code, name).await);
+    }
+    app.start(&code, &players[0]).await.assert_ok();
+
+    let targets = app.targets(&code).await;
+    let victim = players.iter().find(|p| p.name == targets["alice"]).unwrap();
+    let bystander = players
+        .iter()
+        .find(|p| p.name != "alice" && p.id != victim.id)
+        .unwrap();
+    let own_target = targets[&bystander.name].as_str();
+    app.kill(&code, &players[0], &victim.secret)
+        .await
+        .assert_ok();
+
+    // Only their own target, and no name on the kill, on every route a
+    // player can read.
+    for path in [
+        format!("/api/game/{code}"),
+        format!("/api/game/{code}/activity"),
+        format!("/api/game/{code}/chat"),
+        format!("/api/game/{code}/hints"),
+        format!("/api/v1/games/{code}"),
+        format!("/api/v1/games/{code}/activity"),
+        format!("/api/v1/games/{code}/messages"),
+        format!("/api/v1/games/{code}/hints"),
+    ] {
+        let res = app.get(&path, Some(&bystander.token)).await;
+        let body = res.assert_ok();
+        let seen = target_names(body);
+        assert!(seen.iter().all(|&t| t == own_target), "{path}: {body}");
+        if let Some(events) = body["events"].as_array() {
+            let kill = events.iter().find(|e| e["kind"] == "kill").unwrap();
+            assert!(kill["actor_name"].is_null(), "{path}: {kill}");
+        }
+    }
+}
+
+#[tokio::test]
+async fn leaving_and_host_changes_are_listed() {
+    let app = TestApp::in_memory();
+    let (code, alice) = app.create_game("alice").await;
+    let bob = app.join(&code, "bob").await;
+    let carol = app.join(&code, "carol").await;
+    let dave = app.join(&code, "dave").await;
+    app.leave(&code, &alice).await.assert_ok();
+
+    let page = activity(&app, &code, &bob, "?limit=2").await;
+    assert_eq!(
+        entries(&page),
+        [
+            ("host_changed".to_string(), Some("bob".to_string())),
+            ("left".to_string(), Some("alice".to_string())),
+        ]
+    );
+
+    app.start(&code, &bob).await.assert_ok();
+    app.leave(&code, &dave).await.assert_ok();
+    let page = activity(&app, &code, &carol, "?limit=1").await;
+    assert_eq!(
+        entries(&page),
+        [("forfeited".to_string(), Some("dave".to_string()))]
+    );
+}
+
+#[tokio::test]
+async fn only_players_of_the_game_see_its_feed() {
+    let app = TestApp::in_memory();
+    let (code, _) = app.create_game("alice").await;
+    let (_, stranger) = app.create_game("mallory").await;
+    app.get(&format!("/api/game/{code}/activity"), Some(&stranger.token))
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "GAME_NOT_FOUND");
+    app.get(&format!("/api/game/{code}/activity"), Some("nope"))
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "INVALID_AUTH_TOKEN");
+}
+
+#[tokio::test]
+async fn v1_reads_and_replaces_settings() {
+    let app = TestApp::in_memory();
+    let (code, alice) = app.create_game("alice").await;
+    let path = format!("/api/v1/games/{code}/settings");
+    let current = app.get(&path, Some(&alice.token)).await;
+    assert_eq!(current.assert_ok()["anonymise_killer"], false);
+    let saved = app
+        .request(
+            Method::PUT,
+            &path,
+            Some(&alice.token),
+            Some(json!({ "anonymise_killer": true })),
+        )
+        .await;
+    assert_eq!(saved.assert_ok()["anonymise_killer"], true);
+    let current = app.get(&path, Some(&alice.token)).await;
+    assert_eq!(current.assert_ok()["anonymise_killer"], true);
+
+    let feed = app
+        .get(
+            &format!("/api/v1/games/{code}/activity"),
+            Some(&alice.token),
+        )
+        .await;
+    assert_eq!(
+        entries(feed.assert_ok()),
+        [("joined".to_string(), Some("alice".to_string()))]
+    );
+}
+
+#[tokio::test]
+async fn the_feed_is_on_the_game_and_eliminated_pages() {
+    let app = TestApp::in_memory();
+    let (code, players) = app.started_game(&["alice", "bob"]).await;
+    for page in ["game", "eliminated"] {
+        let res = app
+            .get(
+                &format!("/game/{code}/player/{}/{page}", players[0].token),
+                None,
+            )
+            .await;
+        assert_eq!(res.status, StatusCode::OK);
+        let html = res.body.as_str().unwrap();
+        assert!(html.contains("id=\"activityFeed\""), "{page}: {html}");
+        assert!(html.contains("activity-strings"), "{page}");
+    }
+}
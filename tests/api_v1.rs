@@ -0,0 +1,240 @@
+//! The versioned API and its OpenAPI document.
+
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn a_whole_game_through_v1() {
+    let app = TestApp::in_memory();
+    let created = app
+        .post(
+            "/api/v1/games",
+            None,
+            Some(json!({ "player_name": "alice" })),
+        )
+        .await;
+    assert_eq!(created.status, StatusCode::CREATED, "{}", created.body);
+    let code = created.body["game"]["code"].as_str().unwrap().to_string();
+    let alice = created.body["auth_token"].as_str().unwrap().to_string();
+    assert_eq!(created.body["game"]["status"], "Lobby");
+    assert_eq!(created.body["player"]["name"], "alice");
+    assert_eq!(
+        created.body["game"]["host_id"],
+        created.body["player"]["id"]
+    );
+
+    let joined = app
+        .post(
+            &format!("/api/v1/games/{code}/players"),
+            None,
+            Some(json!({ "player_name": "bob" })),
+        )
+        .await;
+    assert_eq!(joined.status, StatusCode::CREATED, "{}", joined.body);
+    let bob = joined.body["auth_token"].as_str().unwrap().to_string();
+    let bob_secret = joined.body["player"]["secret_code"].as_str().unwrap();
+    let players = joined.body["game"]["players"].as_array().unwrap();
+    assert_eq!(players.len(), 2);
+    let alice_seen = players.iter().find(|p| p["name"] == "alice").unwrap();
+    assert!(alice_seen["secret_code"].is_null(), "{alice_seen}");
+
+    let carol = app
+        .post(
+            &format!("/api/v1/games/{code}/players"),
+            None,
+            Some(json!({ "player_name": "carol" })),
+        )
+        .await;
+    let carol = carol.assert_ok()["auth_token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let started = app
+        .post(&format!("/api/v1/games/{code}/start"), Some(&alice), None)
+        .await;
+    assert_eq!(started.assert_ok()["status"], "InProgress");
+    let version = started.body["version"].as_i64().unwrap();
+
+    let unchanged = app
+        .get(
+            &format!("/api/v1/games/{code}/changes?version={version}"),
+            Some(&alice),
+        )
+        .await;
+    assert_eq!(unchanged.assert_ok()["changed"], false);
+
+    // Whoever hunts bob takes him out with his secret.
+    let mut hunter = None;
+    for token in [&alice, &carol] {
+        let game = app.get(&format!("/api/v1/games/{code}"), Some(token)).await;
+        let players = game.assert_ok()["players"].as_array().unwrap();
+        let me = players
+            .iter()
+            .find(|p| p["secret_code"].is_string())
+            .unwrap();
+        if me["target_name"] == "bob" {
+            hunter = Some(token.clone());
+        }
+    }
+    let hunter = hunter.expect("someone hunts bob");
+    let kill = app
+        .post(
+            &format!("/api/v1/games/{code}/kills"),
+            Some(&hunter),
+            Some(json!({ "secret_code": bob_secret })),
+        )
+        .await;
+    assert_eq!(kill.status, StatusCode::CREATED, "{}", kill.body);
+    assert_eq!(kill.body["eliminated_player_name"], "bob");
+
+    let changed = app
+        .get(
+            &format!("/api/v1/games/{code}/changes?version={version}"),
+            Some(&alice),
+        )
+        .await;
+    assert_eq!(changed.assert_ok()["changed"], true);
+
+    let left = app
+        .request(
+            Method::DELETE,
+            &format!("/api/v1/games/{code}/players/me"),
+            Some(&bob),
+            None,
+        )
+        .await;
+    assert_eq!(left.status, StatusCode::NO_CONTENT, "{}", left.body);
+}
+
+#[tokio::test]
+async fn players_only_see_their_own_target() {
+    let app = TestApp::in_memory();
+    let (code, players) = app.started_game(&["alice", "bob", "carol"]).await;
+    let targets = app.targets(&code).await;
+
+    for viewer in &players {
+        for path in [format!("/api/v1/games/{code}"), format!("/api/game/{code}")] {
+            let game = app.get(&path, Some(&viewer.token)).await;
+            for p in game.assert_ok()["players"].as_array().unwrap() {
+                if p["id"] == viewer.id {
+                    assert_eq!(p["target_name"], targets[&viewer.name].as_str());
+                } else {
+                    assert!(p["target_name"].is_null(), "{path}: {p}");
+                }
+            }
+        }
+    }
+
+    // The host's start response only carries their own first target.
+    let (code, host) = app.create_game("dave").await;
+    app.join(&code, "erin").await;
+    app.join(&code, "frank").await;
+    let started = app.start(&code, &host).await;
+    for p in started.assert_ok().as_array().unwrap() {
+        assert_eq!(p["target_name"].is_string(), p["id"] == host.id, "{p}");
+    }
+
+    // A token only opens the game it belongs to.
+    let (other, _) = app.create_game("grace").await;
+    for path in [
+        format!("/api/v1/games/{other}"),
+        format!("/api/game/{other}"),
+    ] {
+        app.get(&path, Some(&players[0].token))
+            .await
+            .assert_error(StatusCode::NOT_FOUND, "GAME_NOT_FOUND");
+    }
+}
+
+#[tokio::test]
+async fn starting_twice_is_documented() {
+    let app = TestApp::in_memory();
+    let (code, players) = app.started_game(&["alice", "bob", "carol"]).await;
+    app.post(
+        &format!("/api/v1/games/{code}/start"),
+        Some(&players[0].token),
+        None,
+    )
+    .await
+    .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "GAME_ALREADY_STARTED");
+
+    let doc = app.get("/api/v1/openapi.json", None).await;
+    let responses =
+        &doc.assert_ok()["paths"]["/api/v1/games/{game_code}/start"]["post"]["responses"];
+    assert!(responses["422"].is_object(), "{responses}");
+    assert!(responses["409"].is_null(), "{responses}");
+}
+
+#[tokio::test]
+async fn errors_use_the_documented_body() {
+    let app = TestApp::in_memory();
+    let res = app.get("/api/v1/games/NOPE", Some("token")).await;
+    res.assert_error(StatusCode::FORBIDDEN, "INVALID_AUTH_TOKEN");
+    assert!(res.body["request_id"].is_string());
+
+    let (code, _) = app.create_game("alice").await;
+    let res = app
+        .post(
+            &format!("/api/v1/games/{code}/players"),
+            None,
+            Some(json!({ "player_name": "alice" })),
+        )
+        .await;
+    assert_eq!(res.status.as_u16() / 100, 4, "{}", res.body);
+    assert!(res.body["code"].is_string());
+}
+
+#[tokio::test]
+async fn openapi_document_covers_every_route() {
+    let app = TestApp::in_memory();
+    let res = app.get("/api/v1/openapi.json", None).await;
+    let doc = res.assert_ok();
+    assert!(doc["openapi"].as_str().unwrap().starts_with("3."));
+
+    let paths = doc["paths"].as_object().unwrap();
+    for (path, method) in [
+        ("/api/v1/games", "post"),
+        ("/api/v1/games/{game_code}", "get"),
+        ("/api/v1/games/{game_code}/start", "post"),
+        ("/api/v1/games/{game_code}/changes", "get"),
+        ("/api/v1/games/{game_code}/players", "post"),
+        ("/api/v1/games/{game_code}/players/me", "delete"),
+        ("/api/v1/games/{game_code}/kills", "post"),
+        ("/api/v1/games/{game_code}/kill-token", "get"),
+        ("/api/v1/games/{game_code}/secret/rotate", "post"),
+        ("/api/v1/admin/games/{game_code}/ring", "post"),
+        ("/api/v1/admin/games/{game_code}/finish", "post"),
+        ("/api/v1/admin/purge", "post"),
+    ] {
+        assert!(
+            paths.get(path).and_then(|p| p.get(method)).is_some(),
+            "{method} {path} is not documented"
+        );
+    }
+
+    let components = &doc["components"];
+    for schema in [
+        "ErrorPayload",
+        "GamePayload",
+        "PlayerSessionPayload",
+        "GameStatus",
+    ] {
+        assert!(
+            components["schemas"][schema].is_object(),
+            "{schema} missing"
+        );
+    }
+    assert_eq!(components["securitySchemes"]["player"]["scheme"], "bearer");
+    assert_eq!(components["securitySchemes"]["admin"]["scheme"], "bearer");
+}
+
+#[test]
+fn the_served_document_matches_the_code() {
+    let doc = hitman::handlers::api::v1::openapi();
+    assert_eq!(doc.info.title, "Hitman API");
+    assert!(doc.paths.paths.keys().all(|p| p.starts_with("/api/v1/")));
+}
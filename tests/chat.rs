@@ -0,0 +1,242 @@
+//! Per-game chat: host announcements, the lobby chat and the dead players'
+//! chat, delivered through the change poll.
+
+mod common;
+
+use axum::http::StatusCode;
+use common::{on_every_backend, TestApp, TestPlayer};
+use hitman::config::Config;
+use serde_json::{json, Value};
+
+async fn say(
+    app: &TestApp,
+    code: &str,
+    player: &TestPlayer,
+    channel: &str,
+    body: &str,
+) -> common::TestResponse {
+    app.post(
+        &format!("/api/game/{code}/chat"),
+        Some(&player.token),
+        Some(json!({ "channel": channel, "body": body })),
+    )
+    .await
+}
+
+/// `(channel, sender_name, body)` of every message `player` can read.
+async fn read(app: &TestApp, code: &str, player: &TestPlayer) -> Vec<(String, String, String)> {
+    let messages = app
+        .get(&format!("/api/game/{code}/chat"), Some(&player.token))
+        .await;
+    messages
+        .assert_ok()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| {
+            let field = |name: &str| m[name].as_str().unwrap().to_string();
+            (field("channel"), field("sender_name"), field("body"))
+        })
+        .collect()
+}
+
+async fn version(app: &TestApp, code: &str, player: &TestPlayer) -> i64 {
+    app.game_state(code, player).await["version"]
+        .as_i64()
+        .unwrap()
+}
+
+async fn chat_follows_the_game(app: TestApp) {
+    let (code, alice) = app.create_game("alice").await;
+    let bob = app.join(&code, "bob").await;
+    let carol = app.join(&code, "carol").await;
+    app.post(
+        &format!("/api/game/{code}/settings"),
+        Some(&alice.token),
+        Some(json!({ "dead_chat": true })),
+    )
+    .await
+    .assert_ok();
+
+    let before = version(&app, &code, &bob).await;
+    let posted = say(&app, &code, &bob, "lobby", "  hi all  ").await;
+    assert_eq!(posted.status, StatusCode::CREATED, "{}", posted.body);
+    assert_eq!(posted.body["body"], "hi all");
+    assert_eq!(posted.body["sender_id"], bob.id);
+    assert!(version(&app, &code, &bob).await > before);
+    say(&app, &code, &bob, "announcements", "I'm in charge")
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "NOT_HOST");
+    say(&app, &code, &alice, "announcements", "Meet at noon")
+        .await
+        .assert_ok();
+    say(&app, &code, &bob, "dead", "boo")
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "CHAT_CLOSED");
+    say(&app, &code, &bob, "lobby", "   ")
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "INVALID_MESSAGE");
+    say(&app, &code, &bob, "lobby", &"x".repeat(501))
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "INVALID_MESSAGE");
+
+    app.start(&code, &alice).await.assert_ok();
+    say(&app, &code, &bob, "lobby", "too late")
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "CHAT_CLOSED");
+    let players = [alice.clone(), bob.clone(), carol.clone()];
+    let targets = app.targets(&code).await;
+    let victim = players.iter().find(|p| p.name == targets["alice"]).unwrap();
+    app.kill(&code, &alice, &victim.secret).await.assert_ok();
+    let survivor = players
+        .iter()
+        .find(|p| p.id != alice.id && p.id != victim.id)
+        .unwrap();
+
+    say(&app, &code, survivor, "dead", "let me in")
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "CHAT_FORBIDDEN");
+    say(&app, &code, victim, "dead", "it was alice")
+        .await
+        .assert_ok();
+    say(&app, &code, &alice, "announcements", "Two left")
+        .await
+        .assert_ok();
+
+    let everyone = vec![
+        ("lobby".to_string(), "bob".to_string(), "hi all".to_string()),
+        (
+            "announcements".to_string(),
+            "alice".to_string(),
+            "Meet at noon".to_string(),
+        ),
+        (
+            "announcements".to_string(),
+            "alice".to_string(),
+            "Two left".to_string(),
+        ),
+    ];
+    assert_eq!(read(&app, &code, survivor).await, everyone);
+    let mut dead = everyone.clone();
+    dead.insert(
+        2,
+        (
+            "dead".to_string(),
+            victim.name.clone(),
+            "it was alice".to_string(),
+        ),
+    );
+    assert_eq!(read(&app, &code, victim).await, dead);
+
+    let newest = app
+        .get(&format!("/api/game/{code}/chat"), Some(&victim.token))
+        .await;
+    let second = newest.assert_ok()[1]["id"].as_i64().unwrap();
+    let after = app
+        .get(
+            &format!("/api/game/{code}/chat?after={second}"),
+            Some(&victim.token),
+        )
+        .await;
+    assert_eq!(after.assert_ok().as_array().unwrap().len(), 2);
+}
+
+on_every_backend!(chat_follows_the_game);
+
+#[tokio::test]
+async fn chatting_is_rate_limited() {
+    let mut config = Config::default();
+    config.rate_limits.chat_message_limit = 2;
+    let app = TestApp::in_memory_with_config(config);
+    let (code, alice) = app.create_game("alice").await;
+    let bob = app.join(&code, "bob").await;
+    say(&app, &code, &alice, "lobby", "one").await.assert_ok();
+    say(&app, &code, &alice, "announcements", "two")
+        .await
+        .assert_ok();
+    let limited = say(&app, &code, &alice, "lobby", "three").await;
+    limited.assert_error(StatusCode::TOO_MANY_REQUESTS, "CHAT_RATE_LIMITED");
+    assert!(limited.body["error"].as_str().unwrap().contains("seconds"));
+    // The limit is per player.
+    say(&app, &code, &bob, "lobby", "mine").await.assert_ok();
+}
+
+#[tokio::test]
+async fn messages_outlive_players_who_leave() {
+    let app = TestApp::in_memory();
+    let (code, alice) = app.create_game("alice").await;
+    let bob = app.join(&code, "bob").await;
+    say(&app, &code, &bob, "lobby", "bye").await.assert_ok();
+    app.leave(&code, &bob).await.assert_ok();
+    let messages = app
+        .get(&format!("/api/game/{code}/chat"), Some(&alice.token))
+        .await;
+    let message = &messages.assert_ok()[0];
+    assert_eq!(message["sender_name"], "bob");
+    assert!(message["sender_id"].is_null());
+}
+
+#[tokio::test]
+async fn spectators_read_along_only_when_allowed() {
+    let app = TestApp::in_memory();
+    let (code, alice) = app.create_game("alice").await;
+    say(&app, &code, &alice, "announcements", "Welcome")
+        .await
+        .assert_ok();
+    let token = app
+        .get(
+            &format!("/api/game/{code}/spectator-link"),
+            Some(&alice.token),
+        )
+        .await
+        .assert_ok()["spectator_token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let scoreboard = format!("/api/game/{code}/spectate?token={token}");
+    let messages = app.get(&scoreboard, None).await;
+    assert_eq!(messages.assert_ok()["messages"], json!([]));
+
+    app.post(
+        &format!("/api/game/{code}/settings"),
+        Some(&alice.token),
+        Some(json!({ "spectator_chat": true, "dead_chat": true })),
+    )
+    .await
+    .assert_ok();
+    let messages = app.get(&scoreboard, None).await;
+    assert_eq!(messages.assert_ok()["messages"][0]["body"], "Welcome");
+}
+
+#[tokio::test]
+async fn v1_messages() {
+    let app = TestApp::in_memory();
+    let (code, alice) = app.create_game("alice").await;
+    let path = format!("/api/v1/games/{code}/messages");
+    let posted = app
+        .post(
+            &path,
+            Some(&alice.token),
+            Some(json!({ "channel": "announcements", "body": "Hello" })),
+        )
+        .await;
+    assert_eq!(posted.status, StatusCode::CREATED, "{}", posted.body);
+    let messages = app.get(&path, Some(&alice.token)).await;
+    assert_eq!(messages.assert_ok()[0]["channel"], "announcements");
+}
+
+#[tokio::test]
+async fn pages_offer_the_channels_you_may_post_in() {
+    let app = TestApp::in_memory();
+    let (code, alice) = app.create_game("alice").await;
+    let bob = app.join(&code, "bob").await;
+    let page = |player: &TestPlayer| format!("/game/{code}/player/{}/lobby", player.token);
+    let html = |body: &Value| body.as_str().unwrap().to_string();
+
+    let host = html(&app.get(&page(&alice), None).await.body);
+    assert!(host.contains("chatMessages"));
+    assert!(host.contains(r#"<option value="announcements">"#));
+    let guest = html(&app.get(&page(&bob), None).await.body);
+    assert!(guest.contains(r#"<option value="lobby">"#));
+    assert!(!guest.contains(r#"<option value="announcements">"#));
+}
@@ -0,0 +1,320 @@
+//! Shared harness for the HTTP tests: builds the real router on top of the
+//! per-test database handed out by `sqlx::test` (or the in-memory and SQLite
+//! repositories) and drives it in-process.
+
+#![allow(dead_code, unused_imports, unused_macros)]
+
+use axum::{
+    body::Body,
+    http::{header, Method, Request, StatusCode},
+    Router,
+};
+use dashmap::DashMap;
+use hitman::{
+    config::Config,
+    create_router,
+    db::Db,
+    kill_token::KillTokenSigner,
+    metrics::Metrics,
+    repository::{GameRepository, MemoryRepository, SqliteRepository},
+    state::AppState,
+};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tera::Tera;
+use tower::ServiceExt;
+
+/// Run each `async fn(TestApp)` against every backend, as the tests
+/// `<name>::on_postgres` (on the database `sqlx::test` hands out),
+/// `<name>::in_memory` and `<name>::on_sqlite`.
+macro_rules! on_every_backend {
+    ($($test:ident),+ $(,)?) => {$(
+        mod $test {
+            use $crate::common::TestApp;
+
+            #[sqlx::test]
+            async fn on_postgres(pool: sqlx::PgPool) {
+                super::$test(TestApp::new(pool)).await;
+            }
+
+            #[tokio::test]
+            async fn in_memory() {
+                super::$test(TestApp::in_memory()).await;
+            }
+
+            #[tokio::test]
+            async fn on_sqlite() {
+                super::$test(TestApp::sqlite().await).await;
+            }
+        }
+    )+};
+}
+pub(crate) use on_every_backend;
+
+pub struct TestApp {
+    pub router: Router,
+    /// Set when running on Postgres, for arranging state the API can't reach.
+    pub db: Option<Db>,
+    /// The backend behind the router, for reading what no player may see.
+    pub repository: Arc<dyn GameRepository>,
+}
+
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: axum::http::HeaderMap,
+    pub body: Value,
+}
+
+impl TestResponse {
+    /// Assert this is an error response with the given status and code.
+    #[track_caller]
+    pub fn assert_error(&self, status: StatusCode, code: &str) {
+        assert_eq!(
+            self.status, status,
+            "unexpected status, body: {}",
+            self.body
+        );
+        assert_eq!(
+            self.body["code"], code,
+            "unexpected code, body: {}",
+            self.body
+        );
+        assert!(self.body["error"].is_string(), "error without a message");
+    }
+
+    #[track_caller]
+    pub fn assert_ok(&self) -> &Value {
+        assert!(
+            self.status.is_success(),
+            "expected success, got {}: {}",
+            self.status,
+            self.body
+        );
+        &self.body
+    }
+}
+
+/// A player as seen by their own client: everything needed to act as them.
+#[derive(Debug, Clone)]
+pub struct TestPlayer {
+    pub id: i32,
+    pub name: String,
+    pub token: String,
+    pub secret: String,
+}
+
+impl TestApp {
+    pub fn new(pool: PgPool) -> Self {
+        let db = Db::from_pool(pool);
+        TestApp {
+            db: Some(db.clone()),
+            ..Self::with_repository(Arc::new(db), Config::default())
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self::in_memory_with_config(Config::default())
+    }
+
+    pub fn in_memory_with_config(config: Config) -> Self {
+        Self::with_repository(Arc::new(MemoryRepository::new()), config)
+    }
+
+    /// A fresh, migrated SQLite database that lives as long as the app.
+    pub async fn sqlite() -> Self {
+        let repository = SqliteRepository::connect("sqlite::memory:", 1)
+            .await
+            .expect("in-memory SQLite must open");
+        Self::with_repository(Arc::new(repository), Config::default())
+    }
+
+    fn with_repository(repository: Arc<dyn GameRepository>, config: Config) -> Self {
+        let templates = concat!(env!("CARGO_MANIFEST_DIR"), "/templates/**/*");
+        let mut tera = Tera::new(templates).expect("templates must parse");
+        hitman::i18n::register_tera_functions(&mut tera);
+        let state = AppState {
+            db: repository.clone(),
+            tera,
+            versions: Arc::new(DashMap::new()),
+            kill_tokens: KillTokenSigner::new(vec![b"test-key".to_vec()], 60),
+            config: Arc::new(config),
+            metrics: Metrics::new(),
+        };
+        TestApp {
+            router: create_router(state),
+            db: None,
+            repository,
+        }
+    }
+
+    pub async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        token: Option<&str>,
+        body: Option<Value>,
+    ) -> TestResponse {
+        self.request_with_headers(method, path, token, body, &[])
+            .await
+    }
+
+    pub async fn request_with_headers(
+        &self,
+        method: Method,
+        path: &str,
+        token: Option<&str>,
+        body: Option<Value>,
+        headers: &[(&str, &str)],
+    ) -> TestResponse {
+        let mut builder = Request::builder().method(method).uri(path);
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        if let Some(token) = token {
+            builder = builder.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        let body = match body {
+            Some(json) => {
+                builder = builder.header(header::CONTENT_TYPE, "application/json");
+                Body::from(json.to_string())
+            }
+            None => Body::empty(),
+        };
+        let response = self
+            .router
+            .clone()
+            .oneshot(builder.body(body).unwrap())
+            .await
+            .unwrap();
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let body = if bytes.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&bytes)
+                .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).into()))
+        };
+        TestResponse {
+            status,
+            headers,
+            body,
+        }
+    }
+
+    pub async fn get(&self, path: &str, token: Option<&str>) -> TestResponse {
+        self.request(Method::GET, path, token, None).await
+    }
+
+    pub async fn post(&self, path: &str, token: Option<&str>, body: Option<Value>) -> TestResponse {
+        self.request(Method::POST, path, token, body).await
+    }
+
+    /// Create a game and return its code together with the host.
+    pub async fn create_game(&self, host: &str) -> (String, TestPlayer) {
+        let res = self
+            .post("/api/game", None, Some(json!({ "player_name": host })))
+            .await;
+        assert_eq!(res.status, StatusCode::CREATED, "{}", res.body);
+        let code = res.body["game_code"].as_str().unwrap().to_string();
+        (code, player_from(&res.body, host))
+    }
+
+    pub async fn try_join(&self, code: &str, name: &str) -> TestResponse {
+        self.post(
+            &format!("/api/game/{code}/join"),
+            None,
+            Some(json!({ "player_name": name })),
+        )
+        .await
+    }
+
+    pub async fn join(&self, code: &str, name: &str) -> TestPlayer {
+        let res = self.try_join(code, name).await;
+        player_from(res.assert_ok(), name)
+    }
+
+    pub async fn start(&self, code: &str, host: &TestPlayer) -> TestResponse {
+        self.post(&format!("/api/game/{code}/start"), Some(&host.token), None)
+            .await
+    }
+
+    pub async fn kill(&self, code: &str, killer: &TestPlayer, secret: &str) -> TestResponse {
+        self.post(
+            &format!("/api/game/{code}/eliminate"),
+            Some(&killer.token),
+            Some(json!({ "secret_code": secret })),
+        )
+        .await
+    }
+
+    /// A signed kill token for `player`'s QR code.
+    pub async fn kill_token(&self, code: &str, player: &TestPlayer) -> String {
+        self.get(&format!("/api/game/{code}/kill-token"), Some(&player.token))
+            .await
+            .assert_ok()["token"]
+            .as_str()
+            .expect("kill token missing")
+            .to_string()
+    }
+
+    pub async fn leave(&self, code: &str, player: &TestPlayer) -> TestResponse {
+        self.post(
+            &format!("/api/game/{code}/leave"),
+            Some(&player.token),
+            None,
+        )
+        .await
+    }
+
+    pub async fn game_state(&self, code: &str, player: &TestPlayer) -> Value {
+        self.get(&format!("/api/game/{code}"), Some(&player.token))
+            .await
+            .assert_ok()
+            .clone()
+    }
+
+    /// Create a game with `names[0]` as host, let everyone else join, and
+    /// start it.
+    pub async fn started_game(&self, names: &[&str]) -> (String, Vec<TestPlayer>) {
+        let (code, host) = self.create_game(names[0]).await;
+        let mut players = vec![host];
+        for name in &names[1..] {
+            players.push(self.join(&code, name).await);
+        }
+        self.start(&code, &players[0]).await.assert_ok();
+        (code, players)
+    }
+
+    /// Who each living player is hunting, by name. Players only ever see
+    /// their own target, so this reads the ring from the repository.
+    pub async fn targets(&self, code: &str) -> HashMap<String, String> {
+        let (_, players) = self
+            .repository
+            .get_game_state(code)
+            .await
+            .unwrap()
+            .expect("game must exist");
+        players
+            .into_iter()
+            .filter(|p| p.is_alive)
+            .filter_map(|p| {
+                let target = p.target_name.filter(|t| !t.is_empty())?;
+                Some((p.name, target))
+            })
+            .collect()
+    }
+}
+
+fn player_from(body: &Value, name: &str) -> TestPlayer {
+    TestPlayer {
+        id: body["player_id"].as_i64().unwrap() as i32,
+        name: name.to_string(),
+        token: body["auth_token"].as_str().unwrap().to_string(),
+        secret: body["player_secret"].as_str().unwrap().to_string(),
+    }
+}
@@ -0,0 +1,156 @@
+//! Fires concurrent kills (and leaves) at a running game and checks that the
+//! alive players still form a single target ring after every round.
+//!
+//! Nothing mends a broken ring on its own: debug builds fail a transaction
+//! that would commit one, which shows up here as an unexpected error, and
+//! the ring is checked as the kills left it.
+
+use hitman::{
+    db::Db,
+    engine::MIN_PLAYERS,
+    errors::{AppError, ErrorCode},
+    kill_token::KillProof,
+    models::{GameStatus, Player},
+};
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use tokio::task::JoinSet;
+
+async fn start_ring(db: &Db, code: &str, size: usize) -> (i32, Vec<Player>) {
+    let (game_id, host_id, _, _) = db.create_game("player0".into(), code.into()).await.unwrap();
+    for i in 1..size {
+        db.join_game(code.into(), format!("player{i}"), usize::MAX, false)
+            .await
+            .unwrap();
+    }
+    let players = db.start_game(code, host_id, MIN_PLAYERS).await.unwrap();
+    (game_id, players)
+}
+
+/// Every alive player has an alive target and exactly one alive hunter, and
+/// following targets from any alive player visits all of them in one cycle.
+async fn assert_ring(db: &Db, game_id: i32) -> Vec<Player> {
+    let players = db.get_players_by_game_id(&**db, game_id).await.unwrap();
+    let game = db.get_game_by_id(game_id).await.unwrap().unwrap();
+    let alive: HashMap<i32, &Player> = players
+        .iter()
+        .filter(|p| p.is_alive)
+        .map(|p| (p.id, p))
+        .collect();
+
+    if alive.len() <= 1 {
+        assert_eq!(game.status, GameStatus::Finished);
+        assert!(alive.values().all(|p| p.target_id.is_none()));
+        return alive.into_values().cloned().collect();
+    }
+    assert_eq!(game.status, GameStatus::InProgress);
+
+    let mut hunted = HashSet::new();
+    for p in alive.values() {
+        let target = p.target_id.expect("alive player without a target");
+        assert!(
+            alive.contains_key(&target),
+            "{} targets a dead player",
+            p.name
+        );
+        assert!(hunted.insert(target), "two hunters share target {target}");
+    }
+
+    let start = *alive.keys().next().unwrap();
+    let mut seen = HashSet::new();
+    let mut current = start;
+    while seen.insert(current) {
+        current = alive[&current].target_id.unwrap();
+    }
+    assert_eq!(current, start, "targets do not loop back to the start");
+    assert_eq!(seen.len(), alive.len(), "alive players form several cycles");
+
+    alive.into_values().cloned().collect()
+}
+
+/// A kill or leave that lost a race. An internal error is never one: it is
+/// what the ring check returns when a transaction would break the ring.
+fn assert_expected_race(err: &AppError) {
+    assert!(
+        matches!(
+            err.code(),
+            ErrorCode::KillerEliminated
+                | ErrorCode::TargetEliminated
+                | ErrorCode::WrongTarget
+                | ErrorCode::GameNotInProgress
+        ),
+        "unexpected error from a racing request: {err:?}"
+    );
+}
+
+#[sqlx::test]
+async fn concurrent_kills_keep_the_ring_intact(pool: PgPool) {
+    let db = Db::from_pool(pool);
+    let (game_id, players) = start_ring(&db, "RACE", 12).await;
+    let secrets: HashMap<i32, String> = players
+        .iter()
+        .map(|p| (p.id, p.secret_code.clone()))
+        .collect();
+
+    let mut alive = assert_ring(&db, game_id).await;
+    while alive.len() > 1 {
+        // Everybody goes for their target at the same time.
+        let mut kills = JoinSet::new();
+        for hunter in &alive {
+            let db = db.clone();
+            let token = hunter.auth_token.clone();
+            let secret = secrets[&hunter.target_id.unwrap()].clone();
+            kills.spawn(async move {
+                db.process_kill("RACE", &token, &KillProof::Secret(secret))
+                    .await
+            });
+        }
+        let mut succeeded = 0;
+        while let Some(result) = kills.join_next().await {
+            match result.unwrap() {
+                Ok(_) => succeeded += 1,
+                Err(err) => assert_expected_race(&err),
+            }
+        }
+        assert!(succeeded > 0, "a round of kills made no progress");
+        alive = assert_ring(&db, game_id).await;
+    }
+    assert_eq!(alive.len(), 1);
+}
+
+#[sqlx::test]
+async fn kills_racing_leaves_keep_the_ring_intact(pool: PgPool) {
+    let db = Db::from_pool(pool);
+    let (game_id, players) = start_ring(&db, "LEAV", 16).await;
+    let secrets: HashMap<i32, String> = players
+        .iter()
+        .map(|p| (p.id, p.secret_code.clone()))
+        .collect();
+
+    let mut alive = assert_ring(&db, game_id).await;
+    let mut round = 0;
+    while alive.len() > 1 {
+        round += 1;
+        let mut requests = JoinSet::new();
+        for (i, player) in alive.iter().enumerate() {
+            let db = db.clone();
+            let token = player.auth_token.clone();
+            if (i + round) % 3 == 0 {
+                requests.spawn(async move { db.leave_game("LEAV", &token).await });
+            } else {
+                let secret = secrets[&player.target_id.unwrap()].clone();
+                requests.spawn(async move {
+                    db.process_kill("LEAV", &token, &KillProof::Secret(secret))
+                        .await
+                        .map(|_| ())
+                });
+            }
+        }
+        while let Some(result) = requests.join_next().await {
+            if let Err(err) = result.unwrap() {
+                assert_expected_race(&err);
+            }
+        }
+        alive = assert_ring(&db, game_id).await;
+    }
+}
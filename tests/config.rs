@@ -0,0 +1,182 @@
+//! Loading and validating settings from a TOML file and the environment.
+
+use hitman::config::{Config, ConfigError, LogFormat};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let vars: HashMap<String, String> = vars
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    move |var| vars.get(var).cloned()
+}
+
+fn write_file(contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("hitman-{}.toml", uuid::Uuid::new_v4()));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn defaults_only_need_a_database() {
+    let config = Config::from_sources(None, env(&[("DATABASE_URL", "memory")])).unwrap();
+    assert_eq!(config.server.bind.port(), 3000);
+    assert_eq!(config.database.max_connections, 5);
+    assert_eq!(config.game.code_length, 4);
+    assert_eq!(config.rate_limits.secret_rotation_limit, 3);
+    assert!(config.retention.game_ttl().is_none());
+    assert!(config.features.kill_tokens && config.features.secret_rotation);
+    assert!(config.features.spectators);
+    assert!(config.admin.token.is_none());
+
+    let err = Config::from_sources(None, env(&[])).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ConfigError::Invalid {
+                key: "database.url",
+                ..
+            }
+        ),
+        "{err}"
+    );
+}
+
+#[test]
+fn environment_overrides_the_file() {
+    let path = write_file(
+        r#"
+        [server]
+        bind = "127.0.0.1:8080"
+        cors_origins = ["https://hitman.example"]
+
+        [database]
+        url = "sqlite://hitman.db"
+        max_connections = 2
+
+        [game]
+        code_length = 6
+
+        [retention]
+        game_ttl_hours = 48
+
+        [features]
+        secret_rotation = false
+        "#,
+    );
+    let config = Config::from_sources(
+        Some(&path),
+        env(&[
+            ("GAME_CODE_LENGTH", "8"),
+            ("GAME_MAX_PLAYERS", "12"),
+            ("FEATURE_KILL_TOKENS", "off"),
+            ("FEATURE_SPECTATORS", "false"),
+            ("KILL_TOKEN_KEYS", "new, old"),
+            ("LOG_FORMAT", "JSON"),
+        ]),
+    )
+    .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(config.server.bind.to_string(), "127.0.0.1:8080");
+    assert_eq!(config.server.cors_origins, ["https://hitman.example"]);
+    assert_eq!(config.database.url, "sqlite://hitman.db");
+    assert_eq!(config.database.max_connections, 2);
+    assert_eq!(config.game.code_length, 8);
+    assert_eq!(config.game.max_players, 12);
+    assert_eq!(config.kill_tokens.keys, ["new", "old"]);
+    assert_eq!(
+        config.retention.game_ttl(),
+        Some(std::time::Duration::from_secs(48 * 3600))
+    );
+    assert!(!config.features.kill_tokens);
+    assert!(!config.features.secret_rotation);
+    assert!(!config.features.spectators);
+    assert_eq!(config.log.format, LogFormat::Json);
+}
+
+#[test]
+fn bad_values_are_reported_by_name() {
+    let base = ("DATABASE_URL", "memory");
+    let err = Config::from_sources(None, env(&[base, ("GAME_CODE_LENGTH", "four")])).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ConfigError::Env {
+                var: "GAME_CODE_LENGTH",
+                ..
+            }
+        ),
+        "{err}"
+    );
+
+    let err = Config::from_sources(None, env(&[base, ("GAME_CODE_LENGTH", "2")])).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ConfigError::Invalid {
+                key: "game.code_length",
+                ..
+            }
+        ),
+        "{err}"
+    );
+
+    let err = Config::from_sources(
+        None,
+        env(&[
+            base,
+            (
+                "CORS_ORIGINS",
+                "https://ok.example,https://bad.example/path",
+            ),
+        ]),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("bad.example/path"), "{err}");
+
+    let err = Config::from_sources(
+        None,
+        env(&[base, ("OTEL_EXPORTER_OTLP_ENDPOINT", "collector:4318")]),
+    )
+    .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ConfigError::Invalid {
+                key: "telemetry.otlp_endpoint",
+                ..
+            }
+        ),
+        "{err}"
+    );
+
+    let err =
+        Config::from_sources(None, env(&[base, ("FEATURE_KILL_TOKENS", "maybe")])).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ConfigError::Env {
+                var: "FEATURE_KILL_TOKENS",
+                ..
+            }
+        ),
+        "{err}"
+    );
+}
+
+#[test]
+fn unknown_keys_in_the_file_are_rejected() {
+    let path = write_file("[game]\ncode_lenght = 6\n");
+    let err = Config::from_sources(Some(&path), env(&[("DATABASE_URL", "memory")])).unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(err, ConfigError::Parse { .. }), "{err}");
+    assert!(err.to_string().contains("code_lenght"), "{err}");
+}
+
+#[test]
+fn example_file_is_valid() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/hitman.example.toml");
+    Config::from_sources(Some(path.as_ref()), env(&[])).unwrap();
+}
@@ -0,0 +1,615 @@
+//! The game rules on their own, without any storage behind them.
+
+use hitman::{
+    engine::{self, RingPlan, RingViolation},
+    errors::{AppError, ErrorCode},
+    i18n::Locale,
+    kill_token::KillTokenClaims,
+    models::{
+        ChatChannel, DisputeStatus, Game, GameSettings, GameStatus, KillDispute, Player,
+        RecoveryRequest, RequestStatus,
+    },
+};
+use std::collections::HashSet;
+
+fn game(status: GameStatus) -> Game {
+    Game {
+        id: 1,
+        status,
+        host_id: Some(1),
+        code: "ABCD".into(),
+    }
+}
+
+fn player(id: i32, target_id: Option<i32>) -> Player {
+    Player {
+        id,
+        name: format!("player{id}"),
+        secret_code: format!("SECRET{id}"),
+        auth_token: format!("token-{id}"),
+        is_alive: true,
+        is_ready: false,
+        target_id,
+        game_id: 1,
+        target_name: None,
+    }
+}
+
+fn dead(mut p: Player) -> Player {
+    p.is_alive = false;
+    p
+}
+
+#[track_caller]
+fn assert_code(result: Result<(), AppError>, code: ErrorCode) {
+    assert_eq!(result.expect_err("expected an error").code(), code);
+}
+
+// ---------- validate_kill ----------
+
+#[test]
+fn kill_of_the_current_target_is_allowed() {
+    let g = game(GameStatus::InProgress);
+    assert!(engine::validate_kill(&player(1, Some(2)), &player(2, Some(1)), &g).is_ok());
+}
+
+#[test]
+fn kill_rules_in_order() {
+    let g = game(GameStatus::InProgress);
+    let killer = player(1, Some(2));
+    let target = player(2, Some(1));
+
+    assert_code(
+        engine::validate_kill(&dead(killer.clone()), &target, &g),
+        ErrorCode::KillerEliminated,
+    );
+    assert_code(
+        engine::validate_kill(&killer, &dead(target.clone()), &g),
+        ErrorCode::TargetEliminated,
+    );
+    let stranger = Player {
+        game_id: 2,
+        ..target.clone()
+    };
+    assert_code(
+        engine::validate_kill(&killer, &stranger, &g),
+        ErrorCode::NotSameGame,
+    );
+    assert_code(
+        engine::validate_kill(&killer, &killer, &g),
+        ErrorCode::SelfKill,
+    );
+    for status in [GameStatus::Lobby, GameStatus::Finished] {
+        assert_code(
+            engine::validate_kill(&killer, &target, &game(status)),
+            ErrorCode::GameNotInProgress,
+        );
+    }
+    assert_code(
+        engine::validate_kill(&killer, &player(3, Some(1)), &g),
+        ErrorCode::WrongTarget,
+    );
+}
+
+// ---------- Lobby ----------
+
+#[test]
+fn join_rules() {
+    let lobby = game(GameStatus::Lobby);
+    assert!(engine::check_join(&lobby, None, 1, 10, false).is_ok());
+    assert_code(
+        engine::check_join(&lobby, Some(&player(2, None)), 1, 10, false),
+        ErrorCode::NameTaken,
+    );
+    assert_code(
+        engine::check_join(&lobby, Some(&dead(player(2, None))), 1, 10, false),
+        ErrorCode::PlayerEliminated,
+    );
+    assert_code(
+        engine::check_join(&game(GameStatus::InProgress), None, 1, 10, false),
+        ErrorCode::GameAlreadyStarted,
+    );
+    assert_code(
+        engine::check_join(&lobby, None, 10, 10, false),
+        ErrorCode::LobbyFull { max: 10 },
+    );
+    // A full lobby still says why the name cannot be used.
+    assert_code(
+        engine::check_join(&lobby, Some(&player(2, None)), 10, 10, false),
+        ErrorCode::NameTaken,
+    );
+}
+
+#[test]
+fn late_join_rules() {
+    let running = game(GameStatus::InProgress);
+    assert!(engine::check_join(&running, None, 3, 10, true).is_ok());
+    assert_code(
+        engine::check_join(&running, None, 10, 10, true),
+        ErrorCode::LobbyFull { max: 10 },
+    );
+    assert_code(
+        engine::check_join(&game(GameStatus::Finished), None, 3, 10, true),
+        ErrorCode::GameAlreadyStarted,
+    );
+}
+
+#[test]
+fn start_rules() {
+    let lobby = game(GameStatus::Lobby);
+    assert!(engine::check_start(&lobby, 1, 2, 2).is_ok());
+    assert_code(engine::check_start(&lobby, 2, 2, 2), ErrorCode::NotHost);
+    assert_code(
+        engine::check_start(&lobby, 1, 1, 2),
+        ErrorCode::NotEnoughPlayers { min: 2 },
+    );
+    assert_code(
+        engine::check_start(&lobby, 1, 3, 4),
+        ErrorCode::NotEnoughPlayers { min: 4 },
+    );
+    assert_code(
+        engine::check_start(&game(GameStatus::InProgress), 1, 2, 2),
+        ErrorCode::GameAlreadyStarted,
+    );
+    // Starting twice is not about joining, even though the code is shared.
+    let twice = engine::check_start(&game(GameStatus::InProgress), 1, 2, 2).unwrap_err();
+    let join = engine::check_join(&game(GameStatus::InProgress), None, 2, 10, false).unwrap_err();
+    assert_ne!(twice.message(Locale::En), join.message(Locale::En));
+}
+
+#[test]
+fn player_limits_come_from_the_settings_and_the_server() {
+    let mut settings = GameSettings::default();
+    assert_eq!(engine::min_players(&settings), 2);
+    assert_eq!(engine::max_players(&settings, 50), 50);
+    settings.min_players = Some(1);
+    settings.max_players = Some(80);
+    assert_eq!(engine::min_players(&settings), 2);
+    assert_eq!(engine::max_players(&settings, 50), 50);
+
+    let limits = |min, max| GameSettings {
+        min_players: min,
+        max_players: max,
+        ..GameSettings::default()
+    };
+    assert!(engine::check_settings(&limits(Some(4), Some(8)), 50).is_ok());
+    assert!(engine::check_settings(&limits(None, Some(2)), 50).is_ok());
+    for (min, max) in [
+        (None, Some(1)),
+        (None, Some(51)),
+        (Some(9), Some(8)),
+        (Some(51), None),
+    ] {
+        assert_code(
+            engine::check_settings(&limits(min, max), 50),
+            ErrorCode::InvalidLobbySize { max: 50 },
+        );
+    }
+}
+
+#[test]
+fn lobby_starts_itself_once_everyone_is_ready() {
+    let ready = |id| Player {
+        is_ready: true,
+        ..player(id, None)
+    };
+    let mut settings = GameSettings {
+        auto_start: true,
+        min_players: Some(3),
+        ..GameSettings::default()
+    };
+    assert!(!engine::should_auto_start(&settings, &[ready(1), ready(2)]));
+    assert!(!engine::should_auto_start(
+        &settings,
+        &[ready(1), ready(2), player(3, None)]
+    ));
+    let everyone = [ready(1), ready(2), ready(3)];
+    assert!(engine::should_auto_start(&settings, &everyone));
+    settings.auto_start = false;
+    assert!(!engine::should_auto_start(&settings, &everyone));
+}
+
+#[test]
+fn assigned_targets_form_a_single_ring() {
+    for size in 2..12 {
+        let pairs = engine::assign_targets((1..=size).collect());
+        let players: Vec<Player> = pairs
+            .iter()
+            .map(|&(id, target)| player(id, Some(target)))
+            .collect();
+        assert_eq!(players.len(), size as usize);
+        assert!(engine::ring_violations(&players).is_empty());
+    }
+}
+
+#[test]
+fn earliest_joiner_becomes_host() {
+    assert_eq!(
+        engine::next_host(&[player(7, None), player(3, None), player(5, None)]),
+        Some(3)
+    );
+    assert_eq!(engine::next_host(&[]), None);
+}
+
+// ---------- Secrets and tokens ----------
+
+#[test]
+fn rotation_rules() {
+    let g = game(GameStatus::InProgress);
+    let p = player(1, Some(2));
+    assert!(engine::check_rotation(&g, &p, 0, 3, None).is_ok());
+    assert!(engine::check_rotation(&g, &p, 2, 3, Some(0)).is_ok());
+    assert_code(
+        engine::check_rotation(&game(GameStatus::Finished), &p, 0, 3, None),
+        ErrorCode::GameFinished,
+    );
+    assert_code(
+        engine::check_rotation(&g, &dead(p.clone()), 0, 3, None),
+        ErrorCode::PlayerEliminated,
+    );
+    assert_code(
+        engine::check_rotation(&g, &p, 3, 3, None),
+        ErrorCode::SecretRotationLimit { limit: 3 },
+    );
+    assert_code(
+        engine::check_rotation(&g, &p, 1, 3, Some(42)),
+        ErrorCode::SecretRotationCooldown { seconds: 42 },
+    );
+}
+
+#[test]
+fn kill_token_rules() {
+    let claims = KillTokenClaims {
+        game: "ABCD".into(),
+        victim: 2,
+        generation: 1,
+        exp: 0,
+        nonce: "n".into(),
+    };
+    assert!(engine::check_token_game(&claims, &game(GameStatus::InProgress)).is_ok());
+    let other = Game {
+        code: "WXYZ".into(),
+        ..game(GameStatus::InProgress)
+    };
+    assert_code(
+        engine::check_token_game(&claims, &other),
+        ErrorCode::KillTokenWrongGame,
+    );
+    assert!(engine::check_token_generation(&claims, 1).is_ok());
+    assert_code(
+        engine::check_token_generation(&claims, 2),
+        ErrorCode::KillTokenStale,
+    );
+}
+
+// ---------- Target ring ----------
+
+#[test]
+fn ring_violations_are_reported() {
+    assert_eq!(
+        engine::ring_violations(&[player(1, Some(2)), dead(player(2, Some(1)))]),
+        [RingViolation::TooFewAlive { alive: 1 }]
+    );
+    assert_eq!(
+        engine::ring_violations(&[
+            player(1, Some(2)),
+            player(2, Some(1)),
+            player(3, Some(4)),
+            player(4, Some(3)),
+        ]),
+        [RingViolation::SplitRing { cycles: 2 }]
+    );
+    assert_eq!(
+        engine::ring_violations(&[player(1, Some(3)), player(2, Some(3)), player(3, Some(3))]),
+        [
+            RingViolation::SelfTarget { player_id: 3 },
+            RingViolation::Unhunted { player_id: 1 },
+            RingViolation::Unhunted { player_id: 2 },
+            RingViolation::SharedTarget {
+                target_id: 3,
+                hunters: vec![1, 2],
+            },
+        ]
+    );
+}
+
+#[test]
+fn repair_keeps_intact_links() {
+    // 1 -> 2 -> 3 is fine, 3 hunts a dead player and 4 is hunted by nobody.
+    let players = [
+        player(1, Some(2)),
+        player(2, Some(3)),
+        player(3, Some(5)),
+        player(4, None),
+        dead(player(5, Some(1))),
+    ];
+    let RingPlan::Retarget(changes) = engine::plan_ring_repair(&players) else {
+        panic!("expected the ring to be rebuilt");
+    };
+    let mut repaired = players.to_vec();
+    for (id, target) in &changes {
+        repaired.iter_mut().find(|p| p.id == *id).unwrap().target_id = Some(*target);
+    }
+    assert!(engine::ring_violations(&repaired).is_empty());
+    assert!(!changes.iter().any(|&(id, _)| id == 1 || id == 2));
+}
+
+#[test]
+fn repair_finishes_a_game_with_one_survivor() {
+    assert_eq!(
+        engine::plan_ring_repair(&[player(1, Some(2)), dead(player(2, None))]),
+        RingPlan::Finish { winner_id: Some(1) }
+    );
+}
+
+#[test]
+fn splicing_only_changes_the_hunter() {
+    // 1 -> 2 -> 3 -> 1, 4 was killed by 2.
+    let players = [
+        player(1, Some(2)),
+        player(2, Some(3)),
+        player(3, Some(1)),
+        dead(player(4, None)),
+    ];
+    let changes = engine::plan_splice(&players, 4, Some(2)).unwrap();
+    assert_eq!(changes, [(4, 3), (2, 4)]);
+    let mut spliced = players.to_vec();
+    spliced[3].is_alive = true;
+    for (id, target) in &changes {
+        spliced.iter_mut().find(|p| p.id == *id).unwrap().target_id = Some(*target);
+    }
+    assert!(engine::ring_violations(&spliced).is_empty());
+
+    // A dead hunter is replaced by the first living player.
+    let players = [
+        player(1, Some(2)),
+        player(2, Some(1)),
+        dead(player(3, None)),
+    ];
+    assert_eq!(
+        engine::plan_splice(&players, 4, Some(3)),
+        Some(vec![(4, 2), (1, 4)])
+    );
+    assert_eq!(engine::plan_splice(&[dead(player(1, None))], 2, None), None);
+}
+
+#[test]
+fn late_joiners_land_behind_a_random_hunter() {
+    let players = [
+        player(1, Some(2)),
+        player(2, Some(3)),
+        player(3, Some(1)),
+        dead(player(4, None)),
+    ];
+    let mut hunters = HashSet::new();
+    for _ in 0..100 {
+        let changes = engine::plan_late_join(&players, 5).unwrap();
+        let mut spliced = players.to_vec();
+        spliced.push(player(5, None));
+        for (id, target) in &changes {
+            spliced.iter_mut().find(|p| p.id == *id).unwrap().target_id = Some(*target);
+        }
+        assert!(engine::ring_violations(&spliced).is_empty());
+        // Only the hunter's and the newcomer's targets change.
+        assert_eq!(changes.len(), 2);
+        hunters.insert(changes[1].0);
+    }
+    assert_eq!(hunters, HashSet::from([1, 2, 3]));
+}
+
+#[test]
+fn disputes_are_settled_by_a_majority_of_the_other_ghosts() {
+    // 5 killed 1; 2, 3 and 4 are the other ghosts.
+    let players = [
+        dead(player(1, None)),
+        dead(player(2, None)),
+        dead(player(3, None)),
+        dead(player(4, None)),
+        player(5, Some(6)),
+        player(6, Some(5)),
+    ];
+    let voters = engine::dispute_voters(&players, 1, Some(5));
+    assert_eq!(voters, [2, 3, 4]);
+    assert_eq!(engine::dispute_majority(voters.len()), 2);
+
+    assert_eq!(engine::dispute_outcome(&[(2, true)], &voters), None);
+    assert_eq!(
+        engine::dispute_outcome(&[(2, true), (3, true)], &voters),
+        Some(DisputeStatus::Overturned)
+    );
+    assert_eq!(
+        engine::dispute_outcome(&[(2, false), (4, false)], &voters),
+        Some(DisputeStatus::Upheld)
+    );
+    // The victim's own vote does not count.
+    assert_eq!(
+        engine::dispute_outcome(&[(1, true), (2, true)], &voters),
+        None
+    );
+    // A tie once everyone voted leaves the kill standing.
+    assert_eq!(
+        engine::dispute_outcome(&[(2, true), (3, false)], &[2, 3]),
+        Some(DisputeStatus::Upheld)
+    );
+    assert_eq!(engine::dispute_outcome(&[], &[]), None);
+}
+
+#[test]
+fn votes_replace_earlier_ones_and_settle_open_disputes() {
+    let players = [
+        dead(player(1, None)),
+        dead(player(2, None)),
+        dead(player(3, None)),
+        player(5, Some(6)),
+        player(6, Some(5)),
+    ];
+    let mut dispute = KillDispute {
+        id: 1,
+        game_id: 1,
+        event_id: 1,
+        victim_id: 1,
+        killer_id: Some(5),
+        status: DisputeStatus::Open,
+        votes: Vec::new(),
+    };
+    assert_eq!(
+        engine::cast_vote(&mut dispute, &players, 2, false).unwrap(),
+        None
+    );
+    assert_eq!(
+        engine::cast_vote(&mut dispute, &players, 2, true).unwrap(),
+        None
+    );
+    assert_eq!(dispute.votes, [(2, true)]);
+    assert_code(
+        engine::cast_vote(&mut dispute, &players, 1, true).map(drop),
+        ErrorCode::OwnDispute,
+    );
+    assert_eq!(
+        engine::cast_vote(&mut dispute, &players, 3, true).unwrap(),
+        Some(DisputeStatus::Overturned)
+    );
+    dispute.status = DisputeStatus::Overturned;
+    assert_code(
+        engine::cast_vote(&mut dispute, &players, 3, false).map(drop),
+        ErrorCode::DisputeClosed,
+    );
+}
+
+#[test]
+fn chat_channels_have_their_own_posters() {
+    let settings = GameSettings {
+        dead_chat: true,
+        ..GameSettings::default()
+    };
+    let host = player(1, None);
+    let guest = player(2, None);
+    let lobby = game(GameStatus::Lobby);
+    let running = game(GameStatus::InProgress);
+    let post = |game: &Game, player: &Player, channel| {
+        engine::check_chat_post(game, &settings, player, channel).map_err(|err| err.code())
+    };
+
+    assert_eq!(post(&lobby, &host, ChatChannel::Announcements), Ok(()));
+    assert_eq!(
+        post(&lobby, &guest, ChatChannel::Announcements),
+        Err(ErrorCode::NotHost)
+    );
+    assert_eq!(post(&lobby, &guest, ChatChannel::Lobby), Ok(()));
+    assert_eq!(
+        post(&running, &guest, ChatChannel::Lobby),
+        Err(ErrorCode::ChatClosed)
+    );
+    assert_eq!(
+        post(&running, &guest, ChatChannel::Dead),
+        Err(ErrorCode::ChatForbidden)
+    );
+    assert_eq!(
+        post(&running, &dead(guest.clone()), ChatChannel::Dead),
+        Ok(())
+    );
+    // Announcements explain themselves rather than borrowing the start message.
+    let announce =
+        engine::check_chat_post(&lobby, &settings, &guest, ChatChannel::Announcements).unwrap_err();
+    let start = engine::check_start(&lobby, guest.id, 2, 2).unwrap_err();
+    assert_ne!(announce.message(Locale::En), start.message(Locale::En));
+    assert!(!engine::can_read_chat(ChatChannel::Dead, &guest, &settings));
+    assert!(!engine::can_read_chat(
+        ChatChannel::Dead,
+        &dead(guest.clone()),
+        &GameSettings::default()
+    ));
+    assert!(!engine::spectators_read_chat(
+        ChatChannel::Lobby,
+        &GameSettings::default()
+    ));
+}
+
+#[test]
+fn chat_is_rate_limited_per_window() {
+    assert!(engine::check_chat_rate(&[100, 110], 120, 3, 30).is_ok());
+    let err = engine::check_chat_rate(&[100, 110, 115], 120, 3, 30).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::ChatRateLimited { seconds: 10 });
+    // Messages older than the window no longer count.
+    assert!(engine::check_chat_rate(&[80, 110, 115], 120, 3, 30).is_ok());
+}
+
+// ---------- Recovery ----------
+
+fn recovery(player_id: i32, status: RequestStatus, created_at: i64) -> RecoveryRequest {
+    RecoveryRequest {
+        id: created_at,
+        game_id: 1,
+        player_id,
+        claim_token: format!("claim-{created_at}"),
+        status,
+        auth_token: None,
+        created_at,
+    }
+}
+
+#[test]
+fn recovery_request_rules() {
+    let answered = [recovery(2, RequestStatus::Denied, 100)];
+    assert!(engine::check_recovery_request(&answered, 2, 120, 3, 600).is_ok());
+    let pending = [recovery(2, RequestStatus::Pending, 100)];
+    assert_code(
+        engine::check_recovery_request(&pending, 2, 120, 3, 600),
+        ErrorCode::RecoveryPending,
+    );
+    assert!(engine::check_recovery_request(&pending, 3, 120, 3, 600).is_ok());
+
+    // The limit counts every request in the game, answered or not.
+    let busy = [
+        recovery(2, RequestStatus::Denied, 100),
+        recovery(3, RequestStatus::Approved, 110),
+        recovery(4, RequestStatus::Pending, 115),
+    ];
+    assert_code(
+        engine::check_recovery_request(&busy, 5, 120, 3, 600),
+        ErrorCode::RecoveryRateLimited { seconds: 580 },
+    );
+    assert!(engine::check_recovery_request(&busy, 5, 701, 3, 600).is_ok());
+}
+
+#[test]
+fn the_host_answers_recoveries_unless_it_is_their_own() {
+    let g = game(GameStatus::InProgress);
+    assert!(engine::may_answer_recovery(&g, 1, 2));
+    assert!(!engine::may_answer_recovery(&g, 3, 2));
+    assert!(!engine::may_answer_recovery(&g, 2, 2));
+    // The host lost their link: anyone else may let them back in.
+    assert!(engine::may_answer_recovery(&g, 2, 1));
+    assert!(!engine::may_answer_recovery(&g, 1, 1));
+}
+
+// ---------- Error messages ----------
+
+#[test]
+fn errors_sharing_a_code_keep_their_own_message() {
+    let g = game(GameStatus::InProgress);
+    let rejoin = engine::check_join(&g, Some(&dead(player(2, None))), 1, 10, true).unwrap_err();
+    let rotate = engine::check_rotation(&g, &dead(player(2, None)), 0, 3, None).unwrap_err();
+    assert_eq!(rejoin.code(), rotate.code());
+    for locale in Locale::ALL {
+        assert_ne!(rejoin.message(locale), rotate.message(locale));
+    }
+    // Values the code carries end up in the message.
+    let full = engine::check_join(&game(GameStatus::Lobby), None, 7, 7, false).unwrap_err();
+    assert!(full.message(Locale::Nl).contains('7'));
+}
+
+#[test]
+fn every_locale_translates_every_key() {
+    let keys = |raw: &str| {
+        let catalogue: std::collections::HashMap<String, String> =
+            serde_json::from_str(raw).unwrap();
+        catalogue.into_keys().collect::<HashSet<_>>()
+    };
+    let english = keys(include_str!("../locales/en.json"));
+    let dutch = keys(include_str!("../locales/nl.json"));
+    assert_eq!(
+        english.symmetric_difference(&dutch).collect::<Vec<_>>(),
+        Vec::<&String>::new()
+    );
+}
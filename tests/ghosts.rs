@@ -0,0 +1,338 @@
+//! Ghost mode: eliminated players who stay on to judge disputed kills and
+//! whisper hints to the living.
+
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{on_every_backend, TestApp, TestPlayer};
+use serde_json::{json, Value};
+
+/// A lobby hosted by `names[0]` with `settings`, started.
+async fn game_with(app: &TestApp, names: &[&str], settings: Value) -> (String, Vec<TestPlayer>) {
+    let (code, host) = app.create_game(names[0]).await;
+    let mut players = vec![host];
+    for name in &names[1..] {
+        players.push(app.join(&code, name).await);
+    }
+    app.post(
+        &format!("/api/game/{code}/settings"),
+        Some(&players[0].token),
+        Some(settings),
+    )
+    .await
+    .assert_ok();
+    app.start(&code, &players[0]).await.assert_ok();
+    (code, players)
+}
+
+/// Let `killer` eliminate their current target, who is returned.
+async fn kill_target(
+    app: &TestApp,
+    code: &str,
+    killer: &TestPlayer,
+    players: &[TestPlayer],
+) -> TestPlayer {
+    let targets = app.targets(code).await;
+    let victim = players
+        .iter()
+        .find(|p| p.name == targets[&killer.name])
+        .unwrap()
+        .clone();
+    app.kill(code, killer, &victim.secret).await.assert_ok();
+    victim
+}
+
+async fn ghost(app: &TestApp, code: &str, player: &TestPlayer) -> Value {
+    app.get(&format!("/api/game/{code}/ghost"), Some(&player.token))
+        .await
+        .assert_ok()
+        .clone()
+}
+
+async fn vote(
+    app: &TestApp,
+    code: &str,
+    player: &TestPlayer,
+    dispute_id: i64,
+    overturn: bool,
+) -> crate::common::TestResponse {
+    app.post(
+        &format!("/api/game/{code}/ghost/disputes/{dispute_id}/votes"),
+        Some(&player.token),
+        Some(json!({ "overturn": overturn })),
+    )
+    .await
+}
+
+async fn ghosts_overturn_a_kill(app: TestApp) {
+    let settings = json!({ "ghosts": true, "ghost_hints": true });
+    let (code, players) =
+        game_with(&app, &["alice", "bob", "carol", "dave", "erin"], settings).await;
+    let alice = &players[0];
+    let first = kill_target(&app, &code, alice, &players).await;
+    let second = kill_target(&app, &code, alice, &players).await;
+    let third = kill_target(&app, &code, alice, &players).await;
+    let last = players
+        .iter()
+        .find(|p| ![&alice.id, &first.id, &second.id, &third.id].contains(&&p.id))
+        .unwrap();
+
+    assert_eq!(app.game_state(&code, &third).await["role"], "ghost");
+    assert_eq!(app.game_state(&code, alice).await["role"], "alive");
+    app.get(&format!("/api/game/{code}/ghost"), Some(&alice.token))
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "NOT_A_GHOST");
+
+    let overview = ghost(&app, &code, &third).await;
+    assert_eq!(overview["can_hint"], true);
+    let event_id = overview["disputable_event_id"].as_i64().unwrap();
+    let dispute_path = format!("/api/game/{code}/ghost/disputes");
+    app.post(
+        &dispute_path,
+        Some(&second.token),
+        Some(json!({ "event_id": event_id })),
+    )
+    .await
+    .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "NOT_DISPUTABLE");
+    let opened = app
+        .post(
+            &dispute_path,
+            Some(&third.token),
+            Some(json!({ "event_id": event_id })),
+        )
+        .await;
+    assert_eq!(opened.status, StatusCode::CREATED, "{}", opened.body);
+    assert_eq!(opened.body["killer_name"], "alice");
+    assert_eq!(opened.body["votes_needed"], 2);
+    assert_eq!(opened.body["can_vote"], false);
+    let dispute_id = opened.body["id"].as_i64().unwrap();
+    app.post(
+        &dispute_path,
+        Some(&third.token),
+        Some(json!({ "event_id": event_id })),
+    )
+    .await
+    .assert_error(StatusCode::CONFLICT, "ALREADY_DISPUTED");
+    assert!(ghost(&app, &code, &third).await["disputable_event_id"].is_null());
+
+    vote(&app, &code, &third, dispute_id, true)
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "OWN_DISPUTE");
+    let after_one = vote(&app, &code, &first, dispute_id, true).await;
+    let after_one = after_one.assert_ok();
+    assert_eq!(after_one["status"], "open");
+    assert_eq!(after_one["overturn_votes"], 1);
+    assert_eq!(after_one["my_vote"], true);
+    let settled = vote(&app, &code, &second, dispute_id, true).await;
+    assert_eq!(settled.assert_ok()["status"], "overturned");
+    vote(&app, &code, &first, dispute_id, false)
+        .await
+        .assert_error(StatusCode::CONFLICT, "DISPUTE_CLOSED");
+
+    // Back in, right behind the killer.
+    assert_eq!(app.game_state(&code, &third).await["role"], "alive");
+    let targets = app.targets(&code).await;
+    assert_eq!(targets["alice"], third.name);
+    assert_eq!(targets[&third.name], last.name);
+    assert_eq!(targets[&last.name], "alice");
+    let feed = app
+        .get(&format!("/api/game/{code}/activity"), Some(&alice.token))
+        .await;
+    let newest = &feed.assert_ok()["events"][0];
+    assert_eq!(newest["kind"], "revived");
+    assert_eq!(newest["actor_name"], third.name.as_str());
+
+    let hint_path = format!("/api/game/{code}/ghost/hint");
+    let sent = app
+        .post(
+            &hint_path,
+            Some(&first.token),
+            Some(json!({ "player_id": last.id, "message": "  watch your back  " })),
+        )
+        .await;
+    assert_eq!(sent.status, StatusCode::NO_CONTENT, "{}", sent.body);
+    app.post(
+        &hint_path,
+        Some(&first.token),
+        Some(json!({ "player_id": alice.id, "message": "again" })),
+    )
+    .await
+    .assert_error(StatusCode::CONFLICT, "HINT_ALREADY_SENT");
+    assert_eq!(ghost(&app, &code, &first).await["can_hint"], false);
+    app.post(
+        &hint_path,
+        Some(&second.token),
+        Some(json!({ "player_id": first.id, "message": "boo" })),
+    )
+    .await
+    .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "HINT_TARGET_DEAD");
+    app.post(
+        &hint_path,
+        Some(&second.token),
+        Some(json!({ "player_id": alice.id, "message": "x".repeat(141) })),
+    )
+    .await
+    .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "INVALID_MESSAGE");
+
+    let hints = app
+        .get(&format!("/api/game/{code}/hints"), Some(&last.token))
+        .await;
+    let hints = hints.assert_ok();
+    assert_eq!(hints.as_array().unwrap().len(), 1);
+    assert_eq!(hints[0]["message"], "watch your back");
+    assert!(!hints.to_string().contains(&first.name), "sender leaked");
+    let none = app
+        .get(&format!("/api/game/{code}/hints"), Some(&alice.token))
+        .await;
+    assert_eq!(none.assert_ok(), &json!([]));
+}
+
+on_every_backend!(ghosts_overturn_a_kill);
+
+/// Two deciding votes cast at once: both are counted and the victim comes
+/// back exactly once.
+async fn simultaneous_votes_settle_a_dispute_once(app: TestApp) {
+    let settings = json!({ "ghosts": true });
+    let (code, players) =
+        game_with(&app, &["alice", "bob", "carol", "dave", "erin"], settings).await;
+    let alice = &players[0];
+    let first = kill_target(&app, &code, alice, &players).await;
+    let second = kill_target(&app, &code, alice, &players).await;
+    let third = kill_target(&app, &code, alice, &players).await;
+    let event_id = ghost(&app, &code, &third).await["disputable_event_id"]
+        .as_i64()
+        .unwrap();
+    let opened = app
+        .post(
+            &format!("/api/game/{code}/ghost/disputes"),
+            Some(&third.token),
+            Some(json!({ "event_id": event_id })),
+        )
+        .await;
+    let dispute_id = opened.body["id"].as_i64().unwrap();
+
+    let (one, two) = tokio::join!(
+        vote(&app, &code, &first, dispute_id, true),
+        vote(&app, &code, &second, dispute_id, true),
+    );
+    let statuses = [&one.assert_ok()["status"], &two.assert_ok()["status"]];
+    assert!(statuses.contains(&&json!("overturned")), "{statuses:?}");
+    assert_eq!(app.game_state(&code, &third).await["role"], "alive");
+    let feed = app
+        .get(&format!("/api/game/{code}/activity"), Some(&alice.token))
+        .await;
+    let revivals = feed.assert_ok()["events"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|e| e["kind"] == "revived")
+        .count();
+    assert_eq!(revivals, 1);
+}
+
+on_every_backend!(simultaneous_votes_settle_a_dispute_once);
+
+#[tokio::test]
+async fn the_eliminated_are_only_ghosts_when_the_host_says_so() {
+    let app = TestApp::in_memory();
+    let (code, players) = game_with(&app, &["alice", "bob", "carol"], json!({})).await;
+    let victim = kill_target(&app, &code, &players[0], &players).await;
+    assert_eq!(app.game_state(&code, &victim).await["role"], "eliminated");
+    app.get(&format!("/api/game/{code}/ghost"), Some(&victim.token))
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "NOT_A_GHOST");
+
+    let (code, players) =
+        game_with(&app, &["alice", "bob", "carol"], json!({ "ghosts": true })).await;
+    let victim = kill_target(&app, &code, &players[0], &players).await;
+    assert_eq!(ghost(&app, &code, &victim).await["can_hint"], false);
+    app.post(
+        &format!("/api/game/{code}/ghost/hint"),
+        Some(&victim.token),
+        Some(json!({ "player_id": players[0].id, "message": "hi" })),
+    )
+    .await
+    .assert_error(StatusCode::FORBIDDEN, "HINTS_DISABLED");
+}
+
+#[tokio::test]
+async fn ghosts_see_anonymous_killers() {
+    let app = TestApp::in_memory();
+    let settings = json!({ "anonymise_killer": true, "ghosts": true });
+    let (code, players) = game_with(&app, &["alice", "bob", "carol"], settings).await;
+    let victim = kill_target(&app, &code, &players[0], &players).await;
+    let bystander = players
+        .iter()
+        .find(|p| p.id != players[0].id && p.id != victim.id)
+        .unwrap();
+    let kill_actor = |player: &TestPlayer| {
+        let app = &app;
+        let path = format!("/api/game/{code}/activity");
+        let token = player.token.clone();
+        async move { app.get(&path, Some(&token)).await.assert_ok()["events"][0]["actor_name"].clone() }
+    };
+    assert_eq!(kill_actor(&victim).await, "alice");
+    assert!(kill_actor(bystander).await.is_null());
+}
+
+#[tokio::test]
+async fn a_lone_ghost_cannot_settle_their_own_dispute() {
+    let app = TestApp::in_memory();
+    let (code, players) =
+        game_with(&app, &["alice", "bob", "carol"], json!({ "ghosts": true })).await;
+    let victim = kill_target(&app, &code, &players[0], &players).await;
+    let event_id = ghost(&app, &code, &victim).await["disputable_event_id"]
+        .as_i64()
+        .unwrap();
+    let opened = app
+        .post(
+            &format!("/api/game/{code}/ghost/disputes"),
+            Some(&victim.token),
+            Some(json!({ "event_id": event_id })),
+        )
+        .await;
+    assert_eq!(opened.body["status"], "open");
+    assert_eq!(
+        ghost(&app, &code, &victim).await["disputes"][0]["status"],
+        "open"
+    );
+}
+
+#[tokio::test]
+async fn v1_ghosts() {
+    let app = TestApp::in_memory();
+    let settings = json!({ "ghosts": true, "ghost_hints": true });
+    let (code, players) = game_with(&app, &["alice", "bob", "carol"], settings).await;
+    let victim = kill_target(&app, &code, &players[0], &players).await;
+    let game = app
+        .get(&format!("/api/v1/games/{code}"), Some(&victim.token))
+        .await;
+    assert_eq!(game.assert_ok()["role"], "ghost");
+
+    let overview = app
+        .get(&format!("/api/v1/games/{code}/ghost"), Some(&victim.token))
+        .await;
+    let event_id = overview.assert_ok()["disputable_event_id"].clone();
+    let opened = app
+        .post(
+            &format!("/api/v1/games/{code}/disputes"),
+            Some(&victim.token),
+            Some(json!({ "event_id": event_id })),
+        )
+        .await;
+    assert_eq!(opened.status, StatusCode::CREATED, "{}", opened.body);
+
+    let hints = format!("/api/v1/games/{code}/hints");
+    let sent = app
+        .post(
+            &hints,
+            Some(&victim.token),
+            Some(json!({ "player_id": players[0].id, "message": "behind you" })),
+        )
+        .await;
+    assert_eq!(sent.status, StatusCode::NO_CONTENT, "{}", sent.body);
+    let received = app
+        .request(Method::GET, &hints, Some(&players[0].token), None)
+        .await;
+    assert_eq!(received.assert_ok()[0]["message"], "behind you");
+}
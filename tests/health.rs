@@ -0,0 +1,42 @@
+//! Liveness and readiness probes on every backend.
+
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use sqlx::PgPool;
+
+#[tokio::test]
+async fn probes_answer_without_a_database() {
+    let app = TestApp::in_memory();
+    let live = app.get("/healthz", None).await;
+    assert_eq!(live.status, StatusCode::OK);
+    assert_eq!(live.body["status"], "ok");
+    let ready = app.get("/readyz", None).await;
+    assert_eq!(ready.status, StatusCode::OK);
+    assert_eq!(ready.body["status"], "ready");
+}
+
+#[tokio::test]
+async fn sqlite_is_ready_once_migrated() {
+    let app = TestApp::sqlite().await;
+    assert_eq!(app.get("/readyz", None).await.status, StatusCode::OK);
+}
+
+#[sqlx::test]
+async fn postgres_is_not_ready_with_pending_migrations(pool: PgPool) {
+    let app = TestApp::new(pool.clone());
+    assert_eq!(app.get("/readyz", None).await.status, StatusCode::OK);
+
+    sqlx::query(
+        "DELETE FROM _sqlx_migrations WHERE version = (SELECT MAX(version) FROM _sqlx_migrations)",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    let ready = app.get("/readyz", None).await;
+    assert_eq!(ready.status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(ready.body["status"], "unavailable");
+    // Liveness does not depend on the database.
+    assert_eq!(app.get("/healthz", None).await.status, StatusCode::OK);
+}
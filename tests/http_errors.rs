@@ -0,0 +1,226 @@
+//! Every error `join_game` and `validate_kill` can send back, checked through
+//! the router so the status code and error code clients see are pinned down.
+
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{on_every_backend, TestApp};
+use serde_json::json;
+
+// ---------- join_game ----------
+
+async fn join_unknown_game(app: TestApp) {
+    app.try_join("NOPE", "bob")
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "GAME_NOT_FOUND");
+}
+
+on_every_backend!(join_unknown_game);
+
+async fn join_started_game(app: TestApp) {
+    let (code, _) = app.started_game(&["alice", "bob"]).await;
+    app.try_join(&code, "carol")
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "GAME_ALREADY_STARTED");
+}
+
+on_every_backend!(join_started_game);
+
+async fn join_finished_game(app: TestApp) {
+    let (code, players) = app.started_game(&["alice", "bob"]).await;
+    app.kill(&code, &players[0], &players[1].secret)
+        .await
+        .assert_ok();
+    app.try_join(&code, "carol")
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "GAME_ALREADY_STARTED");
+}
+
+on_every_backend!(join_finished_game);
+
+async fn join_with_a_taken_name(app: TestApp) {
+    let (code, _) = app.create_game("alice").await;
+    app.try_join(&code, "alice")
+        .await
+        .assert_error(StatusCode::CONFLICT, "NAME_TAKEN");
+    // Names are trimmed before they are compared.
+    app.try_join(&code, "  alice ")
+        .await
+        .assert_error(StatusCode::CONFLICT, "NAME_TAKEN");
+}
+
+on_every_backend!(join_with_a_taken_name);
+
+async fn join_with_the_name_of_an_eliminated_player(app: TestApp) {
+    let (code, alice) = app.create_game("alice").await;
+    app.request(
+        Method::PUT,
+        &format!("/api/v1/games/{code}/settings"),
+        Some(&alice.token),
+        Some(json!({ "late_join": true })),
+    )
+    .await
+    .assert_ok();
+    let bob = app.join(&code, "bob").await;
+    let carol = app.join(&code, "carol").await;
+    app.start(&code, &alice).await.assert_ok();
+    // Late joiners may come in, but not under the name of someone who is out.
+    let targets = app.targets(&code).await;
+    let hunter = [&alice, &carol]
+        .into_iter()
+        .find(|p| targets[&p.name] == "bob")
+        .unwrap();
+    app.kill(&code, hunter, &bob.secret).await.assert_ok();
+    app.try_join(&code, "bob")
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "PLAYER_ELIMINATED");
+}
+
+on_every_backend!(join_with_the_name_of_an_eliminated_player);
+
+async fn error_messages_follow_the_request_locale(app: TestApp) {
+    let join = |headers: &'static [(&'static str, &'static str)]| {
+        app.request_with_headers(
+            Method::POST,
+            "/api/game/NOPE/join",
+            None,
+            Some(json!({ "player_name": "bob" })),
+            headers,
+        )
+    };
+    let english = join(&[]).await;
+    let dutch = join(&[("accept-language", "nl-NL,nl;q=0.9")]).await;
+
+    english.assert_error(StatusCode::NOT_FOUND, "GAME_NOT_FOUND");
+    dutch.assert_error(StatusCode::NOT_FOUND, "GAME_NOT_FOUND");
+    assert_ne!(english.body["error"], dutch.body["error"]);
+}
+
+on_every_backend!(error_messages_follow_the_request_locale);
+
+// ---------- validate_kill ----------
+
+async fn kill_by_an_eliminated_player(app: TestApp) {
+    let (code, players) = app.started_game(&["alice", "bob", "carol"]).await;
+    let targets = app.targets(&code).await;
+    let victim = players.iter().find(|p| p.name == targets["alice"]).unwrap();
+    app.kill(&code, &players[0], &victim.secret)
+        .await
+        .assert_ok();
+
+    // The victim tries to take out whoever they were hunting.
+    let their_target = players
+        .iter()
+        .find(|p| p.name == targets[&victim.name])
+        .unwrap();
+    app.kill(&code, victim, &their_target.secret)
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "KILLER_ELIMINATED");
+}
+
+on_every_backend!(kill_by_an_eliminated_player);
+
+async fn kill_of_an_eliminated_target(app: TestApp) {
+    let (code, players) = app.started_game(&["alice", "bob", "carol"]).await;
+    let targets = app.targets(&code).await;
+    let victim = players.iter().find(|p| p.name == targets["alice"]).unwrap();
+    app.kill(&code, &players[0], &victim.secret)
+        .await
+        .assert_ok();
+
+    let other = players
+        .iter()
+        .find(|p| p.name != "alice" && p.name != victim.name)
+        .unwrap();
+    app.kill(&code, other, &victim.secret)
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "TARGET_ELIMINATED");
+}
+
+on_every_backend!(kill_of_an_eliminated_target);
+
+async fn kill_of_yourself(app: TestApp) {
+    let (code, players) = app.started_game(&["alice", "bob", "carol"]).await;
+    app.kill(&code, &players[0], &players[0].secret)
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "SELF_KILL");
+}
+
+on_every_backend!(kill_of_yourself);
+
+async fn kill_before_the_game_started(app: TestApp) {
+    let (code, host) = app.create_game("alice").await;
+    let bob = app.join(&code, "bob").await;
+    app.kill(&code, &host, &bob.secret)
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "GAME_NOT_IN_PROGRESS");
+}
+
+on_every_backend!(kill_before_the_game_started);
+
+async fn kill_of_someone_elses_target(app: TestApp) {
+    let (code, players) = app.started_game(&["alice", "bob", "carol"]).await;
+    let targets = app.targets(&code).await;
+    // With three players, the one alice is not hunting is hunting her.
+    let wrong = players
+        .iter()
+        .find(|p| p.name != "alice" && p.name != targets["alice"])
+        .unwrap();
+    app.kill(&code, &players[0], &wrong.secret)
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "WRONG_TARGET");
+}
+
+on_every_backend!(kill_of_someone_elses_target);
+
+/// `validate_kill` also rejects a target from another game (`NOT_SAME_GAME`),
+/// but both players are looked up within the game named in the URL, so over
+/// HTTP a foreign secret never gets that far.
+async fn kill_with_a_secret_from_another_game(app: TestApp) {
+    let (code, players) = app.started_game(&["alice", "bob"]).await;
+    let (_, others) = app.started_game(&["carol", "dave"]).await;
+    app.kill(&code, &players[0], &others[1].secret)
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "UNKNOWN_SECRET");
+}
+
+on_every_backend!(kill_with_a_secret_from_another_game);
+
+// ---------- process_kill lookups ----------
+
+async fn kill_in_an_unknown_game(app: TestApp) {
+    let (_, players) = app.started_game(&["alice", "bob"]).await;
+    app.kill("NOPE", &players[0], &players[1].secret)
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "GAME_NOT_FOUND");
+}
+
+on_every_backend!(kill_in_an_unknown_game);
+
+async fn kill_by_a_player_of_another_game(app: TestApp) {
+    let (code, players) = app.started_game(&["alice", "bob"]).await;
+    let (_, others) = app.started_game(&["carol", "dave"]).await;
+    app.kill(&code, &others[0], &players[1].secret)
+        .await
+        .assert_error(StatusCode::UNAUTHORIZED, "UNAUTHORIZED");
+}
+
+on_every_backend!(kill_by_a_player_of_another_game);
+
+async fn kill_with_an_unknown_secret(app: TestApp) {
+    let (code, players) = app.started_game(&["alice", "bob"]).await;
+    app.kill(&code, &players[0], "NOTASECRET")
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "UNKNOWN_SECRET");
+}
+
+on_every_backend!(kill_with_an_unknown_secret);
+
+async fn kill_with_a_forged_token(app: TestApp) {
+    let (code, players) = app.started_game(&["alice", "bob"]).await;
+    app.kill(&code, &players[0], "hk1.bm9wZQ.bm9wZQ")
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "INVALID_KILL_TOKEN");
+}
+
+on_every_backend!(kill_with_a_forged_token);
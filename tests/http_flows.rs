@@ -0,0 +1,209 @@
+//! Full game flows driven through the router: create, join, start, kill
+//! chains, leaving and game over.
+
+mod common;
+
+use axum::http::{header, StatusCode};
+use common::{on_every_backend, TestApp, TestPlayer};
+
+fn by_name<'a>(players: &'a [TestPlayer], name: &str) -> &'a TestPlayer {
+    players.iter().find(|p| p.name == name).unwrap()
+}
+
+async fn kill_chain_until_game_over(app: TestApp) {
+    let (code, players) = app
+        .started_game(&["alice", "bob", "carol", "dave", "erin"])
+        .await;
+
+    let state = app.game_state(&code, &players[0]).await;
+    assert_eq!(state["game"]["status"], "InProgress");
+
+    // Whoever alice hunts keeps getting taken out until she is the last one.
+    let mut remaining = players.len();
+    while remaining > 1 {
+        let targets = app.targets(&code).await;
+        assert_eq!(targets.len(), remaining, "every living player has a target");
+        let victim = by_name(&players, &targets["alice"]);
+        let res = app.kill(&code, &players[0], &victim.secret).await;
+        let body = res.assert_ok();
+        remaining -= 1;
+
+        assert_eq!(body["killer_name"], "alice");
+        assert_eq!(body["eliminated_player_name"], victim.name.as_str());
+        assert_eq!(body["game_over"], remaining == 1);
+        if remaining > 1 {
+            // The victim's target is handed down to the killer.
+            assert_eq!(body["new_target_name"], targets[&victim.name].as_str());
+        } else {
+            assert!(body["new_target_name"].is_null());
+        }
+    }
+
+    let state = app.game_state(&code, &players[0]).await;
+    assert_eq!(state["game"]["status"], "Finished");
+    let alive: Vec<&str> = state["players"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|p| p["is_alive"] == true)
+        .map(|p| p["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(alive, ["alice"]);
+}
+
+on_every_backend!(kill_chain_until_game_over);
+
+async fn every_player_sees_only_their_own_secret(app: TestApp) {
+    let (code, players) = app.started_game(&["alice", "bob", "carol"]).await;
+
+    for viewer in &players {
+        let state = app.game_state(&code, viewer).await;
+        for p in state["players"].as_array().unwrap() {
+            if p["id"] == viewer.id {
+                assert_eq!(p["secret_code"], viewer.secret.as_str());
+            } else {
+                assert!(p["secret_code"].is_null());
+            }
+        }
+    }
+}
+
+on_every_backend!(every_player_sees_only_their_own_secret);
+
+async fn leaving_mid_game_hands_the_target_to_the_hunter(app: TestApp) {
+    let (code, players) = app.started_game(&["alice", "bob", "carol", "dave"]).await;
+
+    let targets = app.targets(&code).await;
+    let leaver = by_name(&players, &targets["alice"]);
+    assert_eq!(
+        app.leave(&code, leaver).await.status,
+        StatusCode::NO_CONTENT
+    );
+
+    let after = app.targets(&code).await;
+    assert_eq!(after.len(), 3);
+    assert_eq!(after["alice"], targets[&leaver.name]);
+    assert!(!after.contains_key(&leaver.name));
+}
+
+on_every_backend!(leaving_mid_game_hands_the_target_to_the_hunter);
+
+async fn leaving_until_one_is_left_ends_the_game(app: TestApp) {
+    let (code, players) = app.started_game(&["alice", "bob", "carol"]).await;
+
+    app.leave(&code, &players[1]).await;
+    app.leave(&code, &players[2]).await;
+
+    let state = app.game_state(&code, &players[0]).await;
+    assert_eq!(state["game"]["status"], "Finished");
+    let res = app.kill(&code, &players[0], &players[1].secret).await;
+    res.assert_error(StatusCode::FORBIDDEN, "TARGET_ELIMINATED");
+}
+
+on_every_backend!(leaving_until_one_is_left_ends_the_game);
+
+async fn force_finishing_ends_a_running_game(app: TestApp) {
+    let (code, players) = app.started_game(&["alice", "bob", "carol"]).await;
+    let db = &app.repository;
+
+    assert!(db.force_finish_game(&code).await.unwrap());
+    assert!(!db.force_finish_game(&code).await.unwrap());
+    let state = app.game_state(&code, &players[0]).await;
+    assert_eq!(state["game"]["status"], "Finished");
+    assert_eq!(app.targets(&code).await.len(), 0);
+    let res = app.kill(&code, &players[0], &players[1].secret).await;
+    res.assert_error(StatusCode::UNPROCESSABLE_ENTITY, "GAME_NOT_IN_PROGRESS");
+}
+
+on_every_backend!(force_finishing_ends_a_running_game);
+
+async fn host_leaving_the_lobby_passes_the_host_on(app: TestApp) {
+    let (code, host) = app.create_game("alice").await;
+    let bob = app.join(&code, "bob").await;
+    app.join(&code, "carol").await;
+
+    assert_eq!(app.leave(&code, &host).await.status, StatusCode::NO_CONTENT);
+    let state = app.game_state(&code, &bob).await;
+    assert_eq!(state["game"]["host_id"], bob.id);
+    assert_eq!(state["players"].as_array().unwrap().len(), 2);
+
+    // The old host's token no longer belongs to anyone.
+    app.start(&code, &host)
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "INVALID_AUTH_TOKEN");
+    app.start(&code, &bob).await.assert_ok();
+
+    let targets = app.targets(&code).await;
+    assert_eq!(targets["bob"], "carol");
+    assert_eq!(targets["carol"], "bob");
+}
+
+on_every_backend!(host_leaving_the_lobby_passes_the_host_on);
+
+async fn last_player_leaving_the_lobby_deletes_the_game(app: TestApp) {
+    let (code, host) = app.create_game("alice").await;
+
+    assert_eq!(app.leave(&code, &host).await.status, StatusCode::NO_CONTENT);
+    app.try_join(&code, "bob")
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "GAME_NOT_FOUND");
+}
+
+on_every_backend!(last_player_leaving_the_lobby_deletes_the_game);
+
+async fn changes_bump_the_game_version(app: TestApp) {
+    let (code, host) = app.create_game("alice").await;
+    let changed = |version: i64| {
+        let app = &app;
+        let path = format!("/api/game/{code}/changed?version={version}");
+        async move { app.get(&path, None).await.body }
+    };
+
+    let v1 = changed(0).await["current_version"].as_i64().unwrap();
+    assert_eq!(changed(v1).await["changed"], false);
+
+    let bob = app.join(&code, "bob").await;
+    let v2 = changed(v1).await;
+    assert_eq!(v2["changed"], true);
+    let v2 = v2["current_version"].as_i64().unwrap();
+
+    app.start(&code, &host).await.assert_ok();
+    assert_eq!(changed(v2).await["changed"], true);
+    let v3 = changed(v2).await["current_version"].as_i64().unwrap();
+
+    app.kill(&code, &host, &bob.secret).await.assert_ok();
+    assert_eq!(changed(v3).await["changed"], true);
+}
+
+on_every_backend!(changes_bump_the_game_version);
+
+async fn rejoin_link_redirects_to_the_current_page(app: TestApp) {
+    let (code, host) = app.create_game("alice").await;
+    let bob = app.join(&code, "bob").await;
+    let location =
+        |res: common::TestResponse| res.headers[header::LOCATION].to_str().unwrap().to_string();
+
+    let rejoin = |p: &TestPlayer| format!("/game/{code}/player/{}", p.token);
+    assert_eq!(
+        location(app.get(&rejoin(&bob), None).await),
+        format!("/game/{code}/player/{}/lobby", bob.token)
+    );
+
+    app.start(&code, &host).await.assert_ok();
+    assert_eq!(
+        location(app.get(&rejoin(&bob), None).await),
+        format!("/game/{code}/player/{}/game", bob.token)
+    );
+
+    app.kill(&code, &host, &bob.secret).await.assert_ok();
+    assert_eq!(
+        location(app.get(&rejoin(&bob), None).await),
+        format!("/game/{code}/player/{}/game_over", bob.token)
+    );
+    assert_eq!(
+        location(app.get(&format!("/game/{code}/player/nope"), None).await),
+        "/"
+    );
+}
+
+on_every_backend!(rejoin_link_redirects_to_the_current_page);
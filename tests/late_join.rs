@@ -0,0 +1,247 @@
+//! Joining a game that already runs: newcomers are spliced into the ring,
+//! straight away or once the host lets them in.
+
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{on_every_backend, TestApp, TestPlayer};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+
+/// A game with late joins set up as `settings`, started with alice as host
+/// and bob and carol playing.
+async fn running_game(app: &TestApp, settings: Value) -> (String, TestPlayer, TestPlayer) {
+    let (code, alice) = app.create_game("alice").await;
+    app.post(
+        &format!("/api/game/{code}/settings"),
+        Some(&alice.token),
+        Some(settings),
+    )
+    .await
+    .assert_ok();
+    let bob = app.join(&code, "bob").await;
+    app.join(&code, "carol").await;
+    app.start(&code, &alice).await.assert_ok();
+    (code, alice, bob)
+}
+
+/// Follow the targets from `start`; panics unless they form one ring over
+/// everyone in `targets`.
+fn assert_one_ring(targets: &HashMap<String, String>, start: &str) {
+    let mut seen = HashSet::new();
+    let mut current = start;
+    while seen.insert(current) {
+        current = &targets[current];
+    }
+    assert_eq!(current, start, "{targets:?}");
+    assert_eq!(seen.len(), targets.len(), "{targets:?}");
+}
+
+/// Only the newcomer and the one hunter who now chases them have a new
+/// target; the newcomer took over that hunter's old one.
+fn assert_spliced(before: &HashMap<String, String>, after: &HashMap<String, String>, name: &str) {
+    let changed: Vec<_> = before
+        .iter()
+        .filter(|(hunter, target)| &after[*hunter] != *target)
+        .collect();
+    assert_eq!(changed.len(), 1, "{before:?} -> {after:?}");
+    let (hunter, old_target) = changed[0];
+    assert_eq!(after[hunter], name);
+    assert_eq!(&after[name], old_target);
+    assert_one_ring(after, name);
+}
+
+async fn newcomers_are_spliced_into_the_ring(app: TestApp) {
+    let (code, alice, _) = running_game(&app, json!({ "late_join": true })).await;
+    let before = app.targets(&code).await;
+
+    let dave = app.join(&code, "dave").await;
+    let state = app.game_state(&code, &dave).await;
+    assert_eq!(state["game"]["status"], "InProgress");
+    assert_eq!(state["role"], "alive");
+    let after = app.targets(&code).await;
+    assert_eq!(after.len(), 4);
+    assert_spliced(&before, &after, "dave");
+
+    // Taken names are still turned away.
+    app.try_join(&code, "Dave")
+        .await
+        .assert_error(StatusCode::CONFLICT, "NAME_TAKEN");
+    let activity = app
+        .get(&format!("/api/game/{code}/activity"), Some(&alice.token))
+        .await;
+    assert!(
+        activity.assert_ok().to_string().contains("dave"),
+        "{}",
+        activity.body
+    );
+}
+
+on_every_backend!(newcomers_are_spliced_into_the_ring);
+
+async fn host_lets_late_joiners_in(app: TestApp) {
+    let settings = json!({ "late_join": true, "late_join_approval": true });
+    let (code, alice, _) = running_game(&app, settings).await;
+    let before = app.targets(&code).await;
+
+    let asked = app.try_join(&code, "dave").await;
+    assert_eq!(asked.status, StatusCode::ACCEPTED, "{}", asked.body);
+    assert_eq!(asked.body["status"], "pending");
+    assert!(asked.body["auth_token"].is_null());
+    let claim = format!(
+        "/api/game/{code}/join/{}",
+        asked.body["claim_token"].as_str().unwrap()
+    );
+    // Nobody joins until the host says so.
+    assert_eq!(app.targets(&code).await, before);
+
+    let requests = format!("/api/game/{code}/join-requests");
+    let pending = app.get(&requests, Some(&alice.token)).await;
+    let pending = pending.assert_ok().as_array().unwrap().clone();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0]["player_name"], "dave");
+    let request_id = pending[0]["id"].as_i64().unwrap();
+
+    let decided = app
+        .post(
+            &format!("{requests}/{request_id}"),
+            Some(&alice.token),
+            Some(json!({ "approve": true })),
+        )
+        .await;
+    assert_eq!(decided.status, StatusCode::NO_CONTENT, "{}", decided.body);
+    let claimed = app.get(&claim, None).await;
+    assert_eq!(claimed.assert_ok()["status"], "approved");
+    let token = claimed.body["auth_token"].as_str().unwrap();
+    let state = app.get(&format!("/api/game/{code}"), Some(token)).await;
+    assert_eq!(state.assert_ok()["role"], "alive");
+    assert_spliced(&before, &app.targets(&code).await, "dave");
+
+    app.post(
+        &format!("{requests}/{request_id}"),
+        Some(&alice.token),
+        Some(json!({ "approve": false })),
+    )
+    .await
+    .assert_error(StatusCode::CONFLICT, "JOIN_REQUEST_CLOSED");
+    assert_eq!(
+        app.get(&requests, Some(&alice.token)).await.assert_ok(),
+        &json!([])
+    );
+}
+
+on_every_backend!(host_lets_late_joiners_in);
+
+#[tokio::test]
+async fn turned_away_late_joiners_get_no_token() {
+    let app = TestApp::in_memory();
+    let settings = json!({ "late_join": true, "late_join_approval": true });
+    let (code, alice, bob) = running_game(&app, settings).await;
+
+    // Names already in the game are refused before the host is bothered.
+    app.try_join(&code, "bob")
+        .await
+        .assert_error(StatusCode::CONFLICT, "NAME_TAKEN");
+    let asked = app.try_join(&code, "erin").await;
+    let requests = format!("/api/game/{code}/join-requests");
+    app.get(&requests, Some(&bob.token))
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "NOT_HOST");
+    let request_id = app.get(&requests, Some(&alice.token)).await.assert_ok()[0]["id"]
+        .as_i64()
+        .unwrap();
+    app.post(
+        &format!("{requests}/{request_id}"),
+        Some(&alice.token),
+        Some(json!({ "approve": false })),
+    )
+    .await
+    .assert_ok();
+    let claimed = app
+        .get(
+            &format!(
+                "/api/game/{code}/join/{}",
+                asked.body["claim_token"].as_str().unwrap()
+            ),
+            None,
+        )
+        .await;
+    assert_eq!(claimed.assert_ok()["status"], "denied");
+    assert!(claimed.body["auth_token"].is_null());
+    assert_eq!(app.targets(&code).await.len(), 3);
+
+    app.get(&format!("/api/game/{code}/join/nonsense"), None)
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "JOIN_REQUEST_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn running_games_stay_closed_by_default() {
+    let app = TestApp::in_memory();
+    let (code, _, _) = running_game(&app, json!({})).await;
+    app.try_join(&code, "dave")
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "GAME_ALREADY_STARTED");
+
+    // The game's player limit covers late joiners too.
+    let (code, _, _) = running_game(&app, json!({ "late_join": true, "max_players": 3 })).await;
+    app.try_join(&code, "dave")
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "LOBBY_FULL");
+}
+
+#[tokio::test]
+async fn v1_late_join() {
+    let app = TestApp::in_memory();
+    let settings = json!({ "late_join": true, "late_join_approval": true });
+    let (code, alice, _) = running_game(&app, settings).await;
+    let asked = app
+        .request(
+            Method::POST,
+            &format!("/api/v1/games/{code}/players"),
+            None,
+            Some(json!({ "player_name": "dave" })),
+        )
+        .await;
+    assert_eq!(asked.status, StatusCode::ACCEPTED, "{}", asked.body);
+    let pending = app
+        .get(
+            &format!("/api/v1/games/{code}/join-requests"),
+            Some(&alice.token),
+        )
+        .await;
+    let request_id = pending.assert_ok()[0]["id"].as_i64().unwrap();
+    let decided = app
+        .post(
+            &format!("/api/v1/games/{code}/join-requests/{request_id}"),
+            Some(&alice.token),
+            Some(json!({ "approve": true })),
+        )
+        .await;
+    assert_eq!(decided.status, StatusCode::NO_CONTENT, "{}", decided.body);
+    let claimed = app
+        .get(
+            &format!(
+                "/api/v1/games/{code}/join-claims/{}",
+                asked.body["claim_token"].as_str().unwrap()
+            ),
+            None,
+        )
+        .await;
+    assert_eq!(claimed.assert_ok()["status"], "approved");
+    let game = app
+        .get(
+            &format!("/api/v1/games/{code}"),
+            Some(claimed.body["auth_token"].as_str().unwrap()),
+        )
+        .await;
+    let me = game.assert_ok()["players"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|p| p["secret_code"].is_string())
+        .unwrap()
+        .clone();
+    assert_eq!(me["name"], "dave");
+    assert!(me["target_name"].is_string());
+}
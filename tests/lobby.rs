@@ -0,0 +1,225 @@
+//! Managing the lobby: ready checks, starting by itself once everyone is
+//! ready, and limits on how many players a game takes.
+
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{on_every_backend, TestApp, TestPlayer};
+use hitman::config::Config;
+use serde_json::{json, Value};
+
+async fn ready(
+    app: &TestApp,
+    code: &str,
+    player: &TestPlayer,
+    ready: bool,
+) -> common::TestResponse {
+    app.post(
+        &format!("/api/game/{code}/ready"),
+        Some(&player.token),
+        Some(json!({ "ready": ready })),
+    )
+    .await
+}
+
+async fn settings(
+    app: &TestApp,
+    code: &str,
+    host: &TestPlayer,
+    settings: Value,
+) -> common::TestResponse {
+    app.post(
+        &format!("/api/game/{code}/settings"),
+        Some(&host.token),
+        Some(settings),
+    )
+    .await
+}
+
+async fn status(app: &TestApp, code: &str, player: &TestPlayer) -> String {
+    app.game_state(code, player).await["game"]["status"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+/// Names of the players shown as ready.
+async fn ready_players(app: &TestApp, code: &str, viewer: &TestPlayer) -> Vec<String> {
+    app.game_state(code, viewer).await["players"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|p| p["is_ready"] == true)
+        .map(|p| p["name"].as_str().unwrap().to_string())
+        .collect()
+}
+
+async fn lobby_starts_once_everyone_is_ready(app: TestApp) {
+    let (code, alice) = app.create_game("alice").await;
+    let bob = app.join(&code, "bob").await;
+    assert!(ready_players(&app, &code, &alice).await.is_empty());
+
+    let marked = ready(&app, &code, &bob, true).await;
+    assert_eq!(marked.status, StatusCode::NO_CONTENT, "{}", marked.body);
+    assert_eq!(ready_players(&app, &code, &alice).await, ["bob"]);
+    ready(&app, &code, &bob, false).await.assert_ok();
+    assert!(ready_players(&app, &code, &alice).await.is_empty());
+
+    settings(
+        &app,
+        &code,
+        &alice,
+        json!({ "auto_start": true, "min_players": 3 }),
+    )
+    .await
+    .assert_ok();
+    ready(&app, &code, &alice, true).await.assert_ok();
+    ready(&app, &code, &bob, true).await.assert_ok();
+    // Everyone is ready, but there are too few of them.
+    assert_eq!(status(&app, &code, &alice).await, "Lobby");
+    app.start(&code, &alice)
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "NOT_ENOUGH_PLAYERS");
+
+    let carol = app.join(&code, "carol").await;
+    assert_eq!(status(&app, &code, &alice).await, "Lobby");
+    ready(&app, &code, &carol, true).await.assert_ok();
+    assert_eq!(status(&app, &code, &alice).await, "InProgress");
+    assert_eq!(app.targets(&code).await.len(), 3);
+    ready(&app, &code, &carol, false)
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "GAME_ALREADY_STARTED");
+}
+
+on_every_backend!(lobby_starts_once_everyone_is_ready);
+
+async fn full_lobbies_turn_players_away(app: TestApp) {
+    let (code, alice) = app.create_game("alice").await;
+    app.join(&code, "bob").await;
+    settings(&app, &code, &alice, json!({ "max_players": 2 }))
+        .await
+        .assert_ok();
+    let full = app.try_join(&code, "carol").await;
+    full.assert_error(StatusCode::UNPROCESSABLE_ENTITY, "LOBBY_FULL");
+    assert!(full.body["error"].as_str().unwrap().contains('2'));
+    // Taken names are still reported as such.
+    app.try_join(&code, "bob")
+        .await
+        .assert_error(StatusCode::CONFLICT, "NAME_TAKEN");
+
+    settings(&app, &code, &alice, json!({ "max_players": 3 }))
+        .await
+        .assert_ok();
+    app.join(&code, "carol").await;
+}
+
+on_every_backend!(full_lobbies_turn_players_away);
+
+async fn names_must_be_given_and_short(app: TestApp) {
+    let (code, _) = app.create_game("alice").await;
+    for name in ["", "   ", &"x".repeat(33)] {
+        app.try_join(&code, name)
+            .await
+            .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "INVALID_NAME");
+        app.post("/api/v1/games", None, Some(json!({ "player_name": name })))
+            .await
+            .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "INVALID_NAME");
+    }
+    // Surrounding whitespace does not count towards the limit.
+    let bob = app
+        .try_join(&code, &format!("  {}  ", "b".repeat(32)))
+        .await;
+    let players = bob.assert_ok()["players"].as_array().unwrap();
+    assert!(players.iter().any(|p| p["name"] == "b".repeat(32)));
+}
+
+on_every_backend!(names_must_be_given_and_short);
+
+#[tokio::test]
+async fn the_server_caps_every_lobby() {
+    let mut config = Config::default();
+    config.game.max_players = 3;
+    let app = TestApp::in_memory_with_config(config);
+    let (code, alice) = app.create_game("alice").await;
+    app.join(&code, "bob").await;
+    app.join(&code, "carol").await;
+    app.try_join(&code, "dave")
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "LOBBY_FULL");
+
+    for invalid in [
+        json!({ "max_players": 4 }),
+        json!({ "max_players": 1 }),
+        json!({ "min_players": 4 }),
+        json!({ "min_players": 3, "max_players": 2 }),
+    ] {
+        settings(&app, &code, &alice, invalid)
+            .await
+            .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "INVALID_LOBBY_SIZE");
+    }
+}
+
+#[tokio::test]
+async fn auto_start_follows_the_last_change_that_made_everyone_ready() {
+    let app = TestApp::in_memory();
+    let (code, alice) = app.create_game("alice").await;
+    let bob = app.join(&code, "bob").await;
+    ready(&app, &code, &alice, true).await.assert_ok();
+    ready(&app, &code, &bob, true).await.assert_ok();
+    // Turning auto-start on in a ready lobby starts it.
+    settings(&app, &code, &alice, json!({ "auto_start": true }))
+        .await
+        .assert_ok();
+    assert_eq!(status(&app, &code, &alice).await, "InProgress");
+
+    let (code, alice) = app.create_game("alice").await;
+    let bob = app.join(&code, "bob").await;
+    let carol = app.join(&code, "carol").await;
+    settings(&app, &code, &alice, json!({ "auto_start": true }))
+        .await
+        .assert_ok();
+    ready(&app, &code, &alice, true).await.assert_ok();
+    ready(&app, &code, &bob, true).await.assert_ok();
+    // The one player holding everyone up leaves.
+    app.leave(&code, &carol).await.assert_ok();
+    assert_eq!(status(&app, &code, &alice).await, "InProgress");
+}
+
+#[tokio::test]
+async fn v1_ready() {
+    let app = TestApp::in_memory();
+    let (code, alice) = app.create_game("alice").await;
+    let marked = app
+        .request(
+            Method::PUT,
+            &format!("/api/v1/games/{code}/players/me/ready"),
+            Some(&alice.token),
+            Some(json!({ "ready": true })),
+        )
+        .await;
+    assert_eq!(marked.status, StatusCode::NO_CONTENT, "{}", marked.body);
+    let game = app
+        .get(&format!("/api/v1/games/{code}"), Some(&alice.token))
+        .await;
+    assert_eq!(game.assert_ok()["players"][0]["is_ready"], true);
+}
+
+#[tokio::test]
+async fn the_lobby_page_hands_its_scripts_translated_strings() {
+    let app = TestApp::in_memory();
+    let (code, alice) = app.create_game("alice").await;
+    let page = app
+        .request_with_headers(
+            Method::GET,
+            &format!("/game/{code}/player/{}/lobby", alice.token),
+            None,
+            None,
+            &[("accept-language", "nl")],
+        )
+        .await;
+    assert_eq!(page.status, StatusCode::OK);
+    let html = page.body.as_str().unwrap();
+    assert!(html.contains("id=\"lobby-strings\""), "{html}");
+    assert!(html.contains("id=\"session-strings\""), "{html}");
+    assert!(html.contains("Spellobby: {code}"), "{html}");
+}
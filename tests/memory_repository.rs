@@ -0,0 +1,217 @@
+//! The in-memory repository behind the real router: no database needed.
+
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use hitman::{
+    config::Config,
+    engine::MIN_PLAYERS,
+    kill_token::KillProof,
+    repository::{GameRepository, MemoryRepository},
+};
+use proptest::{prelude::*, sample::Index};
+use std::time::Duration;
+
+#[tokio::test]
+async fn full_game_without_a_database() {
+    let app = TestApp::in_memory();
+    let (code, players) = app.started_game(&["alice", "bob", "carol"]).await;
+
+    for _ in 0..2 {
+        let targets = app.targets(&code).await;
+        let victim = players.iter().find(|p| p.name == targets["alice"]).unwrap();
+        app.kill(&code, &players[0], &victim.secret)
+            .await
+            .assert_ok();
+    }
+    let state = app.game_state(&code, &players[0]).await;
+    assert_eq!(state["game"]["status"], "Finished");
+}
+
+#[tokio::test]
+async fn lobby_rules_without_a_database() {
+    let app = TestApp::in_memory();
+    let (code, host) = app.create_game("alice").await;
+    app.try_join(&code, "ALICE ")
+        .await
+        .assert_error(StatusCode::CONFLICT, "NAME_TAKEN");
+    let bob = app.join(&code, "bob").await;
+    app.start(&code, &bob)
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "NOT_HOST");
+
+    assert_eq!(app.leave(&code, &host).await.status, StatusCode::NO_CONTENT);
+    let state = app.game_state(&code, &bob).await;
+    assert_eq!(state["game"]["host_id"], bob.id);
+    app.start(&code, &bob)
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "NOT_ENOUGH_PLAYERS");
+}
+
+#[tokio::test]
+async fn kill_tokens_without_a_database() {
+    let app = TestApp::in_memory();
+    let (code, players) = app.started_game(&["alice", "bob", "carol"]).await;
+    let targets = app.targets(&code).await;
+    let victim = players.iter().find(|p| p.name == targets["alice"]).unwrap();
+    let hunter = players
+        .iter()
+        .find(|p| targets[&p.name] == "alice")
+        .unwrap();
+
+    let token = app
+        .get(&format!("/api/game/{code}/kill-token"), Some(&victim.token))
+        .await
+        .assert_ok()["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    app.kill(&code, &players[0], &token).await.assert_ok();
+
+    // alice now hunts the hunter, whose token dies when they rotate their code.
+    let token = app
+        .get(&format!("/api/game/{code}/kill-token"), Some(&hunter.token))
+        .await
+        .assert_ok()["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    app.post(
+        &format!("/api/game/{code}/secret/rotate"),
+        Some(&hunter.token),
+        None,
+    )
+    .await
+    .assert_ok();
+    app.kill(&code, &players[0], &token)
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "KILL_TOKEN_STALE");
+}
+
+#[tokio::test]
+async fn disabled_features_are_refused() {
+    let mut config = Config::default();
+    config.features.kill_tokens = false;
+    config.features.secret_rotation = false;
+    config.game.code_length = 8;
+    let app = TestApp::in_memory_with_config(config);
+    let (code, players) = app.started_game(&["alice", "bob"]).await;
+    assert_eq!(code.len(), 8);
+
+    app.get(
+        &format!("/api/game/{code}/kill-token"),
+        Some(&players[0].token),
+    )
+    .await
+    .assert_error(StatusCode::NOT_FOUND, "FEATURE_DISABLED");
+    app.post(
+        &format!("/api/game/{code}/secret/rotate"),
+        Some(&players[0].token),
+        None,
+    )
+    .await
+    .assert_error(StatusCode::NOT_FOUND, "FEATURE_DISABLED");
+}
+
+#[tokio::test]
+async fn purge_drops_old_games() {
+    let repo = MemoryRepository::new();
+    repo.create_game("alice".into(), "OLD1".into())
+        .await
+        .unwrap();
+    assert!(repo
+        .purge_games(Duration::from_secs(3600))
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(repo.purge_games(Duration::ZERO).await.unwrap(), ["OLD1"]);
+    assert!(repo.get_game_by_code("OLD1").await.unwrap().is_none());
+    assert!(repo.get_all_games().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn force_finish_ends_any_game() {
+    let repo = MemoryRepository::new();
+    repo.create_game("alice".into(), "LOBBY".into())
+        .await
+        .unwrap();
+    assert!(repo.force_finish_game("LOBBY").await.unwrap());
+    assert!(!repo.force_finish_game("LOBBY").await.unwrap());
+    let game = repo.get_game_by_code("LOBBY").await.unwrap().unwrap();
+    assert_eq!(game.status.to_string(), "FINISHED");
+    assert!(repo.force_finish_game("NOPE").await.is_err());
+}
+
+#[derive(Debug, Clone)]
+enum Step {
+    Kill(Index),
+    Leave(Index),
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    /// Same property as the Postgres ring tests, cheap enough to run many
+    /// more cases.
+    #[test]
+    fn random_kills_and_leaves_keep_the_ring_intact(
+        size in 2usize..12,
+        steps in prop::collection::vec(
+            prop_oneof![
+                3 => any::<Index>().prop_map(Step::Kill),
+                1 => any::<Index>().prop_map(Step::Leave),
+            ],
+            1..30,
+        ),
+    ) {
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        runtime.block_on(async {
+            let repo = MemoryRepository::new();
+            let (game_id, host_id, _, _) = repo
+                .create_game("player0".into(), "MEMO".into())
+                .await
+                .unwrap();
+            for i in 1..size {
+                repo.join_game("MEMO".into(), format!("player{i}"), usize::MAX, false).await.unwrap();
+            }
+            repo.start_game("MEMO", host_id, MIN_PLAYERS).await.unwrap();
+
+            for step in steps {
+                let alive: Vec<_> = repo
+                    .get_players_by_game_id(game_id)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .filter(|p| p.is_alive)
+                    .collect();
+                if alive.len() <= 1 {
+                    break;
+                }
+                match step {
+                    Step::Kill(idx) => {
+                        let hunter = idx.get(&alive);
+                        let target = alive
+                            .iter()
+                            .find(|p| Some(p.id) == hunter.target_id)
+                            .expect("hunter's target is not alive");
+                        repo.process_kill(
+                            "MEMO",
+                            &hunter.auth_token,
+                            &KillProof::Secret(target.secret_code.clone()),
+                        )
+                        .await
+                        .unwrap();
+                    }
+                    Step::Leave(idx) => {
+                        repo.leave_game("MEMO", &idx.get(&alive).auth_token)
+                            .await
+                            .unwrap();
+                    }
+                }
+                let violations = repo.check_ring("MEMO").await.unwrap();
+                assert!(violations.is_empty(), "broken ring: {violations:?}");
+            }
+        });
+    }
+}
@@ -0,0 +1,88 @@
+//! The Prometheus scrape endpoint. The recorder is process-wide, so this
+//! binary keeps to a single test and never races another app's gauges.
+
+mod common;
+
+use axum::http::{header, StatusCode};
+use common::TestApp;
+
+async fn scrape(app: &TestApp) -> String {
+    let response = app.get("/metrics", None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(response.headers[header::CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .starts_with("text/plain"));
+    response
+        .body
+        .as_str()
+        .expect("metrics are text")
+        .to_string()
+}
+
+/// The value of the sample whose line starts with `series`.
+#[track_caller]
+fn sample(text: &str, series: &str) -> f64 {
+    text.lines()
+        .find(|line| line.starts_with(series) && line[series.len()..].starts_with(' '))
+        .unwrap_or_else(|| panic!("no {series} in\n{text}"))
+        .rsplit(' ')
+        .next()
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn scrape_reports_requests_games_kills_and_pool() {
+    let app = TestApp::in_memory();
+    let (code, players) = app.started_game(&["alice", "bob", "carol"]).await;
+    app.create_game("dave").await;
+
+    let failed = app.kill(&code, &players[0], &players[0].secret).await;
+    let failure_code = failed.body["code"].as_str().unwrap().to_string();
+    let targets = app.targets(&code).await;
+    let victim = players.iter().find(|p| p.name == targets["alice"]).unwrap();
+    app.kill(&code, &players[0], &victim.secret)
+        .await
+        .assert_ok();
+    app.get(
+        &format!("/api/game/{code}/changed?version=0"),
+        Some(&players[1].token),
+    )
+    .await
+    .assert_ok();
+    app.get("/no/such/page", None).await;
+    // A made-up token is not a player.
+    app.get(&format!("/api/v1/games/{code}"), Some("not-a-token"))
+        .await;
+
+    let text = scrape(&app).await;
+    assert!(sample(&text, "hitman_kills_total") >= 1.0);
+    assert!(
+        sample(
+            &text,
+            &format!("hitman_kill_failures_total{{code=\"{failure_code}\"}}")
+        ) >= 1.0
+    );
+    assert_eq!(sample(&text, "hitman_games{status=\"LOBBY\"}"), 1.0);
+    assert_eq!(sample(&text, "hitman_games{status=\"IN_PROGRESS\"}"), 1.0);
+    assert_eq!(sample(&text, "hitman_games{status=\"FINISHED\"}"), 0.0);
+    // Only alice has made an authenticated request so far; bob only polled.
+    assert_eq!(sample(&text, "hitman_players_online"), 1.0);
+    assert_eq!(sample(&text, "hitman_change_subscribers"), 1.0);
+
+    // Latency is labelled by route template, never by the concrete path.
+    let kills = "http_request_duration_seconds_count{method=\"POST\",route=\"/api/game/{game_code}/eliminate\",status=\"200\"}";
+    assert!(sample(&text, kills) >= 1.0);
+    assert!(text.contains("http_request_duration_seconds_bucket{"));
+    assert!(!text.contains(&code), "game code leaked into labels");
+    assert!(!text.contains("/no/such/page"));
+
+    // The in-memory repository has no pool; SQLite does.
+    assert!(!text.contains("hitman_db_pool_max_connections "));
+    let sqlite = TestApp::sqlite().await;
+    let text = scrape(&sqlite).await;
+    assert_eq!(sample(&text, "hitman_db_pool_max_connections"), 1.0);
+    assert!(text.contains("hitman_db_pool_connections{state=\"in_use\"}"));
+}
@@ -0,0 +1,300 @@
+//! Getting back into a game after losing your link: recovery requests the
+//! host (or, for the host's own, the other players) answers, and the cookie
+//! that sends returning players back in.
+
+mod common;
+
+use axum::http::{header, Method, StatusCode};
+use common::{on_every_backend, TestApp, TestPlayer};
+use hitman::config::Config;
+use serde_json::json;
+
+async fn recover(app: &TestApp, code: &str, name: &str) -> common::TestResponse {
+    app.post(
+        &format!("/api/game/{code}/recover"),
+        None,
+        Some(json!({ "player_name": name })),
+    )
+    .await
+}
+
+async fn decide(
+    app: &TestApp,
+    code: &str,
+    answerer: &TestPlayer,
+    request_id: i64,
+    approve: bool,
+) -> common::TestResponse {
+    app.post(
+        &format!("/api/game/{code}/recoveries/{request_id}"),
+        Some(&answerer.token),
+        Some(json!({ "approve": approve })),
+    )
+    .await
+}
+
+/// The `Set-Cookie` header of `response`, which must have one.
+fn set_cookie(response: &common::TestResponse) -> &str {
+    response
+        .headers
+        .get(header::SET_COOKIE)
+        .expect("no Set-Cookie header")
+        .to_str()
+        .unwrap()
+}
+
+async fn host_lets_a_player_back_in(app: TestApp) {
+    let (code, alice) = app.create_game("alice").await;
+    let bob = app.join(&code, "Bob").await;
+    app.try_join(&code, "bob")
+        .await
+        .assert_error(StatusCode::CONFLICT, "NAME_TAKEN");
+
+    let requested = recover(&app, &code, " BOB ").await;
+    assert_eq!(requested.status, StatusCode::CREATED, "{}", requested.body);
+    assert_eq!(requested.body["status"], "pending");
+    assert!(requested.body["auth_token"].is_null());
+    let claim = format!(
+        "/api/game/{code}/recover/{}",
+        requested.body["claim_token"].as_str().unwrap()
+    );
+
+    let recoveries = format!("/api/game/{code}/recoveries");
+    let not_for_bob = app.get(&recoveries, Some(&bob.token)).await;
+    assert_eq!(not_for_bob.assert_ok(), &json!([]));
+    let pending = app.get(&recoveries, Some(&alice.token)).await;
+    let pending = pending.assert_ok().as_array().unwrap().clone();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0]["player_name"], "Bob");
+    assert_eq!(pending[0]["player_id"], bob.id);
+    assert!(pending[0]["created_at"].as_i64().unwrap() > 0);
+    let request_id = pending[0]["id"].as_i64().unwrap();
+    decide(&app, &code, &bob, request_id, true)
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "NOT_HOST");
+
+    let decided = decide(&app, &code, &alice, request_id, true).await;
+    assert_eq!(decided.status, StatusCode::NO_CONTENT, "{}", decided.body);
+    let claimed = app.get(&claim, None).await;
+    assert_eq!(claimed.assert_ok()["status"], "approved");
+    assert!(set_cookie(&claimed).starts_with("player="));
+    let token = claimed.body["auth_token"].as_str().unwrap().to_string();
+    assert_ne!(token, bob.token);
+
+    app.get(&format!("/api/game/{code}"), Some(&bob.token))
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "INVALID_AUTH_TOKEN");
+    let state = app.get(&format!("/api/game/{code}"), Some(&token)).await;
+    let me = state.assert_ok()["players"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|p| p["secret_code"].is_string())
+        .unwrap();
+    assert_eq!(me["id"], bob.id);
+    decide(&app, &code, &alice, request_id, false)
+        .await
+        .assert_error(StatusCode::CONFLICT, "RECOVERY_CLOSED");
+    let pending = app.get(&recoveries, Some(&alice.token)).await;
+    assert_eq!(pending.assert_ok(), &json!([]));
+}
+
+on_every_backend!(host_lets_a_player_back_in);
+
+async fn one_pending_request_per_player(app: TestApp) {
+    let (code, alice) = app.create_game("alice").await;
+    let bob = app.join(&code, "bob").await;
+    let first = recover(&app, &code, "bob").await;
+    assert_eq!(first.status, StatusCode::CREATED, "{}", first.body);
+    recover(&app, &code, "BOB")
+        .await
+        .assert_error(StatusCode::CONFLICT, "RECOVERY_PENDING");
+    let claim = |body: &serde_json::Value| {
+        format!(
+            "/api/game/{code}/recover/{}",
+            body["claim_token"].as_str().unwrap()
+        )
+    };
+
+    let pending = app
+        .get(&format!("/api/game/{code}/recoveries"), Some(&alice.token))
+        .await;
+    let request_id = pending.assert_ok()[0]["id"].as_i64().unwrap();
+    decide(&app, &code, &alice, request_id, false)
+        .await
+        .assert_ok();
+    let claimed = app.get(&claim(&first.body), None).await;
+    assert_eq!(claimed.assert_ok()["status"], "denied");
+    assert!(claimed.body["auth_token"].is_null());
+    assert!(claimed.headers.get(header::SET_COOKIE).is_none());
+    app.game_state(&code, &bob).await;
+
+    // Once answered, the player may ask again; the answered request goes.
+    let second = recover(&app, &code, "bob").await;
+    assert_eq!(second.status, StatusCode::CREATED, "{}", second.body);
+    app.get(&claim(&first.body), None)
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "RECOVERY_NOT_FOUND");
+}
+
+on_every_backend!(one_pending_request_per_player);
+
+async fn other_players_let_a_lost_host_back_in(app: TestApp) {
+    let (code, alice) = app.create_game("alice").await;
+    let bob = app.join(&code, "bob").await;
+    let carol = app.join(&code, "carol").await;
+    let requested = recover(&app, &code, "alice").await;
+    assert_eq!(requested.status, StatusCode::CREATED, "{}", requested.body);
+
+    let recoveries = format!("/api/game/{code}/recoveries");
+    let for_alice = app.get(&recoveries, Some(&alice.token)).await;
+    assert_eq!(for_alice.assert_ok(), &json!([]));
+    let for_carol = app.get(&recoveries, Some(&carol.token)).await;
+    let request_id = for_carol.assert_ok()[0]["id"].as_i64().unwrap();
+    assert_eq!(for_carol.body[0]["player_id"], alice.id);
+    decide(&app, &code, &alice, request_id, true)
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "NOT_HOST");
+
+    let decided = decide(&app, &code, &bob, request_id, true).await;
+    assert_eq!(decided.status, StatusCode::NO_CONTENT, "{}", decided.body);
+    let claimed = app
+        .get(
+            &format!(
+                "/api/game/{code}/recover/{}",
+                requested.body["claim_token"].as_str().unwrap()
+            ),
+            None,
+        )
+        .await;
+    assert_eq!(claimed.assert_ok()["status"], "approved");
+    decide(&app, &code, &carol, request_id, false)
+        .await
+        .assert_error(StatusCode::CONFLICT, "RECOVERY_CLOSED");
+}
+
+on_every_backend!(other_players_let_a_lost_host_back_in);
+
+#[tokio::test]
+async fn recovery_requests_are_rate_limited_per_game() {
+    let mut config = Config::default();
+    config.rate_limits.recovery_request_limit = 2;
+    let app = TestApp::in_memory_with_config(config);
+    let (code, _) = app.create_game("alice").await;
+    for name in ["bob", "carol", "dave"] {
+        app.join(&code, name).await;
+    }
+    recover(&app, &code, "bob").await.assert_ok();
+    recover(&app, &code, "carol").await.assert_ok();
+    let limited = recover(&app, &code, "dave").await;
+    limited.assert_error(StatusCode::TOO_MANY_REQUESTS, "RECOVERY_RATE_LIMITED");
+    assert!(limited.body["error"].as_str().unwrap().contains("seconds"));
+    // Other games are not affected.
+    let (other, _) = app.create_game("erin").await;
+    app.join(&other, "frank").await;
+    recover(&app, &other, "frank").await.assert_ok();
+}
+
+#[tokio::test]
+async fn recovery_needs_a_known_player_and_claim() {
+    let app = TestApp::in_memory();
+    let (code, alice) = app.create_game("alice").await;
+    recover(&app, &code, "mallory")
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "PLAYER_NOT_FOUND");
+    app.get(&format!("/api/game/{code}/recover/nonsense"), None)
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "RECOVERY_NOT_FOUND");
+    decide(&app, &code, &alice, 42, true)
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "RECOVERY_NOT_FOUND");
+
+    // A claim token only works for its own game.
+    let requested = recover(&app, &code, "alice").await;
+    let (other, _) = app.create_game("carol").await;
+    app.get(
+        &format!(
+            "/api/game/{other}/recover/{}",
+            requested.body["claim_token"].as_str().unwrap()
+        ),
+        None,
+    )
+    .await
+    .assert_error(StatusCode::NOT_FOUND, "RECOVERY_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn returning_players_are_sent_back_to_their_game() {
+    let app = TestApp::in_memory();
+    let created = app
+        .post("/api/game", None, Some(json!({ "player_name": "alice" })))
+        .await;
+    let cookie = set_cookie(&created);
+    assert!(cookie.starts_with("player="), "{cookie}");
+    assert!(cookie.contains("HttpOnly"), "{cookie}");
+    let code = created.body["game_code"].as_str().unwrap();
+    let token = created.body["auth_token"].as_str().unwrap();
+    let joined = app.try_join(code, "bob").await;
+    assert!(set_cookie(&joined).starts_with("player="));
+
+    let remembered = format!("player={token}");
+    let index = app
+        .request_with_headers(Method::GET, "/", None, None, &[("cookie", &remembered)])
+        .await;
+    assert_eq!(index.status, StatusCode::SEE_OTHER);
+    assert_eq!(
+        index.headers[header::LOCATION],
+        format!("/game/{code}/player/{token}").as_str()
+    );
+
+    let left = app
+        .post(&format!("/api/game/{code}/leave"), Some(token), None)
+        .await;
+    assert!(set_cookie(&left).contains("Max-Age=0"));
+    let index = app
+        .request_with_headers(Method::GET, "/", None, None, &[("cookie", &remembered)])
+        .await;
+    assert_eq!(index.status, StatusCode::OK);
+    assert!(set_cookie(&index).contains("Max-Age=0"));
+}
+
+#[tokio::test]
+async fn v1_recoveries() {
+    let app = TestApp::in_memory();
+    let (code, alice) = app.create_game("alice").await;
+    app.join(&code, "bob").await;
+    let requested = app
+        .post(
+            &format!("/api/v1/games/{code}/recoveries"),
+            None,
+            Some(json!({ "player_name": "bob" })),
+        )
+        .await;
+    assert_eq!(requested.status, StatusCode::CREATED, "{}", requested.body);
+    let pending = app
+        .get(
+            &format!("/api/v1/games/{code}/recoveries"),
+            Some(&alice.token),
+        )
+        .await;
+    let request_id = pending.assert_ok()[0]["id"].as_i64().unwrap();
+    let decided = app
+        .post(
+            &format!("/api/v1/games/{code}/recoveries/{request_id}"),
+            Some(&alice.token),
+            Some(json!({ "approve": true })),
+        )
+        .await;
+    assert_eq!(decided.status, StatusCode::NO_CONTENT, "{}", decided.body);
+    let claimed = app
+        .get(
+            &format!(
+                "/api/v1/games/{code}/recovery-claims/{}",
+                requested.body["claim_token"].as_str().unwrap()
+            ),
+            None,
+        )
+        .await;
+    assert_eq!(claimed.assert_ok()["status"], "approved");
+    assert!(claimed.body["auth_token"].is_string());
+}
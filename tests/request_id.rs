@@ -0,0 +1,113 @@
+//! Request ids on responses, in error bodies and in the logs.
+
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::TestApp;
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use tracing_subscriber::fmt::MakeWriter;
+
+#[tokio::test]
+async fn generated_ids_are_echoed_and_quoted_in_errors() {
+    let app = TestApp::in_memory();
+    let ok = app.get("/healthz", None).await;
+    let id = ok.headers["x-request-id"].to_str().unwrap();
+    assert_eq!(uuid::Uuid::parse_str(id).unwrap().get_version_num(), 4);
+
+    let err = app.get("/api/game/NOPE", Some("token")).await;
+    err.assert_error(StatusCode::FORBIDDEN, "INVALID_AUTH_TOKEN");
+    let id = err.headers["x-request-id"].to_str().unwrap();
+    assert_eq!(err.body["request_id"], id);
+    assert_ne!(ok.headers["x-request-id"], err.headers["x-request-id"]);
+}
+
+async fn with_request_id(app: &TestApp, id: &str) -> common::TestResponse {
+    app.request_with_headers(
+        Method::GET,
+        "/api/game/NOPE",
+        Some("token"),
+        None,
+        &[("x-request-id", id)],
+    )
+    .await
+}
+
+#[tokio::test]
+async fn client_ids_are_kept_when_well_formed() {
+    let app = TestApp::in_memory();
+    let kept = with_request_id(&app, "lb-7f3a:42").await;
+    assert_eq!(kept.headers["x-request-id"], "lb-7f3a:42");
+    assert_eq!(kept.body["request_id"], "lb-7f3a:42");
+
+    for bad in ["", "has spaces", "quote\"d", &"x".repeat(129)] {
+        let replaced = with_request_id(&app, bad).await;
+        let id = replaced.headers["x-request-id"].to_str().unwrap();
+        assert!(
+            uuid::Uuid::parse_str(id).is_ok(),
+            "{bad:?} was kept as {id}"
+        );
+    }
+}
+
+/// Collects everything the JSON formatter writes.
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for Captured {
+    type Writer = Captured;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+#[tokio::test]
+async fn handler_logs_carry_request_game_and_player() {
+    let app = TestApp::in_memory();
+    let (code, players) = app.started_game(&["alice", "bob", "carol"]).await;
+    let alice = &players[0];
+
+    let logs = Captured::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .json()
+        .flatten_event(true)
+        .with_current_span(true)
+        .with_span_list(false)
+        .with_writer(logs.clone())
+        .finish();
+    let response = {
+        let _guard = tracing::subscriber::set_default(subscriber);
+        app.get(&format!("/api/game/{code}"), Some(&alice.token))
+            .await
+    };
+    let request_id = response.headers["x-request-id"].to_str().unwrap();
+
+    let output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    let spans: Vec<Value> = output
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap())
+        .filter_map(|event| event.get("span").cloned())
+        .filter(|span| span["name"] == "request")
+        .collect();
+    assert!(!spans.is_empty(), "no request events in\n{output}");
+    let handler = spans
+        .iter()
+        .find(|span| span.get("player_id").is_some())
+        .unwrap_or_else(|| panic!("no player_id in\n{output}"));
+    assert_eq!(handler["request_id"], request_id);
+    assert_eq!(handler["game_code"], code.as_str());
+    assert_eq!(handler["player_id"], alice.id);
+}
@@ -0,0 +1,214 @@
+//! Property tests for the target ring: random sequences of kills and leaves
+//! must always leave the living players in a single cycle, and a ring broken
+//! on purpose must always come back whole after a repair.
+
+use hitman::{
+    db::Db,
+    engine::MIN_PLAYERS,
+    kill_token::KillProof,
+    models::{GameStatus, Player},
+};
+use proptest::{prelude::*, sample::Index, test_runner::TestRunner};
+use sqlx::PgPool;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static GAMES: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Clone)]
+enum Step {
+    /// The chosen player eliminates their target.
+    Kill(Index),
+    /// The chosen player leaves the game.
+    Leave(Index),
+}
+
+fn step() -> impl Strategy<Value = Step> {
+    prop_oneof![
+        3 => any::<Index>().prop_map(Step::Kill),
+        1 => any::<Index>().prop_map(Step::Leave),
+    ]
+}
+
+async fn start_ring(db: &Db, size: usize) -> (String, i32) {
+    let code = format!("P{:05}", GAMES.fetch_add(1, Ordering::Relaxed));
+    let (game_id, host_id, _, _) = db
+        .create_game("player0".into(), code.clone())
+        .await
+        .unwrap();
+    for i in 1..size {
+        db.join_game(code.clone(), format!("player{i}"), usize::MAX, false)
+            .await
+            .unwrap();
+    }
+    db.start_game(&code, host_id, MIN_PLAYERS).await.unwrap();
+    (code, game_id)
+}
+
+async fn alive_players(db: &Db, game_id: i32) -> Vec<Player> {
+    let mut players = db.get_players_by_game_id(&**db, game_id).await.unwrap();
+    players.retain(|p| p.is_alive);
+    players.sort_by_key(|p| p.id);
+    players
+}
+
+/// Run every case of `strategy` on a blocking thread, driving the async test
+/// body on the `sqlx::test` runtime.
+async fn run_cases<S, F, Fut>(cases: u32, strategy: S, test: F)
+where
+    S: Strategy + Send + 'static,
+    S::Value: Send,
+    F: Fn(S::Value) -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = ()>,
+{
+    let handle = tokio::runtime::Handle::current();
+    tokio::task::spawn_blocking(move || {
+        let mut runner = TestRunner::new(ProptestConfig {
+            cases,
+            ..ProptestConfig::default()
+        });
+        let result = runner.run(&strategy, |value| {
+            handle.block_on(test(value));
+            Ok(())
+        });
+        if let Err(err) = result {
+            panic!("{err}");
+        }
+    })
+    .await
+    .unwrap();
+}
+
+#[sqlx::test]
+async fn random_kills_and_leaves_keep_the_ring_intact(pool: PgPool) {
+    let db = Db::from_pool(pool);
+    let strategy = (2usize..9, prop::collection::vec(step(), 1..20));
+    run_cases(48, strategy, move |(size, steps)| {
+        let db = db.clone();
+        async move {
+            let (code, game_id) = start_ring(&db, size).await;
+            for step in steps {
+                let alive = alive_players(&db, game_id).await;
+                if alive.len() <= 1 {
+                    break;
+                }
+                match step {
+                    Step::Kill(idx) => {
+                        let hunter = idx.get(&alive);
+                        let target = alive
+                            .iter()
+                            .find(|p| Some(p.id) == hunter.target_id)
+                            .expect("hunter's target is not alive");
+                        db.process_kill(
+                            &code,
+                            &hunter.auth_token,
+                            &KillProof::Secret(target.secret_code.clone()),
+                        )
+                        .await
+                        .unwrap();
+                    }
+                    Step::Leave(idx) => {
+                        db.leave_game(&code, &idx.get(&alive).auth_token)
+                            .await
+                            .unwrap();
+                    }
+                }
+
+                let violations = db.check_ring(&code).await.unwrap();
+                assert!(violations.is_empty(), "broken ring: {violations:?}");
+                let game = db.get_game_by_id(game_id).await.unwrap().unwrap();
+                let alive = alive_players(&db, game_id).await;
+                assert_eq!(
+                    game.status == GameStatus::Finished,
+                    alive.len() <= 1,
+                    "game status {} with {} players alive",
+                    game.status,
+                    alive.len()
+                );
+            }
+        }
+    })
+    .await;
+}
+
+#[sqlx::test]
+async fn repair_restores_any_broken_ring(pool: PgPool) {
+    let db = Db::from_pool(pool);
+    let strategy = (2usize..9).prop_flat_map(|size| {
+        // For each player: a new target (or none), and whether they die.
+        let targets = prop::collection::vec(prop::option::of(any::<Index>()), size);
+        let deaths = prop::collection::vec(prop::bool::weighted(0.2), size);
+        (Just(size), targets, deaths)
+    });
+    run_cases(48, strategy, move |(size, targets, deaths)| {
+        let db = db.clone();
+        async move {
+            let (code, game_id) = start_ring(&db, size).await;
+            let players = alive_players(&db, game_id).await;
+            for ((player, target), dead) in players.iter().zip(&targets).zip(&deaths) {
+                let target_id = target.as_ref().map(|idx| idx.get(&players).id);
+                sqlx::query("UPDATE players SET target_id = $1, is_alive = $2 WHERE id = $3")
+                    .bind(target_id)
+                    .bind(!dead)
+                    .bind(player.id)
+                    .execute(&*db)
+                    .await
+                    .unwrap();
+            }
+
+            let survivors = alive_players(&db, game_id).await;
+            let repair = db.repair_ring(&code).await.unwrap();
+            assert!(db.check_ring(&code).await.unwrap().is_empty());
+            assert_eq!(
+                alive_players(&db, game_id)
+                    .await
+                    .iter()
+                    .map(|p| p.id)
+                    .collect::<Vec<_>>(),
+                survivors.iter().map(|p| p.id).collect::<Vec<_>>(),
+                "repair must not change who is alive"
+            );
+
+            let game = db.get_game_by_id(game_id).await.unwrap().unwrap();
+            if survivors.len() <= 1 {
+                assert_eq!(game.status, GameStatus::Finished);
+                assert_eq!(repair.winner_id, survivors.first().map(|p| p.id));
+            } else {
+                assert_eq!(game.status, GameStatus::InProgress);
+                // A second pass has nothing left to do.
+                assert!(db.repair_ring(&code).await.unwrap().violations.is_empty());
+            }
+        }
+    })
+    .await;
+}
+
+/// Debug builds refuse to commit anything on top of a broken ring, so tests
+/// see the bug instead of a quietly mended ring; only the admin repair fixes it.
+#[cfg(debug_assertions)]
+#[sqlx::test]
+async fn changes_to_a_game_with_a_broken_ring_fail(pool: PgPool) {
+    let db = Db::from_pool(pool);
+    let (code, game_id) = start_ring(&db, 4).await;
+    let players = alive_players(&db, game_id).await;
+    // Everyone hunts the host, the host included.
+    sqlx::query("UPDATE players SET target_id = $1 WHERE game_id = $2")
+        .bind(players[0].id)
+        .bind(game_id)
+        .execute(&*db)
+        .await
+        .unwrap();
+    let broken = db.check_ring(&code).await.unwrap();
+    assert!(!broken.is_empty());
+
+    assert!(db
+        .rotate_secret(&code, &players[1].auth_token, 0, 3)
+        .await
+        .is_err());
+    assert_eq!(db.check_ring(&code).await.unwrap(), broken);
+
+    db.repair_ring(&code).await.unwrap();
+    assert!(db.check_ring(&code).await.unwrap().is_empty());
+    db.rotate_secret(&code, &players[1].auth_token, 0, 3)
+        .await
+        .unwrap();
+}
@@ -0,0 +1,96 @@
+//! Cross-origin policy and security headers on pages and API responses.
+
+mod common;
+
+use axum::http::{header, Method, StatusCode};
+use common::TestApp;
+use hitman::config::Config;
+
+fn app_with(configure: impl FnOnce(&mut Config)) -> TestApp {
+    let mut config = Config::default();
+    configure(&mut config);
+    TestApp::in_memory_with_config(config)
+}
+
+#[tokio::test]
+async fn pages_and_api_carry_security_headers() {
+    let app = TestApp::in_memory();
+    let (code, host) = app.create_game("alice").await;
+
+    for response in [
+        app.get("/", None).await,
+        app.get(&format!("/game/{code}/player/{}", host.token), None)
+            .await,
+        app.get(&format!("/api/game/{code}"), Some(&host.token))
+            .await,
+        app.get("/api/game/NOPE", Some(&host.token)).await,
+    ] {
+        let headers = &response.headers;
+        let csp = headers[header::CONTENT_SECURITY_POLICY].to_str().unwrap();
+        assert!(csp.contains("script-src 'self';"), "{csp}");
+        assert!(csp.contains("frame-ancestors 'none'"), "{csp}");
+        assert_eq!(headers[header::X_FRAME_OPTIONS], "DENY");
+        assert_eq!(headers[header::REFERRER_POLICY], "no-referrer");
+        assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+        assert!(!headers.contains_key(header::STRICT_TRANSPORT_SECURITY));
+    }
+}
+
+#[tokio::test]
+async fn hsts_only_behind_tls() {
+    let app = app_with(|c| c.server.behind_tls = true);
+    let response = app.get("/", None).await;
+    assert_eq!(
+        response.headers[header::STRICT_TRANSPORT_SECURITY],
+        "max-age=31536000; includeSubDomains"
+    );
+}
+
+async fn preflight(app: &TestApp, origin: &str) -> common::TestResponse {
+    app.request_with_headers(
+        Method::OPTIONS,
+        "/api/game",
+        None,
+        None,
+        &[
+            ("origin", origin),
+            ("access-control-request-method", "POST"),
+            (
+                "access-control-request-headers",
+                "authorization,content-type",
+            ),
+        ],
+    )
+    .await
+}
+
+#[tokio::test]
+async fn cross_origin_requests_need_an_allowed_origin() {
+    let app = app_with(|c| c.server.cors_origins = vec!["https://club.example".into()]);
+
+    let allowed = preflight(&app, "https://club.example").await;
+    assert_eq!(allowed.status, StatusCode::OK);
+    assert_eq!(
+        allowed.headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+        "https://club.example"
+    );
+    let allowed_headers = allowed.headers[header::ACCESS_CONTROL_ALLOW_HEADERS]
+        .to_str()
+        .unwrap();
+    assert!(
+        allowed_headers.contains("authorization"),
+        "{allowed_headers}"
+    );
+
+    let denied = preflight(&app, "https://evil.example").await;
+    assert!(!denied
+        .headers
+        .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+
+    // Without any configured origin, no page elsewhere may call the API.
+    let default = TestApp::in_memory();
+    let denied = preflight(&default, "https://club.example").await;
+    assert!(!denied
+        .headers
+        .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+}
@@ -0,0 +1,206 @@
+//! The spectator link: a read-only scoreboard that never shows targets or
+//! secrets.
+
+mod common;
+
+use axum::http::StatusCode;
+use common::{on_every_backend, TestApp, TestPlayer};
+use hitman::config::Config;
+use serde_json::Value;
+
+async fn spectator_token(app: &TestApp, code: &str, host: &TestPlayer) -> String {
+    app.get(
+        &format!("/api/game/{code}/spectator-link"),
+        Some(&host.token),
+    )
+    .await
+    .assert_ok()["spectator_token"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+async fn scoreboard(app: &TestApp, code: &str, token: &str) -> Value {
+    app.get(&format!("/api/game/{code}/spectate?token={token}"), None)
+        .await
+        .assert_ok()
+        .clone()
+}
+
+fn event_kinds(scoreboard: &Value) -> Vec<&str> {
+    scoreboard["events"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["kind"].as_str().unwrap())
+        .collect()
+}
+
+async fn spectators_follow_a_whole_game(app: TestApp) {
+    let (code, alice) = app.create_game("alice").await;
+    let bob = app.join(&code, "bob").await;
+    let carol = app.join(&code, "carol").await;
+
+    let link = app
+        .get(
+            &format!("/api/game/{code}/spectator-link"),
+            Some(&bob.token),
+        )
+        .await;
+    link.assert_error(StatusCode::FORBIDDEN, "NOT_HOST");
+    let token = spectator_token(&app, &code, &alice).await;
+    app.get(&format!("/api/game/{code}/spectate?token=nope"), None)
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "INVALID_SPECTATOR_TOKEN");
+    app.get(
+        &format!("/api/game/{code}/spectate?token={}", alice.token),
+        None,
+    )
+    .await
+    .assert_error(StatusCode::FORBIDDEN, "INVALID_SPECTATOR_TOKEN");
+
+    let lobby = scoreboard(&app, &code, &token).await;
+    assert_eq!(lobby["status"], "Lobby");
+    assert!(lobby["started_at"].is_null());
+    assert_eq!(event_kinds(&lobby), ["joined", "joined", "joined"]);
+
+    app.start(&code, &alice).await.assert_ok();
+    let targets = app.targets(&code).await;
+    let first = [&alice, &bob, &carol]
+        .into_iter()
+        .find(|p| p.name == targets["alice"])
+        .unwrap();
+    app.kill(&code, &alice, &first.secret).await.assert_ok();
+    let last = [&bob, &carol]
+        .into_iter()
+        .find(|p| p.id != first.id)
+        .unwrap();
+    app.kill(&code, &alice, &last.secret).await.assert_ok();
+
+    let over = scoreboard(&app, &code, &token).await;
+    assert_eq!(over["status"], "Finished");
+    assert_eq!(
+        event_kinds(&over)[3..],
+        ["started", "kill", "kill", "finished"]
+    );
+    let kill = &over["events"][4];
+    assert_eq!(kill["actor_name"], "alice");
+    assert_eq!(kill["subject_name"], first.name.as_str());
+    assert_eq!(over["events"][6]["actor_name"], "alice");
+    let started_at = over["started_at"].as_i64().unwrap();
+    assert!(over["finished_at"].as_i64().unwrap() >= started_at);
+    let alive: Vec<&str> = over["players"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|p| p["is_alive"] == true)
+        .map(|p| p["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(alive, ["alice"]);
+
+    let body = over.to_string();
+    for player in [&alice, &bob, &carol] {
+        assert!(!body.contains(&player.secret), "secret leaked: {body}");
+        assert!(!body.contains(&player.token), "token leaked: {body}");
+    }
+    assert!(!body.contains("target"), "target leaked: {body}");
+    assert!(!body.contains("secret"), "secret leaked: {body}");
+
+    // The version is the one players poll, so spectators see every change.
+    let changes = app
+        .get(&format!("/api/game/{code}/changed?version=0"), None)
+        .await;
+    assert_eq!(changes.assert_ok()["current_version"], over["version"]);
+}
+
+on_every_backend!(spectators_follow_a_whole_game);
+
+#[tokio::test]
+async fn leaving_shows_up_in_the_feed() {
+    let app = TestApp::in_memory();
+    let (code, players) = app.started_game(&["alice", "bob"]).await;
+    let token = spectator_token(&app, &code, &players[0]).await;
+    app.leave(&code, &players[1]).await.assert_ok();
+
+    let over = scoreboard(&app, &code, &token).await;
+    assert_eq!(over["status"], "Finished");
+    assert_eq!(
+        event_kinds(&over)[2..],
+        ["started", "forfeited", "finished"]
+    );
+    assert_eq!(over["events"][3]["actor_name"], "bob");
+    assert_eq!(over["events"][4]["actor_name"], "alice");
+}
+
+#[tokio::test]
+async fn the_page_needs_the_right_token() {
+    let app = TestApp::in_memory();
+    let (code, host) = app.create_game("alice").await;
+    let token = spectator_token(&app, &code, &host).await;
+
+    let lobby = app
+        .get(&format!("/game/{code}/player/{}/lobby", host.token), None)
+        .await;
+    let html = lobby.body.as_str().unwrap();
+    assert!(
+        html.contains(&format!("/game/{code}/spectate?token={token}")),
+        "host sees the spectator link"
+    );
+
+    let page = app
+        .get(&format!("/game/{code}/spectate?token={token}"), None)
+        .await;
+    assert_eq!(page.status, StatusCode::OK);
+    assert!(page.body.as_str().unwrap().contains("spectate.js"));
+    for path in [
+        format!("/game/{code}/spectate?token=nope"),
+        format!("/game/{code}/spectate"),
+    ] {
+        let page = app.get(&path, None).await;
+        assert_eq!(page.status, StatusCode::OK);
+        assert!(!page.body.as_str().unwrap().contains("spectate.js"));
+    }
+}
+
+#[tokio::test]
+async fn v1_takes_the_token_as_bearer() {
+    let app = TestApp::in_memory();
+    let (code, host) = app.create_game("alice").await;
+    let link = app
+        .get(
+            &format!("/api/v1/games/{code}/spectator-link"),
+            Some(&host.token),
+        )
+        .await;
+    let link = link.assert_ok();
+    let token = link["spectator_token"].as_str().unwrap();
+    assert_eq!(
+        link["path"],
+        format!("/game/{code}/spectate?token={token}").as_str()
+    );
+
+    let board = app
+        .get(&format!("/api/v1/games/{code}/spectate"), Some(token))
+        .await;
+    assert_eq!(board.assert_ok()["players"][0]["name"], "alice");
+    app.get(&format!("/api/v1/games/{code}/spectate"), Some(&host.token))
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "INVALID_SPECTATOR_TOKEN");
+}
+
+#[tokio::test]
+async fn spectating_can_be_turned_off() {
+    let mut config = Config::default();
+    config.features.spectators = false;
+    let app = TestApp::in_memory_with_config(config);
+    let (code, host) = app.create_game("alice").await;
+    app.get(
+        &format!("/api/game/{code}/spectator-link"),
+        Some(&host.token),
+    )
+    .await
+    .assert_error(StatusCode::NOT_FOUND, "FEATURE_DISABLED");
+    app.get(&format!("/api/game/{code}/spectate?token=any"), None)
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "FEATURE_DISABLED");
+}
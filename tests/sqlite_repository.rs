@@ -0,0 +1,191 @@
+//! The SQLite repository behind the real router, on a throwaway database.
+
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use hitman::{
+    engine::MIN_PLAYERS,
+    kill_token::KillProof,
+    repository::{GameRepository, SqliteRepository},
+};
+use proptest::{prelude::*, sample::Index};
+use std::time::Duration;
+
+#[tokio::test]
+async fn full_game_on_sqlite() {
+    let app = TestApp::sqlite().await;
+    let (code, players) = app.started_game(&["alice", "bob", "carol"]).await;
+
+    for _ in 0..2 {
+        let targets = app.targets(&code).await;
+        let victim = players.iter().find(|p| p.name == targets["alice"]).unwrap();
+        app.kill(&code, &players[0], &victim.secret)
+            .await
+            .assert_ok();
+    }
+    let state = app.game_state(&code, &players[0]).await;
+    assert_eq!(state["game"]["status"], "Finished");
+}
+
+#[tokio::test]
+async fn lobby_rules_on_sqlite() {
+    let app = TestApp::sqlite().await;
+    let (code, host) = app.create_game("Émile").await;
+    app.try_join(&code, "éMILE ")
+        .await
+        .assert_error(StatusCode::CONFLICT, "NAME_TAKEN");
+    let bob = app.join(&code, "bob").await;
+    app.start(&code, &bob)
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "NOT_HOST");
+
+    assert_eq!(app.leave(&code, &host).await.status, StatusCode::NO_CONTENT);
+    let state = app.game_state(&code, &bob).await;
+    assert_eq!(state["game"]["host_id"], bob.id);
+    app.start(&code, &bob)
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "NOT_ENOUGH_PLAYERS");
+}
+
+#[tokio::test]
+async fn kill_tokens_and_rotation_on_sqlite() {
+    let app = TestApp::sqlite().await;
+    let (code, players) = app.started_game(&["alice", "bob", "carol"]).await;
+    let targets = app.targets(&code).await;
+    let victim = players.iter().find(|p| p.name == targets["alice"]).unwrap();
+    let hunter = players
+        .iter()
+        .find(|p| targets[&p.name] == "alice")
+        .unwrap();
+
+    let token = app.kill_token(&code, victim).await;
+    app.kill(&code, &players[0], &token).await.assert_ok();
+
+    // alice now hunts the hunter, whose token dies when they rotate their code.
+    let token = app.kill_token(&code, hunter).await;
+    let rotate = format!("/api/game/{code}/secret/rotate");
+    app.post(&rotate, Some(&hunter.token), None)
+        .await
+        .assert_ok();
+    app.post(&rotate, Some(&hunter.token), None)
+        .await
+        .assert_error(StatusCode::TOO_MANY_REQUESTS, "SECRET_ROTATION_COOLDOWN");
+    app.kill(&code, &players[0], &token)
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "KILL_TOKEN_STALE");
+}
+
+#[tokio::test]
+async fn games_survive_reopening_the_file() {
+    let path = std::env::temp_dir().join(format!("hitman-{}.db", uuid::Uuid::new_v4()));
+    let url = format!("sqlite://{}", path.display());
+
+    let repo = SqliteRepository::connect(&url, 1).await.unwrap();
+    repo.create_game("alice".into(), "FILE".into())
+        .await
+        .unwrap();
+    drop(repo);
+
+    let repo = SqliteRepository::connect(&url, 1).await.unwrap();
+    let (_, players) = repo.get_game_state("FILE").await.unwrap().unwrap();
+    assert_eq!(players[0].name, "alice");
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn purge_drops_old_games_on_sqlite() {
+    let repo = SqliteRepository::connect("sqlite::memory:", 1)
+        .await
+        .unwrap();
+    let (_, _, _, token) = repo
+        .create_game("alice".into(), "OLD1".into())
+        .await
+        .unwrap();
+    assert!(repo
+        .purge_games(Duration::from_secs(3600))
+        .await
+        .unwrap()
+        .is_empty());
+    // `created_at` has one second resolution.
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    assert_eq!(repo.purge_games(Duration::ZERO).await.unwrap(), ["OLD1"]);
+    assert!(repo.get_game_by_code("OLD1").await.unwrap().is_none());
+    assert!(repo
+        .get_player_by_auth_token(&token)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[derive(Debug, Clone)]
+enum Step {
+    Kill(Index),
+    Leave(Index),
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    /// Same property as the Postgres and in-memory ring tests.
+    #[test]
+    fn random_kills_and_leaves_keep_the_ring_intact(
+        size in 2usize..10,
+        steps in prop::collection::vec(
+            prop_oneof![
+                3 => any::<Index>().prop_map(Step::Kill),
+                1 => any::<Index>().prop_map(Step::Leave),
+            ],
+            1..20,
+        ),
+    ) {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(async {
+            let repo = SqliteRepository::connect("sqlite::memory:", 1).await.unwrap();
+            let (game_id, host_id, _, _) = repo
+                .create_game("player0".into(), "LITE".into())
+                .await
+                .unwrap();
+            for i in 1..size {
+                repo.join_game("LITE".into(), format!("player{i}"), usize::MAX, false).await.unwrap();
+            }
+            repo.start_game("LITE", host_id, MIN_PLAYERS).await.unwrap();
+
+            for step in steps {
+                let alive: Vec<_> = repo
+                    .get_players_by_game_id(game_id)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .filter(|p| p.is_alive)
+                    .collect();
+                if alive.len() <= 1 {
+                    break;
+                }
+                match step {
+                    Step::Kill(idx) => {
+                        let hunter = idx.get(&alive);
+                        let target = alive
+                            .iter()
+                            .find(|p| Some(p.id) == hunter.target_id)
+                            .expect("hunter's target is not alive");
+                        repo.process_kill(
+                            "LITE",
+                            &hunter.auth_token,
+                            &KillProof::Secret(target.secret_code.clone()),
+                        )
+                        .await
+                        .unwrap();
+                    }
+                    Step::Leave(idx) => {
+                        repo.leave_game("LITE", &idx.get(&alive).auth_token)
+                            .await
+                            .unwrap();
+                    }
+                }
+                let violations = repo.check_ring("LITE").await.unwrap();
+                assert!(violations.is_empty(), "broken ring: {violations:?}");
+            }
+        });
+    }
+}
@@ -0,0 +1,128 @@
+//! OTLP trace export, checked against a stand-in collector that decodes what
+//! the exporter sends.
+
+mod common;
+
+use axum::{body::Bytes, routing::post, Router};
+use common::TestApp;
+use hitman::config::TelemetryConfig;
+use hitman::telemetry;
+use opentelemetry_proto::tonic::collector::trace::v1::{
+    ExportTraceServiceRequest, ExportTraceServiceResponse,
+};
+use opentelemetry_proto::tonic::common::v1::any_value::Value as AnyValue;
+use prost::Message;
+use serde_json::json;
+use sqlx::PgPool;
+use std::sync::{Arc, Mutex};
+use tracing_subscriber::prelude::*;
+
+/// `(service name, span name, trace id)` of every span received.
+type Received = Arc<Mutex<Vec<(String, String, Vec<u8>)>>>;
+
+/// Listen on a free port like an OTLP/HTTP collector; returns its base URL.
+async fn collector() -> (String, Received) {
+    let received = Received::default();
+    let sink = received.clone();
+    let app = Router::new().route(
+        "/v1/traces",
+        post(move |body: Bytes| async move {
+            let request = ExportTraceServiceRequest::decode(body).expect("valid OTLP protobuf");
+            for resource_spans in request.resource_spans {
+                let service = resource_spans
+                    .resource
+                    .iter()
+                    .flat_map(|r| &r.attributes)
+                    .find(|kv| kv.key == "service.name")
+                    .and_then(|kv| match kv.value.as_ref()?.value.as_ref()? {
+                        AnyValue::StringValue(s) => Some(s.clone()),
+                        _ => None,
+                    })
+                    .unwrap_or_default();
+                for span in resource_spans
+                    .scope_spans
+                    .into_iter()
+                    .flat_map(|scope| scope.spans)
+                {
+                    sink.lock()
+                        .unwrap()
+                        .push((service.clone(), span.name, span.trace_id));
+                }
+            }
+            ExportTraceServiceResponse::default().encode_to_vec()
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{addr}/"), received)
+}
+
+#[test]
+fn export_is_off_by_default() {
+    assert!(telemetry::tracer_provider(&TelemetryConfig::default())
+        .unwrap()
+        .is_none());
+}
+
+#[sqlx::test]
+async fn a_kill_is_one_trace_with_its_transaction_steps(pool: PgPool) {
+    let (endpoint, received) = collector().await;
+    let provider = telemetry::tracer_provider(&TelemetryConfig {
+        otlp_endpoint: Some(endpoint),
+        service_name: "hitman-test".into(),
+    })
+    .unwrap()
+    .expect("an endpoint turns export on");
+
+    let app = TestApp::new(pool);
+    let (code, players) = app.started_game(&["alice", "bob", "carol"]).await;
+    let alice = &players[0];
+    let targets = app.targets(&code).await;
+    let victim = players.iter().find(|p| p.name == targets["alice"]).unwrap();
+
+    // The caller's trace is continued, so everything lands under its id.
+    let trace_id = "4bf92f3577b34da6a3ce929d0e0e4736";
+    {
+        let subscriber = tracing_subscriber::registry().with(telemetry::layer(&provider));
+        let _guard = tracing::subscriber::set_default(subscriber);
+        app.request_with_headers(
+            axum::http::Method::POST,
+            &format!("/api/game/{code}/eliminate"),
+            Some(&alice.token),
+            Some(json!({ "secret_code": victim.secret })),
+            &[("traceparent", &format!("00-{trace_id}-00f067aa0ba902b7-01"))],
+        )
+        .await
+        .assert_ok();
+    }
+    // The exporter's HTTP client blocks; keep the runtime free to answer it.
+    tokio::task::spawn_blocking(move || provider.shutdown())
+        .await
+        .unwrap()
+        .unwrap();
+
+    let received = received.lock().unwrap();
+    let in_trace: Vec<&str> = received
+        .iter()
+        .filter(|(_, _, id)| hex(id) == trace_id)
+        .map(|(_, name, _)| name.as_str())
+        .collect();
+    for step in [
+        "request",
+        "process_kill",
+        "get_game_by_code_in_tx",
+        "get_player_by_auth_token_in_tx",
+        "validate_kill",
+        "update_game_state_after_kill",
+    ] {
+        assert!(in_trace.contains(&step), "{step} missing from {in_trace:?}");
+    }
+    assert!(received
+        .iter()
+        .all(|(service, _, _)| service == "hitman-test"));
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}